@@ -2,8 +2,9 @@
 
 use rustc_hir::def_id::LocalDefId;
 use rustc_index::{IndexSlice, IndexVec};
-use rustc_middle::mir::{Body, Promoted};
+use rustc_middle::mir::{Body, BorrowCheckResult, Promoted};
 use rustc_middle::ty::TyCtxt;
+use rustc_session::time_report;
 
 pub use super::constraints::OutlivesConstraint;
 pub use super::dataflow::{BorrowIndex, Borrows, calculate_borrows_out_of_scope_at_location};
@@ -13,6 +14,7 @@
 pub use super::place_ext::PlaceExt;
 pub use super::places_conflict::{PlaceConflictBias, places_conflict};
 pub use super::region_infer::RegionInferenceContext;
+pub use super::type_check::Locations;
 use crate::borrow_set::BorrowSet;
 
 /// Options determining the output behavior of [`get_body_with_borrowck_facts`].
@@ -104,3 +106,20 @@ pub fn get_body_with_borrowck_facts(
     let promoted: &IndexSlice<_, _> = &promoted.borrow();
     *super::do_mir_borrowck(tcx, input_body, promoted, Some(options)).1.unwrap()
 }
+
+/// Borrow-checks the given body exactly like the `mir_borrowck` query, additionally returning the
+/// facts determined by the [`ConsumerOptions`].
+///
+/// This is meant for overrides of the `mir_borrowck` query that want to retain the facts of
+/// every body: unlike calling [`get_body_with_borrowck_facts`] before the default provider, it
+/// only borrow-checks the body once. No facts are returned for bodies that the borrow checker
+/// skips, e.g. because they are tainted by errors.
+pub fn mir_borrowck_with_body_facts(
+    tcx: TyCtxt<'_>,
+    def: LocalDefId,
+    options: ConsumerOptions,
+) -> (&BorrowCheckResult<'_>, Option<Box<BodyWithBorrowckFacts<'_>>>) {
+    tcx.sess.time_item(time_report::BORROWCK, def, || {
+        super::inner_mir_borrowck(tcx, def, Some(options))
+    })
+}
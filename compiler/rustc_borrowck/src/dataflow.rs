@@ -230,21 +230,34 @@ struct PoloniusOutOfScopePrecomputer<'a, 'tcx> {
 }
 
 impl<'a, 'tcx> PoloniusOutOfScopePrecomputer<'a, 'tcx> {
-    fn new(body: &'a Body<'tcx>, regioncx: &'a RegionInferenceContext<'tcx>) -> Self {
-        Self {
+    fn compute(
+        body: &'a Body<'tcx>,
+        regioncx: &'a RegionInferenceContext<'tcx>,
+        borrow_set: &BorrowSet<'tcx>,
+    ) -> FxIndexMap<Location, Vec<BorrowIndex>> {
+        let mut prec = PoloniusOutOfScopePrecomputer {
             visited: BitSet::new_empty(body.basic_blocks.len()),
             visit_stack: vec![],
             body,
             regioncx,
             loans_out_of_scope_at_location: FxIndexMap::default(),
+        };
+        for (loan_idx, loan_data) in borrow_set.iter_enumerated() {
+            let issuing_region = loan_data.region;
+            let loan_issued_at = loan_data.reserve_location;
+            prec.precompute_loans_out_of_scope(loan_idx, issuing_region, loan_issued_at);
         }
+
+        prec.loans_out_of_scope_at_location
     }
 }
 
 impl<'tcx> PoloniusOutOfScopePrecomputer<'_, 'tcx> {
     /// Loans are in scope while they are live: whether they are contained within any live region.
-    /// In the location-insensitive analysis, a loan will be contained in a region if the issuing
-    /// region can reach it in the subset graph. So this is a reachability problem.
+    /// In the location-sensitive analysis, a loan is contained in a region at a given point if the
+    /// issuing region can reach it at that point in the localized constraint graph. The loans live
+    /// at each point were computed by `PoloniusContext::compute_live_loans`, this function finds
+    /// where they stop being live.
     fn precompute_loans_out_of_scope(
         &mut self,
         loan_idx: BorrowIndex,
@@ -254,11 +267,9 @@ fn precompute_loans_out_of_scope(
         let sccs = self.regioncx.constraint_sccs();
         let universal_regions = self.regioncx.universal_regions();
 
-        // We first handle the cases where the loan doesn't go out of scope, depending on the issuing
-        // region's successors.
+        // We first handle the case where the loan doesn't go out of scope, depending on the issuing
+        // region's successors: via applied member constraints.
         for successor in graph::depth_first_search(&self.regioncx.region_graph(), issuing_region) {
-            // 1. Via applied member constraints
-            //
             // The issuing region can flow into the choice regions, and they are either:
             // - placeholders or free regions themselves,
             // - or also transitively outlive a free region.
@@ -275,16 +286,11 @@ fn precompute_loans_out_of_scope(
                     return;
                 }
             }
-
-            // 2. Via regions that are live at all points: placeholders and free regions.
-            //
-            // If the issuing region outlives such a region, its loan escapes the function and
-            // cannot go out of scope. We can early return.
-            if self.regioncx.is_region_live_at_all_points(successor) {
-                return;
-            }
         }
 
+        // Loans flowing into placeholders and free regions don't escape the function from every
+        // point: they are only live at the points reachable from where they flow into these
+        // regions, and go out of scope like any other loan elsewhere.
         let first_block = loan_issued_at.block;
         let first_bb_data = &self.body.basic_blocks[first_block];
 
@@ -390,33 +396,15 @@ pub fn new(
         regioncx: &RegionInferenceContext<'tcx>,
         borrow_set: &'a BorrowSet<'tcx>,
     ) -> Self {
-        let mut borrows_out_of_scope_at_location =
-            calculate_borrows_out_of_scope_at_location(body, regioncx, borrow_set);
-
         // The in-tree polonius analysis computes loans going out of scope using the set-of-loans
-        // model, and makes sure they're identical to the existing computation of the set-of-points
-        // model.
-        if tcx.sess.opts.unstable_opts.polonius.is_next_enabled() {
-            let mut polonius_prec = PoloniusOutOfScopePrecomputer::new(body, regioncx);
-            for (loan_idx, loan_data) in borrow_set.iter_enumerated() {
-                let issuing_region = loan_data.region;
-                let loan_issued_at = loan_data.reserve_location;
-
-                polonius_prec.precompute_loans_out_of_scope(
-                    loan_idx,
-                    issuing_region,
-                    loan_issued_at,
-                );
-            }
-
-            assert_eq!(
-                borrows_out_of_scope_at_location, polonius_prec.loans_out_of_scope_at_location,
-                "polonius loan scopes differ from NLL borrow scopes, for body {:?}",
-                body.span,
-            );
-
-            borrows_out_of_scope_at_location = polonius_prec.loans_out_of_scope_at_location;
-        }
+        // model, with the loans that are live at each point computed by the location-sensitive
+        // analysis. It accepts more programs than the set-of-points model of NLLs.
+        let borrows_out_of_scope_at_location =
+            if tcx.sess.opts.unstable_opts.polonius.is_next_enabled() {
+                PoloniusOutOfScopePrecomputer::compute(body, regioncx, borrow_set)
+            } else {
+                calculate_borrows_out_of_scope_at_location(body, regioncx, borrow_set)
+            };
 
         Borrows { tcx, body, borrow_set, borrows_out_of_scope_at_location }
     }
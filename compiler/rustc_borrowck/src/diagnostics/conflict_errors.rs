@@ -1536,7 +1536,7 @@ pub(crate) fn report_move_out_while_borrowed(
                 self.suggest_cloning(&mut err, ty, expr, Some(move_spans));
             }
         }
-        self.buffer_error(err);
+        self.buffer_conflicting_access_error(err, location, (place, span), None, borrow);
     }
 
     pub(crate) fn report_use_while_mutably_borrowed(
@@ -5,6 +5,7 @@
 
 use std::assert_matches::assert_matches;
 
+use rustc_errors::json::{BorrowckAccess, BorrowckConstraint, BorrowckExplanation, BorrowckLoan};
 use rustc_errors::{Applicability, Diag};
 use rustc_hir as hir;
 use rustc_hir::intravisit::Visitor;
@@ -12,8 +13,8 @@
 use rustc_infer::infer::NllRegionVariableOrigin;
 use rustc_middle::middle::resolve_bound_vars::ObjectLifetimeDefault;
 use rustc_middle::mir::{
-    Body, CallSource, CastKind, ConstraintCategory, FakeReadCause, Local, LocalInfo, Location,
-    Operand, Place, Rvalue, Statement, StatementKind, TerminatorKind,
+    Body, BorrowKind, CallSource, CastKind, ConstraintCategory, FakeReadCause, Local, LocalInfo,
+    Location, Operand, Place, Rvalue, Statement, StatementKind, TerminatorKind,
 };
 use rustc_middle::ty::adjustment::PointerCoercion;
 use rustc_middle::ty::{self, RegionVid, Ty, TyCtxt};
@@ -822,3 +823,77 @@ fn was_captured_by_trait_object(&self, borrow: &BorrowData<'tcx>) -> bool {
         false
     }
 }
+
+impl<'infcx, 'tcx> MirBorrowckCtxt<'_, 'infcx, 'tcx> {
+    /// Buffers the error `err`, about an access conflicting with `borrow` at `location`. The
+    /// access is a borrow of the given kind, or a move if `access` is `None`.
+    ///
+    /// With `-Zborrowck-explain-json`, a structured explanation of why the loan is still live at
+    /// the access is buffered next to the error: the loan, the outlives constraints from its region
+    /// to a region that is live at the access, and the access itself.
+    pub(crate) fn buffer_conflicting_access_error(
+        &mut self,
+        err: Diag<'infcx>,
+        location: Location,
+        (place, span): (Place<'tcx>, Span),
+        access: Option<BorrowKind>,
+        borrow: &BorrowData<'tcx>,
+    ) {
+        if !self.infcx.tcx.sess.opts.unstable_opts.borrowck_explain_json {
+            self.buffer_error(err);
+            return;
+        }
+
+        let live_region = self.regioncx.find_sub_region_live_at(borrow.region, location);
+        let constraint_path = self
+            .regioncx
+            .find_constraint_paths_between_regions(borrow.region, |r| r == live_region)
+            .map_or_else(Vec::new, |(path, _)| path)
+            .into_iter()
+            .map(|constraint| {
+                let category = constraint.category.description().trim_end();
+                BorrowckConstraint {
+                    sup: self.describe_region_vid(constraint.sup),
+                    sub: self.describe_region_vid(constraint.sub),
+                    category: (!category.is_empty()).then(|| category.to_owned()),
+                    span: constraint.span,
+                }
+            })
+            .collect();
+
+        let describe_kind =
+            |kind: BorrowKind| if kind.mutability().is_mut() { "mutable" } else { "immutable" };
+        let explanation = BorrowckExplanation {
+            code: err.code,
+            span: err.span.primary_span().unwrap_or(span),
+            loan: BorrowckLoan {
+                kind: describe_kind(borrow.kind).to_owned(),
+                place: self.describe_any_place(borrow.borrowed_place.as_ref()),
+                region: self.describe_region_vid(borrow.region),
+                span: self.retrieve_borrow_spans(borrow).args_or_use(),
+            },
+            live_region: self.describe_region_vid(live_region),
+            constraint_path,
+            access: BorrowckAccess {
+                kind: match access {
+                    Some(kind) => format!("{} borrow", describe_kind(kind)),
+                    None => "move".to_owned(),
+                },
+                place: self.describe_any_place(place.as_ref()),
+                span,
+            },
+        };
+
+        let sort_span = err.sort_span;
+        self.buffer_error(err);
+        self.buffer_borrowck_explanation(sort_span, explanation);
+    }
+
+    /// Describes `region` by its name if it has one, e.g. `'a`, or by its index otherwise.
+    fn describe_region_vid(&self, region: RegionVid) -> String {
+        match self.regioncx.region_definition(region).external_name {
+            Some(name) if name.has_name() => name.to_string(),
+            _ => format!("{region:?}"),
+        }
+    }
+}
@@ -44,6 +44,7 @@
     InitIndex, InitLocation, LookupResult, MoveData, MoveOutIndex, MovePathIndex,
 };
 use rustc_session::lint::builtin::UNUSED_MUT;
+use rustc_session::time_report;
 use rustc_span::{Span, Symbol};
 use rustc_target::abi::FieldIdx;
 use smallvec::SmallVec;
@@ -102,6 +103,16 @@ pub fn provide(providers: &mut Providers) {
 }
 
 fn mir_borrowck(tcx: TyCtxt<'_>, def: LocalDefId) -> &BorrowCheckResult<'_> {
+    tcx.sess.time_item(time_report::BORROWCK, def, || inner_mir_borrowck(tcx, def, None).0)
+}
+
+/// Borrow-check `def`, additionally returning [`BodyWithBorrowckFacts`] according to the given
+/// [`ConsumerOptions`]. No facts are returned for bodies that are not borrow-checked.
+fn inner_mir_borrowck<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    def: LocalDefId,
+    consumer_options: Option<ConsumerOptions>,
+) -> (&'tcx BorrowCheckResult<'tcx>, Option<Box<BodyWithBorrowckFacts<'tcx>>>) {
     let (input_body, promoted) = tcx.mir_promoted(def);
     debug!("run query mir_borrowck: {}", tcx.def_path_str(def));
 
@@ -116,14 +127,15 @@ fn mir_borrowck(tcx: TyCtxt<'_>, def: LocalDefId) -> &BorrowCheckResult<'_> {
             used_mut_upvars: SmallVec::new(),
             tainted_by_errors: input_body.tainted_by_errors,
         };
-        return tcx.arena.alloc(result);
+        return (tcx.arena.alloc(result), None);
     }
 
     let promoted: &IndexSlice<_, _> = &promoted.borrow();
-    let opt_closure_req = do_mir_borrowck(tcx, input_body, promoted, None).0;
+    let (opt_closure_req, body_with_facts) =
+        do_mir_borrowck(tcx, input_body, promoted, consumer_options);
     debug!("mir_borrowck done");
 
-    tcx.arena.alloc(opt_closure_req)
+    (tcx.arena.alloc(opt_closure_req), body_with_facts)
 }
 
 /// Perform the actual borrow checking.
@@ -1105,7 +1117,13 @@ fn check_access_for_conflict(
                         ReadKind::Borrow(bk) => {
                             let err =
                                 this.report_conflicting_borrow(location, place_span, bk, borrow);
-                            this.buffer_error(err);
+                            this.buffer_conflicting_access_error(
+                                err,
+                                location,
+                                place_span,
+                                Some(bk),
+                                borrow,
+                            );
                         }
                     }
                     Control::Break
@@ -1136,7 +1154,13 @@ fn check_access_for_conflict(
                         WriteKind::MutableBorrow(bk) => {
                             let err =
                                 this.report_conflicting_borrow(location, place_span, bk, borrow);
-                            this.buffer_error(err);
+                            this.buffer_conflicting_access_error(
+                                err,
+                                location,
+                                place_span,
+                                Some(bk),
+                                borrow,
+                            );
                         }
                         WriteKind::StorageDeadOrDrop => this
                             .report_borrowed_value_does_not_live_long_enough(
@@ -2409,12 +2433,16 @@ fn dominators(&self) -> &Dominators<BasicBlock> {
 
 mod diags {
     use rustc_errors::ErrorGuaranteed;
+    use rustc_errors::json::BorrowckExplanation;
 
     use super::*;
 
     enum BufferedDiag<'infcx> {
         Error(Diag<'infcx>),
         NonError(Diag<'infcx, ()>),
+        /// With `-Zborrowck-explain-json`, the explanation of the error buffered just before it,
+        /// with the same sort span so that it's emitted next to it.
+        BorrowckExplanation(Span, BorrowckExplanation),
     }
 
     impl<'infcx> BufferedDiag<'infcx> {
@@ -2422,6 +2450,7 @@ fn sort_span(&self) -> Span {
             match self {
                 BufferedDiag::Error(diag) => diag.sort_span,
                 BufferedDiag::NonError(diag) => diag.sort_span,
+                BufferedDiag::BorrowckExplanation(sort_span, _) => *sort_span,
             }
         }
     }
@@ -2465,6 +2494,14 @@ pub(crate) fn buffer_error(&mut self, diag: Diag<'infcx>) {
         pub(crate) fn buffer_non_error(&mut self, diag: Diag<'infcx, ()>) {
             self.buffered_diags.push(BufferedDiag::NonError(diag));
         }
+
+        pub(crate) fn buffer_borrowck_explanation(
+            &mut self,
+            sort_span: Span,
+            explanation: BorrowckExplanation,
+        ) {
+            self.buffered_diags.push(BufferedDiag::BorrowckExplanation(sort_span, explanation));
+        }
     }
 
     impl<'infcx, 'tcx> MirBorrowckCtxt<'_, 'infcx, 'tcx> {
@@ -2476,6 +2513,14 @@ pub(crate) fn buffer_non_error(&mut self, diag: Diag<'infcx, ()>) {
             self.diags.buffer_non_error(diag);
         }
 
+        pub(crate) fn buffer_borrowck_explanation(
+            &mut self,
+            sort_span: Span,
+            explanation: BorrowckExplanation,
+        ) {
+            self.diags.buffer_borrowck_explanation(sort_span, explanation);
+        }
+
         pub(crate) fn buffer_move_error(
             &mut self,
             move_out_indices: Vec<MoveOutIndex>,
@@ -2523,10 +2568,14 @@ pub(crate) fn emit_errors(&mut self) -> Option<ErrorGuaranteed> {
 
             if !self.diags.buffered_diags.is_empty() {
                 self.diags.buffered_diags.sort_by_key(|buffered_diag| buffered_diag.sort_span());
+                let dcx = self.infcx.dcx();
                 for buffered_diag in self.diags.buffered_diags.drain(..) {
                     match buffered_diag {
                         BufferedDiag::Error(diag) => res = Some(diag.emit()),
                         BufferedDiag::NonError(diag) => diag.emit(),
+                        BufferedDiag::BorrowckExplanation(_, explanation) => {
+                            dcx.emit_borrowck_explanation(&explanation)
+                        }
                     }
                 }
             }
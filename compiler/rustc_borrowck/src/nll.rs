@@ -101,21 +101,25 @@ pub(crate) fn compute_regions<'a, 'tcx>(
     let elements = Rc::new(DenseLocationMap::new(body));
 
     // Run the MIR type-checker.
-    let MirTypeckResults { constraints, universal_region_relations, opaque_type_values } =
-        type_check::type_check(
-            infcx,
-            param_env,
-            body,
-            promoted,
-            universal_regions.clone(),
-            location_table,
-            borrow_set,
-            &mut all_facts,
-            flow_inits,
-            move_data,
-            elements.clone(),
-            upvars,
-        );
+    let MirTypeckResults {
+        constraints,
+        universal_region_relations,
+        opaque_type_values,
+        polonius_context,
+    } = type_check::type_check(
+        infcx,
+        param_env,
+        body,
+        promoted,
+        universal_regions.clone(),
+        location_table,
+        borrow_set,
+        &mut all_facts,
+        flow_inits,
+        move_data,
+        elements.clone(),
+        upvars,
+    );
 
     // Create the region inference context, taking ownership of the
     // region inference data that was contained in `infcx`, and the
@@ -123,8 +127,8 @@ pub(crate) fn compute_regions<'a, 'tcx>(
     let var_origins = infcx.get_region_var_origins();
     let MirTypeckRegionConstraints {
         placeholder_indices,
-        placeholder_index_to_region: _,
-        liveness_constraints,
+        placeholder_index_to_region,
+        mut liveness_constraints,
         mut outlives_constraints,
         mut member_constraints,
         universe_causes,
@@ -144,6 +148,22 @@ pub(crate) fn compute_regions<'a, 'tcx>(
         &universal_region_relations,
     );
 
+    // If requested, compute the loans that are live at each point, with the location-sensitive
+    // polonius analysis. This has to be done before the outlives constraints are cleared below.
+    if let Some(polonius_context) = &polonius_context {
+        let live_loans = polonius_context.compute_live_loans(
+            infcx.tcx,
+            body,
+            &elements,
+            &universal_regions,
+            placeholder_index_to_region.iter().map(|r| r.as_var()),
+            &liveness_constraints,
+            &outlives_constraints,
+            borrow_set,
+        );
+        liveness_constraints.record_live_loans(live_loans);
+    }
+
     if let Some(guar) = universal_regions.tainted_by_errors() {
         // Suppress unhelpful extra errors in `infer_opaque_types` by clearing out all
         // outlives bounds that we may end up checking.
@@ -0,0 +1,54 @@
+use rustc_data_structures::fx::FxHashMap;
+use rustc_middle::ty::RegionVid;
+use rustc_mir_dataflow::points::PointIndex;
+
+/// A region at a given point in the CFG: the node of the localized constraint graph.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub(super) struct LocalizedNode {
+    pub(super) region: RegionVid,
+    pub(super) point: PointIndex,
+}
+
+/// The edges of the localized constraint graph, along which loans flow from a region at a point to
+/// another region at a (possibly different) point.
+///
+/// Typeck constraints that hold at a single point are localized there, and are stored as physical
+/// edges. Constraints that hold at all points are stored once as logical edges, and are applied at
+/// whichever point a loan is being traversed. The edges due to liveness are not stored, they're
+/// computed during traversal, see `LivenessEdges`.
+#[derive(Default, Debug)]
+pub(super) struct LocalizedConstraintGraph {
+    physical_edges: FxHashMap<LocalizedNode, Vec<LocalizedNode>>,
+    logical_edges: FxHashMap<RegionVid, Vec<RegionVid>>,
+}
+
+impl LocalizedConstraintGraph {
+    /// Records that loans flow from `source` into `target`.
+    pub(super) fn add_physical_edge(&mut self, source: LocalizedNode, target: LocalizedNode) {
+        if source != target {
+            self.physical_edges.entry(source).or_default().push(target);
+        }
+    }
+
+    /// Records that loans flow from `source` into `target` at every point.
+    pub(super) fn add_logical_edge(&mut self, source: RegionVid, target: RegionVid) {
+        if source != target {
+            self.logical_edges.entry(source).or_default().push(target);
+        }
+    }
+
+    /// Returns the nodes that loans flow into from `node`, via the typeck constraints.
+    pub(super) fn outgoing_edges(
+        &self,
+        node: LocalizedNode,
+    ) -> impl Iterator<Item = LocalizedNode> + '_ {
+        let physical_edges = self.physical_edges.get(&node).into_iter().flatten().copied();
+        let logical_edges = self
+            .logical_edges
+            .get(&node.region)
+            .into_iter()
+            .flatten()
+            .map(move |&region| LocalizedNode { region, point: node.point });
+        physical_edges.chain(logical_edges)
+    }
+}
@@ -0,0 +1,205 @@
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_middle::mir::{Body, Location};
+use rustc_middle::ty::relate::{
+    Relate, RelateResult, TypeRelation, structurally_relate_consts, structurally_relate_tys,
+};
+use rustc_middle::ty::{self, RegionVid, Ty, TyCtxt};
+use rustc_mir_dataflow::points::{DenseLocationMap, PointIndex};
+
+use super::constraints::LocalizedNode;
+use crate::region_infer::values::LivenessValues;
+use crate::universal_regions::UniversalRegions;
+
+/// The direction in which loans flow between the points where a live region is live, depending on
+/// the variance of this region in the type of the live value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) enum ConstraintDirection {
+    /// Loans flow to the successor points: the region is covariant.
+    Forward,
+    /// Loans flow to the predecessor points: the region is contravariant.
+    Backward,
+    /// Loans flow in both directions: the region is invariant, or has been seen with different
+    /// variances.
+    Bidirectional,
+}
+
+/// Extracts the variance of the free regions of a value, to know in which direction loans flow
+/// between the points where these regions are live. The value is related with itself, which can't
+/// fail.
+pub(super) struct VarianceExtractor<'a, 'tcx> {
+    pub(super) tcx: TyCtxt<'tcx>,
+    pub(super) ambient_variance: ty::Variance,
+    pub(super) directions: &'a mut FxHashMap<RegionVid, ConstraintDirection>,
+    pub(super) universal_regions: &'a UniversalRegions<'tcx>,
+}
+
+impl<'tcx> VarianceExtractor<'_, 'tcx> {
+    fn record_variance(&mut self, region: ty::Region<'tcx>, variance: ty::Variance) {
+        // We're only interested in the variance of the free regions, bound regions are not live.
+        if region.is_bound() {
+            return;
+        }
+
+        let direction = match variance {
+            ty::Covariant => ConstraintDirection::Forward,
+            ty::Contravariant => ConstraintDirection::Backward,
+            ty::Invariant => ConstraintDirection::Bidirectional,
+            // Loans don't flow through bivariant regions.
+            ty::Bivariant => return,
+        };
+
+        let region = self.universal_regions.to_region_vid(region);
+        self.directions
+            .entry(region)
+            .and_modify(|entry| {
+                // If a region is both covariant and contravariant, loans flow in both directions.
+                if *entry != direction {
+                    *entry = ConstraintDirection::Bidirectional;
+                }
+            })
+            .or_insert(direction);
+    }
+}
+
+impl<'tcx> TypeRelation<TyCtxt<'tcx>> for VarianceExtractor<'_, 'tcx> {
+    fn cx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn relate_with_variance<T: Relate<TyCtxt<'tcx>>>(
+        &mut self,
+        variance: ty::Variance,
+        _info: ty::VarianceDiagInfo<TyCtxt<'tcx>>,
+        a: T,
+        b: T,
+    ) -> RelateResult<'tcx, T> {
+        let old_ambient_variance = self.ambient_variance;
+        self.ambient_variance = self.ambient_variance.xform(variance);
+        let r = self.relate(a, b)?;
+        self.ambient_variance = old_ambient_variance;
+        Ok(r)
+    }
+
+    fn tys(&mut self, a: Ty<'tcx>, b: Ty<'tcx>) -> RelateResult<'tcx, Ty<'tcx>> {
+        assert_eq!(a, b); // we are misusing TypeRelation here; both LHS and RHS ought to be ==
+        structurally_relate_tys(self, a, b)
+    }
+
+    fn regions(
+        &mut self,
+        a: ty::Region<'tcx>,
+        b: ty::Region<'tcx>,
+    ) -> RelateResult<'tcx, ty::Region<'tcx>> {
+        assert_eq!(a, b); // we are misusing TypeRelation here; both LHS and RHS ought to be ==
+        self.record_variance(a, self.ambient_variance);
+        Ok(a)
+    }
+
+    fn consts(
+        &mut self,
+        a: ty::Const<'tcx>,
+        b: ty::Const<'tcx>,
+    ) -> RelateResult<'tcx, ty::Const<'tcx>> {
+        assert_eq!(a, b); // we are misusing TypeRelation here; both LHS and RHS ought to be ==
+        structurally_relate_consts(self, a, b)
+    }
+
+    fn binders<T>(
+        &mut self,
+        a: ty::Binder<'tcx, T>,
+        _: ty::Binder<'tcx, T>,
+    ) -> RelateResult<'tcx, ty::Binder<'tcx, T>>
+    where
+        T: Relate<TyCtxt<'tcx>>,
+    {
+        self.relate(a.skip_binder(), a.skip_binder())?;
+        Ok(a)
+    }
+}
+
+/// The edges of the localized constraint graph due to liveness: loans flowing into a live region
+/// stay in that region at the adjacent points where it's live, in the direction given by its
+/// variance. These edges are not materialized, they are computed during traversal.
+pub(super) struct LivenessEdges<'a, 'tcx> {
+    body: &'a Body<'tcx>,
+    elements: &'a DenseLocationMap,
+    liveness: &'a LivenessValues,
+    live_region_variances: &'a FxHashMap<RegionVid, ConstraintDirection>,
+    /// The universal and placeholder regions, which are live at all points.
+    live_at_all_points: FxHashSet<RegionVid>,
+}
+
+impl<'a, 'tcx> LivenessEdges<'a, 'tcx> {
+    pub(super) fn new(
+        body: &'a Body<'tcx>,
+        elements: &'a DenseLocationMap,
+        liveness: &'a LivenessValues,
+        live_region_variances: &'a FxHashMap<RegionVid, ConstraintDirection>,
+        live_at_all_points: FxHashSet<RegionVid>,
+    ) -> Self {
+        LivenessEdges { body, elements, liveness, live_region_variances, live_at_all_points }
+    }
+
+    /// Returns whether `region` is live at `point`.
+    pub(super) fn is_live_at(&self, region: RegionVid, point: PointIndex) -> bool {
+        self.live_at_all_points.contains(&region) || self.liveness.is_live_at_point(region, point)
+    }
+
+    /// Calls `f` on each node that loans flow into from `node` due to liveness.
+    pub(super) fn for_each_successor(&self, node: LocalizedNode, mut f: impl FnMut(LocalizedNode)) {
+        let LocalizedNode { region, point } = node;
+        if !self.is_live_at(region, point) {
+            return;
+        }
+
+        // Loans flowing into regions that are live at all points escape the function from there:
+        // they flow forward, but not backward to points before they entered the region.
+        let direction = if self.live_at_all_points.contains(&region) {
+            ConstraintDirection::Forward
+        } else {
+            // The regions whose variance is unknown are conservatively considered invariant.
+            self.live_region_variances
+                .get(&region)
+                .copied()
+                .unwrap_or(ConstraintDirection::Bidirectional)
+        };
+
+        let mut visit = |point| {
+            if self.is_live_at(region, point) {
+                f(LocalizedNode { region, point });
+            }
+        };
+        if direction != ConstraintDirection::Backward {
+            self.for_each_successor_point(point, &mut visit);
+        }
+        if direction != ConstraintDirection::Forward {
+            self.for_each_predecessor_point(point, &mut visit);
+        }
+    }
+
+    /// Calls `f` on the CFG successors of `point`.
+    fn for_each_successor_point(&self, point: PointIndex, mut f: impl FnMut(PointIndex)) {
+        let location = self.elements.to_location(point);
+        if location.statement_index < self.body[location.block].statements.len() {
+            f(self.elements.point_from_location(location.successor_within_block()));
+        } else {
+            for successor in self.body[location.block].terminator().successors() {
+                f(self.elements.entry_point(successor));
+            }
+        }
+    }
+
+    /// Calls `f` on the CFG predecessors of `point`.
+    fn for_each_predecessor_point(&self, point: PointIndex, mut f: impl FnMut(PointIndex)) {
+        let location = self.elements.to_location(point);
+        if location.statement_index > 0 {
+            let predecessor =
+                Location { block: location.block, statement_index: location.statement_index - 1 };
+            f(self.elements.point_from_location(predecessor));
+        } else {
+            for &predecessor in &self.body.basic_blocks.predecessors()[location.block] {
+                f(self.elements.point_from_location(self.body.terminator_loc(predecessor)));
+            }
+        }
+    }
+}
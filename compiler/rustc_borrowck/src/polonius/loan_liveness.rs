@@ -0,0 +1,172 @@
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_index::bit_set::{BitSet, SparseBitMatrix};
+use rustc_middle::mir::visit::Visitor;
+use rustc_middle::mir::{
+    Body, Local, Location, Place, Statement, StatementKind, Terminator, TerminatorKind,
+};
+use rustc_middle::ty::TyCtxt;
+use rustc_mir_dataflow::points::{DenseLocationMap, PointIndex};
+
+use super::constraints::{LocalizedConstraintGraph, LocalizedNode};
+use super::liveness_constraints::LivenessEdges;
+use crate::region_infer::values::LiveLoans;
+use crate::{BorrowIndex, BorrowSet, PlaceConflictBias, places_conflict};
+
+/// Computes the loans that are live at each point: the loans that flow into a region at a point
+/// where it is live.
+///
+/// All the loans are propagated at once through the localized constraint graph, from their issuing
+/// region, until a fixpoint is reached: each node is only revisited when new loans flow into it,
+/// instead of traversing the graph once per loan. Loans don't flow past the points where they are
+/// killed, that is where the borrowed place is overwritten or goes out of storage.
+pub(super) fn compute_loan_liveness<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &Body<'tcx>,
+    elements: &DenseLocationMap,
+    borrow_set: &BorrowSet<'tcx>,
+    localized_graph: &LocalizedConstraintGraph,
+    liveness_edges: &LivenessEdges<'_, 'tcx>,
+) -> LiveLoans {
+    let kills = collect_kills(tcx, body, elements, borrow_set);
+
+    // The loans flowing into each node of the graph, and the nodes whose loans have changed since
+    // they were last propagated to their successors.
+    let mut inflowing_loans: FxHashMap<LocalizedNode, BitSet<BorrowIndex>> = FxHashMap::default();
+    let mut worklist = Vec::new();
+    let mut in_worklist = FxHashSet::default();
+
+    for (loan_idx, loan) in borrow_set.iter_enumerated() {
+        let start = LocalizedNode {
+            region: loan.region,
+            point: elements.point_from_location(loan.reserve_location),
+        };
+        inflowing_loans
+            .entry(start)
+            .or_insert_with(|| BitSet::new_empty(borrow_set.len()))
+            .insert(loan_idx);
+        if in_worklist.insert(start) {
+            worklist.push(start);
+        }
+    }
+
+    while let Some(node) = worklist.pop() {
+        in_worklist.remove(&node);
+
+        // The loans killed at this point can still flow into other regions here, but not to other
+        // points.
+        let loans = inflowing_loans[&node].clone();
+        let mut unkilled_loans = loans.clone();
+        if let Some(killed_loans) = kills.row(node.point) {
+            unkilled_loans.subtract(killed_loans);
+        }
+
+        let mut visit = |successor: LocalizedNode| {
+            let loans = if successor.point == node.point { &loans } else { &unkilled_loans };
+            let changed = inflowing_loans
+                .entry(successor)
+                .or_insert_with(|| BitSet::new_empty(borrow_set.len()))
+                .union(loans);
+            if changed && in_worklist.insert(successor) {
+                worklist.push(successor);
+            }
+        };
+        localized_graph.outgoing_edges(node).for_each(&mut visit);
+        liveness_edges.for_each_successor(node, &mut visit);
+    }
+
+    let mut live_loans = LiveLoans::new(borrow_set.len());
+    // We can iterate the HashMap here because the union of the rows doesn't depend on the order.
+    #[allow(rustc::potential_query_instability)]
+    for (node, loans) in &inflowing_loans {
+        if liveness_edges.is_live_at(node.region, node.point) {
+            live_loans.union_row(node.point, loans);
+        }
+    }
+    live_loans
+}
+
+/// Collects, for each point, the loans killed there.
+fn collect_kills<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &Body<'tcx>,
+    elements: &DenseLocationMap,
+    borrow_set: &BorrowSet<'tcx>,
+) -> SparseBitMatrix<PointIndex, BorrowIndex> {
+    let mut collector = KillsCollector {
+        tcx,
+        body,
+        elements,
+        borrow_set,
+        kills: SparseBitMatrix::new(borrow_set.len()),
+    };
+    collector.visit_body(body);
+    collector.kills
+}
+
+struct KillsCollector<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    body: &'a Body<'tcx>,
+    elements: &'a DenseLocationMap,
+    borrow_set: &'a BorrowSet<'tcx>,
+    kills: SparseBitMatrix<PointIndex, BorrowIndex>,
+}
+
+impl<'tcx> KillsCollector<'_, 'tcx> {
+    /// Records the loans of `local` as killed at `location`.
+    fn kill_loans_of_local(&mut self, local: Local, location: Location) {
+        let Some(borrow_indices) = self.borrow_set.local_map.get(&local) else {
+            return;
+        };
+        let point = self.elements.point_from_location(location);
+        for &borrow_index in borrow_indices {
+            self.kills.insert(point, borrow_index);
+        }
+    }
+
+    /// Records the loans of `place` as killed at `location`, like the `Borrows` dataflow analysis
+    /// does.
+    fn kill_loans_on_place(&mut self, place: Place<'tcx>, location: Location) {
+        // If the borrowed place is a local with no projections, all other borrows of this local
+        // must conflict.
+        if place.projection.is_empty() {
+            if !self.body.local_decls[place.local].is_ref_to_static() {
+                self.kill_loans_of_local(place.local, location);
+            }
+            return;
+        }
+
+        let Some(borrow_indices) = self.borrow_set.local_map.get(&place.local) else {
+            return;
+        };
+        let point = self.elements.point_from_location(location);
+        for &borrow_index in borrow_indices {
+            // By passing `PlaceConflictBias::NoOverlap`, we only kill the loans of the places
+            // that definitely conflict.
+            if places_conflict(
+                self.tcx,
+                self.body,
+                self.borrow_set[borrow_index].borrowed_place,
+                place,
+                PlaceConflictBias::NoOverlap,
+            ) {
+                self.kills.insert(point, borrow_index);
+            }
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for KillsCollector<'_, 'tcx> {
+    fn visit_statement(&mut self, statement: &Statement<'tcx>, location: Location) {
+        match &statement.kind {
+            StatementKind::Assign(box (place, _)) => self.kill_loans_on_place(*place, location),
+            StatementKind::StorageDead(local) => self.kill_loans_of_local(*local, location),
+            _ => {}
+        }
+    }
+
+    fn visit_terminator(&mut self, terminator: &Terminator<'tcx>, location: Location) {
+        if let TerminatorKind::Call { destination, .. } = terminator.kind {
+            self.kill_loans_on_place(destination, location);
+        }
+    }
+}
@@ -1,21 +1,113 @@
-//! Functions dedicated to fact generation for the `-Zpolonius=legacy` datalog implementation.
+//! Polonius-related parts of borrowck:
+//! - fact generation for the `-Zpolonius=legacy` datalog implementation. It will be removed in the
+//!   future, once the in-tree `-Zpolonius=next` implementation reaches feature parity.
+//! - the location-sensitive analysis of `-Zpolonius=next`, computing the loans that are live at
+//!   each point.
 //!
-//! Will be removed in the future, once the in-tree `-Zpolonius=next` implementation reaches feature
-//! parity.
+//! The location-sensitive analysis builds a localized constraint graph, whose nodes are regions at
+//! a given point in the CFG:
+//! - the typeck constraints are localized at the points where they hold, or hold at all points.
+//! - the liveness constraints make loans flow between the adjacent points where a region is live,
+//!   forward or backward depending on the variance of the region in the type of the live value.
+//!
+//! A loan is then live at a point if it can reach, from its issuing region and point, a region
+//! that is live at that point, without going past the points where the loan is killed.
 
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_middle::mir::{Body, LocalKind, Location, START_BLOCK};
-use rustc_middle::ty::TyCtxt;
+use rustc_middle::ty::relate::{Relate, TypeRelation};
+use rustc_middle::ty::{self, RegionVid, TyCtxt};
 use rustc_mir_dataflow::move_paths::{InitKind, InitLocation, MoveData};
+use rustc_mir_dataflow::points::DenseLocationMap;
 use tracing::debug;
 
+use self::constraints::LocalizedConstraintGraph;
+use self::liveness_constraints::{ConstraintDirection, LivenessEdges, VarianceExtractor};
 use crate::borrow_set::BorrowSet;
+use crate::constraints::OutlivesConstraintSet;
 use crate::facts::{AllFacts, PoloniusRegionVid};
 use crate::location::LocationTable;
+use crate::region_infer::values::{LiveLoans, LivenessValues};
 use crate::type_check::free_region_relations::UniversalRegionRelations;
 use crate::universal_regions::UniversalRegions;
 
+mod constraints;
+mod liveness_constraints;
 mod loan_invalidations;
 mod loan_kills;
+mod loan_liveness;
+mod typeck_constraints;
+
+/// The data recorded during MIR typeck for the location-sensitive analysis of `-Zpolonius=next`.
+#[derive(Default)]
+pub(crate) struct PoloniusContext {
+    /// The direction in which loans flow between the points where a region is live, given by the
+    /// variance of the region in the types of the live values.
+    live_region_variances: FxHashMap<RegionVid, ConstraintDirection>,
+}
+
+impl PoloniusContext {
+    /// Records the variance of the free regions of `value`, a value that is live at some points.
+    pub(crate) fn record_live_region_variance<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        universal_regions: &UniversalRegions<'tcx>,
+        value: impl Relate<TyCtxt<'tcx>>,
+    ) {
+        let mut extractor = VarianceExtractor {
+            tcx,
+            ambient_variance: ty::Variance::Covariant,
+            directions: &mut self.live_region_variances,
+            universal_regions,
+        };
+        extractor.relate(value, value).expect("Can't have a type error relating to itself");
+    }
+
+    /// Computes the loans that are live at each point, by localizing the typeck and liveness
+    /// constraints, and propagating the loans through the resulting graph.
+    pub(crate) fn compute_live_loans<'tcx>(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        body: &Body<'tcx>,
+        elements: &DenseLocationMap,
+        universal_regions: &UniversalRegions<'tcx>,
+        placeholder_regions: impl Iterator<Item = RegionVid>,
+        liveness: &LivenessValues,
+        outlives_constraints: &OutlivesConstraintSet<'tcx>,
+        borrow_set: &BorrowSet<'tcx>,
+    ) -> LiveLoans {
+        let _prof_timer = tcx.prof.generic_activity("polonius_compute_live_loans");
+
+        let mut localized_graph = LocalizedConstraintGraph::default();
+        typeck_constraints::localize_typeck_constraints(
+            tcx,
+            body,
+            elements,
+            universal_regions,
+            outlives_constraints,
+            &mut localized_graph,
+        );
+
+        let live_at_all_points: FxHashSet<_> =
+            universal_regions.universal_regions().chain(placeholder_regions).collect();
+        let liveness_edges = LivenessEdges::new(
+            body,
+            elements,
+            liveness,
+            &self.live_region_variances,
+            live_at_all_points,
+        );
+
+        loan_liveness::compute_loan_liveness(
+            tcx,
+            body,
+            elements,
+            borrow_set,
+            &localized_graph,
+            &liveness_edges,
+        )
+    }
+}
 
 /// When requested, emit most of the facts needed by polonius:
 /// - moves and assignments
@@ -0,0 +1,80 @@
+use either::Either;
+use rustc_middle::mir::{Body, Location, StatementKind, TerminatorKind};
+use rustc_middle::ty::{RegionVid, Ty, TyCtxt};
+use rustc_mir_dataflow::points::DenseLocationMap;
+use tracing::debug;
+
+use super::constraints::{LocalizedConstraintGraph, LocalizedNode};
+use crate::constraints::OutlivesConstraintSet;
+use crate::type_check::Locations;
+use crate::universal_regions::UniversalRegions;
+
+/// Converts the typeck constraints into edges of the localized constraint graph.
+///
+/// A constraint `'a: 'b` means loans flow from `'a` into `'b`. When it holds at a single point,
+/// it's localized there, except for assignments: the assigned place only holds the new value after
+/// the assignment, so its regions are related at the successor point.
+pub(super) fn localize_typeck_constraints<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &Body<'tcx>,
+    elements: &DenseLocationMap,
+    universal_regions: &UniversalRegions<'tcx>,
+    outlives_constraints: &OutlivesConstraintSet<'tcx>,
+    localized_graph: &mut LocalizedConstraintGraph,
+) {
+    for constraint in outlives_constraints.outlives() {
+        match constraint.locations {
+            Locations::All(_) => {
+                localized_graph.add_logical_edge(constraint.sup, constraint.sub);
+            }
+            Locations::Single(location) => {
+                let point = elements.point_from_location(location);
+                let (mut source_point, mut target_point) = (point, point);
+
+                if let Some((assigned_ty, successor)) = assignment_at(tcx, body, location) {
+                    let successor = elements.point_from_location(successor);
+                    let mentions = |region: RegionVid| {
+                        tcx.any_free_region_meets(&assigned_ty, |r| {
+                            universal_regions.to_region_vid(r) == region
+                        })
+                    };
+                    if mentions(constraint.sub) {
+                        target_point = successor;
+                    }
+                    if mentions(constraint.sup) {
+                        source_point = successor;
+                    }
+                }
+
+                let source = LocalizedNode { region: constraint.sup, point: source_point };
+                let target = LocalizedNode { region: constraint.sub, point: target_point };
+                debug!(?constraint, ?source, ?target, "localized typeck constraint");
+                localized_graph.add_physical_edge(source, target);
+            }
+        }
+    }
+}
+
+/// If the statement or terminator at `location` assigns a place, returns the type of that place
+/// and the location where the assignment has taken effect.
+fn assignment_at<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &Body<'tcx>,
+    location: Location,
+) -> Option<(Ty<'tcx>, Location)> {
+    match body.stmt_at(location) {
+        Either::Left(statement) => {
+            let StatementKind::Assign(box (place, _)) = &statement.kind else {
+                return None;
+            };
+            Some((place.ty(body, tcx).ty, location.successor_within_block()))
+        }
+        Either::Right(terminator) => {
+            let TerminatorKind::Call { destination, target: Some(target), .. } = terminator.kind
+            else {
+                return None;
+            };
+            Some((destination.ty(body, tcx).ty, target.start_location()))
+        }
+    }
+}
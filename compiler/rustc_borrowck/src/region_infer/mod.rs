@@ -583,6 +583,14 @@ pub(crate) fn region_contains(&self, r: RegionVid, p: impl ToElementIndex) -> bo
         self.scc_values.contains(scc, p)
     }
 
+    /// Returns `true` if the region `r` contains the given MIR `location`.
+    ///
+    /// This is `pub` because it's used by unstable external borrowck data users, see
+    /// `consumers.rs`. Panics if called before `solve()` executes.
+    pub fn region_contains_location(&self, r: RegionVid, location: Location) -> bool {
+        self.region_contains(r, location)
+    }
+
     /// Returns the lowest statement index in `start..=end` which is not contained by `r`.
     ///
     /// Panics if called before `solve()` executes.
@@ -2211,22 +2219,8 @@ pub(crate) fn region_graph(&self) -> RegionGraph<'_, 'tcx, graph::Normal> {
         self.constraint_graph.region_graph(&self.constraints, self.universal_regions.fr_static)
     }
 
-    /// Returns whether the given region is considered live at all points: whether it is a
-    /// placeholder or a free region.
-    pub(crate) fn is_region_live_at_all_points(&self, region: RegionVid) -> bool {
-        // FIXME: there must be a cleaner way to find this information. At least, when
-        // higher-ranked subtyping is abstracted away from the borrowck main path, we'll only
-        // need to check whether this is a universal region.
-        let origin = self.region_definition(region).origin;
-        let live_at_all_points = matches!(
-            origin,
-            NllRegionVariableOrigin::Placeholder(_) | NllRegionVariableOrigin::FreeRegion
-        );
-        live_at_all_points
-    }
-
-    /// Returns whether the `loan_idx` is live at the given `location`: whether its issuing
-    /// region is contained within the type of a variable that is live at this point.
+    /// Returns whether the `loan_idx` is live at the given `location`: whether it flows, at this
+    /// point, into a region contained within the type of a variable that is live there.
     /// Note: for now, the sets of live loans is only available when using `-Zpolonius=next`.
     pub(crate) fn is_loan_live_at(&self, loan_idx: BorrowIndex, location: Location) -> bool {
         let point = self.liveness_constraints.point_from_location(location);
@@ -37,11 +37,19 @@ pub(super) fn generate<'a, 'tcx>(
 ) {
     debug!("liveness::generate");
 
-    let free_regions = regions_that_outlive_free_regions(
-        typeck.infcx.num_region_vars(),
-        typeck.borrowck_context.universal_regions,
-        &typeck.borrowck_context.constraints.outlives_constraints,
-    );
+    let free_regions = if typeck.tcx().sess.opts.unstable_opts.polonius.is_next_enabled() {
+        // When using `-Zpolonius=next`, a region that outlives a free region somewhere in the body
+        // is not live everywhere: the location-sensitive analysis needs to know where it is live to
+        // track the loans flowing through it. Only the locals whose regions are all free regions,
+        // which are live at all points, are boring.
+        typeck.borrowck_context.universal_regions.universal_regions().collect()
+    } else {
+        regions_that_outlive_free_regions(
+            typeck.infcx.num_region_vars(),
+            typeck.borrowck_context.universal_regions,
+            &typeck.borrowck_context.constraints.outlives_constraints,
+        )
+    };
     let (relevant_live_locals, boring_locals) =
         compute_relevant_live_locals(typeck.tcx(), &free_regions, body);
 
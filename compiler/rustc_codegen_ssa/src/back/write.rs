@@ -10,7 +10,7 @@
 use rustc_ast::attr;
 use rustc_data_structures::fx::{FxHashMap, FxIndexMap};
 use rustc_data_structures::memmap::Mmap;
-use rustc_data_structures::profiling::{SelfProfilerRef, VerboseTimingGuard};
+use rustc_data_structures::profiling::{ItemTimes, SelfProfilerRef, VerboseTimingGuard};
 use rustc_errors::emitter::Emitter;
 use rustc_errors::translation::Translate;
 use rustc_errors::{
@@ -28,10 +28,10 @@
 use rustc_middle::dep_graph::{WorkProduct, WorkProductId};
 use rustc_middle::middle::exported_symbols::SymbolExportInfo;
 use rustc_middle::ty::TyCtxt;
-use rustc_session::Session;
 use rustc_session::config::{
     self, CrateType, Lto, OutFileName, OutputFilenames, OutputType, Passes, SwitchWithOptPath,
 };
+use rustc_session::{Session, time_report};
 use rustc_span::source_map::SourceMap;
 use rustc_span::symbol::sym;
 use rustc_span::{BytePos, FileName, InnerSpan, Pos, Span};
@@ -345,6 +345,8 @@ pub struct CodegenContext<B: WriteBackendMethods> {
     pub save_temps: bool,
     pub fewer_names: bool,
     pub time_trace: bool,
+    /// The times of LLVM on codegen units, if requested with `-Ztime-report=items`.
+    pub codegen_unit_times: Option<Arc<ItemTimes<String>>>,
     pub exported_symbols: Option<Arc<ExportedSymbols>>,
     pub opts: Arc<config::Options>,
     pub crate_types: Vec<CrateType>,
@@ -1194,6 +1196,7 @@ fn start_executing_work<B: ExtraBackendMethods>(
         fewer_names: sess.fewer_names(),
         save_temps: sess.opts.cg.save_temps,
         time_trace: sess.opts.unstable_opts.llvm_time_trace,
+        codegen_unit_times: sess.time_report.as_ref().map(|r| Arc::clone(&r.codegen_units)),
         opts: Arc::new(sess.opts.clone()),
         prof: sess.prof.clone(),
         exported_symbols,
@@ -1830,7 +1833,12 @@ fn drop(&mut self) {
                 WorkItem::Optimize(m) => {
                     let _timer =
                         cgcx.prof.generic_activity_with_arg("codegen_module_optimize", &*m.name);
-                    execute_optimize_work_item(&cgcx, m, module_config)
+                    match &cgcx.codegen_unit_times {
+                        Some(times) => times.time(time_report::LLVM, m.name.clone(), || {
+                            execute_optimize_work_item(&cgcx, m, module_config)
+                        }),
+                        None => execute_optimize_work_item(&cgcx, m, module_config),
+                    }
                 }
                 WorkItem::CopyPostLtoArtifacts(m) => {
                     let _timer = cgcx.prof.generic_activity_with_arg(
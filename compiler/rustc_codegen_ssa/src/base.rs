@@ -22,8 +22,8 @@
 use rustc_middle::query::Providers;
 use rustc_middle::ty::layout::{HasTyCtxt, LayoutOf, TyAndLayout};
 use rustc_middle::ty::{self, Instance, Ty, TyCtxt};
-use rustc_session::Session;
 use rustc_session::config::{self, CrateType, EntryFnType, OptLevel, OutputType};
+use rustc_session::{Session, time_report};
 use rustc_span::symbol::sym;
 use rustc_span::{DUMMY_SP, Symbol};
 use rustc_target::abi::FIRST_VARIANT;
@@ -750,7 +750,7 @@ pub fn codegen_crate<B: ExtraBackendMethods>(
             let start_time = Instant::now();
 
             let pre_compiled_cgus = par_map(cgus, |(i, _)| {
-                let module = backend.compile_codegen_unit(tcx, codegen_units[i].name());
+                let module = compile_codegen_unit(&backend, tcx, codegen_units[i].name());
                 (i, module)
             });
 
@@ -774,7 +774,7 @@ pub fn codegen_crate<B: ExtraBackendMethods>(
                     cgu
                 } else {
                     let start_time = Instant::now();
-                    let module = backend.compile_codegen_unit(tcx, cgu.name());
+                    let module = compile_codegen_unit(&backend, tcx, cgu.name());
                     total_codegen_time += start_time.elapsed();
                     module
                 };
@@ -834,6 +834,23 @@ pub fn codegen_crate<B: ExtraBackendMethods>(
     ongoing_codegen
 }
 
+/// Generates the codegen unit named `cgu_name`, attributing the time it takes to it for
+/// `-Ztime-report=items`.
+fn compile_codegen_unit<B: ExtraBackendMethods>(
+    backend: &B,
+    tcx: TyCtxt<'_>,
+    cgu_name: Symbol,
+) -> (ModuleCodegen<B::Module>, u64) {
+    match &tcx.sess.time_report {
+        Some(time_report) => {
+            time_report.codegen_units.time(time_report::LLVM, cgu_name.to_string(), || {
+                backend.compile_codegen_unit(tcx, cgu_name)
+            })
+        }
+        None => backend.compile_codegen_unit(tcx, cgu_name),
+    }
+}
+
 /// Returns whether a call from the current crate to the [`Instance`] would produce a call
 /// from `compiler_builtins` to a symbol the linker must resolve.
 ///
@@ -1078,6 +1095,10 @@ pub fn determine_cgu_reuse<'tcx>(tcx: TyCtxt<'tcx>, cgu: &CodegenUnit<'tcx>) ->
     if tcx.dep_graph.previous_work_product(work_product_id).is_none() {
         // We don't have anything cached for this CGU. This can happen
         // if the CGU did not exist in the previous session.
+        tcx.dep_graph.explain_computation(
+            &cgu.codegen_dep_node(tcx),
+            &format!("there is no cached object file for the codegen unit `{}`", cgu.name()),
+        );
         return CguReuse::No;
     }
 
@@ -765,6 +765,17 @@ pub fn get_alloc_bytes_unchecked_raw_mut(
         interp_ok(alloc.get_bytes_unchecked_raw_mut())
     }
 
+    /// Gives raw mutable access to a writable `Allocation` that native code can access, without
+    /// bounds or alignment checks, so that machines calling native code can bring the allocation
+    /// in line with what native code did to its bytes.
+    /// The caller is responsible for calling the access hooks!
+    pub fn get_alloc_for_native_call_mut(
+        &mut self,
+        id: AllocId,
+    ) -> InterpResult<'tcx, &mut Allocation<M::Provenance, M::AllocExtra, M::Bytes>> {
+        interp_ok(self.get_alloc_raw_mut(id)?.0)
+    }
+
     /// Bounds-checked *but not align-checked* allocation access.
     pub fn get_ptr_alloc_mut<'a>(
         &'a mut self,
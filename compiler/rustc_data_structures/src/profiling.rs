@@ -82,9 +82,11 @@
 //! [mm]: https://github.com/rust-lang/measureme/
 
 use std::borrow::Borrow;
+use std::cell::Cell;
 use std::collections::hash_map::Entry;
 use std::error::Error;
 use std::fmt::Display;
+use std::hash::Hash;
 use std::intrinsics::unlikely;
 use std::path::Path;
 use std::sync::Arc;
@@ -97,8 +99,9 @@
 use smallvec::SmallVec;
 use tracing::warn;
 
-use crate::fx::FxHashMap;
+use crate::fx::{FxHashMap, FxIndexMap, FxIndexSet};
 use crate::outline;
+use crate::sync::Lock;
 
 bitflags::bitflags! {
     #[derive(Clone, Copy)]
@@ -855,6 +858,58 @@ pub fn duration_to_secs_str(dur: std::time::Duration) -> String {
     format!("{:.3}", dur.as_secs_f64())
 }
 
+thread_local! {
+    /// The total time of the activities timed by an `ItemTimes` nested in the one currently
+    /// running on this thread.
+    static NESTED_ITEM_TIME: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+}
+
+/// The times of activities attributed to the items they are about, e.g. source-level items or
+/// codegen units, for `-Z time-report=items`.
+///
+/// The time attributed to an activity is its self time: the time of the activities timed by any
+/// `ItemTimes` while it runs, e.g. type-checking another item, is attributed to these instead.
+pub struct ItemTimes<K> {
+    times: Lock<FxIndexMap<(&'static str, K), Duration>>,
+}
+
+impl<K> Default for ItemTimes<K> {
+    fn default() -> Self {
+        ItemTimes { times: Default::default() }
+    }
+}
+
+impl<K: Hash + Eq> ItemTimes<K> {
+    /// Runs `f`, attributing the time it takes to `activity` on `item`.
+    pub fn time<R>(&self, activity: &'static str, item: K, f: impl FnOnce() -> R) -> R {
+        let outer_nested_time = NESTED_ITEM_TIME.replace(Duration::ZERO);
+        let start = Instant::now();
+        let result = f();
+        let time = start.elapsed();
+        let nested_time = NESTED_ITEM_TIME.replace(outer_nested_time + time);
+        self.record(activity, item, time.saturating_sub(nested_time));
+        result
+    }
+
+    /// Attributes `time`, measured by the caller, to `activity` on `item`.
+    pub fn record(&self, activity: &'static str, item: K, time: Duration) {
+        *self.times.lock().entry((activity, item)).or_default() += time;
+    }
+
+    /// Returns the items with times recorded so far.
+    pub fn items(&self) -> FxIndexSet<K>
+    where
+        K: Clone,
+    {
+        self.times.lock().keys().map(|(_, item)| item.clone()).collect()
+    }
+
+    /// Takes the times recorded so far.
+    pub fn take(&self) -> FxIndexMap<(&'static str, K), Duration> {
+        std::mem::take(&mut *self.times.lock())
+    }
+}
+
 fn get_thread_id() -> u32 {
     std::thread::current().id().as_u64().get() as u32
 }
@@ -325,6 +325,7 @@ fn run_compiler(
         hash_untracked_state: None,
         register_lints: None,
         override_queries: None,
+        mir_passes: Vec::new(),
         make_codegen_backend,
         registry: diagnostics_registry(),
         using_internal_features,
@@ -346,11 +347,11 @@ fn run_compiler(
         },
     };
 
-    drop(default_early_dcx);
-
     callbacks.config(&mut config);
 
-    interface::run_compiler(config, |compiler| {
+    // The early diagnostics are kept until the end of the compilation, so that they end up in
+    // the same SARIF log as the diagnostics of the session.
+    let result = interface::run_compiler(config, |compiler| {
         let sess = &compiler.sess;
         let codegen_backend = &*compiler.codegen_backend;
 
@@ -469,7 +470,10 @@ fn run_compiler(
         }
 
         Ok(())
-    })
+    });
+
+    drop(default_early_dcx);
+    result
 }
 
 // Extract output directory and file from matches.
@@ -346,6 +346,20 @@ pub fn as_str(&self) -> Option<&str> {
             DiagMessage::FluentIdentifier(_, _) => None,
         }
     }
+
+    /// Returns the Fluent identifier of the message, followed by its attribute if any, e.g.
+    /// `borrowck_move_out_of_borrow` or `borrowck_move_out_of_borrow.label`. Unlike the message
+    /// itself, it doesn't change with the arguments or the language of the diagnostic.
+    ///
+    /// Non-translatable messages have no identifier, diagnostics are then identified by their lint
+    /// name, error code or creation site instead.
+    pub fn slug(&self) -> Option<String> {
+        match self {
+            DiagMessage::FluentIdentifier(id, None) => Some(id.to_string()),
+            DiagMessage::FluentIdentifier(id, Some(attr)) => Some(format!("{id}.{attr}")),
+            DiagMessage::Str(_) | DiagMessage::Translated(_) => None,
+        }
+    }
 }
 
 impl From<String> for DiagMessage {
@@ -4,6 +4,7 @@
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
 use std::panic;
+use std::path::PathBuf;
 use std::thread::panicking;
 
 use rustc_data_structures::fx::FxIndexMap;
@@ -18,7 +19,7 @@
 use crate::snippet::Style;
 use crate::{
     CodeSuggestion, DiagCtxtHandle, DiagMessage, ErrCode, ErrorGuaranteed, ExplicitBug, Level,
-    MultiSpan, StashKey, SubdiagMessage, Substitution, SubstitutionPart, SuggestionStyle,
+    MultiSpan, NewFile, StashKey, SubdiagMessage, Substitution, SubstitutionPart, SuggestionStyle,
     Suggestions,
 };
 
@@ -355,6 +356,21 @@ pub(crate) fn has_future_breakage(&self) -> bool {
         matches!(self.is_lint, Some(IsLint { has_future_breakage: true, .. }))
     }
 
+    /// Returns an identifier of the diagnostic that, unlike its message, doesn't change with the
+    /// arguments or the language: the Fluent identifier of the message, or else the name of the
+    /// lint or the error code. Diagnostics with none of these are `"unnamed"`.
+    pub(crate) fn slug(&self) -> String {
+        if let Some(slug) = self.messages.first().and_then(|(message, _)| message.slug()) {
+            slug
+        } else if let Some(IsLint { name, .. }) = &self.is_lint {
+            name.clone()
+        } else if let Some(code) = self.code {
+            code.to_string()
+        } else {
+            "unnamed".to_string()
+        }
+    }
+
     pub(crate) fn is_force_warn(&self) -> bool {
         match self.level {
             Level::ForceWarning(_) => {
@@ -903,19 +919,59 @@ pub fn multipart_suggestion_verbose(
     pub fn multipart_suggestion_with_style(
         &mut self,
         msg: impl Into<SubdiagMessage>,
-        mut suggestion: Vec<(Span, String)>,
+        suggestion: Vec<(Span, String)>,
+        applicability: Applicability,
+        style: SuggestionStyle,
+    ) -> &mut Self {
+        assert!(!suggestion.is_empty());
+        self.multifile_suggestion_with_style(msg, suggestion, vec![], applicability, style)
+    }
+
+    with_fn! { with_multifile_suggestion,
+    /// Show a suggestion that also creates files, given with their path and contents, e.g. a
+    /// module declaration along with the file of the module.
+    ///
+    /// The edits of existing code may be in different files, as with any multipart suggestion.
+    #[rustc_lint_diagnostics]
+    pub fn multifile_suggestion(
+        &mut self,
+        msg: impl Into<SubdiagMessage>,
+        edits: Vec<(Span, String)>,
+        new_files: Vec<(PathBuf, String)>,
+        applicability: Applicability,
+    ) -> &mut Self {
+        self.multifile_suggestion_with_style(
+            msg,
+            edits,
+            new_files,
+            applicability,
+            SuggestionStyle::ShowCode,
+        )
+    } }
+
+    /// [`Diag::multifile_suggestion()`] but you can set the [`SuggestionStyle`].
+    #[rustc_lint_diagnostics]
+    pub fn multifile_suggestion_with_style(
+        &mut self,
+        msg: impl Into<SubdiagMessage>,
+        mut edits: Vec<(Span, String)>,
+        new_files: Vec<(PathBuf, String)>,
         applicability: Applicability,
         style: SuggestionStyle,
     ) -> &mut Self {
         let mut seen = crate::FxHashSet::default();
-        suggestion.retain(|(span, msg)| seen.insert((span.lo(), span.hi(), msg.clone())));
+        edits.retain(|(span, msg)| seen.insert((span.lo(), span.hi(), msg.clone())));
 
-        let parts = suggestion
+        let parts = edits
             .into_iter()
             .map(|(span, snippet)| SubstitutionPart { snippet, span })
             .collect::<Vec<_>>();
+        let new_files = new_files
+            .into_iter()
+            .map(|(path, contents)| NewFile { path, contents })
+            .collect::<Vec<_>>();
 
-        assert!(!parts.is_empty());
+        assert!(!parts.is_empty() || !new_files.is_empty());
         debug_assert_eq!(
             parts.iter().find(|part| part.span.is_empty() && part.snippet.is_empty()),
             None,
@@ -928,7 +984,7 @@ pub fn multipart_suggestion_with_style(
         );
 
         self.push_suggestion(CodeSuggestion {
-            substitutions: vec![Substitution { parts }],
+            substitutions: vec![Substitution { parts, new_files }],
             msg: self.subdiagnostic_message_to_diagnostic_message(msg),
             style,
             applicability,
@@ -1010,6 +1066,7 @@ pub fn span_suggestion_with_style(
         self.push_suggestion(CodeSuggestion {
             substitutions: vec![Substitution {
                 parts: vec![SubstitutionPart { snippet: suggestion.to_string(), span: sp }],
+                new_files: vec![],
             }],
             msg: self.subdiagnostic_message_to_diagnostic_message(msg),
             style,
@@ -1074,7 +1131,10 @@ pub fn span_suggestions_with_style(
                     !(sp.is_empty() && snippet.is_empty()),
                     "Span must not be empty and have no suggestion"
                 );
-                Substitution { parts: vec![SubstitutionPart { snippet, span: sp }] }
+                Substitution {
+                    parts: vec![SubstitutionPart { snippet, span: sp }],
+                    new_files: vec![],
+                }
             })
             .collect();
         self.push_suggestion(CodeSuggestion {
@@ -1118,7 +1178,7 @@ pub fn multipart_suggestions(
                     "suggestion must not have overlapping parts",
                 );
 
-                Substitution { parts }
+                Substitution { parts, new_files: vec![] }
             })
             .collect();
 
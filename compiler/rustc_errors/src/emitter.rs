@@ -27,6 +27,7 @@
 use tracing::{debug, instrument, trace, warn};
 
 use crate::diagnostic::DiagLocation;
+use crate::json::BorrowckExplanation;
 use crate::snippet::{
     Annotation, AnnotationColumn, AnnotationType, Line, MultilineAnnotation, Style, StyledString,
 };
@@ -34,7 +35,8 @@
 use crate::translation::{Translate, to_fluent_args};
 use crate::{
     CodeSuggestion, DiagCtxt, DiagInner, DiagMessage, ErrCode, FluentBundle, LazyFallbackBundle,
-    Level, MultiSpan, Subdiag, SubstitutionHighlight, SuggestionStyle, TerminalUrl,
+    Level, MultiSpan, Subdiag, SubstitutionHighlight, SubstitutionPart, SuggestionStyle,
+    TerminalUrl,
 };
 
 /// Default column width, used in tests and when terminal dimensions cannot be determined.
@@ -195,6 +197,10 @@ fn emit_unused_externs(
     ) {
     }
 
+    /// Emit a structured explanation of a borrowck error.
+    /// Currently only supported for the JSON format.
+    fn emit_borrowck_explanation(&mut self, _explanation: &BorrowckExplanation) {}
+
     /// Checks if should show explanations about "rustc --explain"
     fn should_show_explain(&self) -> bool {
         true
@@ -232,6 +238,7 @@ fn primary_span_formatted(
                && let [substitution] = sugg.substitutions.as_slice()
                // don't display multipart suggestions as labels
                && let [part] = substitution.parts.as_slice()
+               && substitution.new_files.is_empty()
                // don't display long messages as labels
                && msg.split_whitespace().count() < 10
                // don't display multiline suggestions as labels
@@ -1807,11 +1814,7 @@ fn emit_suggestion_default(
         buffer.append(0, ": ", Style::HeaderMsg);
 
         let mut msg = vec![(suggestion.msg.to_owned(), Style::NoStyle)];
-        if suggestions
-            .iter()
-            .take(MAX_SUGGESTIONS)
-            .any(|(_, _, _, only_capitalization)| *only_capitalization)
-        {
+        if suggestions.iter().take(MAX_SUGGESTIONS).any(|spliced| spliced.only_capitalization) {
             msg.push((" (notice the capitalization difference)".into(), Style::NoStyle));
         }
         self.msgs_to_buffer(
@@ -1825,118 +1828,206 @@ fn emit_suggestion_default(
 
         let mut row_num = 2;
         draw_col_separator_no_space(&mut buffer, 1, max_line_num_len + 1);
-        for (complete, parts, highlights, _) in suggestions.iter().take(MAX_SUGGESTIONS) {
-            debug!(?complete, ?parts, ?highlights);
-
-            let has_deletion = parts.iter().any(|p| p.is_deletion(sm));
-            let is_multiline = complete.lines().count() > 1;
-
-            if let Some(span) = span.primary_span() {
-                // Compare the primary span of the diagnostic with the span of the suggestion
-                // being emitted. If they belong to the same file, we don't *need* to show the
-                // file name, saving in verbosity, but if it *isn't* we do need it, otherwise we're
-                // telling users to make a change but not clarifying *where*.
-                let loc = sm.lookup_char_pos(parts[0].span.lo());
-                if loc.file.name != sm.span_to_filename(span) && loc.file.name.is_real() {
-                    let arrow = "--> ";
-                    buffer.puts(row_num - 1, 0, arrow, Style::LineNumber);
-                    let filename = sm.filename_for_diagnostics(&loc.file.name);
-                    let offset = sm.doctest_offset_line(&loc.file.name, loc.line);
-                    let message = format!("{}:{}:{}", filename, offset, loc.col.0 + 1);
-                    if row_num == 2 {
-                        let col = usize::max(max_line_num_len + 1, arrow.len());
-                        buffer.puts(1, col, &message, Style::LineAndColumn);
-                    } else {
-                        buffer.append(row_num - 1, &message, Style::LineAndColumn);
-                    }
-                    for _ in 0..max_line_num_len {
-                        buffer.prepend(row_num - 1, " ", Style::NoStyle);
+        for spliced in suggestions.iter().take(MAX_SUGGESTIONS) {
+            for (complete, parts, highlights) in &spliced.files {
+                row_num = self.emit_suggestion_file(
+                    &mut buffer,
+                    row_num,
+                    sm,
+                    span,
+                    complete,
+                    parts,
+                    highlights,
+                    max_line_num_len,
+                );
+            }
+            // The files to create are shown after the edits of existing code, as added lines.
+            for new_file in spliced.new_files {
+                let name = FileName::Real(sm.path_mapping().to_real_filename(&new_file.path));
+                // When nothing was rendered yet, the header goes on the separator line after
+                // the message.
+                let mut row = if row_num == 2 { row_num - 1 } else { row_num };
+                buffer.puts(row, max_line_num_len, "--> ", Style::LineNumber);
+                buffer.append(
+                    row,
+                    &format!("{} (new file)", sm.filename_for_diagnostics(&name)),
+                    Style::LineAndColumn,
+                );
+                row += 1;
+                if !new_file.contents.is_empty() {
+                    draw_col_separator_no_space(&mut buffer, row, max_line_num_len + 1);
+                    row += 1;
+                    for (index, line) in new_file.contents.lines().enumerate() {
+                        buffer.puts(row, 0, &self.maybe_anonymized(index + 1), Style::LineNumber);
+                        buffer.puts(row, max_line_num_len + 1, "+ ", Style::Addition);
+                        buffer.puts(
+                            row,
+                            max_line_num_len + 3,
+                            &normalize_whitespace(line),
+                            Style::Addition,
+                        );
+                        row += 1;
                     }
-                    row_num += 1;
                 }
+                draw_col_separator_no_space(&mut buffer, row, max_line_num_len + 1);
+                row_num = row + 1;
             }
-            let show_code_change = if has_deletion && !is_multiline {
-                DisplaySuggestion::Diff
-            } else if let [part] = &parts[..]
-                && part.snippet.ends_with('\n')
-                && part.snippet.trim() == complete.trim()
-            {
-                // We are adding a line(s) of code before code that was already there.
-                DisplaySuggestion::Add
-            } else if (parts.len() != 1 || parts[0].snippet.trim() != complete.trim())
-                && !is_multiline
-            {
-                DisplaySuggestion::Underline
-            } else {
-                DisplaySuggestion::None
-            };
+        }
+        if suggestions.len() > MAX_SUGGESTIONS {
+            let others = suggestions.len() - MAX_SUGGESTIONS;
+            let msg = format!("and {} other candidate{}", others, pluralize!(others));
+            buffer.puts(row_num, max_line_num_len + 3, &msg, Style::NoStyle);
+        }
+        emit_to_destination(&buffer.render(), level, &mut self.dst, self.short_message)?;
+        Ok(())
+    }
 
-            if let DisplaySuggestion::Diff = show_code_change {
+    /// Renders the edits of a suggestion to one file, starting at `row_num`, and returns the row
+    /// following them.
+    fn emit_suggestion_file(
+        &self,
+        buffer: &mut StyledBuffer,
+        mut row_num: usize,
+        sm: &SourceMap,
+        span: &MultiSpan,
+        complete: &str,
+        parts: &[SubstitutionPart],
+        highlights: &[Vec<SubstitutionHighlight>],
+        max_line_num_len: usize,
+    ) -> usize {
+        debug!(?complete, ?parts, ?highlights);
+
+        let has_deletion = parts.iter().any(|p| p.is_deletion(sm));
+        let is_multiline = complete.lines().count() > 1;
+
+        if let Some(span) = span.primary_span() {
+            // Compare the primary span of the diagnostic with the span of the suggestion
+            // being emitted. If they belong to the same file, we don't *need* to show the
+            // file name, saving in verbosity, but if it *isn't* we do need it, otherwise we're
+            // telling users to make a change but not clarifying *where*.
+            let loc = sm.lookup_char_pos(parts[0].span.lo());
+            if loc.file.name != sm.span_to_filename(span) && loc.file.name.is_real() {
+                let arrow = "--> ";
+                buffer.puts(row_num - 1, 0, arrow, Style::LineNumber);
+                let filename = sm.filename_for_diagnostics(&loc.file.name);
+                let offset = sm.doctest_offset_line(&loc.file.name, loc.line);
+                let message = format!("{}:{}:{}", filename, offset, loc.col.0 + 1);
+                if row_num == 2 {
+                    let col = usize::max(max_line_num_len + 1, arrow.len());
+                    buffer.puts(1, col, &message, Style::LineAndColumn);
+                } else {
+                    buffer.append(row_num - 1, &message, Style::LineAndColumn);
+                }
+                for _ in 0..max_line_num_len {
+                    buffer.prepend(row_num - 1, " ", Style::NoStyle);
+                }
                 row_num += 1;
             }
+        }
+        let show_code_change = if has_deletion && !is_multiline {
+            DisplaySuggestion::Diff
+        } else if let [part] = &parts[..]
+            && part.snippet.ends_with('\n')
+            && part.snippet.trim() == complete.trim()
+        {
+            // We are adding a line(s) of code before code that was already there.
+            DisplaySuggestion::Add
+        } else if (parts.len() != 1 || parts[0].snippet.trim() != complete.trim()) && !is_multiline
+        {
+            DisplaySuggestion::Underline
+        } else {
+            DisplaySuggestion::None
+        };
 
-            let file_lines = sm
-                .span_to_lines(parts[0].span)
-                .expect("span_to_lines failed when emitting suggestion");
+        if let DisplaySuggestion::Diff = show_code_change {
+            row_num += 1;
+        }
 
-            assert!(!file_lines.lines.is_empty() || parts[0].span.is_dummy());
-
-            let line_start = sm.lookup_char_pos(parts[0].span.lo()).line;
-            draw_col_separator_no_space(&mut buffer, row_num - 1, max_line_num_len + 1);
-            let mut lines = complete.lines();
-            if lines.clone().next().is_none() {
-                // Account for a suggestion to completely remove a line(s) with whitespace (#94192).
-                let line_end = sm.lookup_char_pos(parts[0].span.hi()).line;
-                for line in line_start..=line_end {
-                    buffer.puts(
-                        row_num - 1 + line - line_start,
-                        0,
-                        &self.maybe_anonymized(line),
-                        Style::LineNumber,
-                    );
-                    buffer.puts(
-                        row_num - 1 + line - line_start,
-                        max_line_num_len + 1,
-                        "- ",
-                        Style::Removal,
-                    );
-                    buffer.puts(
-                        row_num - 1 + line - line_start,
-                        max_line_num_len + 3,
-                        &normalize_whitespace(&file_lines.file.get_line(line - 1).unwrap()),
-                        Style::Removal,
-                    );
-                }
-                row_num += line_end - line_start;
+        let file_lines =
+            sm.span_to_lines(parts[0].span).expect("span_to_lines failed when emitting suggestion");
+
+        assert!(!file_lines.lines.is_empty() || parts[0].span.is_dummy());
+
+        let line_start = sm.lookup_char_pos(parts[0].span.lo()).line;
+        draw_col_separator_no_space(buffer, row_num - 1, max_line_num_len + 1);
+        let mut lines = complete.lines();
+        if lines.clone().next().is_none() {
+            // Account for a suggestion to completely remove a line(s) with whitespace (#94192).
+            let line_end = sm.lookup_char_pos(parts[0].span.hi()).line;
+            for line in line_start..=line_end {
+                buffer.puts(
+                    row_num - 1 + line - line_start,
+                    0,
+                    &self.maybe_anonymized(line),
+                    Style::LineNumber,
+                );
+                buffer.puts(
+                    row_num - 1 + line - line_start,
+                    max_line_num_len + 1,
+                    "- ",
+                    Style::Removal,
+                );
+                buffer.puts(
+                    row_num - 1 + line - line_start,
+                    max_line_num_len + 3,
+                    &normalize_whitespace(&file_lines.file.get_line(line - 1).unwrap()),
+                    Style::Removal,
+                );
             }
-            let mut unhighlighted_lines = Vec::new();
-            let mut last_pos = 0;
-            let mut is_item_attribute = false;
-            for (line_pos, (line, highlight_parts)) in lines.by_ref().zip(highlights).enumerate() {
-                last_pos = line_pos;
-                debug!(%line_pos, %line, ?highlight_parts);
-
-                // Remember lines that are not highlighted to hide them if needed
-                if highlight_parts.is_empty() {
-                    unhighlighted_lines.push((line_pos, line));
-                    continue;
-                }
-                if highlight_parts.len() == 1
-                    && line.trim().starts_with("#[")
-                    && line.trim().ends_with(']')
-                {
-                    is_item_attribute = true;
-                }
+            row_num += line_end - line_start;
+        }
+        let mut unhighlighted_lines = Vec::new();
+        let mut last_pos = 0;
+        let mut is_item_attribute = false;
+        for (line_pos, (line, highlight_parts)) in lines.by_ref().zip(highlights).enumerate() {
+            last_pos = line_pos;
+            debug!(%line_pos, %line, ?highlight_parts);
+
+            // Remember lines that are not highlighted to hide them if needed
+            if highlight_parts.is_empty() {
+                unhighlighted_lines.push((line_pos, line));
+                continue;
+            }
+            if highlight_parts.len() == 1
+                && line.trim().starts_with("#[")
+                && line.trim().ends_with(']')
+            {
+                is_item_attribute = true;
+            }
+
+            match unhighlighted_lines.len() {
+                0 => (),
+                // Since we show first line, "..." line and last line,
+                // There is no reason to hide if there are 3 or less lines
+                // (because then we just replace a line with ... which is
+                // not helpful)
+                n if n <= 3 => unhighlighted_lines.drain(..).for_each(|(p, l)| {
+                    self.draw_code_line(
+                        buffer,
+                        &mut row_num,
+                        &[],
+                        p + line_start,
+                        l,
+                        show_code_change,
+                        max_line_num_len,
+                        &file_lines,
+                        is_multiline,
+                    )
+                }),
+                // Print first unhighlighted line, "..." and last unhighlighted line, like so:
+                //
+                // LL | this line was highlighted
+                // LL | this line is just for context
+                // ...
+                // LL | this line is just for context
+                // LL | this line was highlighted
+                _ => {
+                    let last_line = unhighlighted_lines.pop();
+                    let first_line = unhighlighted_lines.drain(..).next();
 
-                match unhighlighted_lines.len() {
-                    0 => (),
-                    // Since we show first line, "..." line and last line,
-                    // There is no reason to hide if there are 3 or less lines
-                    // (because then we just replace a line with ... which is
-                    // not helpful)
-                    n if n <= 3 => unhighlighted_lines.drain(..).for_each(|(p, l)| {
+                    if let Some((p, l)) = first_line {
                         self.draw_code_line(
-                            &mut buffer,
+                            buffer,
                             &mut row_num,
                             &[],
                             p + line_start,
@@ -1946,187 +2037,154 @@ fn emit_suggestion_default(
                             &file_lines,
                             is_multiline,
                         )
-                    }),
-                    // Print first unhighlighted line, "..." and last unhighlighted line, like so:
-                    //
-                    // LL | this line was highlighted
-                    // LL | this line is just for context
-                    // ...
-                    // LL | this line is just for context
-                    // LL | this line was highlighted
-                    _ => {
-                        let last_line = unhighlighted_lines.pop();
-                        let first_line = unhighlighted_lines.drain(..).next();
-
-                        if let Some((p, l)) = first_line {
-                            self.draw_code_line(
-                                &mut buffer,
-                                &mut row_num,
-                                &[],
-                                p + line_start,
-                                l,
-                                show_code_change,
-                                max_line_num_len,
-                                &file_lines,
-                                is_multiline,
-                            )
-                        }
+                    }
 
-                        buffer.puts(row_num, 0, "...", Style::LineNumber);
-                        row_num += 1;
+                    buffer.puts(row_num, 0, "...", Style::LineNumber);
+                    row_num += 1;
 
-                        if let Some((p, l)) = last_line {
-                            self.draw_code_line(
-                                &mut buffer,
-                                &mut row_num,
-                                &[],
-                                p + line_start,
-                                l,
-                                show_code_change,
-                                max_line_num_len,
-                                &file_lines,
-                                is_multiline,
-                            )
-                        }
+                    if let Some((p, l)) = last_line {
+                        self.draw_code_line(
+                            buffer,
+                            &mut row_num,
+                            &[],
+                            p + line_start,
+                            l,
+                            show_code_change,
+                            max_line_num_len,
+                            &file_lines,
+                            is_multiline,
+                        )
                     }
                 }
+            }
 
+            self.draw_code_line(
+                buffer,
+                &mut row_num,
+                highlight_parts,
+                line_pos + line_start,
+                line,
+                show_code_change,
+                max_line_num_len,
+                &file_lines,
+                is_multiline,
+            )
+        }
+        if let DisplaySuggestion::Add = show_code_change
+            && is_item_attribute
+        {
+            // The suggestion adds an entire line of code, ending on a newline, so we'll also
+            // print the *following* line, to provide context of what we're advising people to
+            // do. Otherwise you would only see contextless code that can be confused for
+            // already existing code, despite the colors and UI elements.
+            // We special case `#[derive(_)]\n` and other attribute suggestions, because those
+            // are the ones where context is most useful.
+            let file_lines = sm
+                .span_to_lines(parts[0].span.shrink_to_hi())
+                .expect("span_to_lines failed when emitting suggestion");
+            let line_num = sm.lookup_char_pos(parts[0].span.lo()).line;
+            if let Some(line) = file_lines.file.get_line(line_num - 1) {
+                let line = normalize_whitespace(&line);
                 self.draw_code_line(
-                    &mut buffer,
+                    buffer,
                     &mut row_num,
-                    highlight_parts,
-                    line_pos + line_start,
-                    line,
-                    show_code_change,
+                    &[],
+                    line_num + last_pos + 1,
+                    &line,
+                    DisplaySuggestion::None,
                     max_line_num_len,
                     &file_lines,
                     is_multiline,
                 )
             }
-            if let DisplaySuggestion::Add = show_code_change
-                && is_item_attribute
-            {
-                // The suggestion adds an entire line of code, ending on a newline, so we'll also
-                // print the *following* line, to provide context of what we're advising people to
-                // do. Otherwise you would only see contextless code that can be confused for
-                // already existing code, despite the colors and UI elements.
-                // We special case `#[derive(_)]\n` and other attribute suggestions, because those
-                // are the ones where context is most useful.
-                let file_lines = sm
-                    .span_to_lines(parts[0].span.shrink_to_hi())
-                    .expect("span_to_lines failed when emitting suggestion");
-                let line_num = sm.lookup_char_pos(parts[0].span.lo()).line;
-                if let Some(line) = file_lines.file.get_line(line_num - 1) {
-                    let line = normalize_whitespace(&line);
-                    self.draw_code_line(
-                        &mut buffer,
-                        &mut row_num,
-                        &[],
-                        line_num + last_pos + 1,
-                        &line,
-                        DisplaySuggestion::None,
-                        max_line_num_len,
-                        &file_lines,
-                        is_multiline,
-                    )
-                }
-            }
-
-            // This offset and the ones below need to be signed to account for replacement code
-            // that is shorter than the original code.
-            let mut offsets: Vec<(usize, isize)> = Vec::new();
-            // Only show an underline in the suggestions if the suggestion is not the
-            // entirety of the code being shown and the displayed code is not multiline.
-            if let DisplaySuggestion::Diff | DisplaySuggestion::Underline | DisplaySuggestion::Add =
-                show_code_change
-            {
-                draw_col_separator_no_space(&mut buffer, row_num, max_line_num_len + 1);
-                for part in parts {
-                    let span_start_pos = sm.lookup_char_pos(part.span.lo()).col_display;
-                    let span_end_pos = sm.lookup_char_pos(part.span.hi()).col_display;
-
-                    // If this addition is _only_ whitespace, then don't trim it,
-                    // or else we're just not rendering anything.
-                    let is_whitespace_addition = part.snippet.trim().is_empty();
-
-                    // Do not underline the leading...
-                    let start = if is_whitespace_addition {
-                        0
-                    } else {
-                        part.snippet.len().saturating_sub(part.snippet.trim_start().len())
-                    };
-                    // ...or trailing spaces. Account for substitutions containing unicode
-                    // characters.
-                    let sub_len: usize =
-                        if is_whitespace_addition { &part.snippet } else { part.snippet.trim() }
-                            .chars()
-                            .map(|ch| char_width(ch))
-                            .sum();
+        }
 
-                    let offset: isize = offsets
-                        .iter()
-                        .filter_map(
-                            |(start, v)| if span_start_pos < *start { None } else { Some(v) },
-                        )
+        // This offset and the ones below need to be signed to account for replacement code
+        // that is shorter than the original code.
+        let mut offsets: Vec<(usize, isize)> = Vec::new();
+        // Only show an underline in the suggestions if the suggestion is not the
+        // entirety of the code being shown and the displayed code is not multiline.
+        if let DisplaySuggestion::Diff | DisplaySuggestion::Underline | DisplaySuggestion::Add =
+            show_code_change
+        {
+            draw_col_separator_no_space(buffer, row_num, max_line_num_len + 1);
+            for part in parts {
+                let span_start_pos = sm.lookup_char_pos(part.span.lo()).col_display;
+                let span_end_pos = sm.lookup_char_pos(part.span.hi()).col_display;
+
+                // If this addition is _only_ whitespace, then don't trim it,
+                // or else we're just not rendering anything.
+                let is_whitespace_addition = part.snippet.trim().is_empty();
+
+                // Do not underline the leading...
+                let start = if is_whitespace_addition {
+                    0
+                } else {
+                    part.snippet.len().saturating_sub(part.snippet.trim_start().len())
+                };
+                // ...or trailing spaces. Account for substitutions containing unicode
+                // characters.
+                let sub_len: usize =
+                    if is_whitespace_addition { &part.snippet } else { part.snippet.trim() }
+                        .chars()
+                        .map(|ch| char_width(ch))
                         .sum();
-                    let underline_start = (span_start_pos + start) as isize + offset;
-                    let underline_end = (span_start_pos + start + sub_len) as isize + offset;
-                    assert!(underline_start >= 0 && underline_end >= 0);
-                    let padding: usize = max_line_num_len + 3;
-                    for p in underline_start..underline_end {
-                        if let DisplaySuggestion::Underline = show_code_change
-                            && is_different(sm, &part.snippet, part.span)
-                        {
-                            // If this is a replacement, underline with `~`, if this is an addition
-                            // underline with `+`.
-                            buffer.putc(
-                                row_num,
-                                (padding as isize + p) as usize,
-                                if part.is_addition(sm) { '+' } else { '~' },
-                                Style::Addition,
-                            );
-                        }
-                    }
-                    if let DisplaySuggestion::Diff = show_code_change {
-                        // Colorize removal with red in diff format.
-                        buffer.set_style_range(
-                            row_num - 2,
-                            (padding as isize + span_start_pos as isize) as usize,
-                            (padding as isize + span_end_pos as isize) as usize,
-                            Style::Removal,
-                            true,
+
+                let offset: isize = offsets
+                    .iter()
+                    .filter_map(|(start, v)| if span_start_pos < *start { None } else { Some(v) })
+                    .sum();
+                let underline_start = (span_start_pos + start) as isize + offset;
+                let underline_end = (span_start_pos + start + sub_len) as isize + offset;
+                assert!(underline_start >= 0 && underline_end >= 0);
+                let padding: usize = max_line_num_len + 3;
+                for p in underline_start..underline_end {
+                    if let DisplaySuggestion::Underline = show_code_change
+                        && is_different(sm, &part.snippet, part.span)
+                    {
+                        // If this is a replacement, underline with `~`, if this is an addition
+                        // underline with `+`.
+                        buffer.putc(
+                            row_num,
+                            (padding as isize + p) as usize,
+                            if part.is_addition(sm) { '+' } else { '~' },
+                            Style::Addition,
                         );
                     }
-
-                    // length of the code after substitution
-                    let full_sub_len =
-                        part.snippet.chars().map(|ch| char_width(ch)).sum::<usize>() as isize;
-
-                    // length of the code to be substituted
-                    let snippet_len = span_end_pos as isize - span_start_pos as isize;
-                    // For multiple substitutions, use the position *after* the previous
-                    // substitutions have happened, only when further substitutions are
-                    // located strictly after.
-                    offsets.push((span_end_pos, full_sub_len - snippet_len));
                 }
-                row_num += 1;
-            }
+                if let DisplaySuggestion::Diff = show_code_change {
+                    // Colorize removal with red in diff format.
+                    buffer.set_style_range(
+                        row_num - 2,
+                        (padding as isize + span_start_pos as isize) as usize,
+                        (padding as isize + span_end_pos as isize) as usize,
+                        Style::Removal,
+                        true,
+                    );
+                }
 
-            // if we elided some lines, add an ellipsis
-            if lines.next().is_some() {
-                buffer.puts(row_num, max_line_num_len - 1, "...", Style::LineNumber);
-            } else if let DisplaySuggestion::None = show_code_change {
-                draw_col_separator_no_space(&mut buffer, row_num, max_line_num_len + 1);
-                row_num += 1;
+                // length of the code after substitution
+                let full_sub_len =
+                    part.snippet.chars().map(|ch| char_width(ch)).sum::<usize>() as isize;
+
+                // length of the code to be substituted
+                let snippet_len = span_end_pos as isize - span_start_pos as isize;
+                // For multiple substitutions, use the position *after* the previous
+                // substitutions have happened, only when further substitutions are
+                // located strictly after.
+                offsets.push((span_end_pos, full_sub_len - snippet_len));
             }
+            row_num += 1;
         }
-        if suggestions.len() > MAX_SUGGESTIONS {
-            let others = suggestions.len() - MAX_SUGGESTIONS;
-            let msg = format!("and {} other candidate{}", others, pluralize!(others));
-            buffer.puts(row_num, max_line_num_len + 3, &msg, Style::NoStyle);
+
+        // if we elided some lines, add an ellipsis
+        if lines.next().is_some() {
+            buffer.puts(row_num, max_line_num_len - 1, "...", Style::LineNumber);
+        } else if let DisplaySuggestion::None = show_code_change {
+            draw_col_separator_no_space(buffer, row_num, max_line_num_len + 1);
+            row_num += 1;
         }
-        emit_to_destination(&buffer.render(), level, &mut self.dst, self.short_message)?;
-        Ok(())
+        row_num
     }
 
     #[instrument(level = "trace", skip(self, args, code, children, suggestions))]
@@ -19,9 +19,9 @@
 use rustc_data_structures::sync::{IntoDynSyncSend, Lrc};
 use rustc_error_messages::FluentArgs;
 use rustc_lint_defs::Applicability;
-use rustc_span::Span;
 use rustc_span::hygiene::ExpnData;
 use rustc_span::source_map::SourceMap;
+use rustc_span::{FileName, Span};
 use serde::Serialize;
 use termcolor::{ColorSpec, WriteColor};
 
@@ -33,8 +33,8 @@
 use crate::registry::Registry;
 use crate::translation::{Translate, to_fluent_args};
 use crate::{
-    CodeSuggestion, FluentBundle, LazyFallbackBundle, MultiSpan, SpanLabel, Subdiag, Suggestions,
-    TerminalUrl,
+    CodeSuggestion, ErrCode, FluentBundle, LazyFallbackBundle, MultiSpan, NewFile, SpanLabel,
+    Subdiag, Suggestions, TerminalUrl,
 };
 
 #[cfg(test)]
@@ -108,6 +108,7 @@ enum EmitTyped<'a> {
     Artifact(ArtifactNotification<'a>),
     FutureIncompat(FutureIncompatReport<'a>),
     UnusedExtern(UnusedExterns<'a>),
+    BorrowckExplanation(BorrowckExplanationJson<'a>),
 }
 
 impl Translate for JsonEmitter {
@@ -172,6 +173,40 @@ fn emit_unused_externs(&mut self, lint_level: rustc_lint_defs::Level, unused_ext
         }
     }
 
+    fn emit_borrowck_explanation(&mut self, explanation: &BorrowckExplanation) {
+        let span = |span| DiagnosticSpan::from_span_etc(span, false, None, None, self);
+        let data = BorrowckExplanationJson {
+            code: explanation.code.map(|code| code.to_string()),
+            span: DiagnosticSpan::from_span_etc(explanation.span, true, None, None, self),
+            loan: BorrowckLoanJson {
+                kind: &explanation.loan.kind,
+                place: &explanation.loan.place,
+                region: &explanation.loan.region,
+                span: span(explanation.loan.span),
+            },
+            live_region: &explanation.live_region,
+            constraint_path: explanation
+                .constraint_path
+                .iter()
+                .map(|constraint| BorrowckConstraintJson {
+                    sup: &constraint.sup,
+                    sub: &constraint.sub,
+                    category: constraint.category.as_deref(),
+                    span: span(constraint.span),
+                })
+                .collect(),
+            access: BorrowckAccessJson {
+                kind: &explanation.access.kind,
+                place: &explanation.access.place,
+                span: span(explanation.access.span),
+            },
+        };
+        let result = self.emit(EmitTyped::BorrowckExplanation(data));
+        if let Err(e) = result {
+            panic!("failed to print borrowck explanation: {e:?}");
+        }
+    }
+
     fn source_map(&self) -> Option<&SourceMap> {
         Some(&self.sm)
     }
@@ -181,6 +216,51 @@ fn should_show_explain(&self) -> bool {
     }
 }
 
+/// A structured explanation of a borrowck error about a conflicting access: why the loan is still
+/// live at that access. It's emitted after the error with `-Zborrowck-explain-json`.
+pub struct BorrowckExplanation {
+    /// The code of the explained error, e.g. `E0499`.
+    pub code: Option<ErrCode>,
+    /// The primary span of the explained error.
+    pub span: Span,
+    /// The loan that is live at the conflicting access.
+    pub loan: BorrowckLoan,
+    /// A region that is live at the conflicting access, and that the loan's region outlives.
+    pub live_region: String,
+    /// The outlives constraints, from the loan's region to the live region, that forced the loan
+    /// to be live.
+    pub constraint_path: Vec<BorrowckConstraint>,
+    /// The access conflicting with the loan.
+    pub access: BorrowckAccess,
+}
+
+pub struct BorrowckLoan {
+    /// The kind of borrow, e.g. "mutable".
+    pub kind: String,
+    /// The borrowed place.
+    pub place: String,
+    /// The region of the loan.
+    pub region: String,
+    pub span: Span,
+}
+
+/// An outlives constraint `sup: sub`.
+pub struct BorrowckConstraint {
+    pub sup: String,
+    pub sub: String,
+    /// What caused the constraint, e.g. "assignment", if known.
+    pub category: Option<String>,
+    pub span: Span,
+}
+
+pub struct BorrowckAccess {
+    /// The kind of access, e.g. "mutable borrow" or "move".
+    pub kind: String,
+    /// The accessed place.
+    pub place: String,
+    pub span: Span,
+}
+
 // The following data types are provided just for serialisation.
 
 #[derive(Serialize)]
@@ -188,6 +268,10 @@ struct Diagnostic {
     /// The primary error message.
     message: String,
     code: Option<DiagnosticCode>,
+    /// The identifier of the diagnostic, see `DiagInner::slug`. Only emitted for top-level
+    /// diagnostics.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    slug: Option<String>,
     /// "error: internal compiler error", "error", "warning", "note", "help".
     level: &'static str,
     spans: Vec<DiagnosticSpan>,
@@ -222,6 +306,10 @@ struct DiagnosticSpan {
     suggestion_applicability: Option<Applicability>,
     /// Macro invocations that created the code at this span, if any.
     expansion: Option<Box<DiagnosticSpanMacroExpansion>>,
+    /// If the suggestion creates the file, with `suggested_replacement` as its contents. Only
+    /// emitted when true.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    new_file: bool,
 }
 
 #[derive(Serialize)]
@@ -289,6 +377,39 @@ struct UnusedExterns<'a> {
     unused_extern_names: &'a [&'a str],
 }
 
+#[derive(Serialize)]
+struct BorrowckExplanationJson<'a> {
+    code: Option<String>,
+    span: DiagnosticSpan,
+    loan: BorrowckLoanJson<'a>,
+    live_region: &'a str,
+    constraint_path: Vec<BorrowckConstraintJson<'a>>,
+    access: BorrowckAccessJson<'a>,
+}
+
+#[derive(Serialize)]
+struct BorrowckLoanJson<'a> {
+    kind: &'a str,
+    place: &'a str,
+    region: &'a str,
+    span: DiagnosticSpan,
+}
+
+#[derive(Serialize)]
+struct BorrowckConstraintJson<'a> {
+    sup: &'a str,
+    sub: &'a str,
+    category: Option<&'a str>,
+    span: DiagnosticSpan,
+}
+
+#[derive(Serialize)]
+struct BorrowckAccessJson<'a> {
+    kind: &'a str,
+    place: &'a str,
+    span: DiagnosticSpan,
+}
+
 impl Diagnostic {
     /// Converts from `rustc_errors::DiagInner` to `Diagnostic`.
     fn from_errors_diagnostic(diag: crate::DiagInner, je: &JsonEmitter) -> Diagnostic {
@@ -299,6 +420,7 @@ fn from_errors_diagnostic(diag: crate::DiagInner, je: &JsonEmitter) -> Diagnosti
             Diagnostic {
                 message: translated_message.to_string(),
                 code: None,
+                slug: None,
                 level: "help",
                 spans: DiagnosticSpan::from_suggestion(sugg, &args, je),
                 children: vec![],
@@ -351,6 +473,7 @@ fn reset(&mut self) -> io::Result<()> {
         } else {
             None
         };
+        let slug = Some(diag.slug());
         let level = diag.level.to_str();
         let spans = DiagnosticSpan::from_multispan(&diag.span, &args, je);
         let children = diag
@@ -384,6 +507,7 @@ fn reset(&mut self) -> io::Result<()> {
         Diagnostic {
             message: translated_message.to_string(),
             code,
+            slug,
             level,
             spans,
             children,
@@ -400,6 +524,7 @@ fn from_sub_diagnostic(
         Diagnostic {
             message: translated_message.to_string(),
             code: None,
+            slug: None,
             level: subdiag.level.to_str(),
             spans: DiagnosticSpan::from_multispan(&subdiag.span, args, je),
             children: vec![],
@@ -494,6 +619,32 @@ fn from_span_full(
             suggestion_applicability: suggestion.map(|x| x.1),
             expansion: backtrace_step,
             label,
+            new_file: false,
+        }
+    }
+
+    /// The span of a file created by a suggestion: an empty span at its start.
+    fn from_new_file(
+        new_file: &NewFile,
+        applicability: Applicability,
+        je: &JsonEmitter,
+    ) -> DiagnosticSpan {
+        let name = FileName::Real(je.sm.path_mapping().to_real_filename(&new_file.path));
+        DiagnosticSpan {
+            file_name: je.sm.filename_for_diagnostics(&name).to_string(),
+            byte_start: 0,
+            byte_end: 0,
+            line_start: 1,
+            line_end: 1,
+            column_start: 1,
+            column_end: 1,
+            is_primary: true,
+            text: vec![],
+            suggested_replacement: Some(new_file.contents.clone()),
+            suggestion_applicability: Some(applicability),
+            expansion: None,
+            label: None,
+            new_file: true,
         }
     }
 
@@ -517,7 +668,7 @@ fn from_suggestion(
             .substitutions
             .iter()
             .flat_map(|substitution| {
-                substitution.parts.iter().map(move |suggestion_inner| {
+                let edits = substitution.parts.iter().map(move |suggestion_inner| {
                     let span_label =
                         SpanLabel { span: suggestion_inner.span, is_primary: true, label: None };
                     DiagnosticSpan::from_span_label(
@@ -526,7 +677,11 @@ fn from_suggestion(
                         args,
                         je,
                     )
-                })
+                });
+                let new_files = substitution.new_files.iter().map(move |new_file| {
+                    DiagnosticSpan::from_new_file(new_file, suggestion.applicability, je)
+                });
+                edits.chain(new_files)
             })
             .collect()
     }
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::str;
 
 use rustc_span::BytePos;
@@ -162,3 +163,148 @@ fn span_after_crlf() {
         column_end: 2,
     })
 }
+
+#[test]
+fn borrowck_explanation() {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("test.rs").to_owned().into(), "let a = &mut x;".to_owned());
+        let fallback_bundle =
+            crate::fallback_fluent_bundle(vec![crate::DEFAULT_LOCALE_RESOURCE], false);
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let je = JsonEmitter::new(
+            Box::new(Shared { data: output.clone() }),
+            sm,
+            fallback_bundle,
+            false, // pretty
+            HumanReadableErrorType::Short,
+            ColorConfig::Never,
+        );
+
+        let span = |lo, hi| Span::with_root_ctxt(BytePos(lo), BytePos(hi));
+        let explanation = BorrowckExplanation {
+            code: Some(crate::codes::E0499),
+            span: span(8, 14),
+            loan: BorrowckLoan {
+                kind: "mutable".to_owned(),
+                place: "x".to_owned(),
+                region: "'?1".to_owned(),
+                span: span(8, 14),
+            },
+            live_region: "'?2".to_owned(),
+            constraint_path: vec![BorrowckConstraint {
+                sup: "'?1".to_owned(),
+                sub: "'?2".to_owned(),
+                category: Some("assignment".to_owned()),
+                span: span(4, 14),
+            }],
+            access: BorrowckAccess {
+                kind: "mutable borrow".to_owned(),
+                place: "x".to_owned(),
+                span: span(8, 14),
+            },
+        };
+        DiagCtxt::new(Box::new(je)).handle().emit_borrowck_explanation(&explanation);
+
+        let bytes = output.lock().unwrap();
+        let output: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(output["$message_type"], "borrowck_explanation");
+        assert_eq!(output["code"], "E0499");
+        assert_eq!(output["span"]["is_primary"], true);
+        assert_eq!(output["loan"]["region"], "'?1");
+        assert_eq!(output["loan"]["span"]["byte_start"], 8);
+        assert_eq!(output["live_region"], "'?2");
+        assert_eq!(output["constraint_path"][0]["category"], "assignment");
+        assert_eq!(output["constraint_path"][0]["span"]["byte_start"], 4);
+        assert_eq!(output["access"]["kind"], "mutable borrow");
+    })
+}
+
+#[test]
+fn multifile_suggestion() {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        let lib = sm.new_source_file(Path::new("lib.rs").to_owned().into(), "mod a;".to_owned());
+        let a = sm.new_source_file(Path::new("a.rs").to_owned().into(), "fn f() {}".to_owned());
+        let fallback_bundle =
+            crate::fallback_fluent_bundle(vec![crate::DEFAULT_LOCALE_RESOURCE], false);
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let je = JsonEmitter::new(
+            Box::new(Shared { data: output.clone() }),
+            sm,
+            fallback_bundle,
+            false, // pretty
+            HumanReadableErrorType::Short,
+            ColorConfig::Never,
+        );
+
+        let lib_span = Span::with_root_ctxt(lib.start_pos, lib.start_pos);
+        let a_span = Span::with_root_ctxt(a.start_pos, a.start_pos);
+        DiagCtxt::new(Box::new(je))
+            .handle()
+            .struct_span_err(lib_span, "foo")
+            .with_multifile_suggestion(
+                "bar",
+                vec![(lib_span, "mod b;\n".to_owned()), (a_span, "pub ".to_owned())],
+                vec![(PathBuf::from("b.rs"), "pub fn g() {}\n".to_owned())],
+                Applicability::MachineApplicable,
+            )
+            .emit();
+
+        let bytes = output.lock().unwrap();
+        let output: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        let spans = &output["children"][0]["spans"];
+        assert_eq!(spans[0]["file_name"], "lib.rs");
+        assert_eq!(spans[0]["suggested_replacement"], "mod b;\n");
+        assert_eq!(spans[0].get("new_file"), None);
+        assert_eq!(spans[1]["file_name"], "a.rs");
+        assert_eq!(spans[1]["suggested_replacement"], "pub ");
+        assert_eq!(spans[2]["file_name"], "b.rs");
+        assert_eq!(spans[2]["byte_start"], 0);
+        assert_eq!(spans[2]["byte_end"], 0);
+        assert_eq!(spans[2]["suggested_replacement"], "pub fn g() {}\n");
+        assert_eq!(spans[2]["suggestion_applicability"], "MachineApplicable");
+        assert_eq!(spans[2]["new_file"], true);
+    })
+}
+
+#[test]
+fn slug() {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        let fallback_bundle =
+            crate::fallback_fluent_bundle(vec![crate::DEFAULT_LOCALE_RESOURCE], false);
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let je = JsonEmitter::new(
+            Box::new(Shared { data: output.clone() }),
+            sm,
+            fallback_bundle,
+            false, // pretty
+            HumanReadableErrorType::Short,
+            ColorConfig::Never,
+        );
+
+        let dcx = DiagCtxt::new(Box::new(je));
+        dcx.handle()
+            .struct_err(crate::fluent_generated::errors_expected_lifetime_parameter)
+            .with_arg("count", 2)
+            .with_note("foo")
+            .emit();
+        dcx.handle().struct_err("bar").with_code(crate::codes::E0499).emit();
+        dcx.handle().struct_err("baz").emit();
+
+        let bytes = output.lock().unwrap();
+        let mut outputs = serde_json::Deserializer::from_slice(&bytes).into_iter();
+        let output: serde_json::Value = outputs.next().unwrap().unwrap();
+        assert_eq!(output["message"], "expected lifetime parameters");
+        assert_eq!(output["slug"], "errors_expected_lifetime_parameter");
+        assert_eq!(output["children"][0].get("slug"), None);
+        let output: serde_json::Value = outputs.next().unwrap().unwrap();
+        assert_eq!(output["slug"], "E0499");
+        let output: serde_json::Value = outputs.next().unwrap().unwrap();
+        assert_eq!(output["slug"], "unnamed");
+    })
+}
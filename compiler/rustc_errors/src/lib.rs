@@ -55,6 +55,7 @@
 };
 pub use emitter::ColorConfig;
 use emitter::{DynEmitter, Emitter, is_case_difference, is_different};
+use json::BorrowckExplanation;
 use registry::Registry;
 use rustc_data_structures::AtomicRef;
 use rustc_data_structures::fx::{FxHashSet, FxIndexMap, FxIndexSet};
@@ -87,6 +88,7 @@
 mod lock;
 pub mod markdown;
 pub mod registry;
+pub mod sarif;
 mod snippet;
 mod styled_buffer;
 #[cfg(test)]
@@ -199,7 +201,10 @@ pub struct CodeSuggestion {
 #[derive(Clone, Debug, PartialEq, Hash, Encodable, Decodable)]
 /// See the docs on `CodeSuggestion::substitutions`
 pub struct Substitution {
+    /// The edits of existing code. They may be in different files.
     pub parts: Vec<SubstitutionPart>,
+    /// The files to create, e.g. for a missing module.
+    pub new_files: Vec<NewFile>,
 }
 
 #[derive(Clone, Debug, PartialEq, Hash, Encodable, Decodable)]
@@ -208,6 +213,13 @@ pub struct SubstitutionPart {
     pub snippet: String,
 }
 
+/// A file created by a suggestion.
+#[derive(Clone, Debug, PartialEq, Hash, Encodable, Decodable)]
+pub struct NewFile {
+    pub path: PathBuf,
+    pub contents: String,
+}
+
 /// Used to translate between `Span`s and byte positions within a single output line in highlighted
 /// code of structured suggestions.
 #[derive(Debug, Clone, Copy)]
@@ -235,14 +247,21 @@ fn replaces_meaningful_content(&self, sm: &SourceMap) -> bool {
     }
 }
 
+/// A substitution of a suggestion, assembled for rendering by `CodeSuggestion::splice_lines`.
+pub(crate) struct SplicedSubstitution<'a> {
+    /// The assembled code of each file the substitution edits, with the parts of the substitution
+    /// in this file and the substrings to highlight.
+    pub(crate) files: Vec<(String, Vec<SubstitutionPart>, Vec<Vec<SubstitutionHighlight>>)>,
+    /// The files the substitution creates.
+    pub(crate) new_files: &'a [NewFile],
+    /// Whether the substitution only differs in capitalization.
+    pub(crate) only_capitalization: bool,
+}
+
 impl CodeSuggestion {
-    /// Returns the assembled code suggestions, whether they should be shown with an underline
-    /// and whether the substitution only differs in capitalization.
-    pub(crate) fn splice_lines(
-        &self,
-        sm: &SourceMap,
-    ) -> Vec<(String, Vec<SubstitutionPart>, Vec<Vec<SubstitutionHighlight>>, bool)> {
-        // For the `Vec<Vec<SubstitutionHighlight>>` value, the first level of the vector
+    /// Returns the assembled code suggestions, one per substitution.
+    pub(crate) fn splice_lines(&self, sm: &SourceMap) -> Vec<SplicedSubstitution<'_>> {
+        // For the `Vec<Vec<SubstitutionHighlight>>` values, the first level of the vector
         // corresponds to the output snippet's lines, while the second level corresponds to the
         // substrings within that line that should be highlighted.
 
@@ -294,6 +313,143 @@ fn push_trailing(
             line_count
         }
 
+        /// Assembles the code of a file with the given parts of a substitution, which are disjoint
+        /// and sorted in ascending order. Returns `None` if the parts can't be shown or don't
+        /// change the code.
+        fn splice_file(
+            sm: &SourceMap,
+            parts: &[SubstitutionPart],
+        ) -> Option<(String, Vec<SubstitutionPart>, Vec<Vec<SubstitutionHighlight>>, bool)> {
+            // Find the bounding span.
+            let lo = parts.iter().map(|part| part.span.lo()).min()?;
+            let hi = parts.iter().map(|part| part.span.hi()).max()?;
+            let bounding_span = Span::with_root_ctxt(lo, hi);
+            // The different spans might belong to different contexts, if so ignore suggestion.
+            let lines = sm.span_to_lines(bounding_span).ok()?;
+            assert!(!lines.lines.is_empty() || bounding_span.is_dummy());
+
+            // We can't splice anything if the source is unavailable.
+            if !sm.ensure_source_file_source_present(&lines.file) {
+                return None;
+            }
+
+            let mut highlights = vec![];
+            // To build up the result, we do this for each span:
+            // - push the line segment trailing the previous span
+            //   (at the beginning a "phantom" span pointing at the start of the line)
+            // - push lines between the previous and current span (if any)
+            // - if the previous and current span are not on the same line
+            //   push the line segment leading up to the current span
+            // - splice in the span substitution
+            //
+            // Finally push the trailing line segment of the last span
+            let sf = &lines.file;
+            let mut prev_hi = sm.lookup_char_pos(bounding_span.lo());
+            prev_hi.col = CharPos::from_usize(0);
+            let mut prev_line =
+                lines.lines.get(0).and_then(|line0| sf.get_line(line0.line_index));
+            let mut buf = String::new();
+
+            let mut line_highlight = vec![];
+            // We need to keep track of the difference between the existing code and the added
+            // or deleted code in order to point at the correct column *after* substitution.
+            let mut acc = 0;
+            let mut only_capitalization = false;
+            for part in parts {
+                only_capitalization |= is_case_difference(sm, &part.snippet, part.span);
+                let cur_lo = sm.lookup_char_pos(part.span.lo());
+                if prev_hi.line == cur_lo.line {
+                    let mut count =
+                        push_trailing(&mut buf, prev_line.as_ref(), &prev_hi, Some(&cur_lo));
+                    while count > 0 {
+                        highlights.push(std::mem::take(&mut line_highlight));
+                        acc = 0;
+                        count -= 1;
+                    }
+                } else {
+                    acc = 0;
+                    highlights.push(std::mem::take(&mut line_highlight));
+                    let mut count = push_trailing(&mut buf, prev_line.as_ref(), &prev_hi, None);
+                    while count > 0 {
+                        highlights.push(std::mem::take(&mut line_highlight));
+                        count -= 1;
+                    }
+                    // push lines between the previous and current span (if any)
+                    for idx in prev_hi.line..(cur_lo.line - 1) {
+                        if let Some(line) = sf.get_line(idx) {
+                            buf.push_str(line.as_ref());
+                            buf.push('\n');
+                            highlights.push(std::mem::take(&mut line_highlight));
+                        }
+                    }
+                    if let Some(cur_line) = sf.get_line(cur_lo.line - 1) {
+                        let end = match cur_line.char_indices().nth(cur_lo.col.to_usize()) {
+                            Some((i, _)) => i,
+                            None => cur_line.len(),
+                        };
+                        buf.push_str(&cur_line[..end]);
+                    }
+                }
+                // Add a whole line highlight per line in the snippet.
+                let len: isize = part
+                    .snippet
+                    .split('\n')
+                    .next()
+                    .unwrap_or(&part.snippet)
+                    .chars()
+                    .map(|c| match c {
+                        '\t' => 4,
+                        _ => 1,
+                    })
+                    .sum();
+                if !is_different(sm, &part.snippet, part.span) {
+                    // Account for cases where we are suggesting the same code that's already
+                    // there. This shouldn't happen often, but in some cases for multipart
+                    // suggestions it's much easier to handle it here than in the origin.
+                } else {
+                    line_highlight.push(SubstitutionHighlight {
+                        start: (cur_lo.col.0 as isize + acc) as usize,
+                        end: (cur_lo.col.0 as isize + acc + len) as usize,
+                    });
+                }
+                buf.push_str(&part.snippet);
+                let cur_hi = sm.lookup_char_pos(part.span.hi());
+                // Account for the difference between the width of the current code and the
+                // snippet being suggested, so that the *later* suggestions are correctly
+                // aligned on the screen. Note that cur_hi and cur_lo can be on different
+                // lines, so cur_hi.col can be smaller than cur_lo.col
+                acc += len - (cur_hi.col.0 as isize - cur_lo.col.0 as isize);
+                prev_hi = cur_hi;
+                prev_line = sf.get_line(prev_hi.line - 1);
+                for line in part.snippet.split('\n').skip(1) {
+                    acc = 0;
+                    highlights.push(std::mem::take(&mut line_highlight));
+                    let end: usize = line
+                        .chars()
+                        .map(|c| match c {
+                            '\t' => 4,
+                            _ => 1,
+                        })
+                        .sum();
+                    line_highlight.push(SubstitutionHighlight { start: 0, end });
+                }
+            }
+            highlights.push(std::mem::take(&mut line_highlight));
+            // if the replacement already ends with a newline, don't print the next line
+            if !buf.ends_with('\n') {
+                push_trailing(&mut buf, prev_line.as_ref(), &prev_hi, None);
+            }
+            // remove trailing newlines
+            while buf.ends_with('\n') {
+                buf.pop();
+            }
+            if highlights.iter().all(|parts| parts.is_empty()) {
+                None
+            } else {
+                Some((buf, parts.to_vec(), highlights, only_capitalization))
+            }
+        }
+
         assert!(!self.substitutions.is_empty());
 
         self.substitutions
@@ -307,139 +463,32 @@ fn push_trailing(
                 }
                 !invalid
             })
-            .cloned()
-            .filter_map(|mut substitution| {
-                // Assumption: all spans are in the same file, and all spans
-                // are disjoint. Sort in ascending order.
-                substitution.parts.sort_by_key(|part| part.span.lo());
-
-                // Find the bounding span.
-                let lo = substitution.parts.iter().map(|part| part.span.lo()).min()?;
-                let hi = substitution.parts.iter().map(|part| part.span.hi()).max()?;
-                let bounding_span = Span::with_root_ctxt(lo, hi);
-                // The different spans might belong to different contexts, if so ignore suggestion.
-                let lines = sm.span_to_lines(bounding_span).ok()?;
-                assert!(!lines.lines.is_empty() || bounding_span.is_dummy());
-
-                // We can't splice anything if the source is unavailable.
-                if !sm.ensure_source_file_source_present(&lines.file) {
-                    return None;
-                }
-
-                let mut highlights = vec![];
-                // To build up the result, we do this for each span:
-                // - push the line segment trailing the previous span
-                //   (at the beginning a "phantom" span pointing at the start of the line)
-                // - push lines between the previous and current span (if any)
-                // - if the previous and current span are not on the same line
-                //   push the line segment leading up to the current span
-                // - splice in the span substitution
-                //
-                // Finally push the trailing line segment of the last span
-                let sf = &lines.file;
-                let mut prev_hi = sm.lookup_char_pos(bounding_span.lo());
-                prev_hi.col = CharPos::from_usize(0);
-                let mut prev_line =
-                    lines.lines.get(0).and_then(|line0| sf.get_line(line0.line_index));
-                let mut buf = String::new();
-
-                let mut line_highlight = vec![];
-                // We need to keep track of the difference between the existing code and the added
-                // or deleted code in order to point at the correct column *after* substitution.
-                let mut acc = 0;
+            .filter_map(|substitution| {
+                // The parts in different files are spliced separately. The files to create are
+                // rendered by the emitter.
+                let mut parts = substitution.parts.clone();
+                parts.sort_by_key(|part| part.span.lo());
+                let mut files = vec![];
                 let mut only_capitalization = false;
-                for part in &substitution.parts {
-                    only_capitalization |= is_case_difference(sm, &part.snippet, part.span);
-                    let cur_lo = sm.lookup_char_pos(part.span.lo());
-                    if prev_hi.line == cur_lo.line {
-                        let mut count =
-                            push_trailing(&mut buf, prev_line.as_ref(), &prev_hi, Some(&cur_lo));
-                        while count > 0 {
-                            highlights.push(std::mem::take(&mut line_highlight));
-                            acc = 0;
-                            count -= 1;
-                        }
-                    } else {
-                        acc = 0;
-                        highlights.push(std::mem::take(&mut line_highlight));
-                        let mut count = push_trailing(&mut buf, prev_line.as_ref(), &prev_hi, None);
-                        while count > 0 {
-                            highlights.push(std::mem::take(&mut line_highlight));
-                            count -= 1;
-                        }
-                        // push lines between the previous and current span (if any)
-                        for idx in prev_hi.line..(cur_lo.line - 1) {
-                            if let Some(line) = sf.get_line(idx) {
-                                buf.push_str(line.as_ref());
-                                buf.push('\n');
-                                highlights.push(std::mem::take(&mut line_highlight));
-                            }
-                        }
-                        if let Some(cur_line) = sf.get_line(cur_lo.line - 1) {
-                            let end = match cur_line.char_indices().nth(cur_lo.col.to_usize()) {
-                                Some((i, _)) => i,
-                                None => cur_line.len(),
-                            };
-                            buf.push_str(&cur_line[..end]);
-                        }
-                    }
-                    // Add a whole line highlight per line in the snippet.
-                    let len: isize = part
-                        .snippet
-                        .split('\n')
-                        .next()
-                        .unwrap_or(&part.snippet)
-                        .chars()
-                        .map(|c| match c {
-                            '\t' => 4,
-                            _ => 1,
-                        })
-                        .sum();
-                    if !is_different(sm, &part.snippet, part.span) {
-                        // Account for cases where we are suggesting the same code that's already
-                        // there. This shouldn't happen often, but in some cases for multipart
-                        // suggestions it's much easier to handle it here than in the origin.
-                    } else {
-                        line_highlight.push(SubstitutionHighlight {
-                            start: (cur_lo.col.0 as isize + acc) as usize,
-                            end: (cur_lo.col.0 as isize + acc + len) as usize,
-                        });
-                    }
-                    buf.push_str(&part.snippet);
-                    let cur_hi = sm.lookup_char_pos(part.span.hi());
-                    // Account for the difference between the width of the current code and the
-                    // snippet being suggested, so that the *later* suggestions are correctly
-                    // aligned on the screen. Note that cur_hi and cur_lo can be on different
-                    // lines, so cur_hi.col can be smaller than cur_lo.col
-                    acc += len - (cur_hi.col.0 as isize - cur_lo.col.0 as isize);
-                    prev_hi = cur_hi;
-                    prev_line = sf.get_line(prev_hi.line - 1);
-                    for line in part.snippet.split('\n').skip(1) {
-                        acc = 0;
-                        highlights.push(std::mem::take(&mut line_highlight));
-                        let end: usize = line
-                            .chars()
-                            .map(|c| match c {
-                                '\t' => 4,
-                                _ => 1,
-                            })
-                            .sum();
-                        line_highlight.push(SubstitutionHighlight { start: 0, end });
+                let same_file = |a: &SubstitutionPart, b: &SubstitutionPart| {
+                    sm.lookup_source_file_idx(a.span.lo()) == sm.lookup_source_file_idx(b.span.lo())
+                };
+                for parts in parts.chunk_by(same_file) {
+                    if let Some((buf, parts, highlights, file_only_capitalization)) =
+                        splice_file(sm, parts)
+                    {
+                        files.push((buf, parts, highlights));
+                        only_capitalization |= file_only_capitalization;
                     }
                 }
-                highlights.push(std::mem::take(&mut line_highlight));
-                // if the replacement already ends with a newline, don't print the next line
-                if !buf.ends_with('\n') {
-                    push_trailing(&mut buf, prev_line.as_ref(), &prev_hi, None);
-                }
-                // remove trailing newlines
-                while buf.ends_with('\n') {
-                    buf.pop();
-                }
-                if highlights.iter().all(|parts| parts.is_empty()) {
+                if files.is_empty() && substitution.new_files.is_empty() {
                     None
                 } else {
-                    Some((buf, substitution.parts, highlights, only_capitalization))
+                    Some(SplicedSubstitution {
+                        files,
+                        new_files: &substitution.new_files,
+                        only_capitalization,
+                    })
                 }
             })
             .collect()
@@ -1061,6 +1110,10 @@ pub fn emit_artifact_notification(&self, path: &Path, artifact_type: &str) {
         self.inner.borrow_mut().emitter.emit_artifact_notification(path, artifact_type);
     }
 
+    pub fn emit_borrowck_explanation(&self, explanation: &BorrowckExplanation) {
+        self.inner.borrow_mut().emitter.emit_borrowck_explanation(explanation);
+    }
+
     pub fn emit_future_breakage_report(&self) {
         let mut inner = self.inner.borrow_mut();
         let diags = std::mem::take(&mut inner.future_breakage_diagnostics);
@@ -0,0 +1,509 @@
+//! A SARIF emitter for errors.
+//!
+//! [SARIF](https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html) is the format used by
+//! code scanning tools to report their findings. A SARIF log is a single JSON document, so the
+//! emitter collects the diagnostics and writes the whole log once it is dropped, i.e., when the
+//! `DiagCtxt` is. A compilation uses several `DiagCtxt`s, e.g., the early ones for errors in
+//! command line arguments and the one of the session, so the emitters writing to stderr share
+//! their log, which is written once the last of them is dropped. Nothing is written if there were
+//! no diagnostics.
+//!
+//! Diagnostics map to SARIF as follows:
+//! - the error code or the lint name is the rule of the result, and the explanation of error codes
+//!   is the help of their rule;
+//! - the primary spans are the locations of the result, and the other spans and the child notes are
+//!   its related locations;
+//! - each substitution of a suggestion is a fix;
+//! - the macro backtrace of the primary span is a code flow going from the expanded code to the
+//!   outermost macro invocation.
+//!
+//! Failure notes, e.g., "aborting due to 2 previous errors", are not results and are not emitted.
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex, Weak};
+
+use derive_setters::Setters;
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_data_structures::sync::{IntoDynSyncSend, Lrc};
+use rustc_error_messages::FluentArgs;
+use rustc_lint_defs::Applicability;
+use rustc_span::source_map::SourceMap;
+use rustc_span::{FileName, Span};
+use serde::Serialize;
+
+use crate::diagnostic::IsLint;
+use crate::emitter::Emitter;
+use crate::registry::Registry;
+use crate::snippet::Style;
+use crate::translation::{Translate, to_fluent_args};
+use crate::{
+    CodeSuggestion, DiagInner, DiagMessage, FluentBundle, LazyFallbackBundle, Level, MultiSpan,
+    Suggestions,
+};
+
+#[cfg(test)]
+mod tests;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const VERSION: &str = "2.1.0";
+
+/// The diagnostics collected for a log, which is written once this is dropped.
+struct SarifOutput {
+    dst: IntoDynSyncSend<Box<dyn Write + Send>>,
+    rules: FxIndexMap<String, ReportingDescriptor>,
+    results: Vec<SarifResult>,
+}
+
+impl SarifOutput {
+    fn new(dst: Box<dyn Write + Send>) -> SarifOutput {
+        SarifOutput { dst: IntoDynSyncSend(dst), rules: FxIndexMap::default(), results: Vec::new() }
+    }
+
+    fn write_log(&mut self) -> io::Result<()> {
+        let log = SarifLog {
+            schema: SCHEMA,
+            version: VERSION,
+            runs: [Run {
+                tool: Tool {
+                    driver: ToolComponent {
+                        name: "rustc",
+                        information_uri: "https://www.rust-lang.org/",
+                        rules: self.rules.values().collect(),
+                    },
+                },
+                column_kind: "unicodeCodePoints",
+                results: &self.results,
+            }],
+        };
+        serde_json::to_writer_pretty(&mut *self.dst, &log)?;
+        self.dst.write_all(b"\n")?;
+        self.dst.flush()
+    }
+}
+
+impl Drop for SarifOutput {
+    fn drop(&mut self) {
+        if self.results.is_empty() {
+            return;
+        }
+        if let Err(e) = self.write_log()
+            && !std::thread::panicking()
+        {
+            panic!("failed to print diagnostics: {e:?}");
+        }
+    }
+}
+
+/// The log shared by the emitters writing to stderr, if any of them is alive.
+static STDERR_OUTPUT: Mutex<Weak<Mutex<SarifOutput>>> = Mutex::new(Weak::new());
+
+#[derive(Setters)]
+pub struct SarifEmitter {
+    #[setters(skip)]
+    output: Arc<Mutex<SarifOutput>>,
+    registry: Option<Registry>,
+    #[setters(skip)]
+    sm: Lrc<SourceMap>,
+    fluent_bundle: Option<Lrc<FluentBundle>>,
+    #[setters(skip)]
+    fallback_bundle: LazyFallbackBundle,
+}
+
+impl SarifEmitter {
+    pub fn new(
+        dst: Box<dyn Write + Send>,
+        sm: Lrc<SourceMap>,
+        fallback_bundle: LazyFallbackBundle,
+    ) -> SarifEmitter {
+        let output = Arc::new(Mutex::new(SarifOutput::new(dst)));
+        SarifEmitter { output, registry: None, sm, fluent_bundle: None, fallback_bundle }
+    }
+
+    /// An emitter writing to stderr, which shares its log with the other emitters writing to
+    /// stderr.
+    pub fn stderr(sm: Lrc<SourceMap>, fallback_bundle: LazyFallbackBundle) -> SarifEmitter {
+        let mut shared = STDERR_OUTPUT.lock().unwrap();
+        let output = shared.upgrade().unwrap_or_else(|| {
+            let output =
+                Arc::new(Mutex::new(SarifOutput::new(Box::new(io::BufWriter::new(io::stderr())))));
+            *shared = Arc::downgrade(&output);
+            output
+        });
+        SarifEmitter { output, registry: None, sm, fluent_bundle: None, fallback_bundle }
+    }
+
+    /// Returns the index of the rule of the diagnostic, registering the rule if needed.
+    fn rule(&mut self, diag: &DiagInner) -> Option<(String, usize)> {
+        let (id, explanation) = if let Some(code) = diag.code {
+            let explanation = self
+                .registry
+                .as_ref()
+                .and_then(|registry| registry.try_find_description(code).ok());
+            (code.to_string(), explanation)
+        } else if let Some(IsLint { name, .. }) = &diag.is_lint {
+            (name.clone(), None)
+        } else {
+            return None;
+        };
+        let mut output = self.output.lock().unwrap();
+        let entry = output.rules.entry(id.clone());
+        let index = entry.index();
+        entry.or_insert_with(|| ReportingDescriptor {
+            help_uri: diag
+                .code
+                .map(|code| format!("https://doc.rust-lang.org/error_codes/{code}.html")),
+            help: explanation.map(|explanation| MultiformatMessage {
+                text: explanation.to_string(),
+                markdown: Some(explanation.to_string()),
+            }),
+            id: id.clone(),
+        });
+        Some((id, index))
+    }
+
+    fn result(&mut self, diag: DiagInner) -> SarifResult {
+        let args = to_fluent_args(diag.args.iter());
+        let rule = self.rule(&diag);
+
+        let (locations, mut related_locations) = self.locations(&diag.span, &args);
+        for child in &diag.children {
+            let message = self.message_texts(&child.messages, &args);
+            let message = format!("{}: {}", child.level.to_str(), message);
+            let (primary, _) = self.locations(&child.span, &args);
+            let physical_location =
+                primary.into_iter().next().and_then(|location| location.physical_location);
+            related_locations
+                .push(Location { physical_location, message: Some(Message { text: message }) });
+        }
+
+        let suggestions: &[CodeSuggestion] = match &diag.suggestions {
+            Suggestions::Enabled(suggestions) => suggestions,
+            Suggestions::Sealed(suggestions) => suggestions,
+            Suggestions::Disabled => &[],
+        };
+        let fixes = suggestions.iter().flat_map(|sugg| self.fixes(sugg, &args)).collect();
+        let code_flows = diag.span.primary_span().and_then(|span| self.code_flow(span));
+
+        SarifResult {
+            rule_id: rule.as_ref().map(|(id, _)| id.clone()),
+            rule_index: rule.map(|(_, index)| index),
+            level: match diag.level {
+                Level::Bug | Level::DelayedBug | Level::Fatal | Level::Error => "error",
+                Level::ForceWarning(_) | Level::Warning => "warning",
+                _ => "note",
+            },
+            message: Message { text: self.message_texts(&diag.messages, &args) },
+            locations,
+            related_locations,
+            fixes,
+            code_flows: code_flows.into_iter().collect(),
+        }
+    }
+
+    /// Translates `message`. A message that can't be translated is replaced by its Fluent
+    /// identifier, as losing the whole log over one message would be worse. Only Fluent messages
+    /// are translated, so there always is one.
+    fn message_text(&self, message: &DiagMessage, args: &FluentArgs<'_>) -> String {
+        match self.translate_message(message, args) {
+            Ok(text) => text.into_owned(),
+            Err(_) => message.slug().expect("untranslatable message without Fluent identifier"),
+        }
+    }
+
+    fn message_texts(&self, messages: &[(DiagMessage, Style)], args: &FluentArgs<'_>) -> String {
+        messages.iter().map(|(message, _)| self.message_text(message, args)).collect()
+    }
+
+    /// Returns the locations of the primary spans of `msp`, and the locations of its other labels.
+    fn locations(&self, msp: &MultiSpan, args: &FluentArgs<'_>) -> (Vec<Location>, Vec<Location>) {
+        let mut primary = Vec::new();
+        let mut secondary = Vec::new();
+        for label in msp.span_labels() {
+            let message =
+                label.label.as_ref().map(|label| Message { text: self.message_text(label, args) });
+            let location =
+                Location { physical_location: Some(self.physical_location(label.span)), message };
+            if label.is_primary { primary.push(location) } else { secondary.push(location) }
+        }
+        (primary, secondary)
+    }
+
+    fn physical_location(&self, span: Span) -> PhysicalLocation {
+        let start = self.sm.lookup_char_pos(span.lo());
+        let end = self.sm.lookup_char_pos(span.hi());
+        let byte_start = start.file.original_relative_byte_pos(span.lo()).0;
+        let byte_end = start.file.original_relative_byte_pos(span.hi()).0;
+        PhysicalLocation {
+            artifact_location: ArtifactLocation {
+                uri: self.sm.filename_for_diagnostics(&start.file.name).to_string(),
+            },
+            region: Region {
+                start_line: start.line,
+                start_column: start.col.0 + 1,
+                end_line: end.line,
+                end_column: end.col.0 + 1,
+                byte_offset: byte_start,
+                byte_length: byte_end - byte_start,
+            },
+        }
+    }
+
+    /// Each substitution of a suggestion is an alternative fix.
+    fn fixes(&self, sugg: &CodeSuggestion, args: &FluentArgs<'_>) -> Vec<Fix> {
+        let description = self.message_text(&sugg.msg, args);
+        sugg.substitutions
+            .iter()
+            .map(|substitution| {
+                let mut changes: FxIndexMap<String, Vec<Replacement>> = FxIndexMap::default();
+                for part in &substitution.parts {
+                    let PhysicalLocation { artifact_location, region } =
+                        self.physical_location(part.span);
+                    changes.entry(artifact_location.uri).or_default().push(Replacement {
+                        deleted_region: region,
+                        inserted_content: ArtifactContent { text: part.snippet.clone() },
+                    });
+                }
+                // A file is created by inserting its contents at the start of an empty file.
+                for new_file in &substitution.new_files {
+                    let name =
+                        FileName::Real(self.sm.path_mapping().to_real_filename(&new_file.path));
+                    let uri = self.sm.filename_for_diagnostics(&name).to_string();
+                    changes.entry(uri).or_default().push(Replacement {
+                        deleted_region: Region {
+                            start_line: 1,
+                            start_column: 1,
+                            end_line: 1,
+                            end_column: 1,
+                            byte_offset: 0,
+                            byte_length: 0,
+                        },
+                        inserted_content: ArtifactContent { text: new_file.contents.clone() },
+                    });
+                }
+                Fix {
+                    description: Message { text: description.clone() },
+                    artifact_changes: changes
+                        .into_iter()
+                        .map(|(uri, replacements)| ArtifactChange {
+                            artifact_location: ArtifactLocation { uri },
+                            replacements,
+                        })
+                        .collect(),
+                    properties: FixProperties { applicability: sugg.applicability },
+                }
+            })
+            .collect()
+    }
+
+    /// The macro backtrace of `span`, from the expanded code to the outermost macro invocation.
+    fn code_flow(&self, span: Span) -> Option<CodeFlow> {
+        let mut backtrace = span.macro_backtrace().peekable();
+        backtrace.peek()?;
+        let mut locations = vec![ThreadFlowLocation {
+            location: Location {
+                physical_location: Some(self.physical_location(span)),
+                message: None,
+            },
+            nesting_level: 0,
+        }];
+        for (depth, expn_data) in backtrace.enumerate() {
+            locations.push(ThreadFlowLocation {
+                location: Location {
+                    physical_location: Some(self.physical_location(expn_data.call_site)),
+                    message: Some(Message {
+                        text: format!("in this expansion of {}", expn_data.kind.descr()),
+                    }),
+                },
+                nesting_level: depth + 1,
+            });
+        }
+        Some(CodeFlow { thread_flows: [ThreadFlow { locations }] })
+    }
+}
+
+impl Translate for SarifEmitter {
+    fn fluent_bundle(&self) -> Option<&FluentBundle> {
+        self.fluent_bundle.as_deref()
+    }
+
+    fn fallback_fluent_bundle(&self) -> &FluentBundle {
+        &self.fallback_bundle
+    }
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, diag: DiagInner) {
+        if diag.level.is_failure_note() {
+            return;
+        }
+        let result = self.result(diag);
+        self.output.lock().unwrap().results.push(result);
+    }
+
+    fn source_map(&self) -> Option<&SourceMap> {
+        Some(&self.sm)
+    }
+
+    fn should_show_explain(&self) -> bool {
+        false
+    }
+}
+
+// The following data types are provided just for serialisation. Their fields are a subset of the
+// SARIF 2.1.0 objects of the same name.
+
+#[derive(Serialize)]
+struct SarifLog<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [Run<'a>; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run<'a> {
+    tool: Tool<'a>,
+    column_kind: &'static str,
+    results: &'a [SarifResult],
+}
+
+#[derive(Serialize)]
+struct Tool<'a> {
+    driver: ToolComponent<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolComponent<'a> {
+    name: &'static str,
+    information_uri: &'static str,
+    rules: Vec<&'a ReportingDescriptor>,
+}
+
+/// A rule, i.e., an error code or a lint.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportingDescriptor {
+    id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_uri: Option<String>,
+    /// The explanation of the error code.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help: Option<MultiformatMessage>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_index: Option<usize>,
+    /// "error", "warning" or "note".
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<Fix>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    code_flows: Vec<CodeFlow>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+struct MultiformatMessage {
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    markdown: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    physical_location: Option<PhysicalLocation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    /// 1-based.
+    start_line: usize,
+    /// 1-based, in Unicode code points.
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+    byte_offset: u32,
+    byte_length: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    description: Message,
+    artifact_changes: Vec<ArtifactChange>,
+    properties: FixProperties,
+}
+
+#[derive(Serialize)]
+struct FixProperties {
+    applicability: Applicability,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    replacements: Vec<Replacement>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+    inserted_content: ArtifactContent,
+}
+
+#[derive(Serialize)]
+struct ArtifactContent {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CodeFlow {
+    thread_flows: [ThreadFlow; 1],
+}
+
+#[derive(Serialize)]
+struct ThreadFlow {
+    locations: Vec<ThreadFlowLocation>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ThreadFlowLocation {
+    location: Location,
+    nesting_level: usize,
+}
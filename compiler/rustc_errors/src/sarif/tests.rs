@@ -0,0 +1,181 @@
+use std::path::Path;
+use std::str;
+use std::sync::{Arc, Mutex};
+
+use rustc_span::BytePos;
+use rustc_span::source_map::FilePathMapping;
+use serde_json::{Value, json};
+
+use super::*;
+use crate::DiagCtxt;
+use crate::codes::E0004;
+use crate::registry::Registry;
+
+struct Shared<T> {
+    data: Arc<Mutex<T>>,
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.data.lock().unwrap().flush()
+    }
+}
+
+/// Runs `f` with a `DiagCtxt` emitting SARIF for `code`, and returns the log written once the
+/// `DiagCtxt` is dropped.
+fn sarif_log(code: &str, f: impl FnOnce(&DiagCtxt)) -> Option<Value> {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("test.rs").to_owned().into(), code.to_owned());
+        let fallback_bundle =
+            crate::fallback_fluent_bundle(vec![crate::DEFAULT_LOCALE_RESOURCE], false);
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let emitter =
+            SarifEmitter::new(Box::new(Shared { data: output.clone() }), sm, fallback_bundle)
+                .registry(Some(Registry::new(&[(E0004, "Long description.")])));
+
+        let dcx = DiagCtxt::new(Box::new(emitter));
+        f(&dcx);
+        drop(dcx);
+
+        let bytes = output.lock().unwrap();
+        let output = str::from_utf8(&bytes).unwrap();
+        (!output.is_empty()).then(|| serde_json::from_str(output).unwrap())
+    })
+}
+
+fn span(lo: u32, hi: u32) -> Span {
+    Span::with_root_ctxt(BytePos(lo), BytePos(hi))
+}
+
+#[test]
+fn no_diagnostics() {
+    assert_eq!(sarif_log("fn main() {}", |_| {}), None);
+}
+
+#[test]
+fn error_with_rule() {
+    let log = sarif_log("let x = y;", |dcx| {
+        dcx.handle()
+            .struct_span_err(span(8, 9), "unresolved name")
+            .with_code(E0004)
+            .with_span_label(span(8, 9), "not found")
+            .with_span_label(span(4, 5), "in this binding")
+            .emit();
+        dcx.handle().struct_span_err(span(4, 5), "second error").with_code(E0004).emit();
+    })
+    .unwrap();
+
+    assert_eq!(log["version"], "2.1.0");
+    let run = &log["runs"][0];
+    assert_eq!(
+        run["tool"]["driver"]["rules"],
+        json!([{
+            "id": "E0004",
+            "helpUri": "https://doc.rust-lang.org/error_codes/E0004.html",
+            "help": { "text": "Long description.", "markdown": "Long description." },
+        }])
+    );
+
+    let result = &run["results"][0];
+    assert_eq!(result["ruleId"], "E0004");
+    assert_eq!(result["ruleIndex"], 0);
+    assert_eq!(result["level"], "error");
+    assert_eq!(result["message"]["text"], "unresolved name");
+    assert_eq!(
+        result["locations"],
+        json!([{
+            "physicalLocation": {
+                "artifactLocation": { "uri": "test.rs" },
+                "region": {
+                    "startLine": 1,
+                    "startColumn": 9,
+                    "endLine": 1,
+                    "endColumn": 10,
+                    "byteOffset": 8,
+                    "byteLength": 1,
+                },
+            },
+            "message": { "text": "not found" },
+        }])
+    );
+    assert_eq!(result["relatedLocations"][0]["message"]["text"], "in this binding");
+    assert_eq!(run["results"][1]["ruleIndex"], 0);
+}
+
+#[test]
+fn notes_and_suggestions() {
+    let log = sarif_log("let x = y;", |dcx| {
+        dcx.handle()
+            .struct_span_warn(span(4, 5), "unused variable")
+            .with_span_note(span(0, 10), "declared here")
+            .with_note("notes without spans are related locations too")
+            .with_span_suggestion(
+                span(4, 5),
+                "prefix it with an underscore",
+                "_x",
+                Applicability::MachineApplicable,
+            )
+            .emit();
+    })
+    .unwrap();
+
+    let result = &log["runs"][0]["results"][0];
+    assert_eq!(result["level"], "warning");
+    assert_eq!(result.get("ruleId"), None);
+    assert_eq!(result["relatedLocations"][0]["message"]["text"], "note: declared here");
+    assert_eq!(result["relatedLocations"][0]["physicalLocation"]["region"]["byteLength"], 10);
+    assert_eq!(
+        result["relatedLocations"][1],
+        json!({ "message": { "text": "note: notes without spans are related locations too" } })
+    );
+    assert_eq!(
+        result["fixes"],
+        json!([{
+            "description": { "text": "prefix it with an underscore" },
+            "artifactChanges": [{
+                "artifactLocation": { "uri": "test.rs" },
+                "replacements": [{
+                    "deletedRegion": {
+                        "startLine": 1,
+                        "startColumn": 5,
+                        "endLine": 1,
+                        "endColumn": 6,
+                        "byteOffset": 4,
+                        "byteLength": 1,
+                    },
+                    "insertedContent": { "text": "_x" },
+                }],
+            }],
+            "properties": { "applicability": "MachineApplicable" },
+        }])
+    );
+}
+
+#[test]
+fn untranslatable_messages() {
+    let missing = |id: &'static str| DiagMessage::FluentIdentifier(id.into(), None);
+    let log = sarif_log("let x = y;", |dcx| {
+        dcx.handle()
+            .struct_span_err(span(8, 9), missing("sarif_missing_message"))
+            .with_span_label(span(8, 9), missing("sarif_missing_label"))
+            .with_span_suggestion(
+                span(8, 9),
+                missing("sarif_missing_suggestion"),
+                "x",
+                Applicability::MaybeIncorrect,
+            )
+            .emit();
+    })
+    .unwrap();
+
+    let result = &log["runs"][0]["results"][0];
+    assert_eq!(result["message"]["text"], "sarif_missing_message");
+    assert_eq!(result["locations"][0]["message"]["text"], "sarif_missing_label");
+    assert_eq!(result["fixes"][0]["description"]["text"], "sarif_missing_suggestion");
+}
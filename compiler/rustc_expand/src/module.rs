@@ -3,7 +3,7 @@
 
 use rustc_ast::ptr::P;
 use rustc_ast::{AttrVec, Attribute, Inline, Item, ModSpans, token};
-use rustc_errors::{Diag, ErrorGuaranteed};
+use rustc_errors::{Applicability, Diag, ErrorGuaranteed};
 use rustc_parse::{new_parser_from_file, unwrap_or_emit_fatal, validate_attr};
 use rustc_session::Session;
 use rustc_session::parse::ParseSess;
@@ -15,6 +15,7 @@
 use crate::errors::{
     ModuleCircular, ModuleFileNotFound, ModuleInBlock, ModuleInBlockName, ModuleMultipleCandidates,
 };
+use crate::fluent_generated;
 
 #[derive(Copy, Clone)]
 pub enum DirOwnership {
@@ -265,12 +266,21 @@ fn report(self, sess: &Session, span: Span) -> ErrorGuaranteed {
                 name: ident.map(|name| ModuleInBlockName { span, name }),
             }),
             ModError::FileNotFound(name, default_path, secondary_path) => {
-                sess.dcx().emit_err(ModuleFileNotFound {
+                // An empty file is a valid module, so the error is fixed by creating it. The help
+                // also mentions the `mod.rs` path.
+                let mut diag = sess.dcx().create_err(ModuleFileNotFound {
                     span,
                     name,
                     default_path: default_path.display().to_string(),
                     secondary_path: secondary_path.display().to_string(),
-                })
+                });
+                diag.multifile_suggestion(
+                    fluent_generated::_subdiag::suggestion,
+                    vec![],
+                    vec![(default_path, String::new())],
+                    Applicability::MaybeIncorrect,
+                );
+                diag.emit()
             }
             ModError::MultipleCandidates(name, default_path, secondary_path) => {
                 sess.dcx().emit_err(ModuleMultipleCandidates {
@@ -497,7 +497,7 @@ fn fn_sig_suggestion<'tcx>(
 /// Return placeholder code for the given associated item.
 /// Similar to `ty::AssocItem::suggestion`, but appropriate for use as the code snippet of a
 /// structured suggestion.
-fn suggestion_signature<'tcx>(
+pub fn suggestion_signature<'tcx>(
     tcx: TyCtxt<'tcx>,
     assoc: ty::AssocItem,
     impl_trait_ref: ty::TraitRef<'tcx>,
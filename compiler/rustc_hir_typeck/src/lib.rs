@@ -53,7 +53,7 @@
 use rustc_middle::query::Providers;
 use rustc_middle::ty::{self, Ty, TyCtxt};
 use rustc_middle::{bug, span_bug};
-use rustc_session::config;
+use rustc_session::{config, time_report};
 use rustc_span::Span;
 use rustc_span::def_id::LocalDefId;
 use tracing::{debug, instrument};
@@ -86,8 +86,10 @@ fn used_trait_imports(tcx: TyCtxt<'_>, def_id: LocalDefId) -> &UnordSet<LocalDef
 }
 
 fn typeck<'tcx>(tcx: TyCtxt<'tcx>, def_id: LocalDefId) -> &'tcx ty::TypeckResults<'tcx> {
-    let fallback = move || tcx.type_of(def_id.to_def_id()).instantiate_identity();
-    typeck_with_fallback(tcx, def_id, fallback, None)
+    tcx.sess.time_item(time_report::TYPECK, def_id, || {
+        let fallback = move || tcx.type_of(def_id.to_def_id()).instantiate_identity();
+        typeck_with_fallback(tcx, def_id, fallback, None)
+    })
 }
 
 /// Used only to get `TypeckResults` for type inference during error recovery.
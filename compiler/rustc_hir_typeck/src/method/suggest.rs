@@ -19,6 +19,7 @@
 use rustc_hir::intravisit::{self, Visitor};
 use rustc_hir::lang_items::LangItem;
 use rustc_hir::{self as hir, ExprKind, HirId, Node, PathSegment, QPath};
+use rustc_hir_analysis::check::suggestion_signature;
 use rustc_infer::infer::{self, RegionVariableOrigin};
 use rustc_middle::bug;
 use rustc_middle::ty::fast_reject::{DeepRejectCtxt, TreatParams, simplify_type};
@@ -4029,6 +4030,12 @@ enum Introducer {
                                 )
                             },
                         });
+                        if let SelfSource::MethodCall(rcvr) = source
+                            && param_type.is_none()
+                            && !trait_missing_method
+                        {
+                            self.suggest_implementing_trait(err, rcvr, rcvr_ty, trait_info.def_id);
+                        }
                     }
                 }
                 trait_infos => {
@@ -4077,6 +4084,65 @@ enum Introducer {
         }
     }
 
+    /// Suggests an `impl` of the local trait `trait_def_id` for the local type of `rcvr` when the
+    /// type is defined in another file, along with importing the trait if it isn't in scope.
+    fn suggest_implementing_trait(
+        &self,
+        err: &mut Diag<'_>,
+        rcvr: &hir::Expr<'_>,
+        rcvr_ty: Ty<'tcx>,
+        trait_def_id: DefId,
+    ) {
+        let tcx = self.tcx;
+        let ty = rcvr_ty.peel_refs();
+        let ty::Adt(adt, args) = ty.kind() else { return };
+        let Some(adt_def_id) = adt.did().as_local() else { return };
+        // Only the `Self` parameter of the trait and no generic parameters of the type, so that
+        // the header of the `impl` can be written without placeholders.
+        if !args.is_empty() || tcx.generics_of(trait_def_id).count() != 1 {
+            return;
+        }
+        let sm = tcx.sess.source_map();
+        let adt_span = tcx.source_span(adt_def_id);
+        if adt_span.from_expansion() {
+            return;
+        }
+        if sm.lookup_source_file_idx(adt_span.lo()) == sm.lookup_source_file_idx(rcvr.span.lo()) {
+            // The note pointing at the trait is enough to write the `impl` next to the call.
+            return;
+        }
+
+        let trait_path = with_crate_prefix!(tcx.def_path_str(trait_def_id));
+        let ty_name = tcx.item_name(adt.did());
+        let trait_ref = ty::TraitRef::new(tcx, trait_def_id, [ty]);
+        let items = tcx
+            .associated_items(trait_def_id)
+            .in_definition_order()
+            .filter(|item| !item.defaultness(tcx).has_value())
+            .map(|item| format!("\n    {}", suggestion_signature(tcx, *item, trait_ref)))
+            .collect::<String>();
+        let items = if items.is_empty() { String::new() } else { format!("{items}\n") };
+        let mut edits = vec![(
+            adt_span.shrink_to_hi(),
+            format!("\n\nimpl {trait_path} for {ty_name} {{{items}}}"),
+        )];
+
+        let in_scope = tcx
+            .in_scope_traits(tcx.parent_hir_id(rcvr.hir_id))
+            .is_some_and(|traits| traits.iter().any(|t| t.def_id == trait_def_id));
+        if !in_scope {
+            let scope = tcx.parent_module_from_def_id(self.body_id);
+            let (module, _, _) = tcx.hir().get_module(scope);
+            edits.push((module.spans.inject_use_span, format!("use {trait_path};\n")));
+        }
+
+        err.multipart_suggestion_verbose(
+            format!("implement `{}` for `{ty_name}`", tcx.item_name(trait_def_id)),
+            edits,
+            Applicability::HasPlaceholders,
+        );
+    }
+
     fn detect_and_explain_multiple_crate_versions(
         &self,
         err: &mut Diag<'_>,
@@ -222,6 +222,20 @@ pub(crate) struct DeleteFull<'a> {
     pub err: std::io::Error,
 }
 
+#[derive(Diagnostic)]
+#[diag(incremental_shared_cache_import)]
+pub(crate) struct SharedCacheImport<'a> {
+    pub path: &'a Path,
+    pub err: std::io::Error,
+}
+
+#[derive(Diagnostic)]
+#[diag(incremental_shared_cache_export)]
+pub(crate) struct SharedCacheExport<'a> {
+    pub path: &'a Path,
+    pub err: std::io::Error,
+}
+
 #[derive(Diagnostic)]
 #[diag(incremental_finalize)]
 pub(crate) struct Finalize<'a> {
@@ -607,7 +607,12 @@ fn string_to_timestamp(s: &str) -> Result<SystemTime, &'static str> {
 
 fn crate_path(sess: &Session) -> PathBuf {
     let incr_dir = sess.opts.incremental.as_ref().unwrap().clone();
+    incr_dir.join(crate_dir_name(sess))
+}
 
+/// Returns the name of the directory holding the crate's session directories:
+/// `{crate-name}-{stable-crate-id}`.
+pub(crate) fn crate_dir_name(sess: &Session) -> String {
     let crate_name = find_crate_name(sess, &[]);
     let crate_types = collect_crate_types(sess, &[]);
     let stable_crate_id = StableCrateId::new(
@@ -617,9 +622,7 @@ fn crate_path(sess: &Session) -> PathBuf {
         sess.cfg_version,
     );
 
-    let crate_name =
-        format!("{crate_name}-{}", stable_crate_id.as_u64().to_base_fixed_len(CASE_INSENSITIVE));
-    incr_dir.join(crate_name)
+    format!("{crate_name}-{}", stable_crate_id.as_u64().to_base_fixed_len(CASE_INSENSITIVE))
 }
 
 fn is_old_enough_to_be_collected(timestamp: SystemTime) -> bool {
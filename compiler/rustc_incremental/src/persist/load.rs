@@ -17,6 +17,7 @@
 use super::data::*;
 use super::fs::*;
 use super::save::build_dep_graph;
+use super::shared_cache::import_from_shared_cache;
 use super::{file_format, work_product};
 use crate::errors;
 
@@ -90,7 +91,10 @@ fn delete_dirty_work_product(sess: &Session, swp: SerializedWorkProduct) {
     work_product::delete_workproduct_files(sess, &swp.work_product);
 }
 
-fn load_dep_graph(sess: &Session) -> LoadResult<(Arc<SerializedDepGraph>, WorkProductMap)> {
+fn load_dep_graph(
+    sess: &Session,
+    imported_commandline_args_hash: Option<u64>,
+) -> LoadResult<(Arc<SerializedDepGraph>, WorkProductMap)> {
     let prof = sess.prof.clone();
 
     if sess.opts.incremental.is_none() {
@@ -103,7 +107,13 @@ fn load_dep_graph(sess: &Session) -> LoadResult<(Arc<SerializedDepGraph>, WorkPr
     // Calling `sess.incr_comp_session_dir()` will panic if `sess.opts.incremental.is_none()`.
     // Fortunately, we just checked that this isn't the case.
     let path = dep_graph_path(sess);
-    let expected_hash = sess.opts.dep_tracking_hash(false);
+    // A session imported from the shared cache was saved by another checkout, whose commandline
+    // arguments only match ours up to the local prefixes of the path remappings: the index it was
+    // imported from is named after that portable hash. Its dep-graph must still be the one saved
+    // with the arguments recorded in the index, rather than, e.g., one left over by an older
+    // session of that checkout.
+    let expected_hash =
+        imported_commandline_args_hash.unwrap_or_else(|| sess.opts.dep_tracking_hash(false));
 
     let mut prev_work_products = UnordMap::default();
 
@@ -208,7 +218,17 @@ pub fn setup_dep_graph(sess: &Session) -> Result<DepGraph, ErrorGuaranteed> {
     // `load_dep_graph` can only be called after `prepare_session_directory`.
     prepare_session_directory(sess)?;
 
-    let res = sess.opts.build_dep_graph().then(|| load_dep_graph(sess));
+    // A fresh session directory can be seeded from the shared cache, and then be loaded like one
+    // copied from a previous session.
+    let imported_commandline_args_hash =
+        if sess.opts.incremental.is_some() && !dep_graph_path(sess).exists() {
+            import_from_shared_cache(sess)
+        } else {
+            None
+        };
+
+    let res =
+        sess.opts.build_dep_graph().then(|| load_dep_graph(sess, imported_commandline_args_hash));
 
     if sess.opts.incremental.is_some() {
         sess.time("incr_comp_garbage_collect_session_directories", || {
@@ -4,7 +4,7 @@
 use rustc_data_structures::fx::FxIndexMap;
 use rustc_data_structures::sync::join;
 use rustc_middle::dep_graph::{
-    DepGraph, SerializedDepGraph, WorkProduct, WorkProductId, WorkProductMap,
+    DepGraph, DepNodeFilter, SerializedDepGraph, WorkProduct, WorkProductId, WorkProductMap,
 };
 use rustc_middle::ty::TyCtxt;
 use rustc_serialize::Encodable as RustcEncodable;
@@ -14,6 +14,7 @@
 
 use super::data::*;
 use super::fs::*;
+use super::shared_cache::export_to_shared_cache;
 use super::{dirty_clean, file_format, work_product};
 use crate::assert_dep_graph::assert_dep_graph;
 use crate::errors;
@@ -75,6 +76,10 @@ pub fn save_dep_graph(tcx: TyCtxt<'_>) {
                 });
             },
         );
+
+        // Publish the results right away, so that sessions which don't generate code publish
+        // them too. The work products are published with their index once codegen is done.
+        export_to_shared_cache(sess, false);
     })
 }
 
@@ -119,6 +124,9 @@ pub fn save_work_product_index(
             wp.saved_files.items().all(|(_, path)| in_incr_comp_dir_sess(sess, path).exists())
         })
     });
+
+    // The work product index is the last file written to the session directory.
+    export_to_shared_cache(sess, true);
 }
 
 fn encode_work_product_index(
@@ -179,5 +187,6 @@ pub(crate) fn build_dep_graph(
         encoder,
         sess.opts.unstable_opts.query_dep_graph,
         sess.opts.unstable_opts.incremental_info,
+        sess.opts.unstable_opts.incremental_explain.as_deref().map(DepNodeFilter::new),
     ))
 }
@@ -0,0 +1,258 @@
+//! An incremental compilation cache shared between checkouts of the same sources, enabled with
+//! `-Zincremental-shared-cache=<dir>`.
+//!
+//! Session directories live in the crate's incremental directory, so every new worktree or CI
+//! runner starts from scratch. The shared cache lets such a session start from the results of a
+//! session published by another checkout instead. It consists of:
+//!
+//! - `blobs/`: the files of published sessions -- the dep-graph, the query result cache, the work
+//!   product index and the work products themselves -- each named after the fingerprint of its
+//!   contents, so that identical files published by different sessions are only stored once.
+//! - `index/`: for each compilation, the names and fingerprints of the files of the last session
+//!   published for it, along with the hash of its tracked commandline arguments. Indices are
+//!   named after the crate name, its `StableCrateId`, and a hash of the tracked commandline
+//!   arguments that doesn't depend on where the sources are checked out, see
+//!   `portable_commandline_args_hash`.
+//!
+//! Checkouts at different paths thus share an index when `--remap-path-prefix` maps them to the
+//! same path, which also makes the file names stored in the query result cache match. Importing
+//! only seeds a fresh session directory: its contents are then loaded like the ones of a previous
+//! session in the same checkout, and the dep-graph is checked against the current sources as
+//! usual. The commandline arguments hash stored in the imported dep-graph is checked against the
+//! one recorded in the index rather than the current one, since they differ by the local prefixes
+//! of the path remappings.
+//!
+//! Sessions publish their dep-graph and query result cache once they are saved, whether or not
+//! they generate code, and publish again with their work products once codegen is done.
+//!
+//! Blobs and indices are written under temporary names and then renamed, so that concurrent
+//! sessions never observe partially written files. Nothing is ever removed from the shared cache,
+//! cleaning it up is left to its owner.
+
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+use std::{fs as std_fs, io};
+
+use rand::{RngCore, thread_rng};
+use rustc_data_structures::base_n::{CASE_INSENSITIVE, ToBaseN};
+use rustc_data_structures::fingerprint::Fingerprint;
+use rustc_data_structures::stable_hasher::StableHasher;
+use rustc_fs_util::link_or_copy;
+use rustc_serialize::opaque::{FileEncoder, MemDecoder};
+use rustc_serialize::{Decodable, Encodable};
+use rustc_session::Session;
+use tracing::debug;
+
+use super::file_format;
+use super::fs::*;
+use crate::errors;
+
+const BLOBS_DIR: &str = "blobs";
+const INDEX_DIR: &str = "index";
+
+/// An index entry: the name of a file in the session directory, and the fingerprint of its
+/// contents, naming its blob.
+type IndexEntry = (String, Fingerprint);
+
+/// Seeds the current, fresh session directory with the files of the last session published to the
+/// shared cache for this compilation, if there is one. Returns the commandline arguments hash of
+/// the imported session, which its dep-graph must have been saved with.
+pub(crate) fn import_from_shared_cache(sess: &Session) -> Option<u64> {
+    let cache_dir = sess.opts.unstable_opts.incremental_shared_cache.as_ref()?;
+
+    let _timer = sess.timer("incr_comp_import_from_shared_cache");
+
+    let index_path = index_path(sess, cache_dir);
+    let (commandline_args_hash, entries) = match read_index(sess, &index_path) {
+        Ok(Some(index)) => index,
+        Ok(None) => {
+            debug!("import_from_shared_cache: no usable index at {}", index_path.display());
+            if sess.opts.unstable_opts.incremental_info {
+                eprintln!("[incremental] shared cache: no session published for this compilation");
+            }
+            return None;
+        }
+        Err(err) => {
+            sess.dcx().emit_warn(errors::SharedCacheImport { path: &index_path, err });
+            return None;
+        }
+    };
+
+    let session_dir = sess.incr_comp_session_dir().clone();
+    for (file_name, fingerprint) in &entries {
+        let blob_path = blob_path(cache_dir, *fingerprint);
+        debug!("importing {} from shared cache: {}", file_name, blob_path.display());
+        if let Err(err) = link_or_copy(&blob_path, in_incr_comp_dir(&session_dir, file_name)) {
+            sess.dcx().emit_warn(errors::SharedCacheImport { path: &blob_path, err });
+
+            // Rather than loading an incomplete session, start from an empty one.
+            if let Err(err) = delete_all_session_dir_contents(sess) {
+                sess.dcx().emit_err(errors::DeleteIncompatible { path: session_dir, err });
+            }
+            return None;
+        }
+    }
+
+    if sess.opts.unstable_opts.incremental_info {
+        eprintln!("[incremental] shared cache: {} files imported", entries.len());
+    }
+    Some(commandline_args_hash)
+}
+
+/// Publishes the files of the current session directory to the shared cache, as the last session
+/// for this compilation. Unless `include_work_products` is set, only the dep-graph and the query
+/// result cache are published, since codegen may still be writing the work products.
+///
+/// Nothing is published for sessions with errors, as their directory is about to be deleted.
+pub(crate) fn export_to_shared_cache(sess: &Session, include_work_products: bool) {
+    let Some(cache_dir) = &sess.opts.unstable_opts.incremental_shared_cache else {
+        return;
+    };
+    if sess.dcx().has_errors().is_some() {
+        return;
+    }
+
+    let _timer = sess.timer("incr_comp_export_to_shared_cache");
+
+    if let Err(err) = try_export_to_shared_cache(sess, cache_dir, include_work_products) {
+        sess.dcx().emit_warn(errors::SharedCacheExport { path: cache_dir, err });
+    }
+}
+
+fn try_export_to_shared_cache(
+    sess: &Session,
+    cache_dir: &Path,
+    include_work_products: bool,
+) -> io::Result<()> {
+    std_fs::create_dir_all(cache_dir.join(BLOBS_DIR))?;
+    std_fs::create_dir_all(cache_dir.join(INDEX_DIR))?;
+
+    let saved_with_dep_graph = [dep_graph_path(sess), query_cache_path(sess)];
+    let mut entries = Vec::new();
+    let mut blobs_published = 0;
+    for entry in sess.incr_comp_session_dir().read_dir()? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file()
+            || !(include_work_products || saved_with_dep_graph.contains(&entry.path()))
+        {
+            continue;
+        }
+        let Ok(file_name) = entry.file_name().into_string() else {
+            continue;
+        };
+
+        let fingerprint = fingerprint_contents(&entry.path())?;
+        let blob_path = blob_path(cache_dir, fingerprint);
+        if !blob_path.exists() {
+            debug!("publishing {} to shared cache: {}", file_name, blob_path.display());
+
+            // Files in session directories are never modified in place, so the blob can be a
+            // hard-link to the session's file.
+            let temporary_path = temporary_path(&blob_path);
+            link_or_copy(entry.path(), &temporary_path)?;
+            if let Err(err) = std_fs::rename(&temporary_path, &blob_path) {
+                // Another session may have published the same contents in the meantime.
+                let _ = std_fs::remove_file(&temporary_path);
+                if !blob_path.exists() {
+                    return Err(err);
+                }
+            }
+            blobs_published += 1;
+        }
+        entries.push((file_name, fingerprint));
+    }
+
+    // Keep the index independent of the order in which the directory is read.
+    entries.sort();
+    write_index(sess, &index_path(sess, cache_dir), &entries)?;
+
+    if sess.opts.unstable_opts.incremental_info {
+        eprintln!(
+            "[incremental] shared cache: {} files published, {blobs_published} of them new",
+            entries.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns the path of the index for this compilation.
+fn index_path(sess: &Session, cache_dir: &Path) -> PathBuf {
+    let commandline_args_hash = portable_commandline_args_hash(sess);
+    let index_name = format!(
+        "{}-{}",
+        crate_dir_name(sess),
+        commandline_args_hash.to_base_fixed_len(CASE_INSENSITIVE)
+    );
+    cache_dir.join(INDEX_DIR).join(index_name)
+}
+
+/// Returns the hash of the tracked commandline arguments, without the local prefixes of the path
+/// remappings. These are where each checkout is, e.g. `--remap-path-prefix=$PWD=/src`, while the
+/// paths they are remapped to, and thus the paths the compilation sees, must still match.
+///
+/// The working directory is tracked by its remapped path if it has one. When it doesn't, the hash
+/// still differs between checkouts at different paths, which then can't share their results.
+fn portable_commandline_args_hash(sess: &Session) -> u64 {
+    let mut opts = sess.opts.clone();
+    for (local_prefix, _) in &mut opts.remap_path_prefix {
+        *local_prefix = PathBuf::new();
+    }
+    opts.dep_tracking_hash(false)
+}
+
+fn blob_path(cache_dir: &Path, fingerprint: Fingerprint) -> PathBuf {
+    cache_dir.join(BLOBS_DIR).join(fingerprint.to_hex())
+}
+
+/// Returns a unique path next to `path`, to write to before renaming the result to `path`.
+fn temporary_path(path: &Path) -> PathBuf {
+    let random_number = thread_rng().next_u32();
+    path.with_extension(format!("{}.tmp", random_number.to_base_fixed_len(CASE_INSENSITIVE)))
+}
+
+fn fingerprint_contents(path: &Path) -> io::Result<Fingerprint> {
+    let contents = std_fs::read(path)?;
+    let mut hasher = StableHasher::new();
+    hasher.write(&contents);
+    Ok(hasher.finish())
+}
+
+/// Reads the index at `path`: the commandline arguments hash of the published session and its
+/// files. Returns `Ok(None)` if there is none, or if it was written by an incompatible compiler
+/// version.
+fn read_index(sess: &Session, path: &Path) -> io::Result<Option<(u64, Vec<IndexEntry>)>> {
+    let Some((data, start_pos)) = file_format::read_file(
+        path,
+        sess.opts.unstable_opts.incremental_info,
+        sess.is_nightly_build(),
+        sess.cfg_version,
+    )?
+    else {
+        return Ok(None);
+    };
+
+    let Ok(mut decoder) = MemDecoder::new(&data, start_pos) else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "corrupt shared cache index"));
+    };
+    let commandline_args_hash = u64::decode(&mut decoder);
+    let entries = Vec::<IndexEntry>::decode(&mut decoder);
+
+    // The index names files in the session directory, and nothing else.
+    let is_file_name = |name: &str| Path::new(name).file_name() == Some(name.as_ref());
+    if !entries.iter().all(|(file_name, _)| is_file_name(file_name)) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "malformed shared cache index"));
+    }
+
+    Ok(Some((commandline_args_hash, entries)))
+}
+
+fn write_index(sess: &Session, path: &Path, entries: &[IndexEntry]) -> io::Result<()> {
+    let temporary_path = temporary_path(path);
+    let mut encoder = FileEncoder::new(&temporary_path)?;
+    file_format::write_file_header(&mut encoder, sess);
+    sess.opts.dep_tracking_hash(false).encode(&mut encoder);
+    entries.encode(&mut encoder);
+    encoder.finish().map_err(|(_, err)| err)?;
+    std_fs::rename(&temporary_path, path)
+}
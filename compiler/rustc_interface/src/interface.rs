@@ -11,6 +11,7 @@
 use rustc_errors::registry::Registry;
 use rustc_errors::{DiagCtxtHandle, ErrorGuaranteed};
 use rustc_lint::LintStore;
+use rustc_middle::mir::{CustomMirPass, CustomPassPhase};
 use rustc_middle::ty;
 use rustc_middle::ty::CurrentGcx;
 use rustc_middle::util::Providers;
@@ -42,6 +43,7 @@ pub struct Compiler {
     pub sess: Session,
     pub codegen_backend: Box<dyn CodegenBackend>,
     pub(crate) override_queries: Option<fn(&Session, &mut Providers)>,
+    pub(crate) mir_passes: Vec<(CustomPassPhase, Box<dyn CustomMirPass>)>,
     pub(crate) current_gcx: CurrentGcx,
 }
 
@@ -337,6 +339,10 @@ pub struct Config {
     /// the list of queries.
     pub override_queries: Option<fn(&Session, &mut Providers)>,
 
+    /// Custom MIR passes to run at the given phases of the MIR pipeline, in addition to the
+    /// built-in ones. Passes registered for the same phase run in order.
+    pub mir_passes: Vec<(CustomPassPhase, Box<dyn CustomMirPass>)>,
+
     /// This is a callback from the driver that is called to create a codegen backend.
     pub make_codegen_backend:
         Option<Box<dyn FnOnce(&config::Options) -> Box<dyn CodegenBackend> + Send>>,
@@ -487,6 +493,7 @@ pub fn run_compiler<R: Send>(config: Config, f: impl FnOnce(&Compiler) -> R + Se
                 sess,
                 codegen_backend,
                 override_queries: config.override_queries,
+                mir_passes: config.mir_passes,
                 current_gcx,
             };
 
@@ -679,6 +679,9 @@ pub fn write_dep_info(tcx: TyCtxt<'_>) {
 
     write_out_deps(tcx, &outputs, &output_paths);
 
+    // The accessed files and env vars are now known.
+    sess.verify_reproducibility();
+
     let only_dep_info = sess.opts.output_types.contains_key(&OutputType::DepInfo)
         && sess.opts.output_types.len() == 1;
 
@@ -795,6 +798,7 @@ pub(crate) fn create_global_ctxt<'tcx>(
                     incremental,
                 ),
                 providers.hooks,
+                &compiler.mir_passes,
                 compiler.current_gcx.clone(),
             )
         });
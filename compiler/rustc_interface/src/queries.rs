@@ -5,16 +5,19 @@
 use rustc_ast as ast;
 use rustc_codegen_ssa::CodegenResults;
 use rustc_codegen_ssa::traits::CodegenBackend;
+use rustc_data_structures::fx::FxIndexMap;
 use rustc_data_structures::steal::Steal;
 use rustc_data_structures::svh::Svh;
 use rustc_data_structures::sync::{OnceLock, WorkerLocal};
-use rustc_hir::def_id::LOCAL_CRATE;
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
 use rustc_middle::arena::Arena;
 use rustc_middle::dep_graph::DepGraph;
+use rustc_middle::mir::mono::MonoItem;
 use rustc_middle::ty::{GlobalCtxt, TyCtxt};
 use rustc_serialize::opaque::FileEncodeResult;
 use rustc_session::Session;
 use rustc_session::config::{self, OutputFilenames, OutputType};
+use rustc_session::time_report::{InstanceInfo, ItemInfo, TimeReport};
 
 use crate::errors::FailedWritingFile;
 use crate::interface::{Compiler, Result};
@@ -140,6 +143,12 @@ pub fn codegen_and_build_linker(
             tcx.sess.code_stats.print_vtable_sizes(crate_name);
         }
 
+        // This must run after monomorphization too, to know which items are instantiated in
+        // which codegen units.
+        if let Some(time_report) = &tcx.sess.time_report {
+            time_report.record_items(time_report_items(tcx, time_report));
+        }
+
         Ok(Linker {
             dep_graph: tcx.dep_graph.clone(),
             output_filenames: tcx.output_filenames(()).clone(),
@@ -156,6 +165,10 @@ pub fn link(self, sess: &Session, codegen_backend: &dyn CodegenBackend) -> Resul
         let (codegen_results, work_products) =
             codegen_backend.join_codegen(self.ongoing_codegen, sess, &self.output_filenames);
 
+        if let Some(time_report) = &sess.time_report {
+            time_report.print();
+        }
+
         if let Some(guar) = sess.dcx().has_errors() {
             return Err(guar);
         }
@@ -236,3 +249,45 @@ pub fn enter<F, T>(&self, f: F) -> T
         ret
     }
 }
+
+/// Collects the items to report on with `-Ztime-report=items`: the ones timed during analysis,
+/// and the ones instantiated in codegen units, with each of their instances.
+fn time_report_items(tcx: TyCtxt<'_>, time_report: &TimeReport) -> Vec<ItemInfo> {
+    let mut items: FxIndexMap<DefId, FxIndexMap<_, Vec<(String, f64)>>> =
+        time_report.items.items().into_iter().map(|def_id| (def_id, Default::default())).collect();
+
+    // Don't partition the crate if it isn't going to be codegened, e.g. with `cargo check`.
+    if !tcx.sess.opts.unstable_opts.no_codegen && tcx.sess.opts.output_types.should_codegen() {
+        let (_, codegen_units) = tcx.collect_and_partition_mono_items(());
+        for cgu in codegen_units {
+            let cgu_size = cgu.size_estimate().max(1) as f64;
+            for (item, data) in cgu.items() {
+                let MonoItem::Fn(instance) = item else { continue };
+                let shares =
+                    items.entry(instance.def_id()).or_default().entry(*instance).or_default();
+                let share = data.size_estimate as f64 / cgu_size;
+                match shares.iter_mut().find(|(name, _)| name.as_str() == cgu.name().as_str()) {
+                    Some((_, total_share)) => *total_share += share,
+                    None => shares.push((cgu.name().to_string(), share)),
+                }
+            }
+        }
+    }
+
+    let source_map = tcx.sess.source_map();
+    items
+        .into_iter()
+        .map(|(def_id, instances)| ItemInfo {
+            def_id,
+            path: tcx.def_path_str(def_id),
+            span: source_map.span_to_embeddable_string(tcx.def_span(def_id)),
+            instances: instances
+                .into_iter()
+                .map(|(instance, codegen_unit_shares)| InstanceInfo {
+                    name: instance.to_string(),
+                    codegen_unit_shares,
+                })
+                .collect(),
+        })
+        .collect()
+}
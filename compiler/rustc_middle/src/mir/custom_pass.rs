@@ -0,0 +1,46 @@
+//! MIR passes provided by custom drivers.
+//!
+//! Drivers register them in `rustc_interface::Config::mir_passes`, and the MIR pass manager runs
+//! them at the requested [`CustomPassPhase`], the same way as the built-in passes: they can be
+//! disabled with `-Zmir-enable-passes`, their effect is shown by `-Zdump-mir`, and the resulting
+//! MIR is checked by `-Zvalidate-mir`.
+
+use rustc_data_structures::sync::{DynSend, DynSync};
+use rustc_session::Session;
+
+use crate::mir::Body;
+use crate::ty::TyCtxt;
+
+/// The point of the MIR pipeline where a [`CustomMirPass`] runs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CustomPassPhase {
+    /// After borrow checking, before any of the analysis cleanup passes. The body is in
+    /// `MirPhase::Analysis(AnalysisPhase::Initial)`.
+    AfterBorrowck,
+    /// Before the optimization passes. The body is in
+    /// `MirPhase::Runtime(RuntimePhase::PostCleanup)`.
+    ///
+    /// Only the `optimized_mir` of an item is optimized, so passes registered for this phase and
+    /// for [`CustomPassPhase::AfterOptimization`] don't run on the MIR used by CTFE.
+    BeforeOptimization,
+    /// After the optimization passes, right before the MIR is handed to codegen. The body is in
+    /// `MirPhase::Runtime(RuntimePhase::Optimized)`.
+    AfterOptimization,
+}
+
+/// A MIR pass registered by a custom driver.
+///
+/// The pass must preserve the invariants of the [`MirPhase`](crate::mir::MirPhase) the body is in.
+/// Changes made by a pass are not tracked by the incremental system, so a pass must only depend
+/// on the body it is given and on queries.
+pub trait CustomMirPass: DynSend + DynSync + Send + Sync {
+    /// The name of the pass, used by `-Zmir-enable-passes` and `-Zdump-mir`.
+    fn name(&self) -> &'static str;
+
+    /// Returns `true` if this pass is enabled with the current combination of compiler flags.
+    fn is_enabled(&self, _sess: &Session) -> bool {
+        true
+    }
+
+    fn run_pass<'tcx>(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>);
+}
@@ -46,6 +46,7 @@
 mod basic_blocks;
 mod consts;
 pub mod coverage;
+mod custom_pass;
 mod generic_graph;
 pub mod generic_graphviz;
 pub mod graphviz;
@@ -64,6 +65,7 @@
 pub mod visit;
 
 pub use consts::*;
+pub use custom_pass::{CustomMirPass, CustomPassPhase};
 use pretty::pretty_print_const_value;
 pub use statement::*;
 pub use syntax::*;
@@ -72,7 +72,8 @@ pub fn size_estimate(&self, tcx: TyCtxt<'tcx>) -> usize {
                     // statements, plus one for the terminator.
                     InstanceKind::Item(..)
                     | InstanceKind::DropGlue(..)
-                    | InstanceKind::AsyncDropGlueCtorShim(..) => {
+                    | InstanceKind::AsyncDropGlueCtorShim(..)
+                    | InstanceKind::ConstArgShim(..) => {
                         let mir = tcx.instance_mir(instance.def);
                         mir.basic_blocks.iter().map(|bb| bb.statements.len() + 1).sum()
                     }
@@ -430,7 +431,8 @@ fn item_sort_key<'tcx>(tcx: TyCtxt<'tcx>, item: MonoItem<'tcx>) -> ItemSortKey<'
                             | InstanceKind::CloneShim(..)
                             | InstanceKind::ThreadLocalShim(..)
                             | InstanceKind::FnPtrAddrShim(..)
-                            | InstanceKind::AsyncDropGlueCtorShim(..) => None,
+                            | InstanceKind::AsyncDropGlueCtorShim(..)
+                            | InstanceKind::ConstArgShim(..) => None,
                         }
                     }
                     MonoItem::Static(def_id) => def_id.as_local().map(Idx::index),
@@ -241,6 +241,13 @@ fn dump_path<'tcx>(
             }));
             s
         }
+        ty::InstanceKind::ConstArgShim(_, const_args) => {
+            let mut s = ".const_args(".to_owned();
+            ty::fmt_const_args(&mut s, const_args, ",").unwrap();
+            s.push(')');
+            s.retain(|c| !matches!(c, ' ' | '\'' | ':' | '<' | '>'));
+            s
+        }
         _ => String::new(),
     };
 
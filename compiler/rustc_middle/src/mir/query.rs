@@ -129,6 +129,45 @@ pub struct BorrowCheckResult<'tcx> {
     pub tainted_by_errors: Option<ErrorGuaranteed>,
 }
 
+/// The borrow checker results of a body, retained for external tools after the body it refers to
+/// was stolen. See [`TyCtxt::retain_borrowck_facts`].
+#[derive(Debug)]
+pub struct RetainedBorrowckFacts<'tcx> {
+    /// The promoted MIR that was borrow checked, with its regions replaced by inference variables.
+    pub body: mir::Body<'tcx>,
+    /// The borrows that occur in `body`.
+    pub borrows: Vec<RetainedBorrow<'tcx>>,
+    /// The outlives constraints that were used to infer the region variables.
+    pub outlives_constraints: Vec<RetainedOutlivesConstraint<'tcx>>,
+    /// The locations contained in each region variable after inference.
+    pub region_values: Vec<Vec<mir::Location>>,
+    /// The indices of the borrows that are live right before each location of `body` executes.
+    pub live_loans: Vec<(mir::Location, Vec<usize>)>,
+}
+
+/// A borrow retained in [`RetainedBorrowckFacts::borrows`].
+#[derive(Debug)]
+pub struct RetainedBorrow<'tcx> {
+    pub reserve_location: mir::Location,
+    /// Location where a two-phase borrow is activated, if there's one.
+    pub activation_location: Option<mir::Location>,
+    pub kind: mir::BorrowKind,
+    pub region: ty::RegionVid,
+    pub borrowed_place: mir::Place<'tcx>,
+    pub assigned_place: mir::Place<'tcx>,
+}
+
+/// A constraint retained in [`RetainedBorrowckFacts::outlives_constraints`].
+#[derive(Debug)]
+pub struct RetainedOutlivesConstraint<'tcx> {
+    pub sup: ty::RegionVid,
+    pub sub: ty::RegionVid,
+    /// The location where this constraint must hold, or `None` if it must hold everywhere.
+    pub location: Option<mir::Location>,
+    pub span: Span,
+    pub category: ConstraintCategory<'tcx>,
+}
+
 /// The result of the `mir_const_qualif` query.
 ///
 /// Each field (except `tainted_by_errors`) corresponds to an implementer of the `Qualif` trait in
@@ -350,7 +350,8 @@ fn super_source_scope_data(
                             receiver_by_ref: _,
                         } |
                         ty::InstanceKind::AsyncDropGlueCtorShim(_def_id, None) |
-                        ty::InstanceKind::DropGlue(_def_id, None) => {}
+                        ty::InstanceKind::DropGlue(_def_id, None) |
+                        ty::InstanceKind::ConstArgShim(_def_id, _) => {}
 
                         ty::InstanceKind::FnPtrShim(_def_id, ty) |
                         ty::InstanceKind::DropGlue(_def_id, Some(ty)) |
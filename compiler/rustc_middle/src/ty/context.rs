@@ -66,7 +66,10 @@
 use crate::middle::codegen_fn_attrs::CodegenFnAttrs;
 use crate::middle::{resolve_bound_vars, stability};
 use crate::mir::interpret::{self, Allocation, ConstAllocation};
-use crate::mir::{Body, Local, Place, PlaceElem, ProjectionKind, Promoted};
+use crate::mir::{
+    Body, CustomMirPass, CustomPassPhase, Local, Place, PlaceElem, ProjectionKind, Promoted,
+    RetainedBorrowckFacts,
+};
 use crate::query::plumbing::QuerySystem;
 use crate::query::{IntoQueryParam, LocalCrate, Providers, TyCtxtAt};
 use crate::thir::Thir;
@@ -1309,6 +1312,15 @@ pub struct GlobalCtxt<'tcx> {
     /// Stores memory for globals (statics/consts).
     pub(crate) alloc_map: Lock<interpret::AllocMap<'tcx>>,
 
+    /// Optimized MIR bodies provided by external tools, see [`TyCtxt::replace_optimized_mir`].
+    pub(crate) replaced_mir: Lock<FxHashMap<LocalDefId, &'tcx Body<'tcx>>>,
+
+    /// Borrow checker results retained for external tools, see [`TyCtxt::retain_borrowck_facts`].
+    retained_borrowck_facts: Lock<FxHashMap<LocalDefId, &'tcx RetainedBorrowckFacts<'tcx>>>,
+
+    /// MIR passes registered by the driver, see [`TyCtxt::custom_mir_passes`].
+    custom_mir_passes: &'tcx [(CustomPassPhase, Box<dyn CustomMirPass>)],
+
     current_gcx: CurrentGcx,
 }
 
@@ -1416,6 +1428,56 @@ pub fn alloc_steal_mir(self, mir: Body<'tcx>) -> &'tcx Steal<Body<'tcx>> {
         self.arena.alloc(Steal::new(mir))
     }
 
+    /// Replaces the optimized MIR of `def_id` with `body` for the rest of the compilation session.
+    ///
+    /// The new body is returned by [`TyCtxt::instance_mir`], which is what monomorphization and
+    /// codegen use. Queries that were already computed from the original body (e.g., the
+    /// optimized MIR of callers where it was inlined, or the crate metadata) are not affected.
+    ///
+    /// This is meant for external tools, which are responsible for validating `body` beforehand.
+    /// It is not tracked by the incremental system, so it must not be used in incremental mode.
+    pub fn replace_optimized_mir(self, def_id: LocalDefId, body: Body<'tcx>) {
+        assert!(
+            self.sess.opts.incremental.is_none(),
+            "cannot replace the MIR of {def_id:?} in incremental mode"
+        );
+        let body = self.arena.alloc(body);
+        self.replaced_mir.lock().insert(def_id, body);
+    }
+
+    /// Returns the MIR that replaced the optimized MIR of `def_id`, if any.
+    pub fn replaced_optimized_mir(self, def_id: DefId) -> Option<&'tcx Body<'tcx>> {
+        let def_id = def_id.as_local()?;
+        self.replaced_mir.lock().get(&def_id).copied()
+    }
+
+    /// Keeps the borrow checker results of `def_id` for the rest of the compilation session, so
+    /// external tools can still access them once the promoted MIR has been stolen.
+    pub fn retain_borrowck_facts(self, def_id: LocalDefId, facts: RetainedBorrowckFacts<'tcx>) {
+        let facts = self.arena.alloc(facts);
+        self.retained_borrowck_facts.lock().insert(def_id, facts);
+    }
+
+    /// Returns the borrow checker results retained for `def_id`, if any.
+    pub fn retained_borrowck_facts(
+        self,
+        def_id: LocalDefId,
+    ) -> Option<&'tcx RetainedBorrowckFacts<'tcx>> {
+        self.retained_borrowck_facts.lock().get(&def_id).copied()
+    }
+
+    /// Returns the MIR passes registered by the driver to run at `phase`, in registration order.
+    pub fn custom_mir_passes(
+        self,
+        phase: CustomPassPhase,
+    ) -> impl Iterator<Item = &'tcx dyn CustomMirPass> {
+        self.gcx
+            .custom_mir_passes
+            .iter()
+            .filter(move |(pass_phase, _)| *pass_phase == phase)
+            .map(|(_, pass)| &**pass)
+    }
+
     pub fn alloc_steal_promoted(
         self,
         promoted: IndexVec<Promoted, Body<'tcx>>,
@@ -1495,6 +1557,7 @@ pub fn create_global_ctxt(
         query_kinds: &'tcx [DepKindStruct<'tcx>],
         query_system: QuerySystem<'tcx>,
         hooks: crate::hooks::Providers,
+        custom_mir_passes: &'tcx [(CustomPassPhase, Box<dyn CustomMirPass>)],
         current_gcx: CurrentGcx,
     ) -> GlobalCtxt<'tcx> {
         let data_layout = s.target.parse_data_layout().unwrap_or_else(|err| {
@@ -1530,6 +1593,9 @@ pub fn create_global_ctxt(
             canonical_param_env_cache: Default::default(),
             data_layout,
             alloc_map: Lock::new(interpret::AllocMap::new()),
+            replaced_mir: Default::default(),
+            retained_borrowck_facts: Default::default(),
+            custom_mir_passes,
             current_gcx,
         }
     }
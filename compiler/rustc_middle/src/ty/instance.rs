@@ -173,6 +173,14 @@ pub enum InstanceKind<'tcx> {
     /// The `DefId` is for `core::future::async_drop::async_drop_in_place`, the `Ty`
     /// is the type `T`.
     AsyncDropGlueCtorShim(DefId, Option<Ty<'tcx>>),
+
+    /// Clone of the non-generic function `DefId` specialized for constant arguments by the
+    /// `SpecializeConstArgs` MIR pass.
+    ///
+    /// The `GenericArgsRef` has one entry per argument of the function: the constant value of the
+    /// argument if it is bound, or its type otherwise. Calls passing the same constants share the
+    /// same clone.
+    ConstArgShim(DefId, GenericArgsRef<'tcx>),
 }
 
 impl<'tcx> Instance<'tcx> {
@@ -243,7 +251,8 @@ pub fn def_id(self) -> DefId {
             | InstanceKind::DropGlue(def_id, _)
             | InstanceKind::CloneShim(def_id, _)
             | InstanceKind::FnPtrAddrShim(def_id, _)
-            | InstanceKind::AsyncDropGlueCtorShim(def_id, _) => def_id,
+            | InstanceKind::AsyncDropGlueCtorShim(def_id, _)
+            | InstanceKind::ConstArgShim(def_id, _) => def_id,
         }
     }
 
@@ -264,7 +273,8 @@ pub fn def_id_if_not_guaranteed_local_codegen(self) -> Option<DefId> {
             | InstanceKind::DropGlue(..)
             | InstanceKind::AsyncDropGlueCtorShim(..)
             | InstanceKind::CloneShim(..)
-            | InstanceKind::FnPtrAddrShim(..) => None,
+            | InstanceKind::FnPtrAddrShim(..)
+            | InstanceKind::ConstArgShim(..) => None,
         }
     }
 
@@ -364,7 +374,8 @@ pub fn has_polymorphic_mir_body(&self) -> bool {
             | InstanceKind::FnPtrAddrShim(..)
             | InstanceKind::FnPtrShim(..)
             | InstanceKind::DropGlue(_, Some(_))
-            | InstanceKind::AsyncDropGlueCtorShim(_, Some(_)) => false,
+            | InstanceKind::AsyncDropGlueCtorShim(_, Some(_))
+            | InstanceKind::ConstArgShim(..) => false,
             InstanceKind::ClosureOnceShim { .. }
             | InstanceKind::ConstructCoroutineInClosureShim { .. }
             | InstanceKind::DropGlue(..)
@@ -447,7 +458,31 @@ pub fn fmt_instance(
         InstanceKind::FnPtrAddrShim(_, ty) => write!(f, " - shim({ty})"),
         InstanceKind::AsyncDropGlueCtorShim(_, None) => write!(f, " - shim(None)"),
         InstanceKind::AsyncDropGlueCtorShim(_, Some(ty)) => write!(f, " - shim(Some({ty}))"),
+        InstanceKind::ConstArgShim(_, const_args) => {
+            write!(f, " - shim(const_args: ")?;
+            fmt_const_args(f, const_args, ", ")?;
+            write!(f, ")")
+        }
+    }
+}
+
+/// Prints the constant arguments of a `ConstArgShim`, with `_` for the arguments that are not
+/// bound.
+pub fn fmt_const_args(
+    f: &mut (impl fmt::Write + ?Sized),
+    const_args: GenericArgsRef<'_>,
+    separator: &str,
+) -> fmt::Result {
+    for (index, arg) in const_args.iter().enumerate() {
+        if index > 0 {
+            f.write_str(separator)?;
+        }
+        match arg.unpack() {
+            ty::GenericArgKind::Const(ct) => write!(f, "{ct}")?,
+            _ => f.write_str("_")?,
+        }
     }
+    Ok(())
 }
 
 pub struct ShortInstance<'tcx>(pub Instance<'tcx>, pub usize);
@@ -1687,7 +1687,17 @@ fn pretty_print_const_scalar_ptr(
                     self.tcx().try_get_global_alloc(prov.alloc_id())
                 {
                     self.typed_value(
-                        |this| this.print_value_path(instance.def_id(), instance.args),
+                        |this| {
+                            this.print_value_path(instance.def_id(), instance.args)?;
+                            // Distinguish the clones of a function specialized for different
+                            // constant arguments.
+                            if let ty::InstanceKind::ConstArgShim(_, const_args) = instance.def {
+                                this.write_str(" - shim(const_args: ")?;
+                                ty::fmt_const_args(this, const_args, ", ")?;
+                                this.write_str(")")?;
+                            }
+                            Ok(())
+                        },
                         |this| this.print_type(ty),
                         " as ",
                     )?;
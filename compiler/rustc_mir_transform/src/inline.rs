@@ -114,6 +114,45 @@ fn inline<'tcx>(tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) -> bool {
     this.changed
 }
 
+/// Resolves the direct call terminating `block` in `caller_body`, for `SpecializeConstArgs`.
+/// Returns the callee if its MIR is available and can be used while optimizing `caller_body`
+/// without causing a query cycle.
+///
+/// Cloning the callee does not need the other checks of the `Inline` pass, as the clone keeps the
+/// codegen attributes of the callee, and remains a separate function.
+pub(crate) fn resolve_specializable_call<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    caller_body: &Body<'tcx>,
+    block: BasicBlock,
+) -> Result<Instance<'tcx>, &'static str> {
+    let caller_def_id = caller_body.source.def_id();
+    let this = Inliner {
+        tcx,
+        param_env: tcx.param_env_reveal_all_normalized(caller_def_id),
+        codegen_fn_attrs: tcx.codegen_fn_attrs(caller_def_id),
+        history: Vec::new(),
+        changed: false,
+        caller_is_inline_forwarder: false,
+    };
+    let callsite = this
+        .resolve_callsite(caller_body, block, &caller_body[block])
+        .ok_or("not a direct call")?;
+    this.check_mir_is_available(caller_body, callsite.callee)?;
+    let InstanceKind::Item(callee_def_id) = callsite.callee.def else {
+        return Err("callee is a shim");
+    };
+    if tcx.has_attr(callee_def_id, sym::rustc_intrinsic) {
+        return Err("callee is an intrinsic");
+    }
+    if callsite.fn_sig.abi() == Abi::RustCall {
+        return Err("rust-call functions spread their arguments");
+    }
+    if callsite.fn_sig.c_variadic() {
+        return Err("C variadic");
+    }
+    Ok(callsite.callee)
+}
+
 struct Inliner<'tcx> {
     tcx: TyCtxt<'tcx>,
     param_env: ParamEnv<'tcx>,
@@ -312,7 +351,7 @@ fn check_mir_is_available(
         }
 
         match callee.def {
-            InstanceKind::Item(_) => {
+            InstanceKind::Item(_) | InstanceKind::ConstArgShim(..) => {
                 // If there is no MIR available (either because it was not in metadata or
                 // because it has no MIR because it's an extern function), then the inliner
                 // won't cause cycles on this.
@@ -358,7 +397,9 @@ fn check_mir_is_available(
 
         if callee_def_id.is_local() {
             // If we know for sure that the function we're calling will itself try to
-            // call us, then we avoid inlining that function.
+            // call us, then we avoid inlining that function. A `ConstArgShim` is a clone of the
+            // MIR of its item, so it has the same callees.
+            let callee = Instance::new(callee_def_id, callee.args);
             if self.tcx.mir_callgraph_reachable((callee, caller_def_id.expect_local())) {
                 return Err("caller might be reachable from callee (query cycle avoidance)");
             }
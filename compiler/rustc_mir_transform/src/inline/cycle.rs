@@ -90,7 +90,8 @@ fn process<'tcx>(
                 | InstanceKind::ClosureOnceShim { .. }
                 | InstanceKind::ConstructCoroutineInClosureShim { .. }
                 | InstanceKind::ThreadLocalShim { .. }
-                | InstanceKind::CloneShim(..) => {}
+                | InstanceKind::CloneShim(..)
+                | InstanceKind::ConstArgShim(..) => {}
 
                 // This shim does not call any other functions, thus there can be no recursion.
                 InstanceKind::FnPtrAddrShim(..) => {
@@ -26,13 +26,14 @@
 use rustc_hir::def_id::LocalDefId;
 use rustc_index::IndexVec;
 use rustc_middle::mir::{
-    AnalysisPhase, Body, CallSource, ClearCrossCrate, ConstOperand, ConstQualifs, LocalDecl,
-    MirPhase, Operand, Place, ProjectionElem, Promoted, RuntimePhase, Rvalue, START_BLOCK,
-    SourceInfo, Statement, StatementKind, TerminatorKind,
+    AnalysisPhase, Body, CallSource, ClearCrossCrate, ConstOperand, ConstQualifs, CustomPassPhase,
+    LocalDecl, MirPhase, Operand, Place, ProjectionElem, Promoted, RuntimePhase, Rvalue,
+    START_BLOCK, SourceInfo, Statement, StatementKind, TerminatorKind,
 };
 use rustc_middle::ty::{self, TyCtxt, TypeVisitableExt};
 use rustc_middle::util::Providers;
 use rustc_middle::{bug, query, span_bug};
+use rustc_session::time_report;
 use rustc_span::source_map::Spanned;
 use rustc_span::{DUMMY_SP, sym};
 use rustc_trait_selection::traits;
@@ -108,11 +109,14 @@
 mod simplify_branches;
 mod simplify_comparison_integral;
 mod single_use_consts;
+mod specialize_const_args;
 mod sroa;
 mod unreachable_enum_branching;
 mod unreachable_prop;
 mod validate;
 
+pub use validate::validate_external_body;
+
 rustc_fluent_macro::fluent_messages! { "../messages.ftl" }
 
 pub fn provide(providers: &mut Providers) {
@@ -479,6 +483,8 @@ pub fn run_analysis_to_runtime_passes<'tcx>(tcx: TyCtxt<'tcx>, body: &mut Body<'
     assert!(body.phase == MirPhase::Analysis(AnalysisPhase::Initial));
     let did = body.source.def_id();
 
+    pm::run_custom_passes(tcx, body, CustomPassPhase::AfterBorrowck);
+
     debug!("analysis_mir_cleanup({:?})", did);
     run_analysis_cleanup_passes(tcx, body);
     assert!(body.phase == MirPhase::Analysis(AnalysisPhase::PostCleanup));
@@ -569,6 +575,8 @@ fn o1<T>(x: T) -> WithMinOptLevel<T> {
         WithMinOptLevel(1, x)
     }
 
+    pm::run_custom_passes(tcx, body, CustomPassPhase::BeforeOptimization);
+
     // The main optimizations that we do on MIR.
     pm::run_passes(
         tcx,
@@ -586,6 +594,8 @@ fn o1<T>(x: T) -> WithMinOptLevel<T> {
             &instsimplify::InstSimplify::BeforeInline,
             // Perform inlining, which may add a lot of code.
             &inline::Inline,
+            // Call clones of the callees specialized for their constant arguments.
+            &specialize_const_args::SpecializeConstArgs,
             // Code from other crates may have storage markers, so this needs to happen after
             // inlining.
             &remove_storage_markers::RemoveStorageMarkers,
@@ -638,11 +648,14 @@ fn o1<T>(x: T) -> WithMinOptLevel<T> {
         ],
         Some(MirPhase::Runtime(RuntimePhase::Optimized)),
     );
+
+    pm::run_custom_passes(tcx, body, CustomPassPhase::AfterOptimization);
 }
 
 /// Optimize the MIR and prepare it for codegen.
 fn optimized_mir(tcx: TyCtxt<'_>, did: LocalDefId) -> &Body<'_> {
-    tcx.arena.alloc(inner_optimized_mir(tcx, did))
+    let body = tcx.sess.time_item(time_report::MIR_OPT, did, || inner_optimized_mir(tcx, did));
+    tcx.arena.alloc(body)
 }
 
 fn inner_optimized_mir(tcx: TyCtxt<'_>, did: LocalDefId) -> Body<'_> {
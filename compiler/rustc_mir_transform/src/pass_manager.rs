@@ -2,7 +2,7 @@
 use std::collections::hash_map::Entry;
 
 use rustc_data_structures::fx::FxHashMap;
-use rustc_middle::mir::{self, Body, MirPhase, RuntimePhase};
+use rustc_middle::mir::{self, Body, CustomMirPass, CustomPassPhase, MirPhase, RuntimePhase};
 use rustc_middle::ty::TyCtxt;
 use rustc_session::Session;
 use tracing::trace;
@@ -148,6 +148,46 @@ fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
     }
 }
 
+/// An adapter for the `CustomMirPass`es registered by the driver that implements `MirPass`.
+struct Custom<'a>(&'a dyn CustomMirPass);
+
+impl<'tcx> MirPass<'tcx> for Custom<'_> {
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    fn is_enabled(&self, sess: &Session) -> bool {
+        self.0.is_enabled(sess)
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        self.0.run_pass(tcx, body)
+    }
+}
+
+/// Run the passes registered by the driver for `phase`, the same way as the built-in passes.
+pub(super) fn run_custom_passes<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &mut Body<'tcx>,
+    phase: CustomPassPhase,
+) {
+    let passes: Vec<_> = tcx.custom_mir_passes(phase).map(Custom).collect();
+    if passes.is_empty() {
+        return;
+    }
+    let passes: Vec<&dyn MirPass<'tcx>> = passes.iter().map(|pass| pass as _).collect();
+    run_passes(tcx, body, &passes, None);
+
+    // Optimized MIR is always validated after the phase change, so do the same for the MIR
+    // modified afterwards.
+    if phase == CustomPassPhase::AfterOptimization
+        && !tcx.sess.opts.unstable_opts.validate_mir
+        && !body.should_skip()
+    {
+        validate_body(tcx, body, format!("after custom passes in {}", body.phase.name()));
+    }
+}
+
 /// Run the sequence of passes without validating the MIR after each pass. The MIR is still
 /// validated at the end.
 pub(super) fn run_passes_no_validate<'tcx>(
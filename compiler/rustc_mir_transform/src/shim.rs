@@ -22,6 +22,7 @@
 use crate::{
     abort_unwinding_calls, add_call_guards, add_moves_for_packed_drops, deref_separator,
     instsimplify, mentioned_items, pass_manager as pm, remove_noop_landing_pads, simplify,
+    specialize_const_args,
 };
 
 mod async_destructor_ctor;
@@ -132,6 +133,11 @@ fn make_shim<'tcx>(tcx: TyCtxt<'tcx>, instance: ty::InstanceKind<'tcx>) -> Body<
         ty::InstanceKind::AsyncDropGlueCtorShim(def_id, ty) => {
             async_destructor_ctor::build_async_destructor_ctor_shim(tcx, def_id, ty)
         }
+        // The clone is built from the optimized MIR of the function, so it must not go through the
+        // passes below.
+        ty::InstanceKind::ConstArgShim(..) => {
+            return specialize_const_args::build_const_arg_shim(tcx, instance);
+        }
         ty::InstanceKind::Virtual(..) => {
             bug!("InstanceKind::Virtual ({:?}) is for direct calls only", instance)
         }
@@ -0,0 +1,297 @@
+//! Interprocedural constant propagation, by specializing the callees of calls with constant
+//! arguments.
+//!
+//! `DataflowConstProp` and `GVN` only propagate constants within a body, and the `Inline` pass only
+//! inlines callees that are small without knowing anything about their arguments. When a caller
+//! passes constants to a non-generic function of the same crate, e.g., configuration flags, this
+//! pass makes it call a clone of the callee specialized for these constants instead. The clone is
+//! a `ConstArgShim` instance, whose MIR is the optimized MIR of the callee in which `BindConstArgs`
+//! replaced the uses of the arguments by the constants before simplifying it. The call is only
+//! redirected if the `CostChecker` finds that the clone is cheaper than the callee and below the
+//! threshold.
+//!
+//! All the calls passing the same constants to a function share the same clone. The calls in a
+//! clone are specialized in turn, which propagates constants through several layers of functions.
+
+use rustc_attr::InlineAttr;
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::LocalDefId;
+use rustc_middle::bug;
+use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrFlags;
+use rustc_middle::mir::interpret::{CTFE_ALLOC_SALT, Pointer, Scalar};
+use rustc_middle::mir::visit::*;
+use rustc_middle::mir::*;
+use rustc_middle::ty::{self, Instance, InstanceKind, ParamEnv, Ty, TyCtxt};
+use rustc_session::config::InliningThreshold;
+use rustc_span::sym;
+use tracing::{debug, trace_span};
+
+use crate::cost_checker::CostChecker;
+use crate::{gvn, inline, pass_manager as pm, simplify, simplify_branches};
+
+pub(super) struct SpecializeConstArgs;
+
+impl<'tcx> crate::MirPass<'tcx> for SpecializeConstArgs {
+    fn is_enabled(&self, sess: &rustc_session::Session) -> bool {
+        // The clones of a function would share its coverage mappings.
+        if sess.instrument_coverage() {
+            return false;
+        }
+
+        if let Some(enabled) = sess.opts.unstable_opts.mir_specialize_const_args {
+            return enabled;
+        }
+
+        sess.mir_opt_level() >= 3
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        // The clones of functions from other crates are not specialized further.
+        let Some(def_id) = body.source.def_id().as_local() else {
+            return;
+        };
+        let span = trace_span!("specialize_const_args", body = %tcx.def_path_str(def_id));
+        let _guard = span.enter();
+
+        // Same restrictions on the caller as the `Inline` pass.
+        if !tcx.hir().body_owner_kind(def_id).is_fn_or_closure()
+            || body.source.promoted.is_some()
+            || body.coroutine.is_some()
+        {
+            return;
+        }
+
+        let may_call_private_clones = match body.source.instance {
+            // Other crates only use the clones of functions that are cross-crate inlinable.
+            InstanceKind::ConstArgShim(..) => !tcx.cross_crate_inlinable(def_id),
+            _ => may_call_private_clones(tcx, def_id),
+        };
+        for bb in START_BLOCK..body.basic_blocks.next_index() {
+            if body[bb].is_cleanup {
+                continue;
+            }
+            match specialize_call(tcx, body, bb, may_call_private_clones) {
+                Err(reason) => debug!("not specialized [{}]", reason),
+                Ok(clone) => debug!("calling {}", clone),
+            }
+        }
+    }
+}
+
+/// Replaces the uses of the constant arguments of a `ConstArgShim` by their values, unless the
+/// body may modify these arguments, and simplifies the resulting body.
+///
+/// This runs whenever the MIR of a `ConstArgShim` is built, even in crates that do not enable
+/// `SpecializeConstArgs`.
+pub(super) struct BindConstArgs;
+
+impl<'tcx> crate::MirPass<'tcx> for BindConstArgs {
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        let InstanceKind::ConstArgShim(_, const_args) = body.source.instance else {
+            return;
+        };
+        for (index, arg) in const_args.iter().enumerate() {
+            let Some(value) = arg.as_const() else {
+                continue;
+            };
+            let local = Local::new(index + 1);
+            let decl = &body.local_decls[local];
+            let constant = ConstOperand {
+                span: decl.source_info.span,
+                user_ty: None,
+                const_: Const::from_ty_const(value, decl.ty, tcx),
+            };
+            if !is_mutated(body, local) {
+                ArgReplacer { tcx, arg: local, constant }.visit_body_preserves_cfg(body);
+            }
+        }
+
+        let passes: &[&dyn crate::MirPass<'tcx>] = &[
+            &gvn::GVN,
+            &simplify_branches::SimplifyConstCondition::AfterConstProp,
+            &simplify::SimplifyCfg::Final,
+            &simplify::SimplifyLocals::Final,
+        ];
+        for pass in passes {
+            pass.run_pass(tcx, body);
+        }
+    }
+}
+
+/// Builds the MIR of a `ConstArgShim`, from the optimized MIR of its function.
+pub(super) fn build_const_arg_shim<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    instance: InstanceKind<'tcx>,
+) -> Body<'tcx> {
+    let mut body = tcx.instance_mir(InstanceKind::Item(instance.def_id())).clone();
+    body.source = MirSource::from_instance(instance);
+    pm::run_passes(tcx, &mut body, &[&BindConstArgs, &SpecializeConstArgs], None);
+    body
+}
+
+/// Redirects the call terminating `block` to a clone of the callee specialized for the constant
+/// arguments of the call. Returns the clone.
+fn specialize_call<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &mut Body<'tcx>,
+    block: BasicBlock,
+    may_call_private_clones: bool,
+) -> Result<Instance<'tcx>, &'static str> {
+    let TerminatorKind::Call { func, args, .. } = &body[block].terminator().kind else {
+        return Err("not a call");
+    };
+    if args.iter().all(|arg| arg.node.constant().is_none()) {
+        return Err("no constant argument");
+    }
+
+    let callee = inline::resolve_specializable_call(tcx, body, block)?;
+    let def_id = callee.def_id();
+    if !def_id.is_local() {
+        return Err("callee from another crate");
+    }
+    if callee.args.non_erasable_generics(tcx, def_id).next().is_some() {
+        return Err("generic callee");
+    }
+    let callee_attrs = tcx.codegen_fn_attrs(def_id);
+    // The clones would have the same symbol as the callee.
+    if callee_attrs.contains_extern_indicator() {
+        return Err("callee has an extern indicator");
+    }
+    if callee_attrs.flags.intersects(CodegenFnAttrFlags::TRACK_CALLER | CodegenFnAttrFlags::NAKED) {
+        return Err("callee is #[track_caller] or naked");
+    }
+    if let InlineAttr::Never = callee_attrs.inline {
+        return Err("never inline hint");
+    }
+    // Reachability pass defines which functions are eligible for inlining. Generally using the MIR
+    // of other functions in another crate is incorrect because they could reference symbols that
+    // aren't exported.
+    if !may_call_private_clones && !tcx.cross_crate_inlinable(def_id) {
+        return Err("not exported");
+    }
+
+    let callee_body = tcx.instance_mir(callee.def);
+    if callee_body.tainted_by_errors.is_some() {
+        return Err("body is tainted");
+    }
+    if callee_body.arg_count != args.len() {
+        return Err("argument count mismatch");
+    }
+
+    let const_args = tcx.mk_args_from_iter(args.iter().enumerate().map(|(index, arg)| {
+        let ty = arg.node.ty(&body.local_decls, tcx);
+        let value = arg
+            .node
+            .constant()
+            .filter(|_| ty.is_bool() || ty.is_char() || ty.is_integral())
+            .and_then(|constant| constant.const_.try_to_scalar_int())
+            .filter(|_| !is_mutated(callee_body, Local::new(index + 1)));
+        match value {
+            Some(value) => {
+                ty::Const::new_value(tcx, ty::ValTree::from_scalar_int(value), ty).into()
+            }
+            None => ty.into(),
+        }
+    }));
+    if const_args.iter().all(|arg| arg.as_const().is_none()) {
+        return Err("constant arguments may be modified");
+    }
+
+    let clone = Instance { def: InstanceKind::ConstArgShim(def_id, const_args), args: callee.args };
+    let param_env = tcx.param_env_reveal_all_normalized(def_id);
+    let original_cost = body_cost(tcx, param_env, callee_body);
+    let cost = body_cost(tcx, param_env, tcx.instance_mir(clone.def));
+    let threshold = tcx.sess.opts.unstable_opts.mir_specialize_const_args_threshold.unwrap_or(100);
+    if cost >= original_cost {
+        debug!("NOT specializing {:?} [cost={} >= original cost={}]", clone, cost, original_cost);
+        return Err("specialization does not simplify the callee");
+    } else if cost > threshold {
+        debug!("NOT specializing {:?} [cost={} > threshold={}]", clone, cost, threshold);
+        return Err("cost above threshold");
+    }
+    debug!("SPECIALIZING {:?} [cost={} <= threshold={}]", clone, cost, threshold);
+
+    if !tcx.consider_optimizing(|| format!("SpecializeConstArgs {:?} in {:?}", clone, body.source))
+    {
+        return Err("optimization fuel exhausted");
+    }
+
+    // Call the clone through a constant function pointer, as there is no function item for it.
+    let fn_ptr_ty = Ty::new_fn_ptr(tcx, tcx.erase_regions(func.ty(body, tcx).fn_sig(tcx)));
+    let alloc_id = tcx.reserve_and_set_fn_alloc(clone, CTFE_ALLOC_SALT);
+    let const_ =
+        Const::from_scalar(tcx, Scalar::from_pointer(Pointer::from(alloc_id), &tcx), fn_ptr_ty);
+    let terminator = body[block].terminator_mut();
+    let span = terminator.source_info.span;
+    let TerminatorKind::Call { func, .. } = &mut terminator.kind else { bug!() };
+    *func = Operand::Constant(Box::new(ConstOperand { span, user_ty: None, const_ }));
+    Ok(clone)
+}
+
+/// Whether the MIR of `def_id` is only used to generate code in this crate, so it may call the
+/// clones of functions that are not exported.
+///
+/// Such bodies are never cross-crate inlinable, as `cross_crate_inlinable` only infers that a
+/// function is inlinable if it does not call any other function.
+fn may_call_private_clones(tcx: TyCtxt<'_>, def_id: LocalDefId) -> bool {
+    matches!(tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn)
+        && !tcx.generics_of(def_id).requires_monomorphization(tcx)
+        && matches!(tcx.codegen_fn_attrs(def_id).inline, InlineAttr::None | InlineAttr::Never)
+        && tcx.sess.opts.unstable_opts.cross_crate_inline_threshold != InliningThreshold::Always
+        && !tcx.has_attr(def_id, sym::rustc_intrinsic)
+}
+
+fn body_cost<'tcx>(tcx: TyCtxt<'tcx>, param_env: ParamEnv<'tcx>, body: &Body<'tcx>) -> usize {
+    let mut checker = CostChecker::new(tcx, param_env, None, body);
+    checker.add_function_level_costs();
+    checker.visit_body(body);
+    checker.cost()
+}
+
+/// Whether `body` may modify the argument `arg`, in which case its uses cannot be replaced by the
+/// constant passed by the caller. The argument itself is still initialized by the call, so its
+/// other uses, e.g., in debuginfo, remain correct.
+fn is_mutated(body: &Body<'_>, arg: Local) -> bool {
+    let mut finder = MutationFinder { arg, mutated: false };
+    finder.visit_body(body);
+    finder.mutated
+}
+
+struct MutationFinder {
+    arg: Local,
+    mutated: bool,
+}
+
+impl<'tcx> Visitor<'tcx> for MutationFinder {
+    fn visit_local(&mut self, local: Local, context: PlaceContext, _: Location) {
+        // Borrows are conservatively considered as mutations, as the argument could be modified
+        // through interior mutability or raw pointers.
+        if local == self.arg
+            && (context.is_mutating_use() || context.is_borrow() || context.is_address_of())
+        {
+            self.mutated = true;
+        }
+    }
+}
+
+struct ArgReplacer<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    arg: Local,
+    constant: ConstOperand<'tcx>,
+}
+
+impl<'tcx> MutVisitor<'tcx> for ArgReplacer<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_operand(&mut self, operand: &mut Operand<'tcx>, location: Location) {
+        if let Operand::Copy(place) | Operand::Move(place) = operand
+            && place.as_local() == Some(self.arg)
+        {
+            *operand = Operand::Constant(Box::new(self.constant));
+        } else {
+            self.super_operand(operand, location);
+        }
+    }
+}
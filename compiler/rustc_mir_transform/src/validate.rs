@@ -1,5 +1,7 @@
 //! Validates the MIR to ensure that invariants are upheld.
 
+use std::cell::RefCell;
+
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_hir::LangItem;
 use rustc_index::IndexVec;
@@ -41,6 +43,19 @@ pub(super) struct Validator {
 
 impl<'tcx> crate::MirPass<'tcx> for Validator {
     fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        self.validate(tcx, body, None);
+    }
+}
+
+impl Validator {
+    /// Validate `body`. If `failures` is provided, the violations are recorded there instead of
+    /// causing an ICE.
+    fn validate<'tcx>(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        body: &Body<'tcx>,
+        failures: Option<&RefCell<Vec<(Location, String)>>>,
+    ) {
         // FIXME(JakobDegen): These bodies never instantiated in codegend anyway, so it's not
         // terribly important that they pass the validator. However, I think other passes might
         // still see them, in which case they might be surprised. It would probably be better if we
@@ -86,6 +101,7 @@ fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
             reachable_blocks: traversal::reachable_as_bitset(body),
             value_cache: FxHashSet::default(),
             can_unwind,
+            failures,
         };
         cfg_checker.visit_body(body);
         cfg_checker.check_cleanup_control_flow();
@@ -119,11 +135,17 @@ struct CfgChecker<'a, 'tcx> {
     // If `false`, then the MIR must not contain `UnwindAction::Continue` or
     // `TerminatorKind::Resume`.
     can_unwind: bool,
+    // If set, failures are recorded here instead of causing an ICE.
+    failures: Option<&'a RefCell<Vec<(Location, String)>>>,
 }
 
 impl<'a, 'tcx> CfgChecker<'a, 'tcx> {
     #[track_caller]
     fn fail(&self, location: Location, msg: impl AsRef<str>) {
+        if let Some(failures) = self.failures {
+            failures.borrow_mut().push((location, msg.as_ref().to_string()));
+            return;
+        }
         // We might see broken MIR when other errors have already occurred.
         assert!(
             self.tcx.dcx().has_errors().is_some(),
@@ -529,6 +551,21 @@ fn visit_source_scope(&mut self, scope: SourceScope) {
     }
 }
 
+/// Validates a body that was not produced by the MIR pipeline, e.g., a body provided by an
+/// external tool, against the invariants of its phase.
+///
+/// Unlike the [`Validator`] pass, violations are returned instead of causing an ICE. The body must
+/// only refer to basic blocks and locals that exist.
+pub fn validate_external_body<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &Body<'tcx>,
+    when: String,
+) -> Vec<(Location, String)> {
+    let failures = RefCell::new(Vec::new());
+    Validator { when, mir_phase: body.phase }.validate(tcx, body, Some(&failures));
+    failures.into_inner()
+}
+
 /// A faster version of the validation pass that only checks those things which may break when
 /// instantiating any generic parameters.
 ///
@@ -240,6 +240,7 @@
 use tracing::{debug, instrument, trace};
 
 use crate::errors::{self, EncounteredErrorWhileInstantiating, NoOptimizedMir, RecursionLimit};
+use crate::partitioning;
 
 #[derive(PartialEq)]
 pub(crate) enum MonoItemCollectionStrategy {
@@ -247,7 +248,8 @@ pub(crate) enum MonoItemCollectionStrategy {
     Lazy,
 }
 
-pub(crate) struct UsageMap<'tcx> {
+/// The usage edges between the mono items found by the collector.
+pub struct UsageMap<'tcx> {
     // Maps every mono item to the mono items used by it.
     used_map: UnordMap<MonoItem<'tcx>, Vec<MonoItem<'tcx>>>,
 
@@ -306,10 +308,16 @@ fn record_used<'a>(
         assert!(self.used_map.insert(user_item, used_items).is_none());
     }
 
-    pub(crate) fn get_user_items(&self, item: MonoItem<'tcx>) -> &[MonoItem<'tcx>] {
+    /// Return the mono items that use `item`.
+    pub fn get_user_items(&self, item: MonoItem<'tcx>) -> &[MonoItem<'tcx>] {
         self.user_map.get(&item).map(|items| items.as_slice()).unwrap_or(&[])
     }
 
+    /// Return the mono items used by `item`.
+    pub fn get_used_items(&self, item: MonoItem<'tcx>) -> &[MonoItem<'tcx>] {
+        self.used_map.get(&item).map(|items| items.as_slice()).unwrap_or(&[])
+    }
+
     /// Internally iterate over all inlined items used by `item`.
     pub(crate) fn for_each_inlined_used_item<F>(
         &self,
@@ -927,7 +935,8 @@ fn visit_instance_use<'tcx>(
         | ty::InstanceKind::Item(..)
         | ty::InstanceKind::FnPtrShim(..)
         | ty::InstanceKind::CloneShim(..)
-        | ty::InstanceKind::FnPtrAddrShim(..) => {
+        | ty::InstanceKind::FnPtrAddrShim(..)
+        | ty::InstanceKind::ConstArgShim(..) => {
             output.push(create_fn_mono_item(tcx, instance, source));
         }
     }
@@ -1622,6 +1631,15 @@ pub(crate) fn collect_crate_mono_items<'tcx>(
     (mono_items, state.usage_map.into_inner())
 }
 
+/// Collect the mono items of the local crate with the same strategy as code generation, and the
+/// usage edges between them.
+///
+/// This is meant for tools that inspect the results of the collection, e.g., through StableMIR.
+/// The results are not shared with code generation, which runs the collector separately.
+pub fn collect_mono_item_graph<'tcx>(tcx: TyCtxt<'tcx>) -> (Vec<MonoItem<'tcx>>, UsageMap<'tcx>) {
+    collect_crate_mono_items(tcx, partitioning::collection_strategy(tcx))
+}
+
 pub(crate) fn provide(providers: &mut Providers) {
     providers.hooks.should_codegen_locally = should_codegen_locally;
 }
@@ -20,6 +20,8 @@
 mod polymorphize;
 mod util;
 
+pub use collector::{UsageMap, collect_mono_item_graph};
+
 rustc_fluent_macro::fluent_messages! { "../messages.ftl" }
 
 fn custom_coerce_unsize_info<'tcx>(
@@ -635,7 +635,8 @@ fn characteristic_def_id_of_mono_item<'tcx>(
                 | ty::InstanceKind::CloneShim(..)
                 | ty::InstanceKind::ThreadLocalShim(..)
                 | ty::InstanceKind::FnPtrAddrShim(..)
-                | ty::InstanceKind::AsyncDropGlueCtorShim(..) => return None,
+                | ty::InstanceKind::AsyncDropGlueCtorShim(..)
+                | ty::InstanceKind::ConstArgShim(..) => return None,
             };
 
             // If this is a method, we want to put it into the same module as
@@ -801,7 +802,8 @@ fn mono_item_visibility<'tcx>(
         | InstanceKind::DropGlue(..)
         | InstanceKind::AsyncDropGlueCtorShim(..)
         | InstanceKind::CloneShim(..)
-        | InstanceKind::FnPtrAddrShim(..) => return Visibility::Hidden,
+        | InstanceKind::FnPtrAddrShim(..)
+        | InstanceKind::ConstArgShim(..) => return Visibility::Hidden,
     };
 
     // The `start_fn` lang item is actually a monomorphized instance of a
@@ -1102,8 +1104,10 @@ fn assert_symbols_are_distinct<'a, 'tcx, I>(tcx: TyCtxt<'tcx>, mono_items: I)
     }
 }
 
-fn collect_and_partition_mono_items(tcx: TyCtxt<'_>, (): ()) -> (&DefIdSet, &[CodegenUnit<'_>]) {
-    let collection_strategy = match tcx.sess.opts.unstable_opts.print_mono_items {
+/// Select the collection strategy, which can be requested with `-Zprint-mono-items`.
+/// Otherwise, it is only eager if dead code must be linked.
+pub(crate) fn collection_strategy(tcx: TyCtxt<'_>) -> MonoItemCollectionStrategy {
+    match tcx.sess.opts.unstable_opts.print_mono_items {
         Some(ref s) => {
             let mode = s.to_lowercase();
             let mode = mode.trim();
@@ -1124,8 +1128,11 @@ fn collect_and_partition_mono_items(tcx: TyCtxt<'_>, (): ()) -> (&DefIdSet, &[Co
                 MonoItemCollectionStrategy::Lazy
             }
         }
-    };
+    }
+}
 
+fn collect_and_partition_mono_items(tcx: TyCtxt<'_>, (): ()) -> (&DefIdSet, &[CodegenUnit<'_>]) {
+    let collection_strategy = collection_strategy(tcx);
     let (items, usage_map) = collector::collect_crate_mono_items(tcx, collection_strategy);
 
     // If there was an error during collection (e.g. from one of the constants we evaluated),
@@ -20,6 +20,7 @@
 #[cfg(debug_assertions)]
 use {super::debug::EdgeFilter, std::env};
 
+use super::debug::DepNodeFilter;
 use super::query::DepGraphQuery;
 use super::serialized::{GraphEncoder, SerializedDepGraph, SerializedDepNodeIndex};
 use super::{DepContext, DepKind, DepNode, Deps, HasDepContext, WorkProductId};
@@ -106,6 +107,20 @@ pub(crate) struct DepGraphData<D: Deps> {
     /// a particular query result was decoded from disk
     /// (not just marked green)
     debug_loaded_from_disk: Lock<FxHashSet<DepNode>>,
+
+    /// Set with `-Zincremental-explain`.
+    explainer: Option<RecomputationExplainer>,
+}
+
+/// Explains why the dep-nodes matching a filter are recomputed, by printing the chain of red
+/// dep-nodes leading from them to a node whose result changed.
+struct RecomputationExplainer {
+    filter: DepNodeFilter,
+    /// The fingerprints of the results of the nodes of the previous session that are red in this
+    /// one, or `None` for the nodes whose result is not hashed.
+    red_fingerprints: Lock<FxHashMap<SerializedDepNodeIndex, Option<Fingerprint>>>,
+    /// The nodes of the previous session whose recomputation has already been explained.
+    explained: Lock<FxHashSet<SerializedDepNodeIndex>>,
 }
 
 pub fn hash_result<R>(hcx: &mut StableHashingContext<'_>, result: &R) -> Fingerprint
@@ -125,6 +140,7 @@ pub fn new(
         encoder: FileEncoder,
         record_graph: bool,
         record_stats: bool,
+        explain_filter: Option<DepNodeFilter>,
     ) -> DepGraph<D> {
         let prev_graph_node_count = prev_graph.node_count();
 
@@ -179,6 +195,11 @@ pub fn new(
                 previous: prev_graph,
                 colors,
                 debug_loaded_from_disk: Default::default(),
+                explainer: explain_filter.map(|filter| RecomputationExplainer {
+                    filter,
+                    red_fingerprints: Default::default(),
+                    explained: Default::default(),
+                }),
             })),
             virtual_dep_node_index: Lrc::new(AtomicU32::new(0)),
         }
@@ -389,6 +410,7 @@ pub(crate) fn with_task<Ctxt: HasDepContext<Deps = D>, A: Debug, R>(
                             insertion for {key:?}"
             );
 
+            self.record_red_fingerprint(prev_index, &color, current_fingerprint);
             self.colors.insert(prev_index, color);
         }
 
@@ -592,6 +614,7 @@ pub fn with_feed_task<Ctxt: DepContext<Deps = D>, A: Debug, R: Debug>(
                     "DepGraph::with_task() - Duplicate DepNodeColor insertion for {key:?}",
                 );
 
+                data.record_red_fingerprint(prev_index, &color, current_fingerprint);
                 data.colors.insert(prev_index, color);
             }
 
@@ -650,6 +673,32 @@ pub(crate) fn prev_node_of(&self, prev_index: SerializedDepNodeIndex) -> DepNode
     pub(crate) fn mark_debug_loaded_from_disk(&self, dep_node: DepNode) {
         self.debug_loaded_from_disk.lock().insert(dep_node);
     }
+
+    /// Records the fingerprint of the result of a re-executed node of the previous session, if
+    /// it's red and its recomputation may need to be explained.
+    #[inline]
+    fn record_red_fingerprint(
+        &self,
+        prev_index: SerializedDepNodeIndex,
+        color: &DepNodeColor,
+        current_fingerprint: Option<Fingerprint>,
+    ) {
+        if let Some(explainer) = &self.explainer
+            && let DepNodeColor::Red = color
+        {
+            explainer.red_fingerprints.lock().insert(prev_index, current_fingerprint);
+        }
+    }
+
+    /// Prints why `dep_node` is computed without trying to mark it green, if it matches the
+    /// filter of `-Zincremental-explain`.
+    fn explain_computation(&self, dep_node: &DepNode, reason: &str) {
+        if let Some(explainer) = &self.explainer
+            && explainer.filter.test(dep_node)
+        {
+            eprintln!("[incremental] `{dep_node:?}` is computed because {reason}");
+        }
+    }
 }
 
 impl<D: Deps> DepGraph<D> {
@@ -708,6 +757,14 @@ pub fn try_mark_green<Qcx: QueryContext<Deps = D>>(
     ) -> Option<(SerializedDepNodeIndex, DepNodeIndex)> {
         self.data().and_then(|data| data.try_mark_green(qcx, dep_node))
     }
+
+    /// Prints why `dep_node` is computed without trying to mark it green, e.g. because a result
+    /// needed to reuse it is missing, if it matches the filter of `-Zincremental-explain`.
+    pub fn explain_computation(&self, dep_node: &DepNode, reason: &str) {
+        if let Some(data) = &self.data {
+            data.explain_computation(dep_node, reason);
+        }
+    }
 }
 
 impl<D: Deps> DepGraphData<D> {
@@ -724,18 +781,89 @@ pub(crate) fn try_mark_green<Qcx: QueryContext<Deps = D>>(
         debug_assert!(!qcx.dep_context().is_eval_always(dep_node.kind));
 
         // Return None if the dep node didn't exist in the previous session
-        let prev_index = self.previous.node_to_index_opt(dep_node)?;
+        let Some(prev_index) = self.previous.node_to_index_opt(dep_node) else {
+            self.explain_computation(dep_node, "it didn't exist in the previous session");
+            return None;
+        };
 
         match self.colors.get(prev_index) {
             Some(DepNodeColor::Green(dep_node_index)) => Some((prev_index, dep_node_index)),
-            Some(DepNodeColor::Red) => None,
+            Some(DepNodeColor::Red) => {
+                // The node was already found to be red, e.g. while marking another node green.
+                if let Some(explainer) = &self.explainer {
+                    self.explain_recomputation(explainer, prev_index);
+                }
+                None
+            }
             None => {
                 // This DepNode and the corresponding query invocation existed
                 // in the previous compilation session too, so we can try to
                 // mark it as green by recursively marking all of its
                 // dependencies green.
-                self.try_mark_previous_green(qcx, prev_index, dep_node, None)
-                    .map(|dep_node_index| (prev_index, dep_node_index))
+                let dep_node_index = self.try_mark_previous_green(qcx, prev_index, dep_node, None);
+                if dep_node_index.is_none()
+                    && let Some(explainer) = &self.explainer
+                {
+                    self.explain_recomputation(explainer, prev_index);
+                }
+                dep_node_index.map(|dep_node_index| (prev_index, dep_node_index))
+            }
+        }
+    }
+
+    /// Prints why the node of the previous session at `prev_index`, which could not be marked
+    /// green, is recomputed, if it matches the filter of `-Zincremental-explain`: the chain of red
+    /// nodes from it to the first node whose result changed, and how it changed.
+    #[cold]
+    #[inline(never)]
+    fn explain_recomputation(
+        &self,
+        explainer: &RecomputationExplainer,
+        prev_index: SerializedDepNodeIndex,
+    ) {
+        let dep_node = self.previous.index_to_node(prev_index);
+        if !explainer.filter.test(&dep_node) || !explainer.explained.lock().insert(prev_index) {
+            return;
+        }
+
+        eprintln!("[incremental] `{dep_node:?}` is recomputed because:");
+        let mut node_index = prev_index;
+        loop {
+            let node = self.previous.index_to_node(node_index);
+
+            // The dependencies are marked green in order, so the first one that isn't green is
+            // the one that prevented marking this node green.
+            let Some(dep_index) =
+                self.previous.edge_targets_from(node_index).find(|&dep| !self.is_index_green(dep))
+            else {
+                // All the dependencies are green, so this node was recomputed because it
+                // always is, e.g. because it's an input, and its result changed.
+                let prev_fingerprint = self.previous.fingerprint_by_index(node_index);
+                match explainer.red_fingerprints.lock().get(&node_index) {
+                    Some(Some(fingerprint)) => eprintln!(
+                        "[incremental]   `{node:?}` changed: the fingerprint of its result went \
+                         from {prev_fingerprint} to {fingerprint}"
+                    ),
+                    Some(None) => eprintln!(
+                        "[incremental]   `{node:?}` is considered changed whenever it's recomputed, \
+                         as its result is not hashed"
+                    ),
+                    None => eprintln!("[incremental]   `{node:?}` is always considered changed"),
+                }
+                return;
+            };
+
+            let dep_node = self.previous.index_to_node(dep_index);
+            if let Some(DepNodeColor::Red) = self.colors.get(dep_index) {
+                eprintln!("[incremental]   `{node:?}` depends on `{dep_node:?}`, which changed");
+                node_index = dep_index;
+            } else {
+                // Forcing the dependency failed, e.g. because the item it's about was removed.
+                eprintln!(
+                    "[incremental]   `{node:?}` depends on `{dep_node:?}`, which could not be \
+                     recomputed"
+                );
+                return;
             }
         }
     }
@@ -78,6 +78,17 @@ session_invalid_num_literal_suffix = invalid suffix `{$suffix}` for number liter
 
 session_linker_plugin_lto_windows_not_supported = linker plugin based LTO is not supported together with `-C prefer-dynamic` when targeting Windows-like targets
 
+session_non_reproducible_absolute_path = the absolute path `{$path}` of a source file is not remapped, and ends up in the output
+    .help = remap it with `--remap-path-prefix`
+
+session_non_reproducible_env_var = the environment variable `{$var}` is read during the compilation
+    .help = set its value with `--env-set {$var}=<value>`
+
+session_non_reproducible_outside_working_dir = the file `{$path}` is read from outside the working directory `{$working_dir}`
+
+session_non_reproducible_working_dir = the working directory `{$path}` is not remapped, and ends up in the debuginfo
+    .help = remap it with `--remap-path-prefix`
+
 session_not_circumvent_feature = `-Zunleash-the-miri-inside-of-you` may not be used to circumvent feature gates, except when testing error paths in the CTFE engine
 
 session_not_supported = not supported
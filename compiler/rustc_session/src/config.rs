@@ -631,6 +631,8 @@ pub enum ErrorOutputType {
         json_rendered: HumanReadableErrorType,
         color_config: ColorConfig,
     },
+    /// A single SARIF log, consumed by code scanning tools.
+    Sarif,
 }
 
 impl Default for ErrorOutputType {
@@ -1759,6 +1761,7 @@ pub fn parse_error_format(
             Some("pretty-json") => {
                 ErrorOutputType::Json { pretty: true, json_rendered, color_config: json_color }
             }
+            Some("sarif") => ErrorOutputType::Sarif,
             Some("short") => ErrorOutputType::HumanReadable(HumanReadableErrorType::Short, color),
             Some(arg) => {
                 early_dcx.abort_if_error_and_set_error_format(ErrorOutputType::HumanReadable(
@@ -1831,6 +1834,9 @@ fn check_error_format_stability(
         {
             early_dcx.early_fatal("`--error-format=human-annotate-rs` is unstable");
         }
+        if let ErrorOutputType::Sarif = error_format {
+            early_dcx.early_fatal("`--error-format=sarif` is unstable");
+        }
     }
 }
 
@@ -2979,6 +2985,21 @@ pub fn needs_analysis(&self) -> bool {
     }
 }
 
+/// The breakdowns of the compilation time printed by `-Ztime-report`.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+pub enum TimeReportKind {
+    /// The time attributed to the source-level items it's spent on, for the `limit` items with
+    /// the most time.
+    Items { limit: usize },
+}
+
+/// How `-Zverify-reproducible` reports the ways a compilation depends on its environment.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+pub enum VerifyReproducible {
+    Warn,
+    Deny,
+}
+
 #[derive(Clone, Hash, PartialEq, Eq, Debug)]
 pub enum WasiExecModel {
     Command,
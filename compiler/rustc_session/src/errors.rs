@@ -501,3 +501,31 @@ pub(crate) struct FailedToCreateProfiler {
 #[note]
 #[note(session_soft_float_deprecated_issue)]
 pub(crate) struct SoftFloatDeprecated;
+
+#[derive(Diagnostic)]
+#[diag(session_non_reproducible_working_dir)]
+#[help]
+pub(crate) struct NonReproducibleWorkingDir<'a> {
+    pub(crate) path: &'a std::path::Path,
+}
+
+#[derive(Diagnostic)]
+#[diag(session_non_reproducible_env_var)]
+#[help]
+pub(crate) struct NonReproducibleEnvVar<'a> {
+    pub(crate) var: &'a str,
+}
+
+#[derive(Diagnostic)]
+#[diag(session_non_reproducible_absolute_path)]
+#[help]
+pub(crate) struct NonReproducibleAbsolutePath<'a> {
+    pub(crate) path: &'a std::path::Path,
+}
+
+#[derive(Diagnostic)]
+#[diag(session_non_reproducible_outside_working_dir)]
+pub(crate) struct NonReproducibleOutsideWorkingDir<'a> {
+    pub(crate) path: &'a std::path::Path,
+    pub(crate) working_dir: &'a std::path::Path,
+}
@@ -20,7 +20,9 @@
 pub mod cstore;
 pub mod filesearch;
 mod options;
+mod reproducibility;
 pub mod search_paths;
+pub mod time_report;
 
 mod session;
 pub use session::*;
@@ -377,6 +377,8 @@ mod desc {
     pub(crate) const parse_frame_pointer: &str = "one of `true`/`yes`/`on`, `false`/`no`/`off`, or (with -Zunstable-options) `non-leaf` or `always`";
     pub(crate) const parse_threads: &str = parse_number;
     pub(crate) const parse_time_passes_format: &str = "`text` (default) or `json`";
+    pub(crate) const parse_time_report: &str =
+        "`items`, optionally followed by the number of items to print, e.g. `items=10`";
     pub(crate) const parse_passes: &str = "a space-separated list of passes, or `all`";
     pub(crate) const parse_panic_strategy: &str = "either `unwind` or `abort`";
     pub(crate) const parse_on_broken_pipe: &str = "either `kill`, `error`, or `inherit`";
@@ -430,6 +432,7 @@ mod desc {
     pub(crate) const parse_target_feature: &str = parse_string;
     pub(crate) const parse_terminal_url: &str =
         "either a boolean (`yes`, `no`, `on`, `off`, etc), or `auto`";
+    pub(crate) const parse_verify_reproducible: &str = "either `warn` or `deny`";
     pub(crate) const parse_wasi_exec_model: &str = "either `command` or `reactor`";
     pub(crate) const parse_split_debuginfo: &str =
         "one of supported split-debuginfo modes (`off`, `packed`, or `unpacked`)";
@@ -981,6 +984,18 @@ pub(crate) fn parse_time_passes_format(slot: &mut TimePassesFormat, v: Option<&s
         }
     }
 
+    pub(crate) fn parse_time_report(slot: &mut Option<TimeReportKind>, v: Option<&str>) -> bool {
+        match v.map(|v| v.split_once('=').unwrap_or((v, ""))) {
+            Some(("items", "")) => *slot = Some(TimeReportKind::Items { limit: 20 }),
+            Some(("items", limit)) => match limit.parse() {
+                Ok(limit) => *slot = Some(TimeReportKind::Items { limit }),
+                Err(_) => return false,
+            },
+            _ => return false,
+        }
+        true
+    }
+
     pub(crate) fn parse_dump_mono_stats(slot: &mut DumpMonoStatsFormat, v: Option<&str>) -> bool {
         match v {
             None => true,
@@ -1349,6 +1364,18 @@ pub(crate) fn parse_linker_features(slot: &mut LinkerFeaturesCli, v: Option<&str
         }
     }
 
+    pub(crate) fn parse_verify_reproducible(
+        slot: &mut Option<VerifyReproducible>,
+        v: Option<&str>,
+    ) -> bool {
+        match v {
+            Some("warn") => *slot = Some(VerifyReproducible::Warn),
+            Some("deny") => *slot = Some(VerifyReproducible::Deny),
+            _ => return false,
+        }
+        true
+    }
+
     pub(crate) fn parse_wasi_exec_model(slot: &mut Option<WasiExecModel>, v: Option<&str>) -> bool {
         match v {
             Some("command") => *slot = Some(WasiExecModel::Command),
@@ -1684,6 +1711,10 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
     binary_dep_depinfo: bool = (false, parse_bool, [TRACKED],
         "include artifacts (sysroot, crate dependencies) used during compilation in dep-info \
         (default: no)"),
+    borrowck_explain_json: bool = (false, parse_bool, [UNTRACKED],
+        "emit, with `--error-format=json`, a structured explanation of the borrowck errors about \
+        conflicting accesses: the loan, the outlives constraints keeping it live, and the \
+        conflicting access (default: no)"),
     box_noalias: bool = (true, parse_bool, [TRACKED],
         "emit noalias metadata for box (default: yes)"),
     branch_protection: Option<BranchProtection> = (None, parse_branch_protection, [TRACKED],
@@ -1805,11 +1836,17 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
         "display unnamed regions as `'<id>`, using a non-ident unique id (default: no)"),
     ignore_directory_in_diagnostics_source_blocks: Vec<String> = (Vec::new(), parse_string_push, [UNTRACKED],
         "do not display the source code block in diagnostics for files in the directory"),
+    incremental_explain: Option<String> = (None, parse_opt_string, [UNTRACKED],
+        "explain why the dep-nodes matching this filter are recomputed in an incremental session, \
+        e.g. `typeck & my_crate::my_fn` (default: no)"),
     incremental_ignore_spans: bool = (false, parse_bool, [TRACKED],
         "ignore spans during ICH computation -- used for testing (default: no)"),
     incremental_info: bool = (false, parse_bool, [UNTRACKED],
         "print high-level information about incremental reuse (or the lack thereof) \
         (default: no)"),
+    incremental_shared_cache: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "a directory shared between checkouts of the same sources, where incremental compilation \
+        results are published and reused from, indexed by content (default: no)"),
     incremental_verify_ich: bool = (false, parse_bool, [UNTRACKED],
         "verify extended properties for incr. comp. (default: no):
         - hashes of green query instances
@@ -1899,6 +1936,12 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
     #[rustc_lint_opt_deny_field_access("use `Session::mir_opt_level` instead of this field")]
     mir_opt_level: Option<usize> = (None, parse_opt_number, [TRACKED],
         "MIR optimization level (0-4; default: 1 in non optimized builds and 2 in optimized builds)"),
+    mir_specialize_const_args: Option<bool> = (None, parse_opt_bool, [TRACKED],
+        "specialize the callees of calls with constant arguments into clones \
+        (default: yes at mir-opt-level>=3)"),
+    mir_specialize_const_args_threshold: Option<usize> = (None, parse_opt_number, [TRACKED],
+        "the maximum cost of a clone of a callee specialized for constant arguments \
+        (default: 100)"),
     move_size_limit: Option<usize> = (None, parse_opt_number, [TRACKED],
         "the size at which the `large_assignments` lint starts to be emitted"),
     mutable_noalias: bool = (true, parse_bool, [TRACKED],
@@ -2104,6 +2147,10 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
         "measure time of each rustc pass (default: no)"),
     time_passes_format: TimePassesFormat = (TimePassesFormat::Text, parse_time_passes_format, [UNTRACKED],
         "the format to use for -Z time-passes (`text` (default) or `json`)"),
+    time_report: Option<TimeReportKind> = (None, parse_time_report, [UNTRACKED],
+        "print a breakdown of the compilation time: `items` attributes the time spent in \
+        type-checking, borrow-checking, MIR optimizations and LLVM to the items it's spent on, and \
+        prints the ones with the most time (20 by default, or the given number, e.g. `items=10`)"),
     tiny_const_eval_limit: bool = (false, parse_bool, [TRACKED],
         "sets a tiny, non-configurable limit for const eval; useful for compiler tests"),
     #[rustc_lint_opt_deny_field_access("use `Session::tls_model` instead of this field")]
@@ -2176,6 +2223,10 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
     #[rustc_lint_opt_deny_field_access("use `Session::verify_llvm_ir` instead of this field")]
     verify_llvm_ir: bool = (false, parse_bool, [TRACKED],
         "verify LLVM IR (default: no)"),
+    verify_reproducible: Option<VerifyReproducible> = (None, parse_verify_reproducible, [UNTRACKED],
+        "report the ways the compilation depends on its environment: absolute paths that are \
+        not remapped, environment variables not set with `--env-set`, and files read from \
+        outside the working directory, either as warnings (`warn`) or errors (`deny`)"),
     virtual_function_elimination: bool = (false, parse_bool, [TRACKED],
         "enables dead virtual function elimination optimization. \
         Requires `-Clto[=[fat,yes]]`"),
@@ -0,0 +1,119 @@
+//! Verification that a compilation doesn't depend on its environment, enabled with
+//! `-Zverify-reproducible=<warn|deny>`.
+//!
+//! Two compilations of the same sources with the same commandline arguments are expected to
+//! produce the same outputs, wherever and by whomever they are run. This doesn't hold when:
+//!
+//! - absolute paths of the sources end up in the outputs, e.g. in debuginfo and panic messages,
+//!   because they are not remapped with `--remap-path-prefix`,
+//! - `env!` and `option_env!`, or proc-macros, read environment variables that are not set with
+//!   `--env-set`,
+//! - `include_bytes!` and similar macros, `#[path]` attributes, or proc-macros, read files outside
+//!   of the working directory, which is typically the one remapped.
+//!
+//! The checks are based on the same information as dep-info files, and thus run once macro
+//! expansion is done.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rustc_errors::{Diagnostic, Level};
+use rustc_span::{FileName, RealFileName};
+
+use crate::config::{DebugInfo, VerifyReproducible};
+use crate::{Session, errors};
+
+impl Session {
+    /// Reports the ways this compilation depends on its environment, if requested with
+    /// `-Zverify-reproducible`. Must be called once macro expansion is done.
+    pub fn verify_reproducibility(&self) {
+        let Some(level) = self.opts.unstable_opts.verify_reproducible else {
+            return;
+        };
+        let level = match level {
+            VerifyReproducible::Warn => Level::Warning,
+            VerifyReproducible::Deny => Level::Error,
+        };
+
+        // The working directory is recorded in debuginfo.
+        if self.opts.debuginfo != DebugInfo::None
+            && let RealFileName::LocalPath(working_dir) = &self.opts.working_dir
+        {
+            self.report_non_reproducible(
+                level,
+                errors::NonReproducibleWorkingDir { path: working_dir },
+            );
+        }
+
+        // Don't report a variable set with `--env-set`, its value comes from the commandline.
+        let env_depinfo = self.psess.env_depinfo.borrow();
+        let mut env_vars: Vec<_> = env_depinfo
+            .iter()
+            .map(|(var, _)| var.as_str())
+            .filter(|var| !self.opts.logical_env.contains_key(*var))
+            .collect();
+        env_vars.sort_unstable();
+        env_vars.dedup();
+        for var in env_vars {
+            self.report_non_reproducible(level, errors::NonReproducibleEnvVar { var });
+        }
+
+        // The files of the local crate are all in the source map, including the ones only read
+        // by `include_bytes!`, and the files tracked by proc-macros are in `file_depinfo`.
+        let file_depinfo = self.psess.file_depinfo.borrow();
+        let source_map = self.source_map();
+        let files = source_map.files();
+        let tracked_files = file_depinfo
+            .iter()
+            .map(|path| source_map.path_mapping().to_real_filename(Path::new(path.as_str())));
+        let local_files =
+            files.iter().filter(|file| !file.is_imported()).filter_map(|file| match &file.name {
+                FileName::Real(name) => Some(name.clone()),
+                _ => None,
+            });
+
+        let working_dir = self.opts.working_dir.local_path_if_available();
+        let canonical_working_dir = fs::canonicalize(working_dir).ok();
+        let mut reported = Vec::new();
+        for name in local_files.chain(tracked_files) {
+            let Some(local_path) = name.local_path() else {
+                continue;
+            };
+            if reported.iter().any(|path: &PathBuf| path == local_path) {
+                continue;
+            }
+
+            if let RealFileName::LocalPath(path) = &name
+                && path.is_absolute()
+            {
+                reported.push(path.clone());
+                self.report_non_reproducible(level, errors::NonReproducibleAbsolutePath { path });
+            } else if !is_in_working_dir(local_path, working_dir, canonical_working_dir.as_deref())
+            {
+                reported.push(local_path.to_path_buf());
+                self.report_non_reproducible(
+                    level,
+                    errors::NonReproducibleOutsideWorkingDir { path: local_path, working_dir },
+                );
+            }
+        }
+    }
+
+    fn report_non_reproducible<'a>(&'a self, level: Level, diag: impl Diagnostic<'a, ()>) {
+        diag.into_diag(self.dcx(), level).emit();
+    }
+}
+
+/// Returns whether `path`, relative to `working_dir`, is in it. Paths are compared once
+/// canonicalized when possible, as they may contain `..` components or symlinks.
+fn is_in_working_dir(
+    path: &Path,
+    working_dir: &Path,
+    canonical_working_dir: Option<&Path>,
+) -> bool {
+    let path = working_dir.join(path);
+    match (fs::canonicalize(&path), canonical_working_dir) {
+        (Ok(path), Some(working_dir)) => path.starts_with(working_dir),
+        _ => path.starts_with(working_dir) && !path.components().any(|c| c.as_os_str() == ".."),
+    }
+}
@@ -19,11 +19,13 @@
 use rustc_errors::emitter::{DynEmitter, HumanEmitter, HumanReadableErrorType, stderr_destination};
 use rustc_errors::json::JsonEmitter;
 use rustc_errors::registry::Registry;
+use rustc_errors::sarif::SarifEmitter;
 use rustc_errors::{
     Diag, DiagCtxt, DiagCtxtHandle, DiagMessage, Diagnostic, ErrorGuaranteed, FatalAbort,
     FluentBundle, LazyFallbackBundle, TerminalUrl, fallback_fluent_bundle,
 };
 use rustc_macros::HashStable_Generic;
+use rustc_span::def_id::DefId;
 pub use rustc_span::def_id::StableCrateId;
 use rustc_span::edition::Edition;
 use rustc_span::source_map::{FilePathMapping, SourceMap};
@@ -40,10 +42,11 @@
 use crate::config::{
     self, CoverageLevel, CrateType, DebugInfo, ErrorOutputType, FunctionReturn, Input,
     InstrumentCoverage, OptLevel, OutFileName, OutputType, RemapPathScopeComponents,
-    SwitchWithOptPath,
+    SwitchWithOptPath, TimeReportKind,
 };
 use crate::parse::{ParseSess, add_feature_diagnostics};
 use crate::search_paths::{PathKind, SearchPath};
+use crate::time_report::TimeReport;
 use crate::{errors, filesearch, lint};
 
 struct OptimizationFuel {
@@ -160,6 +163,9 @@ pub struct Session {
     /// Data about code being compiled, gathered during compilation.
     pub code_stats: CodeStats,
 
+    /// Used by `-Z time-report`.
+    pub time_report: Option<TimeReport>,
+
     /// Tracks fuel info if `-zfuel=crate=n` is specified.
     optimization_fuel: Lock<OptimizationFuel>,
 
@@ -247,6 +253,20 @@ pub fn as_usize(self) -> usize {
 }
 
 impl Session {
+    /// Runs `f`, attributing the time it takes to `activity` on `item` in the report of
+    /// `-Ztime-report=items`.
+    pub fn time_item<R>(
+        &self,
+        activity: &'static str,
+        item: impl Into<DefId>,
+        f: impl FnOnce() -> R,
+    ) -> R {
+        match &self.time_report {
+            Some(time_report) => time_report.items.time(activity, item.into(), f),
+            None => f(),
+        }
+    }
+
     pub fn miri_unleashed_feature(&self, span: Span, feature_gate: Option<Symbol>) {
         self.miri_unleashed_features.lock().push((span, feature_gate));
     }
@@ -991,6 +1011,11 @@ fn default_emitter(
             .track_diagnostics(track_diagnostics)
             .terminal_url(terminal_url),
         ),
+        config::ErrorOutputType::Sarif => Box::new(
+            SarifEmitter::stderr(source_map, fallback_bundle)
+                .registry(Some(registry))
+                .fluent_bundle(bundle),
+        ),
     }
 }
 
@@ -1095,6 +1120,10 @@ pub fn build_session(
         sopts.unstable_opts.time_passes.then(|| sopts.unstable_opts.time_passes_format),
     );
 
+    let time_report = sopts.unstable_opts.time_report.map(|kind| match kind {
+        TimeReportKind::Items { limit } => TimeReport::new(limit),
+    });
+
     let ctfe_backtrace = Lock::new(match env::var("RUSTC_CTFE_BACKTRACE") {
         Ok(ref val) if val == "immediate" => CtfeBacktrace::Immediate,
         Ok(ref val) if val != "0" => CtfeBacktrace::Capture,
@@ -1115,6 +1144,7 @@ pub fn build_session(
         incr_comp_session: RwLock::new(IncrCompSession::NotInitialized),
         prof,
         code_stats: Default::default(),
+        time_report,
         optimization_fuel,
         print_fuel,
         jobserver: jobserver::client(),
@@ -1472,6 +1502,10 @@ fn mk_emitter(output: ErrorOutputType) -> Box<DynEmitter> {
                 color_config,
             ))
         }
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::stderr(
+            Lrc::new(SourceMap::new(FilePathMapping::empty())),
+            fallback_bundle,
+        )),
     };
     emitter
 }
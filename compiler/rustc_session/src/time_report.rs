@@ -0,0 +1,144 @@
+//! The breakdown of the compilation time by item printed by `-Ztime-report=items`.
+//!
+//! The analyses of items, e.g. type-checking, are timed as they run. LLVM works on whole codegen
+//! units, so the time it spends on each of them is split between the items instantiated in it, in
+//! proportion to their estimated size. The analyses are done once per item, but LLVM works on
+//! each instantiation, so the LLVM time of generic items is also broken down by instance.
+
+use std::cmp::Reverse;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rustc_data_structures::profiling::{ItemTimes, duration_to_secs_str};
+use rustc_data_structures::sync::Lock;
+use rustc_span::def_id::DefId;
+
+/// The activities timed on items, in the order of their columns in the report.
+pub const ITEM_ACTIVITIES: [&str; 3] = [TYPECK, BORROWCK, MIR_OPT];
+pub const TYPECK: &str = "typeck";
+pub const BORROWCK: &str = "borrowck";
+pub const MIR_OPT: &str = "mir_opt";
+/// The activity timed on codegen units.
+pub const LLVM: &str = "llvm";
+
+pub struct TimeReport {
+    limit: usize,
+    /// The times of the analyses of items.
+    pub items: ItemTimes<DefId>,
+    /// The times of LLVM on codegen units, by name. Shared with the threads running LLVM.
+    pub codegen_units: Arc<ItemTimes<String>>,
+    /// The items timed so far, and the instantiated ones, once monomorphization is done.
+    item_infos: Lock<Vec<ItemInfo>>,
+}
+
+/// An item, as known once monomorphization is done.
+pub struct ItemInfo {
+    pub def_id: DefId,
+    pub path: String,
+    pub span: String,
+    /// The instantiations of the item in codegen units.
+    pub instances: Vec<InstanceInfo>,
+}
+
+/// An instantiation of an item.
+pub struct InstanceInfo {
+    /// The instance, with its generic arguments.
+    pub name: String,
+    /// The codegen units the instance is in, with the share of the estimated size of each unit it
+    /// accounts for.
+    pub codegen_unit_shares: Vec<(String, f64)>,
+}
+
+impl TimeReport {
+    pub fn new(limit: usize) -> Self {
+        TimeReport {
+            limit,
+            items: Default::default(),
+            codegen_units: Default::default(),
+            item_infos: Default::default(),
+        }
+    }
+
+    /// Records the items to report on. Must be called once the analyses of all items and
+    /// monomorphization are done.
+    pub fn record_items(&self, item_infos: Vec<ItemInfo>) {
+        *self.item_infos.lock() = item_infos;
+    }
+
+    /// Prints the items with the most time. Must be called once LLVM is done.
+    pub fn print(&self) {
+        let item_times = self.items.take();
+        let codegen_unit_times = self.codegen_units.take();
+
+        let item_infos = std::mem::take(&mut *self.item_infos.lock());
+        let mut rows: Vec<_> = item_infos
+            .iter()
+            .map(|info| {
+                let analysis_times = ITEM_ACTIVITIES.map(|activity| {
+                    item_times.get(&(activity, info.def_id)).copied().unwrap_or_default()
+                });
+                let mut instance_times: Vec<_> = info
+                    .instances
+                    .iter()
+                    .map(|instance| {
+                        let llvm_time = instance
+                            .codegen_unit_shares
+                            .iter()
+                            .map(|(codegen_unit, share)| {
+                                let time = codegen_unit_times
+                                    .get(&(LLVM, codegen_unit.clone()))
+                                    .copied()
+                                    .unwrap_or_default();
+                                time.mul_f64(*share)
+                            })
+                            .sum::<Duration>();
+                        (llvm_time, &instance.name)
+                    })
+                    .collect();
+                instance_times.sort_by(|a, b| (Reverse(a.0), a.1).cmp(&(Reverse(b.0), b.1)));
+                let llvm_time = instance_times.iter().map(|(time, _)| *time).sum::<Duration>();
+                let total = analysis_times.iter().sum::<Duration>() + llvm_time;
+                (total, analysis_times, llvm_time, info, instance_times)
+            })
+            .collect();
+        rows.sort_by(|a, b| (Reverse(a.0), &a.3.path).cmp(&(Reverse(b.0), &b.3.path)));
+
+        eprintln!(
+            "time-report: the {} items with the most compilation time, in seconds",
+            self.limit.min(rows.len())
+        );
+        eprintln!(
+            "{:>8} {:>8} {:>8} {:>8} {:>8} {:>10}  item",
+            "total", TYPECK, BORROWCK, MIR_OPT, LLVM, "instances"
+        );
+        for (total, [typeck, borrowck, mir_opt], llvm, info, instance_times) in
+            rows.into_iter().take(self.limit)
+        {
+            eprintln!(
+                "{:>8} {:>8} {:>8} {:>8} {:>8} {:>10}  {} ({})",
+                duration_to_secs_str(total),
+                duration_to_secs_str(typeck),
+                duration_to_secs_str(borrowck),
+                duration_to_secs_str(mir_opt),
+                duration_to_secs_str(llvm),
+                info.instances.len(),
+                info.path,
+                info.span,
+            );
+            // The LLVM time of an item with a single instance is already on the item's row.
+            if instance_times.len() > 1 {
+                for (llvm, name) in instance_times {
+                    eprintln!(
+                        "{:>8} {:>8} {:>8} {:>8} {:>8} {:>10}    {name}",
+                        "",
+                        "",
+                        "",
+                        "",
+                        duration_to_secs_str(llvm),
+                        "",
+                    );
+                }
+            }
+        }
+    }
+}
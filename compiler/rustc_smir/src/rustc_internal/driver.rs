@@ -0,0 +1,168 @@
+//! Run the compiler analyses on a crate and StableMIR code without implementing a driver.
+//!
+//! Unlike [run!], this doesn't go through `rustc_driver`, so a tool can analyze many crates in
+//! the same process, e.g., every crate of a workspace, by invoking [analyze_crate] for each one.
+
+use std::ops::ControlFlow;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+
+use rustc_errors::registry::Registry;
+use rustc_interface::interface;
+use rustc_session::config::{ErrorOutputType, Input, build_session_options, rustc_optgroups};
+use rustc_session::{EarlyDiagCtxt, getopts};
+use rustc_span::fatal_error::FatalErrorMarker;
+use stable_mir::CompilerError;
+
+use super::{override_queries, run};
+
+/// Describe a crate to be analyzed by [analyze_crate].
+///
+/// Options are given in the same format as the corresponding `rustc` command line arguments.
+#[derive(Clone, Debug)]
+pub struct CrateConfig {
+    /// The path to the root source file of the crate, e.g., `src/lib.rs`.
+    pub root: PathBuf,
+    /// The crate name, which defaults to the name of the root file.
+    pub name: Option<String>,
+    /// The crate type, e.g., `lib` or `bin`, which defaults to `bin`.
+    pub crate_type: Option<String>,
+    /// The edition of the crate, which defaults to 2015.
+    pub edition: Option<String>,
+    /// The target triple, or the path to a target specification. Defaults to the host.
+    pub target: Option<String>,
+    /// The configuration options, e.g., `unix` or `feature="std"`.
+    pub cfgs: Vec<String>,
+    /// Any other `rustc` arguments, e.g., `--extern` and `-L` arguments to find the crate
+    /// dependencies.
+    pub args: Vec<String>,
+}
+
+impl CrateConfig {
+    pub fn new(root: impl Into<PathBuf>) -> CrateConfig {
+        CrateConfig {
+            root: root.into(),
+            name: None,
+            crate_type: None,
+            edition: None,
+            target: None,
+            cfgs: vec![],
+            args: vec![],
+        }
+    }
+
+    /// The `rustc` arguments that correspond to this configuration, excluding the binary name.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec![self.root.display().to_string()];
+        let options = [
+            ("--crate-name", &self.name),
+            ("--crate-type", &self.crate_type),
+            ("--edition", &self.edition),
+            ("--target", &self.target),
+        ];
+        for (option, value) in options {
+            if let Some(value) = value {
+                args.extend([option.to_string(), value.clone()]);
+            }
+        }
+        for cfg in &self.cfgs {
+            args.extend(["--cfg".to_string(), cfg.clone()]);
+        }
+        args.extend(self.args.iter().cloned());
+        args
+    }
+}
+
+/// Run the compiler analyses on the given crate, then invoke `callback`, which can use the
+/// StableMIR APIs to inspect the crate.
+///
+/// The StableMIR session only lasts for the duration of the callback. Items retrieved in one
+/// session, such as types and definitions, are meaningless in any other session.
+///
+/// Code generation never runs. Since `rustc_smir` cannot depend on `rustc_driver`, the caller must
+/// provide the diagnostic messages of the compiler, which are available as
+/// `rustc_driver::DEFAULT_LOCALE_RESOURCES`:
+///
+/// ```ignore(needs-extern-crate)
+/// # extern crate rustc_driver;
+/// # extern crate rustc_smir;
+/// # extern crate stable_mir;
+/// # use std::ops::ControlFlow;
+/// # use rustc_smir::rustc_internal::{CrateConfig, analyze_crate};
+/// for root in ["foo/src/lib.rs", "bar/src/lib.rs"] {
+///     let mut config = CrateConfig::new(root);
+///     config.crate_type = Some("lib".to_string());
+///     config.cfgs.push("feature=\"std\"".to_string());
+///     let result = analyze_crate(&config, rustc_driver::DEFAULT_LOCALE_RESOURCES, || {
+///         ControlFlow::<(), _>::Continue(stable_mir::all_local_items().len())
+///     });
+/// }
+/// ```
+///
+/// Errors are reported the same way as [run!].
+pub fn analyze_crate<B, C, F>(
+    config: &CrateConfig,
+    locale_resources: &[&'static str],
+    callback: F,
+) -> Result<C, CompilerError<B>>
+where
+    B: Send,
+    C: Send,
+    F: FnOnce() -> ControlFlow<B, C> + Send,
+{
+    let result = catch_fatal_errors(|| {
+        let args = config.to_args();
+        let mut early_dcx = EarlyDiagCtxt::new(ErrorOutputType::default());
+        let mut options = getopts::Options::new();
+        for option in rustc_optgroups() {
+            (option.apply)(&mut options);
+        }
+        let matches = options.parse(&args).unwrap_or_else(|e| early_dcx.early_fatal(e.to_string()));
+        let opts = build_session_options(&mut early_dcx, &matches);
+        let compiler_config = interface::Config {
+            opts,
+            crate_cfg: matches.opt_strs("cfg"),
+            crate_check_cfg: matches.opt_strs("check-cfg"),
+            input: Input::File(config.root.clone()),
+            output_file: None,
+            output_dir: None,
+            ice_file: None,
+            file_loader: None,
+            locale_resources: locale_resources.to_vec(),
+            lint_caps: Default::default(),
+            psess_created: None,
+            hash_untracked_state: None,
+            register_lints: None,
+            override_queries: Some(override_queries),
+            mir_passes: Vec::new(),
+            make_codegen_backend: None,
+            registry: Registry::new(&[]),
+            using_internal_features: Default::default(),
+            expanded_args: args,
+        };
+        interface::run_compiler(compiler_config, |compiler| {
+            compiler.enter(|queries| {
+                queries.global_ctxt()?.enter(|tcx| {
+                    tcx.analysis(())?;
+                    Ok(run(tcx, callback))
+                })
+            })
+        })
+    });
+    match result {
+        Ok(Ok(Ok(ControlFlow::Continue(value)))) => Ok(value),
+        Ok(Ok(Ok(ControlFlow::Break(value)))) => Err(CompilerError::Interrupted(value)),
+        // The StableMIR session could not be started, e.g., because another one is already
+        // active on this thread.
+        Ok(Ok(Err(_))) | Ok(Err(_)) | Err(_) => Err(CompilerError::Failed),
+    }
+}
+
+/// Catch the fatal errors raised by the compiler, the same way `rustc_driver` does.
+fn catch_fatal_errors<R>(f: impl FnOnce() -> R) -> Result<R, ()> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|value| {
+        if !value.is::<FatalErrorMarker>() {
+            panic::resume_unwind(value);
+        }
+    })
+}
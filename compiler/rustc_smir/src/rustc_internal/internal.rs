@@ -7,10 +7,17 @@
 
 use rustc_middle::ty::{self as rustc_ty, Const as InternalConst, Ty as InternalTy, TyCtxt};
 use rustc_span::Symbol;
+use rustc_span::source_map::Spanned;
 use stable_mir::abi::Layout;
 use stable_mir::mir::alloc::AllocId;
 use stable_mir::mir::mono::{Instance, MonoItem, StaticDef};
-use stable_mir::mir::{BinOp, Mutability, Place, ProjectionElem, Safety, UnOp};
+use stable_mir::mir::{
+    AggregateKind, AssertMessage, BinOp, BorrowKind, CastKind, ConstOperand, CoroutineDesugaring,
+    CoroutineKind, CoroutineSource, FakeBorrowKind, MutBorrowKind, Mutability,
+    NonDivergingIntrinsic, NullOp, Operand, Place, PointerCoercion, ProjectionElem, RetagKind,
+    Rvalue, Safety, Statement, StatementKind, SwitchTargets, Terminator, TerminatorKind, UnOp,
+    UnwindAction, VarDebugInfo, VarDebugInfoContents,
+};
 use stable_mir::ty::{
     Abi, AdtDef, Binder, BoundRegionKind, BoundTyKind, BoundVariableKind, ClosureKind, DynKind,
     ExistentialPredicate, ExistentialProjection, ExistentialTraitRef, FloatTy, FnSig,
@@ -596,6 +603,491 @@ fn internal<'tcx>(&self, _tables: &mut Tables<'_>, _tcx: TyCtxt<'tcx>) -> Self::
     }
 }
 
+impl RustcInternal for Statement {
+    type T<'tcx> = rustc_middle::mir::Statement<'tcx>;
+
+    // The statement is placed in the outermost source scope, since StableMIR doesn't keep
+    // track of scopes.
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        rustc_middle::mir::Statement {
+            source_info: rustc_middle::mir::SourceInfo::outermost(self.span.internal(tables, tcx)),
+            kind: self.kind.internal(tables, tcx),
+        }
+    }
+}
+
+impl RustcInternal for StatementKind {
+    type T<'tcx> = rustc_middle::mir::StatementKind<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::mir::StatementKind as InternalKind;
+        match self {
+            StatementKind::Assign(place, rvalue) => InternalKind::Assign(Box::new((
+                place.internal(tables, tcx),
+                rvalue.internal(tables, tcx),
+            ))),
+            StatementKind::SetDiscriminant { place, variant_index } => {
+                InternalKind::SetDiscriminant {
+                    place: Box::new(place.internal(tables, tcx)),
+                    variant_index: variant_index.internal(tables, tcx),
+                }
+            }
+            StatementKind::Deinit(place) => {
+                InternalKind::Deinit(Box::new(place.internal(tables, tcx)))
+            }
+            StatementKind::StorageLive(local) => {
+                InternalKind::StorageLive(rustc_middle::mir::Local::from_usize(*local))
+            }
+            StatementKind::StorageDead(local) => {
+                InternalKind::StorageDead(rustc_middle::mir::Local::from_usize(*local))
+            }
+            StatementKind::Retag(kind, place) => InternalKind::Retag(
+                kind.internal(tables, tcx),
+                Box::new(place.internal(tables, tcx)),
+            ),
+            StatementKind::PlaceMention(place) => {
+                InternalKind::PlaceMention(Box::new(place.internal(tables, tcx)))
+            }
+            StatementKind::Intrinsic(intrinsic) => {
+                InternalKind::Intrinsic(Box::new(intrinsic.internal(tables, tcx)))
+            }
+            StatementKind::ConstEvalCounter => InternalKind::ConstEvalCounter,
+            StatementKind::Nop => InternalKind::Nop,
+            // These contain information that is not fully represented in StableMIR.
+            StatementKind::FakeRead(..)
+            | StatementKind::AscribeUserType { .. }
+            | StatementKind::Coverage(_) => {
+                unreachable!("`{self:?}` should have been rejected by `replace::check_body`")
+            }
+        }
+    }
+}
+
+impl RustcInternal for RetagKind {
+    type T<'tcx> = rustc_middle::mir::RetagKind;
+
+    fn internal<'tcx>(&self, _tables: &mut Tables<'_>, _tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            RetagKind::FnEntry => rustc_middle::mir::RetagKind::FnEntry,
+            RetagKind::TwoPhase => rustc_middle::mir::RetagKind::TwoPhase,
+            RetagKind::Raw => rustc_middle::mir::RetagKind::Raw,
+            RetagKind::Default => rustc_middle::mir::RetagKind::Default,
+        }
+    }
+}
+
+impl RustcInternal for NonDivergingIntrinsic {
+    type T<'tcx> = rustc_middle::mir::NonDivergingIntrinsic<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            NonDivergingIntrinsic::Assume(op) => {
+                rustc_middle::mir::NonDivergingIntrinsic::Assume(op.internal(tables, tcx))
+            }
+            NonDivergingIntrinsic::CopyNonOverlapping(copy) => {
+                rustc_middle::mir::NonDivergingIntrinsic::CopyNonOverlapping(
+                    rustc_middle::mir::CopyNonOverlapping {
+                        src: copy.src.internal(tables, tcx),
+                        dst: copy.dst.internal(tables, tcx),
+                        count: copy.count.internal(tables, tcx),
+                    },
+                )
+            }
+        }
+    }
+}
+
+impl RustcInternal for Rvalue {
+    type T<'tcx> = rustc_middle::mir::Rvalue<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::mir::Rvalue as InternalRvalue;
+        match self {
+            Rvalue::AddressOf(mutability, place) => InternalRvalue::RawPtr(
+                mutability.internal(tables, tcx),
+                place.internal(tables, tcx),
+            ),
+            Rvalue::Aggregate(kind, operands) => InternalRvalue::Aggregate(
+                Box::new(kind.internal(tables, tcx)),
+                operands.iter().map(|op| op.internal(tables, tcx)).collect(),
+            ),
+            Rvalue::BinaryOp(bin_op, lhs, rhs) => InternalRvalue::BinaryOp(
+                bin_op.internal(tables, tcx),
+                Box::new((lhs.internal(tables, tcx), rhs.internal(tables, tcx))),
+            ),
+            Rvalue::Cast(kind, op, ty) => InternalRvalue::Cast(
+                kind.internal(tables, tcx),
+                op.internal(tables, tcx),
+                ty.internal(tables, tcx),
+            ),
+            Rvalue::CheckedBinaryOp(bin_op, lhs, rhs) => InternalRvalue::BinaryOp(
+                bin_op.internal(tables, tcx).wrapping_to_overflowing().unwrap_or_else(|| {
+                    unreachable!(
+                        "`{bin_op:?}` has no checked version and should have been rejected by \
+                         `replace::check_body`"
+                    )
+                }),
+                Box::new((lhs.internal(tables, tcx), rhs.internal(tables, tcx))),
+            ),
+            Rvalue::CopyForDeref(place) => {
+                InternalRvalue::CopyForDeref(place.internal(tables, tcx))
+            }
+            Rvalue::Discriminant(place) => {
+                InternalRvalue::Discriminant(place.internal(tables, tcx))
+            }
+            Rvalue::Len(place) => InternalRvalue::Len(place.internal(tables, tcx)),
+            Rvalue::Ref(region, kind, place) => InternalRvalue::Ref(
+                region.internal(tables, tcx),
+                kind.internal(tables, tcx),
+                place.internal(tables, tcx),
+            ),
+            Rvalue::Repeat(op, len) => {
+                InternalRvalue::Repeat(op.internal(tables, tcx), len.internal(tables, tcx))
+            }
+            Rvalue::ShallowInitBox(op, ty) => {
+                InternalRvalue::ShallowInitBox(op.internal(tables, tcx), ty.internal(tables, tcx))
+            }
+            Rvalue::ThreadLocalRef(item) => {
+                InternalRvalue::ThreadLocalRef(item.internal(tables, tcx))
+            }
+            Rvalue::NullaryOp(null_op, ty) => {
+                InternalRvalue::NullaryOp(null_op.internal(tables, tcx), ty.internal(tables, tcx))
+            }
+            Rvalue::UnaryOp(un_op, op) => {
+                InternalRvalue::UnaryOp(un_op.internal(tables, tcx), op.internal(tables, tcx))
+            }
+            Rvalue::Use(op) => InternalRvalue::Use(op.internal(tables, tcx)),
+        }
+    }
+}
+
+impl RustcInternal for AggregateKind {
+    type T<'tcx> = rustc_middle::mir::AggregateKind<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::mir::AggregateKind as InternalKind;
+        match self {
+            AggregateKind::Array(ty) => InternalKind::Array(ty.internal(tables, tcx)),
+            AggregateKind::Tuple => InternalKind::Tuple,
+            AggregateKind::Adt(def, variant, args, user_ty, field) => InternalKind::Adt(
+                def.0.internal(tables, tcx),
+                variant.internal(tables, tcx),
+                args.internal(tables, tcx),
+                user_ty.map(rustc_ty::UserTypeAnnotationIndex::from_usize),
+                field.map(rustc_target::abi::FieldIdx::from_usize),
+            ),
+            AggregateKind::Closure(def, args) => {
+                InternalKind::Closure(def.0.internal(tables, tcx), args.internal(tables, tcx))
+            }
+            AggregateKind::Coroutine(def, args, _movability) => {
+                InternalKind::Coroutine(def.0.internal(tables, tcx), args.internal(tables, tcx))
+            }
+            AggregateKind::RawPtr(ty, mutability) => {
+                InternalKind::RawPtr(ty.internal(tables, tcx), mutability.internal(tables, tcx))
+            }
+        }
+    }
+}
+
+impl RustcInternal for BorrowKind {
+    type T<'tcx> = rustc_middle::mir::BorrowKind;
+
+    fn internal<'tcx>(&self, _tables: &mut Tables<'_>, _tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::mir::{FakeBorrowKind as InternalFake, MutBorrowKind as InternalMut};
+        match self {
+            BorrowKind::Shared => rustc_middle::mir::BorrowKind::Shared,
+            BorrowKind::Fake(kind) => rustc_middle::mir::BorrowKind::Fake(match kind {
+                FakeBorrowKind::Deep => InternalFake::Deep,
+                FakeBorrowKind::Shallow => InternalFake::Shallow,
+            }),
+            BorrowKind::Mut { kind } => rustc_middle::mir::BorrowKind::Mut {
+                kind: match kind {
+                    MutBorrowKind::Default => InternalMut::Default,
+                    MutBorrowKind::TwoPhaseBorrow => InternalMut::TwoPhaseBorrow,
+                    MutBorrowKind::ClosureCapture => InternalMut::ClosureCapture,
+                },
+            },
+        }
+    }
+}
+
+impl RustcInternal for CastKind {
+    type T<'tcx> = rustc_middle::mir::CastKind;
+
+    // StableMIR doesn't keep track of whether a pointer coercion was written by the user, so
+    // they are all considered implicit.
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::mir::{CastKind as InternalKind, CoercionSource};
+        use rustc_middle::ty::adjustment::PointerCoercion as InternalCoercion;
+        match self {
+            CastKind::PointerExposeAddress => InternalKind::PointerExposeProvenance,
+            CastKind::PointerWithExposedProvenance => InternalKind::PointerWithExposedProvenance,
+            CastKind::PointerCoercion(coercion) => InternalKind::PointerCoercion(
+                coercion.internal(tables, tcx),
+                CoercionSource::Implicit,
+            ),
+            CastKind::DynStar => {
+                InternalKind::PointerCoercion(InternalCoercion::DynStar, CoercionSource::Implicit)
+            }
+            CastKind::IntToInt => InternalKind::IntToInt,
+            CastKind::FloatToInt => InternalKind::FloatToInt,
+            CastKind::FloatToFloat => InternalKind::FloatToFloat,
+            CastKind::IntToFloat => InternalKind::IntToFloat,
+            CastKind::PtrToPtr => InternalKind::PtrToPtr,
+            CastKind::FnPtrToPtr => InternalKind::FnPtrToPtr,
+            CastKind::Transmute => InternalKind::Transmute,
+        }
+    }
+}
+
+impl RustcInternal for PointerCoercion {
+    type T<'tcx> = rustc_middle::ty::adjustment::PointerCoercion;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::ty::adjustment::PointerCoercion as InternalCoercion;
+        match self {
+            PointerCoercion::ReifyFnPointer => InternalCoercion::ReifyFnPointer,
+            PointerCoercion::UnsafeFnPointer => InternalCoercion::UnsafeFnPointer,
+            PointerCoercion::ClosureFnPointer(safety) => {
+                InternalCoercion::ClosureFnPointer(safety.internal(tables, tcx))
+            }
+            PointerCoercion::MutToConstPointer => InternalCoercion::MutToConstPointer,
+            PointerCoercion::ArrayToPointer => InternalCoercion::ArrayToPointer,
+            PointerCoercion::Unsize => InternalCoercion::Unsize,
+        }
+    }
+}
+
+impl RustcInternal for NullOp {
+    type T<'tcx> = rustc_middle::mir::NullOp<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            NullOp::SizeOf => rustc_middle::mir::NullOp::SizeOf,
+            NullOp::AlignOf => rustc_middle::mir::NullOp::AlignOf,
+            NullOp::OffsetOf(indices) => rustc_middle::mir::NullOp::OffsetOf(
+                tcx.mk_offset_of_from_iter(indices.iter().map(|(variant, field)| {
+                    (variant.internal(tables, tcx), rustc_target::abi::FieldIdx::from_usize(*field))
+                })),
+            ),
+            NullOp::UbChecks => rustc_middle::mir::NullOp::UbChecks,
+        }
+    }
+}
+
+impl RustcInternal for Operand {
+    type T<'tcx> = rustc_middle::mir::Operand<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        match self {
+            Operand::Copy(place) => rustc_middle::mir::Operand::Copy(place.internal(tables, tcx)),
+            Operand::Move(place) => rustc_middle::mir::Operand::Move(place.internal(tables, tcx)),
+            Operand::Constant(constant) => {
+                rustc_middle::mir::Operand::Constant(Box::new(constant.internal(tables, tcx)))
+            }
+        }
+    }
+}
+
+impl RustcInternal for ConstOperand {
+    type T<'tcx> = rustc_middle::mir::ConstOperand<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        rustc_middle::mir::ConstOperand {
+            span: self.span.internal(tables, tcx),
+            user_ty: self.user_ty.map(rustc_ty::UserTypeAnnotationIndex::from_usize),
+            const_: self.const_.internal(tables, tcx),
+        }
+    }
+}
+
+impl RustcInternal for Terminator {
+    type T<'tcx> = rustc_middle::mir::Terminator<'tcx>;
+
+    // The terminator is placed in the outermost source scope, since StableMIR doesn't keep
+    // track of scopes.
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::mir::{BasicBlock, TerminatorKind as InternalKind};
+        let span = self.span.internal(tables, tcx);
+        let kind = match &self.kind {
+            TerminatorKind::Goto { target } => {
+                InternalKind::Goto { target: BasicBlock::from_usize(*target) }
+            }
+            TerminatorKind::SwitchInt { discr, targets } => InternalKind::SwitchInt {
+                discr: discr.internal(tables, tcx),
+                targets: targets.internal(tables, tcx),
+            },
+            TerminatorKind::Resume => InternalKind::UnwindResume,
+            TerminatorKind::Abort => {
+                InternalKind::UnwindTerminate(rustc_middle::mir::UnwindTerminateReason::Abi)
+            }
+            TerminatorKind::Return => InternalKind::Return,
+            TerminatorKind::Unreachable => InternalKind::Unreachable,
+            TerminatorKind::Drop { place, target, unwind } => InternalKind::Drop {
+                place: place.internal(tables, tcx),
+                target: BasicBlock::from_usize(*target),
+                unwind: unwind.internal(tables, tcx),
+                replace: false,
+            },
+            TerminatorKind::Call { func, args, destination, target, unwind } => {
+                InternalKind::Call {
+                    func: func.internal(tables, tcx),
+                    args: args
+                        .iter()
+                        .map(|arg| Spanned { node: arg.internal(tables, tcx), span })
+                        .collect(),
+                    destination: destination.internal(tables, tcx),
+                    target: target.map(BasicBlock::from_usize),
+                    unwind: unwind.internal(tables, tcx),
+                    call_source: rustc_middle::mir::CallSource::Normal,
+                    fn_span: span,
+                }
+            }
+            TerminatorKind::Assert { cond, expected, msg, target, unwind } => {
+                InternalKind::Assert {
+                    cond: cond.internal(tables, tcx),
+                    expected: *expected,
+                    msg: Box::new(msg.internal(tables, tcx)),
+                    target: BasicBlock::from_usize(*target),
+                    unwind: unwind.internal(tables, tcx),
+                }
+            }
+            // The assembly is only kept as a debug string in StableMIR.
+            TerminatorKind::InlineAsm { .. } => {
+                unreachable!("inline assembly should have been rejected by `replace::check_body`")
+            }
+        };
+        rustc_middle::mir::Terminator {
+            source_info: rustc_middle::mir::SourceInfo::outermost(span),
+            kind,
+        }
+    }
+}
+
+impl RustcInternal for SwitchTargets {
+    type T<'tcx> = rustc_middle::mir::SwitchTargets;
+
+    fn internal<'tcx>(&self, _tables: &mut Tables<'_>, _tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::mir::BasicBlock;
+        rustc_middle::mir::SwitchTargets::new(
+            self.branches().map(|(value, target)| (value, BasicBlock::from_usize(target))),
+            BasicBlock::from_usize(self.otherwise()),
+        )
+    }
+}
+
+impl RustcInternal for UnwindAction {
+    type T<'tcx> = rustc_middle::mir::UnwindAction;
+
+    fn internal<'tcx>(&self, _tables: &mut Tables<'_>, _tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::mir::UnwindAction as InternalAction;
+        match self {
+            UnwindAction::Continue => InternalAction::Continue,
+            UnwindAction::Unreachable => InternalAction::Unreachable,
+            UnwindAction::Terminate => {
+                InternalAction::Terminate(rustc_middle::mir::UnwindTerminateReason::Abi)
+            }
+            UnwindAction::Cleanup(bb) => {
+                InternalAction::Cleanup(rustc_middle::mir::BasicBlock::from_usize(*bb))
+            }
+        }
+    }
+}
+
+impl RustcInternal for AssertMessage {
+    type T<'tcx> = rustc_middle::mir::AssertMessage<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_middle::mir::AssertKind;
+        match self {
+            AssertMessage::BoundsCheck { len, index } => AssertKind::BoundsCheck {
+                len: len.internal(tables, tcx),
+                index: index.internal(tables, tcx),
+            },
+            AssertMessage::Overflow(bin_op, lhs, rhs) => AssertKind::Overflow(
+                bin_op.internal(tables, tcx),
+                lhs.internal(tables, tcx),
+                rhs.internal(tables, tcx),
+            ),
+            AssertMessage::OverflowNeg(op) => AssertKind::OverflowNeg(op.internal(tables, tcx)),
+            AssertMessage::DivisionByZero(op) => {
+                AssertKind::DivisionByZero(op.internal(tables, tcx))
+            }
+            AssertMessage::RemainderByZero(op) => {
+                AssertKind::RemainderByZero(op.internal(tables, tcx))
+            }
+            AssertMessage::ResumedAfterReturn(coroutine) => {
+                AssertKind::ResumedAfterReturn(coroutine.internal(tables, tcx))
+            }
+            AssertMessage::ResumedAfterPanic(coroutine) => {
+                AssertKind::ResumedAfterPanic(coroutine.internal(tables, tcx))
+            }
+            AssertMessage::MisalignedPointerDereference { required, found } => {
+                AssertKind::MisalignedPointerDereference {
+                    required: required.internal(tables, tcx),
+                    found: found.internal(tables, tcx),
+                }
+            }
+        }
+    }
+}
+
+impl RustcInternal for CoroutineKind {
+    type T<'tcx> = rustc_hir::CoroutineKind;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        use rustc_hir::{
+            CoroutineDesugaring as InternalDesugaring, CoroutineSource as InternalSource,
+        };
+        match self {
+            CoroutineKind::Desugared(desugaring, source) => rustc_hir::CoroutineKind::Desugared(
+                match desugaring {
+                    CoroutineDesugaring::Async => InternalDesugaring::Async,
+                    CoroutineDesugaring::Gen => InternalDesugaring::Gen,
+                    CoroutineDesugaring::AsyncGen => InternalDesugaring::AsyncGen,
+                },
+                match source {
+                    CoroutineSource::Block => InternalSource::Block,
+                    CoroutineSource::Closure => InternalSource::Closure,
+                    CoroutineSource::Fn => InternalSource::Fn,
+                },
+            ),
+            CoroutineKind::Coroutine(movability) => {
+                rustc_hir::CoroutineKind::Coroutine(movability.internal(tables, tcx))
+            }
+        }
+    }
+}
+
+impl RustcInternal for VarDebugInfo {
+    type T<'tcx> = rustc_middle::mir::VarDebugInfo<'tcx>;
+
+    fn internal<'tcx>(&self, tables: &mut Tables<'_>, tcx: TyCtxt<'tcx>) -> Self::T<'tcx> {
+        rustc_middle::mir::VarDebugInfo {
+            name: Symbol::intern(&self.name),
+            source_info: rustc_middle::mir::SourceInfo {
+                span: self.source_info.span.internal(tables, tcx),
+                scope: rustc_middle::mir::SourceScope::from_u32(self.source_info.scope),
+            },
+            composite: self.composite.as_ref().map(|composite| {
+                Box::new(rustc_middle::mir::VarDebugInfoFragment {
+                    ty: composite.ty.internal(tables, tcx),
+                    projection: composite.projection.internal(tables, tcx),
+                })
+            }),
+            value: match &self.value {
+                VarDebugInfoContents::Place(place) => {
+                    rustc_middle::mir::VarDebugInfoContents::Place(place.internal(tables, tcx))
+                }
+                VarDebugInfoContents::Const(constant) => {
+                    rustc_middle::mir::VarDebugInfoContents::Const(constant.internal(tables, tcx))
+                }
+            },
+            argument_index: self.argument_index,
+        }
+    }
+}
+
 impl<T> RustcInternal for &T
 where
     T: RustcInternal,
@@ -13,6 +13,8 @@
 use rustc_middle::mir::interpret::AllocId;
 use rustc_middle::ty;
 use rustc_middle::ty::TyCtxt;
+use rustc_middle::util::Providers;
+use rustc_session::Session;
 use rustc_span::Span;
 use rustc_span::def_id::{CrateNum, DefId};
 use scoped_tls::scoped_thread_local;
@@ -21,11 +23,14 @@
 use stable_mir::ty::IndexedVal;
 
 use crate::rustc_smir::context::TablesWrapper;
-use crate::rustc_smir::{Stable, Tables};
+use crate::rustc_smir::{Stable, Tables, borrowck};
 
+mod driver;
 mod internal;
 pub mod pretty;
 
+pub use driver::{CrateConfig, analyze_crate};
+
 /// Convert an internal Rust compiler item into its stable counterpart, if one exists.
 ///
 /// # Warning
@@ -179,6 +184,22 @@ pub fn crate_num(item: &stable_mir::Crate) -> CrateNum {
     item.id.into()
 }
 
+/// Retain the borrow checker results of every local body, so they are still available
+/// through [stable_mir::CrateItem::borrowck_facts] after the compiler analyses.
+///
+/// This must be invoked before the compiler runs. Drivers that don't use [run!] must also
+/// install [override_queries] in their `rustc_interface::Config`.
+pub fn retain_borrowck_facts() {
+    borrowck::retain_borrowck_facts()
+}
+
+/// Override the compiler queries that StableMIR relies on.
+///
+/// This is done automatically by [run!], [run_with_tcx!], and [analyze_crate].
+pub fn override_queries(session: &Session, providers: &mut Providers) {
+    borrowck::override_queries(session, providers)
+}
+
 // A thread local variable that stores a pointer to the tables mapping between TyCtxt
 // datastructures and stable MIR datastructures
 scoped_thread_local! (static TLV: Cell<*const ()>);
@@ -219,6 +240,7 @@ pub fn run<F, T>(tcx: TyCtxt<'_>, f: F) -> Result<T, Error>
         ty_consts: IndexMap::default(),
         mir_consts: IndexMap::default(),
         layouts: IndexMap::default(),
+        mono_item_graph: None,
     }));
     stable_mir::compiler_interface::run(&tables, || init(&tables, f))
 }
@@ -368,6 +390,11 @@ impl<B, C, F> Callbacks for StableMir<B, C, F>
             C: Send,
             F: FnOnce($(optional!($with_tcx TyCtxt))?) -> ControlFlow<B, C> + Send,
         {
+            /// Install the query overrides required by StableMIR.
+            fn config(&mut self, config: &mut interface::Config) {
+                config.override_queries = Some(rustc_internal::override_queries);
+            }
+
             /// Called after analysis. Return value instructs the compiler whether to
             /// continue the compilation afterwards (defaults to `Compilation::Continue`)
             fn after_analysis<'tcx>(
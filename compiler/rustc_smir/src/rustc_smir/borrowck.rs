@@ -0,0 +1,213 @@
+//! Retrieve the borrow checker results of a body and translate them to StableMIR.
+//!
+//! The borrow checker runs on the promoted MIR of a body, which gets stolen once the
+//! compiler moves on to optimizations. In order to keep the results available after the
+//! compiler analyses, drivers can override the `mir_borrowck` query, so it retains the results
+//! in the `TyCtxt` before the body is stolen.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use rustc_borrowck::borrow_set::TwoPhaseActivation;
+use rustc_borrowck::consumers::{self, BodyWithBorrowckFacts, Borrows, ConsumerOptions, Locations};
+use rustc_middle::mir::{
+    self, BorrowCheckResult, RetainedBorrow, RetainedBorrowckFacts, RetainedOutlivesConstraint,
+};
+use rustc_middle::ty::TyCtxt;
+use rustc_middle::util::Providers;
+use rustc_mir_dataflow::Analysis;
+use rustc_session::Session;
+use rustc_span::def_id::{DefId, LocalDefId};
+use stable_mir::mir::borrowck::{BorrowData, BorrowckFacts, OutlivesConstraint};
+use stable_mir::{Error, opaque};
+
+use crate::rustc_smir::{Stable, Tables};
+
+/// Whether [override_queries] should install [mir_borrowck].
+static RETAIN_BORROWCK_FACTS: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn retain_borrowck_facts() {
+    RETAIN_BORROWCK_FACTS.store(true, Ordering::Relaxed);
+}
+
+pub(crate) fn override_queries(_session: &Session, providers: &mut Providers) {
+    if RETAIN_BORROWCK_FACTS.load(Ordering::Relaxed) {
+        providers.queries.mir_borrowck = mir_borrowck;
+    }
+}
+
+fn mir_borrowck<'tcx>(tcx: TyCtxt<'tcx>, def_id: LocalDefId) -> &'tcx BorrowCheckResult<'tcx> {
+    let (result, body_with_facts) = consumers::mir_borrowck_with_body_facts(
+        tcx,
+        def_id,
+        ConsumerOptions::RegionInferenceContext,
+    );
+    if let Some(body_with_facts) = body_with_facts {
+        tcx.retain_borrowck_facts(def_id, retained_facts(tcx, *body_with_facts));
+    }
+    result
+}
+
+/// Retrieve the borrow checker results of the given item.
+///
+/// If the results were not retained by [mir_borrowck], we try to compute them now, which is
+/// only possible if the body has not been stolen yet.
+pub(crate) fn borrowck_facts<'tcx>(
+    tables: &mut Tables<'tcx>,
+    def_id: DefId,
+) -> Result<BorrowckFacts, Error> {
+    let tcx = tables.tcx;
+    let Some(local_def_id) = def_id.as_local() else {
+        return Err(Error::new(format!("Item `{def_id:?}` is not defined in the local crate")));
+    };
+    if !tcx.mir_keys(()).contains(&local_def_id) {
+        return Err(Error::new(format!("Item `{def_id:?}` does not have a body")));
+    }
+    if let Some(facts) = tcx.retained_borrowck_facts(local_def_id) {
+        return Ok(stable_facts(tables, facts));
+    }
+    if tcx.mir_promoted(local_def_id).0.is_stolen() {
+        return Err(Error::new(format!(
+            "Borrow checker results for `{def_id:?}` are no longer available. \
+            Use `rustc_internal::retain_borrowck_facts` to keep them."
+        )));
+    }
+    let body_with_facts = consumers::get_body_with_borrowck_facts(
+        tcx,
+        local_def_id,
+        ConsumerOptions::RegionInferenceContext,
+    );
+    Ok(stable_facts(tables, &retained_facts(tcx, body_with_facts)))
+}
+
+/// Extract the results exposed by StableMIR from the borrow checker results of a body.
+fn retained_facts<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body_with_facts: BodyWithBorrowckFacts<'tcx>,
+) -> RetainedBorrowckFacts<'tcx> {
+    let body = &body_with_facts.body;
+    let regioncx = &body_with_facts.region_inference_context;
+    let locations: Vec<mir::Location> = body
+        .basic_blocks
+        .iter_enumerated()
+        .flat_map(|(block, data)| {
+            (0..=data.statements.len())
+                .map(move |statement_index| mir::Location { block, statement_index })
+        })
+        .collect();
+
+    let live_loans = {
+        let mut borrows = Borrows::new(tcx, body, regioncx, &body_with_facts.borrow_set)
+            .into_engine(tcx, body)
+            .pass_name("stable_mir")
+            .iterate_to_fixpoint()
+            .into_results_cursor(body);
+        locations
+            .iter()
+            .map(|location| {
+                borrows.seek_before_primary_effect(*location);
+                (*location, borrows.get().iter().map(|idx| idx.as_usize()).collect())
+            })
+            .collect()
+    };
+
+    let region_values = regioncx
+        .regions()
+        .map(|region| {
+            locations
+                .iter()
+                .filter(|location| regioncx.region_contains_location(region, **location))
+                .copied()
+                .collect()
+        })
+        .collect();
+
+    let outlives_constraints = regioncx
+        .outlives_constraints()
+        .map(|constraint| RetainedOutlivesConstraint {
+            sup: constraint.sup,
+            sub: constraint.sub,
+            location: match constraint.locations {
+                Locations::All(_) => None,
+                Locations::Single(location) => Some(location),
+            },
+            span: constraint.span,
+            category: constraint.category,
+        })
+        .collect();
+
+    let borrows = body_with_facts
+        .borrow_set
+        .location_map
+        .values()
+        .map(|borrow| RetainedBorrow {
+            reserve_location: borrow.reserve_location,
+            activation_location: match borrow.activation_location {
+                TwoPhaseActivation::ActivatedAt(location) => Some(location),
+                TwoPhaseActivation::NotTwoPhase | TwoPhaseActivation::NotActivated => None,
+            },
+            kind: borrow.kind,
+            region: borrow.region,
+            borrowed_place: borrow.borrowed_place,
+            assigned_place: borrow.assigned_place,
+        })
+        .collect();
+
+    RetainedBorrowckFacts {
+        body: body_with_facts.body,
+        borrows,
+        outlives_constraints,
+        region_values,
+        live_loans,
+    }
+}
+
+/// Translate the retained borrow checker results of a body.
+fn stable_facts<'tcx>(
+    tables: &mut Tables<'tcx>,
+    facts: &RetainedBorrowckFacts<'tcx>,
+) -> BorrowckFacts {
+    let live_loans = facts
+        .live_loans
+        .iter()
+        .map(|(location, loans)| (location.stable(tables), loans.clone()))
+        .collect();
+
+    let region_values = facts
+        .region_values
+        .iter()
+        .map(|locations| locations.iter().map(|location| location.stable(tables)).collect())
+        .collect();
+
+    let outlives_constraints = facts
+        .outlives_constraints
+        .iter()
+        .map(|constraint| OutlivesConstraint {
+            sup: constraint.sup.as_usize(),
+            sub: constraint.sub.as_usize(),
+            location: constraint.location.map(|location| location.stable(tables)),
+            span: constraint.span.stable(tables),
+            category: opaque(&constraint.category),
+        })
+        .collect();
+
+    let borrows = facts
+        .borrows
+        .iter()
+        .map(|borrow| BorrowData {
+            reserve_location: borrow.reserve_location.stable(tables),
+            activation_location: borrow.activation_location.map(|location| location.stable(tables)),
+            kind: borrow.kind.stable(tables),
+            region: borrow.region.as_usize(),
+            borrowed_place: borrow.borrowed_place.stable(tables),
+            assigned_place: borrow.assigned_place.stable(tables),
+        })
+        .collect();
+
+    BorrowckFacts {
+        body: facts.body.stable(tables),
+        borrows,
+        outlives_constraints,
+        region_values,
+        live_loans,
+    }
+}
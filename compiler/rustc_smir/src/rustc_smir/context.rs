@@ -22,19 +22,22 @@
 use stable_mir::abi::{FnAbi, Layout, LayoutShape};
 use stable_mir::compiler_interface::Context;
 use stable_mir::mir::alloc::GlobalAlloc;
-use stable_mir::mir::mono::{InstanceDef, StaticDef};
+use stable_mir::mir::borrowck::BorrowckFacts;
+use stable_mir::mir::mono::{InstanceDef, MonoItem, StaticDef};
 use stable_mir::mir::{BinOp, Body, Place, UnOp};
 use stable_mir::target::{MachineInfo, MachineSize};
 use stable_mir::ty::{
-    AdtDef, AdtKind, Allocation, ClosureDef, ClosureKind, FieldDef, FnDef, ForeignDef,
-    ForeignItemKind, GenericArgs, IntrinsicDef, LineInfo, MirConst, PolyFnSig, RigidTy, Span, Ty,
-    TyConst, TyKind, UintTy, VariantDef,
+    AdtDef, AdtKind, Allocation, ClosureDef, ClosureKind, ExpnData, FieldDef, FnDef, ForeignDef,
+    ForeignItemKind, GenericArgs, IntrinsicDef, LineInfo, MirConst, PolyFnSig, RigidTy, Span,
+    TraitRef, Ty, TyConst, TyKind, UintTy, VariantDef,
 };
 use stable_mir::{Crate, CrateDef, CrateItem, CrateNum, DefId, Error, Filename, ItemKind, Symbol};
 
 use crate::rustc_internal::RustcInternal;
 use crate::rustc_smir::builder::BodyBuilder;
-use crate::rustc_smir::{Stable, Tables, alloc, new_item_kind, smir_crate};
+use crate::rustc_smir::{
+    Stable, Tables, alloc, borrowck, hir, new_item_kind, replace, smir_crate, traits,
+};
 
 impl<'tcx> Context for TablesWrapper<'tcx> {
     fn target_info(&self) -> MachineInfo {
@@ -71,6 +74,51 @@ fn has_body(&self, def: DefId) -> bool {
         tables.item_has_body(def_id)
     }
 
+    fn hir_body(&self, def: DefId) -> Result<stable_mir::hir::Body, Error> {
+        let mut tables = self.0.borrow_mut();
+        let tcx = tables.tcx;
+        let def_id = def.internal(&mut *tables, tcx);
+        hir::hir_body(&mut *tables, def_id)
+    }
+
+    fn borrowck_facts(&self, def: DefId) -> Result<BorrowckFacts, Error> {
+        let mut tables = self.0.borrow_mut();
+        let tcx = tables.tcx;
+        let def_id = def.internal(&mut *tables, tcx);
+        borrowck::borrowck_facts(&mut *tables, def_id)
+    }
+
+    fn replace_mir_body(&self, def: DefId, body: &Body) -> Result<(), Error> {
+        let mut tables = self.0.borrow_mut();
+        let tcx = tables.tcx;
+        let def_id = def.internal(&mut *tables, tcx);
+        replace::replace_mir_body(&mut *tables, def_id, body)
+    }
+
+    fn implements_trait(
+        &self,
+        trait_ref: &TraitRef,
+        env: stable_mir::ty::ParamEnv,
+    ) -> Result<bool, Error> {
+        let mut tables = self.0.borrow_mut();
+        traits::implements_trait(&mut *tables, trait_ref, env)
+    }
+
+    fn normalize(&self, ty: Ty, env: stable_mir::ty::ParamEnv) -> Result<Ty, Error> {
+        let mut tables = self.0.borrow_mut();
+        traits::normalize(&mut *tables, ty, env)
+    }
+
+    fn type_of_projection(
+        &self,
+        trait_ref: &TraitRef,
+        name: &str,
+        env: stable_mir::ty::ParamEnv,
+    ) -> Result<Ty, Error> {
+        let mut tables = self.0.borrow_mut();
+        traits::type_of_projection(&mut *tables, trait_ref, name, env)
+    }
+
     fn foreign_modules(&self, crate_num: CrateNum) -> Vec<stable_mir::ty::ForeignModuleDef> {
         let mut tables = self.0.borrow_mut();
         let tcx = tables.tcx;
@@ -293,6 +341,11 @@ fn get_lines(&self, span: &Span) -> LineInfo {
         LineInfo { start_line: lines.1, start_col: lines.2, end_line: lines.3, end_col: lines.4 }
     }
 
+    fn span_macro_backtrace(&self, span: &Span) -> Vec<ExpnData> {
+        let mut tables = self.0.borrow_mut();
+        hir::macro_backtrace(&mut *tables, *span)
+    }
+
     fn item_kind(&self, item: CrateItem) -> ItemKind {
         let tables = self.0.borrow();
         new_item_kind(tables.tcx.def_kind(tables[item.0]))
@@ -621,6 +674,20 @@ fn requires_monomorphization(&self, def_id: stable_mir::DefId) -> bool {
         result
     }
 
+    fn all_mono_items(&self) -> Vec<MonoItem> {
+        let mut tables = self.0.borrow_mut();
+        let items = tables.mono_item_graph().0.clone();
+        items.iter().map(|item| item.stable(&mut *tables)).collect()
+    }
+
+    fn mono_item_uses(&self, item: &MonoItem) -> Vec<MonoItem> {
+        let mut tables = self.0.borrow_mut();
+        let tcx = tables.tcx;
+        let item = item.internal(&mut *tables, tcx);
+        let used_items = tables.mono_item_graph().1.get_used_items(item).to_vec();
+        used_items.iter().map(|item| item.stable(&mut *tables)).collect()
+    }
+
     fn resolve_instance(
         &self,
         def: stable_mir::ty::FnDef,
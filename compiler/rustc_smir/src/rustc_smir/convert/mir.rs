@@ -339,6 +339,16 @@ fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
     }
 }
 
+impl<'tcx> Stable<'tcx> for mir::Location {
+    type T = stable_mir::mir::BodyLocation;
+    fn stable(&self, _: &mut Tables<'_>) -> Self::T {
+        stable_mir::mir::BodyLocation {
+            block: self.block.as_usize(),
+            statement_index: self.statement_index,
+        }
+    }
+}
+
 impl<'tcx> Stable<'tcx> for mir::Place<'tcx> {
     type T = stable_mir::mir::Place;
     fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
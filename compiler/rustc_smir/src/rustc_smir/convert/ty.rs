@@ -832,6 +832,7 @@ fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
                     },
                 })
             }
+            ty::ReVar(vid) => RegionKind::ReVar(vid.as_usize()),
             ty::ReErased => RegionKind::ReErased,
             _ => unreachable!("{self:?}"),
         }
@@ -858,9 +859,8 @@ fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
             | ty::InstanceKind::DropGlue(..)
             | ty::InstanceKind::CloneShim(..)
             | ty::InstanceKind::FnPtrShim(..)
-            | ty::InstanceKind::AsyncDropGlueCtorShim(..) => {
-                stable_mir::mir::mono::InstanceKind::Shim
-            }
+            | ty::InstanceKind::AsyncDropGlueCtorShim(..)
+            | ty::InstanceKind::ConstArgShim(..) => stable_mir::mir::mono::InstanceKind::Shim,
         };
         stable_mir::mir::mono::Instance { def, kind }
     }
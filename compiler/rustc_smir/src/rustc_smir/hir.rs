@@ -0,0 +1,376 @@
+//! Translate the HIR body of a local item and the macro backtrace of spans to StableMIR.
+//!
+//! Types are taken from the type checking results of the body, which are shared between a
+//! function and the closures defined inside of it.
+
+use rustc_hir as hir;
+use rustc_hir::def::Res;
+use rustc_middle::ty::{self, TypeckResults};
+use rustc_span::def_id::DefId;
+use rustc_span::hygiene::{self, ExpnKind};
+use stable_mir::Error;
+use stable_mir::hir::{self as stable_hir, BindingId};
+use stable_mir::ty::{ExpnData, MacroKind, Span};
+
+use crate::rustc_smir::{Stable, Tables};
+
+/// Build the HIR body of a local item.
+pub(crate) fn hir_body<'tcx>(
+    tables: &mut Tables<'tcx>,
+    def_id: DefId,
+) -> Result<stable_hir::Body, Error> {
+    let tcx = tables.tcx;
+    let Some(local_def_id) = def_id.as_local() else {
+        return Err(Error::new(format!(
+            "Item `{}` is not local and has no HIR body",
+            tcx.def_path_str(def_id)
+        )));
+    };
+    let Some(body) = tcx.hir().maybe_body_owned_by(local_def_id) else {
+        return Err(Error::new(format!("Item `{}` has no HIR body", tcx.def_path_str(def_id))));
+    };
+    let typeck = tcx.typeck(local_def_id);
+    Ok(BodyBuilder { tables, typeck }.body(body))
+}
+
+/// Return the macro backtrace of a span, innermost expansion first.
+pub(crate) fn macro_backtrace(tables: &mut Tables<'_>, span: Span) -> Vec<ExpnData> {
+    let span = tables[span];
+    span.macro_backtrace()
+        .map(|expn| ExpnData {
+            kind: match expn.kind {
+                ExpnKind::Root => stable_mir::ty::ExpnKind::Root,
+                ExpnKind::Macro(kind, name) => {
+                    let kind = match kind {
+                        hygiene::MacroKind::Bang => MacroKind::Bang,
+                        hygiene::MacroKind::Attr => MacroKind::Attr,
+                        hygiene::MacroKind::Derive => MacroKind::Derive,
+                    };
+                    stable_mir::ty::ExpnKind::Macro(kind, name.to_string())
+                }
+                ExpnKind::AstPass(pass) => stable_mir::ty::ExpnKind::AstPass(pass.descr().into()),
+                ExpnKind::Desugaring(kind) => {
+                    stable_mir::ty::ExpnKind::Desugaring(kind.descr().into())
+                }
+            },
+            call_site: expn.call_site.stable(tables),
+            def_site: expn.def_site.stable(tables),
+            macro_def: expn.macro_def_id.map(|def_id| tables.create_def_id(def_id)),
+        })
+        .collect()
+}
+
+struct BodyBuilder<'a, 'tcx> {
+    tables: &'a mut Tables<'tcx>,
+    typeck: &'tcx TypeckResults<'tcx>,
+}
+
+impl<'a, 'tcx> BodyBuilder<'a, 'tcx> {
+    fn body(&mut self, body: &'tcx hir::Body<'tcx>) -> stable_hir::Body {
+        stable_hir::Body {
+            params: body
+                .params
+                .iter()
+                .map(|param| stable_hir::Param {
+                    pat: self.pat(param.pat),
+                    span: param.span.stable(self.tables),
+                })
+                .collect(),
+            value: self.expr(body.value),
+        }
+    }
+
+    fn expr(&mut self, expr: &'tcx hir::Expr<'tcx>) -> stable_hir::Expr {
+        use stable_hir::ExprKind;
+        let kind = match expr.kind {
+            // Temporaries are only relevant to MIR building, so we skip over them.
+            hir::ExprKind::DropTemps(inner) => return self.expr(inner),
+            hir::ExprKind::Array(exprs) => ExprKind::Array(self.exprs(exprs)),
+            hir::ExprKind::Call(callee, args) => {
+                ExprKind::Call(Box::new(self.expr(callee)), self.exprs(args))
+            }
+            hir::ExprKind::MethodCall(segment, receiver, args, _) => ExprKind::MethodCall {
+                name: segment.ident.to_string(),
+                def: self
+                    .typeck
+                    .type_dependent_def_id(expr.hir_id)
+                    .map(|def_id| self.tables.create_def_id(def_id)),
+                receiver: Box::new(self.expr(receiver)),
+                args: self.exprs(args),
+            },
+            hir::ExprKind::Tup(exprs) => ExprKind::Tup(self.exprs(exprs)),
+            hir::ExprKind::Binary(op, lhs, rhs) => ExprKind::Binary(
+                bin_op(op.node),
+                Box::new(self.expr(lhs)),
+                Box::new(self.expr(rhs)),
+            ),
+            hir::ExprKind::Unary(op, operand) => {
+                let op = match op {
+                    hir::UnOp::Deref => stable_hir::UnOp::Deref,
+                    hir::UnOp::Not => stable_hir::UnOp::Not,
+                    hir::UnOp::Neg => stable_hir::UnOp::Neg,
+                };
+                ExprKind::Unary(op, Box::new(self.expr(operand)))
+            }
+            hir::ExprKind::Lit(lit) => ExprKind::Lit(lit.node.to_string()),
+            hir::ExprKind::Cast(operand, _) => ExprKind::Cast(Box::new(self.expr(operand))),
+            hir::ExprKind::Let(let_expr) => {
+                ExprKind::Let(Box::new(self.pat(let_expr.pat)), Box::new(self.expr(let_expr.init)))
+            }
+            hir::ExprKind::If(cond, then, els) => ExprKind::If(
+                Box::new(self.expr(cond)),
+                Box::new(self.expr(then)),
+                els.map(|els| Box::new(self.expr(els))),
+            ),
+            hir::ExprKind::Loop(block, _, source, _) => {
+                let source = match source {
+                    hir::LoopSource::Loop => stable_hir::LoopSource::Loop,
+                    hir::LoopSource::While => stable_hir::LoopSource::While,
+                    hir::LoopSource::ForLoop => stable_hir::LoopSource::ForLoop,
+                };
+                ExprKind::Loop(self.block(block), source)
+            }
+            hir::ExprKind::Match(scrutinee, arms, source) => {
+                let source = match source {
+                    hir::MatchSource::Normal => stable_hir::MatchSource::Normal,
+                    hir::MatchSource::Postfix => stable_hir::MatchSource::Postfix,
+                    hir::MatchSource::ForLoopDesugar => stable_hir::MatchSource::ForLoopDesugar,
+                    hir::MatchSource::TryDesugar(_) => stable_hir::MatchSource::TryDesugar,
+                    hir::MatchSource::AwaitDesugar => stable_hir::MatchSource::AwaitDesugar,
+                    hir::MatchSource::FormatArgs => stable_hir::MatchSource::FormatArgs,
+                };
+                let arms = arms.iter().map(|arm| self.arm(arm)).collect();
+                ExprKind::Match(Box::new(self.expr(scrutinee)), arms, source)
+            }
+            hir::ExprKind::Closure(closure) => ExprKind::Closure(Box::new(self.closure(closure))),
+            hir::ExprKind::Block(block, _) => ExprKind::Block(self.block(block)),
+            hir::ExprKind::Assign(lhs, rhs, _) => {
+                ExprKind::Assign(Box::new(self.expr(lhs)), Box::new(self.expr(rhs)))
+            }
+            hir::ExprKind::AssignOp(op, lhs, rhs) => ExprKind::AssignOp(
+                bin_op(op.node),
+                Box::new(self.expr(lhs)),
+                Box::new(self.expr(rhs)),
+            ),
+            hir::ExprKind::Field(base, ident) => {
+                ExprKind::Field(Box::new(self.expr(base)), ident.to_string())
+            }
+            hir::ExprKind::Index(base, index, _) => {
+                ExprKind::Index(Box::new(self.expr(base)), Box::new(self.expr(index)))
+            }
+            hir::ExprKind::Path(ref qpath) => ExprKind::Path(self.qpath_res(qpath, expr.hir_id)),
+            hir::ExprKind::AddrOf(kind, mutability, operand) => ExprKind::AddrOf {
+                raw: matches!(kind, hir::BorrowKind::Raw),
+                mutability: mutability.stable(self.tables),
+                expr: Box::new(self.expr(operand)),
+            },
+            hir::ExprKind::Break(_, value) => {
+                ExprKind::Break(value.map(|value| Box::new(self.expr(value))))
+            }
+            hir::ExprKind::Continue(_) => ExprKind::Continue,
+            hir::ExprKind::Ret(value) => {
+                ExprKind::Ret(value.map(|value| Box::new(self.expr(value))))
+            }
+            hir::ExprKind::Struct(qpath, fields, base) => {
+                let fields = fields
+                    .iter()
+                    .map(|field| stable_hir::ExprField {
+                        name: field.ident.to_string(),
+                        expr: self.expr(field.expr),
+                        span: field.span.stable(self.tables),
+                    })
+                    .collect();
+                ExprKind::Struct(
+                    self.qpath_res(qpath, expr.hir_id),
+                    fields,
+                    base.map(|base| Box::new(self.expr(base))),
+                )
+            }
+            hir::ExprKind::Repeat(element, _) => ExprKind::Repeat(Box::new(self.expr(element))),
+            hir::ExprKind::Yield(value, _) => ExprKind::Yield(Box::new(self.expr(value))),
+            hir::ExprKind::ConstBlock(_)
+            | hir::ExprKind::Type(..)
+            | hir::ExprKind::Become(_)
+            | hir::ExprKind::InlineAsm(_)
+            | hir::ExprKind::OffsetOf(..)
+            | hir::ExprKind::Err(_) => ExprKind::Other,
+        };
+        stable_hir::Expr {
+            kind,
+            ty: self.typeck.node_type_opt(expr.hir_id).map(|ty| ty.stable(self.tables)),
+            span: expr.span.stable(self.tables),
+        }
+    }
+
+    fn exprs(&mut self, exprs: &'tcx [hir::Expr<'tcx>]) -> Vec<stable_hir::Expr> {
+        exprs.iter().map(|expr| self.expr(expr)).collect()
+    }
+
+    fn block(&mut self, block: &'tcx hir::Block<'tcx>) -> stable_hir::Block {
+        stable_hir::Block {
+            stmts: block.stmts.iter().map(|stmt| self.stmt(stmt)).collect(),
+            expr: block.expr.map(|expr| Box::new(self.expr(expr))),
+            span: block.span.stable(self.tables),
+        }
+    }
+
+    fn stmt(&mut self, stmt: &'tcx hir::Stmt<'tcx>) -> stable_hir::Stmt {
+        let kind = match stmt.kind {
+            hir::StmtKind::Let(local) => stable_hir::StmtKind::Let(stable_hir::LetStmt {
+                pat: self.pat(local.pat),
+                init: local.init.map(|init| self.expr(init)),
+                els: local.els.map(|els| self.block(els)),
+            }),
+            hir::StmtKind::Item(item) => {
+                stable_hir::StmtKind::Item(self.tables.create_def_id(item.owner_id.to_def_id()))
+            }
+            hir::StmtKind::Expr(expr) => stable_hir::StmtKind::Expr(self.expr(expr)),
+            hir::StmtKind::Semi(expr) => stable_hir::StmtKind::Semi(self.expr(expr)),
+        };
+        stable_hir::Stmt { kind, span: stmt.span.stable(self.tables) }
+    }
+
+    fn arm(&mut self, arm: &'tcx hir::Arm<'tcx>) -> stable_hir::Arm {
+        stable_hir::Arm {
+            pat: self.pat(arm.pat),
+            guard: arm.guard.map(|guard| self.expr(guard)),
+            body: self.expr(arm.body),
+            span: arm.span.stable(self.tables),
+        }
+    }
+
+    fn closure(&mut self, closure: &'tcx hir::Closure<'tcx>) -> stable_hir::Closure {
+        let tcx = self.tables.tcx;
+        let captures = self
+            .typeck
+            .closure_min_captures_flattened(closure.def_id)
+            .map(|capture| stable_hir::Capture {
+                place: capture.to_string(tcx),
+                root: binding_id(capture.get_root_variable()),
+                kind: match capture.info.capture_kind {
+                    ty::UpvarCapture::ByValue => stable_hir::CaptureKind::ByValue,
+                    ty::UpvarCapture::ByRef(kind) => {
+                        stable_hir::CaptureKind::ByRef(kind.to_mutbl_lossy().stable(self.tables))
+                    }
+                },
+                span: capture.get_path_span(tcx).stable(self.tables),
+            })
+            .collect();
+        stable_hir::Closure {
+            def: self.tables.closure_def(closure.def_id.to_def_id()),
+            is_move: matches!(closure.capture_clause, hir::CaptureBy::Value { .. }),
+            body: self.body(tcx.hir().body(closure.body)),
+            fn_decl_span: closure.fn_decl_span.stable(self.tables),
+            captures,
+        }
+    }
+
+    fn pat(&mut self, pat: &'tcx hir::Pat<'tcx>) -> stable_hir::Pat {
+        use stable_hir::PatKind;
+        let kind = match pat.kind {
+            hir::PatKind::Wild => PatKind::Wild,
+            hir::PatKind::Binding(mode, hir_id, ident, sub) => {
+                // Prefer the binding mode after default binding modes have been applied.
+                let hir::BindingMode(by_ref, mutability) =
+                    self.typeck.pat_binding_modes().get(pat.hir_id).copied().unwrap_or(mode);
+                let by_ref = match by_ref {
+                    hir::ByRef::Yes(mutability) => Some(mutability.stable(self.tables)),
+                    hir::ByRef::No => None,
+                };
+                PatKind::Binding {
+                    mode: stable_hir::BindingMode {
+                        by_ref,
+                        mutability: mutability.stable(self.tables),
+                    },
+                    id: binding_id(hir_id),
+                    name: ident.to_string(),
+                    sub: sub.map(|sub| Box::new(self.pat(sub))),
+                }
+            }
+            hir::PatKind::Struct(ref qpath, fields, has_rest) => {
+                let fields = fields
+                    .iter()
+                    .map(|field| stable_hir::PatField {
+                        name: field.ident.to_string(),
+                        pat: self.pat(field.pat),
+                        span: field.span.stable(self.tables),
+                    })
+                    .collect();
+                PatKind::Struct(self.qpath_res(qpath, pat.hir_id), fields, has_rest)
+            }
+            hir::PatKind::TupleStruct(ref qpath, pats, dotdot) => PatKind::TupleStruct(
+                self.qpath_res(qpath, pat.hir_id),
+                self.pats(pats),
+                dotdot.as_opt_usize(),
+            ),
+            hir::PatKind::Or(pats) => PatKind::Or(self.pats(pats)),
+            hir::PatKind::Never => PatKind::Never,
+            hir::PatKind::Path(ref qpath) => PatKind::Path(self.qpath_res(qpath, pat.hir_id)),
+            hir::PatKind::Tuple(pats, dotdot) => {
+                PatKind::Tuple(self.pats(pats), dotdot.as_opt_usize())
+            }
+            hir::PatKind::Box(inner) => PatKind::Box(Box::new(self.pat(inner))),
+            hir::PatKind::Deref(inner) => PatKind::Deref(Box::new(self.pat(inner))),
+            hir::PatKind::Ref(inner, mutability) => {
+                PatKind::Ref(Box::new(self.pat(inner)), mutability.stable(self.tables))
+            }
+            hir::PatKind::Lit(expr) => PatKind::Lit(Box::new(self.expr(expr))),
+            hir::PatKind::Range(start, end, range_end) => PatKind::Range {
+                start: start.map(|start| Box::new(self.expr(start))),
+                end: end.map(|end| Box::new(self.expr(end))),
+                inclusive: matches!(range_end, hir::RangeEnd::Included),
+            },
+            hir::PatKind::Slice(prefix, middle, suffix) => PatKind::Slice {
+                prefix: self.pats(prefix),
+                middle: middle.map(|middle| Box::new(self.pat(middle))),
+                suffix: self.pats(suffix),
+            },
+            hir::PatKind::Err(_) => PatKind::Other,
+        };
+        stable_hir::Pat {
+            kind,
+            ty: self.typeck.node_type_opt(pat.hir_id).map(|ty| ty.stable(self.tables)),
+            span: pat.span.stable(self.tables),
+        }
+    }
+
+    fn pats(&mut self, pats: &'tcx [hir::Pat<'tcx>]) -> Vec<stable_hir::Pat> {
+        pats.iter().map(|pat| self.pat(pat)).collect()
+    }
+
+    fn qpath_res(&mut self, qpath: &hir::QPath<'tcx>, hir_id: hir::HirId) -> stable_hir::Res {
+        match self.typeck.qpath_res(qpath, hir_id) {
+            Res::Def(_, def_id) => stable_hir::Res::Def(self.tables.create_def_id(def_id)),
+            Res::Local(hir_id) => stable_hir::Res::Local(binding_id(hir_id)),
+            _ => stable_hir::Res::Other,
+        }
+    }
+}
+
+fn binding_id(hir_id: hir::HirId) -> BindingId {
+    hir_id.local_id.as_usize()
+}
+
+fn bin_op(op: hir::BinOpKind) -> stable_hir::BinOp {
+    use stable_hir::BinOp;
+    match op {
+        hir::BinOpKind::Add => BinOp::Add,
+        hir::BinOpKind::Sub => BinOp::Sub,
+        hir::BinOpKind::Mul => BinOp::Mul,
+        hir::BinOpKind::Div => BinOp::Div,
+        hir::BinOpKind::Rem => BinOp::Rem,
+        hir::BinOpKind::And => BinOp::And,
+        hir::BinOpKind::Or => BinOp::Or,
+        hir::BinOpKind::BitXor => BinOp::BitXor,
+        hir::BinOpKind::BitAnd => BinOp::BitAnd,
+        hir::BinOpKind::BitOr => BinOp::BitOr,
+        hir::BinOpKind::Shl => BinOp::Shl,
+        hir::BinOpKind::Shr => BinOp::Shr,
+        hir::BinOpKind::Eq => BinOp::Eq,
+        hir::BinOpKind::Lt => BinOp::Lt,
+        hir::BinOpKind::Le => BinOp::Le,
+        hir::BinOpKind::Ne => BinOp::Ne,
+        hir::BinOpKind::Ge => BinOp::Ge,
+        hir::BinOpKind::Gt => BinOp::Gt,
+    }
+}
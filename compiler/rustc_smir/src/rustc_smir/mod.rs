@@ -12,7 +12,9 @@
 use rustc_hir::def::DefKind;
 use rustc_middle::mir;
 use rustc_middle::mir::interpret::AllocId;
+use rustc_middle::mir::mono::MonoItem;
 use rustc_middle::ty::{self, Instance, Ty, TyCtxt};
+use rustc_monomorphize::{UsageMap, collect_mono_item_graph};
 use rustc_span::def_id::{CrateNum, DefId, LOCAL_CRATE};
 use stable_mir::abi::Layout;
 use stable_mir::mir::mono::InstanceDef;
@@ -23,9 +25,13 @@
 use crate::rustc_internal::IndexMap;
 
 mod alloc;
+pub(crate) mod borrowck;
 mod builder;
 pub(crate) mod context;
 mod convert;
+pub(crate) mod hir;
+pub(crate) mod replace;
+pub(crate) mod traits;
 
 pub struct Tables<'tcx> {
     pub(crate) tcx: TyCtxt<'tcx>,
@@ -37,6 +43,8 @@ pub struct Tables<'tcx> {
     pub(crate) ty_consts: IndexMap<ty::Const<'tcx>, TyConstId>,
     pub(crate) mir_consts: IndexMap<mir::Const<'tcx>, MirConstId>,
     pub(crate) layouts: IndexMap<rustc_target::abi::Layout<'tcx>, Layout>,
+    /// The results of the mono item collector, which only runs when they are first requested.
+    pub(crate) mono_item_graph: Option<(Vec<MonoItem<'tcx>>, UsageMap<'tcx>)>,
 }
 
 impl<'tcx> Tables<'tcx> {
@@ -52,6 +60,12 @@ pub(crate) fn intern_mir_const(&mut self, constant: mir::Const<'tcx>) -> MirCons
         self.mir_consts.create_or_fetch(constant)
     }
 
+    /// Return the mono items of the local crate and their usage edges.
+    pub(crate) fn mono_item_graph(&mut self) -> &(Vec<MonoItem<'tcx>>, UsageMap<'tcx>) {
+        let tcx = self.tcx;
+        self.mono_item_graph.get_or_insert_with(|| collect_mono_item_graph(tcx))
+    }
+
     /// Return whether the instance as a body available.
     ///
     /// Items and intrinsics may have a body available from its definition.
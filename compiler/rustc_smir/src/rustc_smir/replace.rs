@@ -0,0 +1,247 @@
+//! Replace the optimized MIR of an item with a body provided by the user.
+//!
+//! The StableMIR body is converted back to its internal representation using the original
+//! body of the item to recover the information that StableMIR doesn't keep, such as source
+//! scopes and cleanup blocks. The result is validated before being handed over to codegen.
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def::DefKind;
+use rustc_index::IndexVec;
+use rustc_middle::mir::{self, UnwindAction, UnwindTerminateReason};
+use rustc_middle::ty;
+use rustc_span::def_id::DefId;
+use stable_mir::Error;
+use stable_mir::mir::visit::{Location, PlaceContext};
+use stable_mir::mir::{
+    BinOp, Body, Local, MirVisitor, Rvalue, Statement, StatementKind, Terminator, TerminatorKind,
+};
+
+use crate::rustc_internal::RustcInternal;
+use crate::rustc_smir::Tables;
+
+/// Replace the optimized MIR of the given item by `body`.
+pub(crate) fn replace_mir_body<'tcx>(
+    tables: &mut Tables<'tcx>,
+    def_id: DefId,
+    body: &Body,
+) -> Result<(), Error> {
+    let tcx = tables.tcx;
+    let Some(local_def_id) = def_id.as_local() else {
+        return Err(Error::new(format!("Item `{def_id:?}` is not defined in the local crate")));
+    };
+    if !matches!(tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn | DefKind::Closure)
+        || !tables.item_has_body(def_id)
+    {
+        return Err(Error::new(format!("Item `{def_id:?}` is not a function with a body")));
+    }
+    if tcx.sess.opts.incremental.is_some() {
+        return Err(Error::new(format!(
+            "Cannot replace the body of `{def_id:?}` in incremental mode"
+        )));
+    }
+
+    let original = tcx.instance_mir(ty::InstanceKind::Item(def_id));
+    check_body(body, original)?;
+    for (local, decl) in body.locals().iter().enumerate().take(original.arg_count + 1) {
+        let local = mir::Local::from_usize(local);
+        if decl.ty.internal(tables, tcx) != original.local_decls[local].ty {
+            return Err(Error::new(format!(
+                "Type of `{local:?}` doesn't match the signature of `{def_id:?}`"
+            )));
+        }
+    }
+
+    let new_body = internal_body(tables, body, original);
+    let failures = rustc_mir_transform::validate_external_body(
+        tcx,
+        &new_body,
+        "after StableMIR body replacement".to_string(),
+    );
+    if let Some((location, msg)) = failures.first() {
+        return Err(Error::new(format!(
+            "Invalid body for `{def_id:?}` at {location:?}: {msg} ({} error(s) found)",
+            failures.len()
+        )));
+    }
+    tcx.replace_optimized_mir(local_def_id, new_body);
+    Ok(())
+}
+
+/// Build the internal body, starting from a copy of the `original` one.
+fn internal_body<'tcx>(
+    tables: &mut Tables<'tcx>,
+    body: &Body,
+    original: &mir::Body<'tcx>,
+) -> mir::Body<'tcx> {
+    let tcx = tables.tcx;
+    let mut blocks: IndexVec<mir::BasicBlock, mir::BasicBlockData<'tcx>> = body
+        .blocks
+        .iter()
+        .map(|block| {
+            let mut data = mir::BasicBlockData::new(Some(block.terminator.internal(tables, tcx)));
+            data.statements = block.statements.internal(tables, tcx);
+            data
+        })
+        .collect();
+    mark_cleanup_blocks(&mut blocks);
+
+    // StableMIR doesn't keep the source scopes, so we recover them from the original body.
+    let scopes: FxHashMap<_, _> = original
+        .basic_blocks
+        .iter()
+        .flat_map(|data| {
+            data.statements
+                .iter()
+                .map(|statement| statement.source_info)
+                .chain(data.terminator.as_ref().map(|terminator| terminator.source_info))
+        })
+        .map(|source_info| (source_info.span, source_info.scope))
+        .collect();
+    for data in blocks.iter_mut() {
+        let source_infos = data
+            .statements
+            .iter_mut()
+            .map(|statement| &mut statement.source_info)
+            .chain(data.terminator.as_mut().map(|terminator| &mut terminator.source_info));
+        for source_info in source_infos {
+            if let Some(scope) = scopes.get(&source_info.span) {
+                source_info.scope = *scope;
+            }
+        }
+    }
+
+    let local_decls = body
+        .locals()
+        .iter()
+        .enumerate()
+        .map(|(local, decl)| {
+            let ty = decl.ty.internal(tables, tcx);
+            let span = decl.span.internal(tables, tcx);
+            let mut new_decl = match original.local_decls.get(mir::Local::from_usize(local)) {
+                Some(original_decl) if original_decl.ty == ty => original_decl.clone(),
+                _ => mir::LocalDecl::new(ty, span),
+            };
+            new_decl.mutability = decl.mutability.internal(tables, tcx);
+            new_decl.source_info.span = span;
+            new_decl
+        })
+        .collect();
+
+    // The remaining information, e.g. the required constants and the mentioned items, is kept
+    // from the original body.
+    let mut new_body = original.clone();
+    *new_body.basic_blocks_mut() = blocks;
+    new_body.local_decls = local_decls;
+    new_body.var_debug_info = body.var_debug_info.internal(tables, tcx);
+    new_body.spread_arg = body.spread_arg().map(mir::Local::from_usize);
+    new_body
+}
+
+/// StableMIR doesn't keep track of cleanup blocks. Instead, we infer that a block is a cleanup
+/// block if it is reachable from an unwind edge.
+fn mark_cleanup_blocks(blocks: &mut IndexVec<mir::BasicBlock, mir::BasicBlockData<'_>>) {
+    let mut worklist: Vec<_> = blocks
+        .iter()
+        .filter_map(|data| match data.terminator().unwind() {
+            Some(UnwindAction::Cleanup(target)) => Some(*target),
+            _ => None,
+        })
+        .collect();
+    while let Some(bb) = worklist.pop() {
+        if !blocks[bb].is_cleanup {
+            blocks[bb].is_cleanup = true;
+            worklist.extend(blocks[bb].terminator().successors());
+        }
+    }
+
+    // Terminating the program from a cleanup block is due to a panic while unwinding.
+    for data in blocks.iter_mut().filter(|data| data.is_cleanup) {
+        let terminator = data.terminator_mut();
+        if let mir::TerminatorKind::UnwindTerminate(reason) = &mut terminator.kind {
+            *reason = UnwindTerminateReason::InCleanup;
+        }
+        if let Some(UnwindAction::Terminate(reason)) = terminator.unwind_mut() {
+            *reason = UnwindTerminateReason::InCleanup;
+        }
+    }
+}
+
+/// Check that `body` can be converted to a body that replaces `original`.
+fn check_body(body: &Body, original: &mir::Body<'_>) -> Result<(), Error> {
+    if body.arg_locals().len() != original.arg_count {
+        return Err(Error::new(format!(
+            "Expected {} arguments, but body has {}",
+            original.arg_count,
+            body.arg_locals().len()
+        )));
+    }
+    if body.blocks.is_empty() {
+        return Err(Error::new("Body must have at least one basic block".to_string()));
+    }
+    let scopes = original.source_scopes.len();
+    if let Some(info) =
+        body.var_debug_info.iter().find(|info| info.source_info.scope as usize >= scopes)
+    {
+        return Err(Error::new(format!("Invalid source scope in debug info of `{}`", info.name)));
+    }
+    let mut checker =
+        BodyChecker { blocks: body.blocks.len(), locals: body.locals().len(), error: None };
+    checker.visit_body(body);
+    checker.error.map_or(Ok(()), Err)
+}
+
+/// Looks for constructs that cannot be converted back to internal MIR, as well as references
+/// to basic blocks and locals that don't exist. The conversion panics on all of them, so they
+/// must be rejected before.
+struct BodyChecker {
+    blocks: usize,
+    locals: usize,
+    error: Option<Error>,
+}
+
+impl BodyChecker {
+    fn fail(&mut self, msg: String) {
+        if self.error.is_none() {
+            self.error = Some(Error::new(msg));
+        }
+    }
+}
+
+impl MirVisitor for BodyChecker {
+    fn visit_statement(&mut self, stmt: &Statement, location: Location) {
+        match &stmt.kind {
+            StatementKind::FakeRead(..)
+            | StatementKind::AscribeUserType { .. }
+            | StatementKind::Coverage(_) => {
+                self.fail(format!("Unsupported statement: {:?}", stmt.kind));
+            }
+            _ => {}
+        }
+        self.super_statement(stmt, location)
+    }
+
+    fn visit_rvalue(&mut self, rvalue: &Rvalue, location: Location) {
+        if let Rvalue::CheckedBinaryOp(op, ..) = rvalue {
+            if !matches!(op, BinOp::Add | BinOp::Sub | BinOp::Mul) {
+                self.fail(format!("Unsupported checked binary operation: `{op:?}`"));
+            }
+        }
+        self.super_rvalue(rvalue, location)
+    }
+
+    fn visit_terminator(&mut self, term: &Terminator, location: Location) {
+        if let TerminatorKind::InlineAsm { .. } = term.kind {
+            self.fail("Unsupported terminator: `InlineAsm`".to_string());
+        }
+        if let Some(bb) = term.successors().into_iter().find(|bb| *bb >= self.blocks) {
+            self.fail(format!("Jump to invalid basic block `bb{bb}`"));
+        }
+        self.super_terminator(term, location)
+    }
+
+    fn visit_local(&mut self, local: &Local, _ptx: PlaceContext, _location: Location) {
+        if *local >= self.locals {
+            self.fail(format!("Local `_{local}` is not declared"));
+        }
+    }
+}
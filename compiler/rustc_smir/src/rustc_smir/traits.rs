@@ -0,0 +1,131 @@
+//! Answer trait solving and normalization queries about StableMIR types.
+
+use rustc_hir::def::DefKind;
+use rustc_middle::ty::{self, TypeVisitableExt};
+use rustc_span::symbol::Ident;
+use rustc_trait_selection::infer::TyCtxtInferExt;
+use rustc_trait_selection::traits::query::evaluate_obligation::InferCtxtExt;
+use rustc_trait_selection::traits::{Obligation, ObligationCause};
+use stable_mir::Error;
+use stable_mir::ty::{ParamEnv, TraitRef, Ty};
+
+use crate::rustc_internal::RustcInternal;
+use crate::rustc_smir::{Stable, Tables};
+
+/// Check whether `trait_ref` holds modulo regions in the given environment.
+pub(crate) fn implements_trait<'tcx>(
+    tables: &mut Tables<'tcx>,
+    trait_ref: &TraitRef,
+    env: ParamEnv,
+) -> Result<bool, Error> {
+    let param_env = internal_param_env(tables, env)?;
+    let trait_ref = internal_trait_ref(tables, trait_ref)?;
+    Ok(holds(tables, trait_ref, param_env))
+}
+
+/// Normalize `ty` in the given environment, erasing its regions.
+pub(crate) fn normalize<'tcx>(
+    tables: &mut Tables<'tcx>,
+    ty: Ty,
+    env: ParamEnv,
+) -> Result<Ty, Error> {
+    let tcx = tables.tcx;
+    let param_env = internal_param_env(tables, env)?;
+    let ty = ty.internal(tables, tcx);
+    if ty.has_escaping_bound_vars() {
+        return Err(Error::new(format!("Cannot normalize `{ty}`, which has escaping bound vars")));
+    }
+    tcx.try_normalize_erasing_regions(param_env, ty)
+        .map(|ty| ty.stable(tables))
+        .map_err(|err| Error::new(format!("Failed to normalize `{ty}`: {err:?}")))
+}
+
+/// Normalize the associated type `name` of `trait_ref` in the given environment.
+pub(crate) fn type_of_projection<'tcx>(
+    tables: &mut Tables<'tcx>,
+    trait_ref: &TraitRef,
+    name: &str,
+    env: ParamEnv,
+) -> Result<Ty, Error> {
+    let tcx = tables.tcx;
+    let param_env = internal_param_env(tables, env)?;
+    let trait_ref = internal_trait_ref(tables, trait_ref)?;
+    let Some(assoc) = tcx.associated_items(trait_ref.def_id).find_by_name_and_kind(
+        tcx,
+        Ident::from_str(name),
+        ty::AssocKind::Type,
+        trait_ref.def_id,
+    ) else {
+        return Err(Error::new(format!("Trait `{trait_ref}` has no associated type `{name}`")));
+    };
+    if !tcx.generics_of(assoc.def_id).own_params.is_empty() {
+        return Err(Error::new(format!(
+            "Associated type `{name}` of `{trait_ref}` has generic parameters"
+        )));
+    }
+    if !holds(tables, trait_ref, param_env) {
+        return Err(Error::new(format!("Trait `{trait_ref}` is not implemented")));
+    }
+    let projection = ty::Ty::new_projection_from_args(tcx, assoc.def_id, trait_ref.args);
+    tcx.try_normalize_erasing_regions(param_env, projection)
+        .map(|ty| ty.stable(tables))
+        .map_err(|err| Error::new(format!("Failed to normalize `{projection}`: {err:?}")))
+}
+
+fn holds<'tcx>(
+    tables: &Tables<'tcx>,
+    trait_ref: ty::TraitRef<'tcx>,
+    param_env: ty::ParamEnv<'tcx>,
+) -> bool {
+    let tcx = tables.tcx;
+    let infcx = tcx.infer_ctxt().build();
+    let obligation = Obligation::new(tcx, ObligationCause::dummy(), param_env, trait_ref);
+    infcx.predicate_must_hold_modulo_regions(&obligation)
+}
+
+/// Convert the trait reference, checking that its arguments match the trait generics, which
+/// the compiler would otherwise assume.
+fn internal_trait_ref<'tcx>(
+    tables: &mut Tables<'tcx>,
+    trait_ref: &TraitRef,
+) -> Result<ty::TraitRef<'tcx>, Error> {
+    let tcx = tables.tcx;
+    let def_id = trait_ref.def_id.0.internal(tables, tcx);
+    let args = trait_ref.args().internal(tables, tcx);
+    if !tcx.check_args_compatible(def_id, args) {
+        return Err(Error::new(format!(
+            "Arguments `{args:?}` don't match the generics of `{}`",
+            tcx.def_path_str(def_id)
+        )));
+    }
+    let trait_ref = ty::TraitRef::new_from_args(tcx, def_id, args);
+    if trait_ref.has_escaping_bound_vars() {
+        return Err(Error::new(format!("Trait `{trait_ref}` has escaping bound vars")));
+    }
+    Ok(trait_ref)
+}
+
+fn internal_param_env<'tcx>(
+    tables: &mut Tables<'tcx>,
+    env: ParamEnv,
+) -> Result<ty::ParamEnv<'tcx>, Error> {
+    let tcx = tables.tcx;
+    match env {
+        ParamEnv::Monomorphic => Ok(ty::ParamEnv::reveal_all()),
+        ParamEnv::Item(def) => {
+            let def_id = def.internal(tables, tcx);
+            match tcx.def_kind(def_id) {
+                DefKind::Mod
+                | DefKind::Macro(_)
+                | DefKind::ExternCrate
+                | DefKind::Use
+                | DefKind::ForeignMod
+                | DefKind::GlobalAsm => Err(Error::new(format!(
+                    "Item `{}` doesn't have a parameter environment",
+                    tcx.def_path_str(def_id)
+                ))),
+                _ => Ok(tcx.param_env(def_id)),
+            }
+        }
+    }
+}
@@ -92,6 +92,9 @@ pub(super) fn mangle<'tcx>(
                 .write_str(if receiver_by_ref { "{{by-move-shim}}" } else { "{{by-ref-shim}}" })
                 .unwrap();
         }
+        ty::InstanceKind::ConstArgShim(..) => {
+            printer.write_str("{{const-arg-shim}}").unwrap();
+        }
         _ => {}
     }
 
@@ -151,6 +154,11 @@ fn get_symbol_hash<'tcx>(
             // Especially, `VTableShim`s and `ReifyShim`s may overlap with their original
             // instances without this.
             discriminant(&instance.def).hash_stable(hcx, &mut hasher);
+
+            // Each set of constant arguments gets its own clone of the function.
+            if let ty::InstanceKind::ConstArgShim(_, const_args) = instance.def {
+                const_args.hash_stable(hcx, &mut hasher);
+            }
         });
 
         // 64 bits should be enough to avoid collisions.
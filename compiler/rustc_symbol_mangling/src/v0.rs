@@ -5,6 +5,7 @@
 use rustc_data_structures::base_n::ToBaseN;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_data_structures::intern::Interned;
+use rustc_data_structures::stable_hasher::{Hash64, HashStable, StableHasher};
 use rustc_hir as hir;
 use rustc_hir::def::CtorKind;
 use rustc_hir::def_id::{CrateNum, DefId};
@@ -57,11 +58,25 @@ pub(super) fn mangle<'tcx>(
             Some("by_ref")
         }
 
+        ty::InstanceKind::ConstArgShim(..) => Some("const_args"),
+
         _ => None,
     };
 
+    // Clones of a function for different constant arguments are distinguished by a hash of these
+    // arguments.
+    let shim_disambiguator = match instance.def {
+        ty::InstanceKind::ConstArgShim(_, const_args) => tcx.with_stable_hashing_context(|mut hcx| {
+            let mut hasher = StableHasher::new();
+            const_args.hash_stable(&mut hcx, &mut hasher);
+            hasher.finish::<Hash64>().as_u64()
+        }),
+        _ => 0,
+    };
+
     if let Some(shim_kind) = shim_kind {
-        cx.path_append_ns(|cx| cx.print_def_path(def_id, args), 'S', 0, shim_kind).unwrap()
+        cx.path_append_ns(|cx| cx.print_def_path(def_id, args), 'S', shim_disambiguator, shim_kind)
+            .unwrap()
     } else {
         cx.print_def_path(def_id, args).unwrap()
     };
@@ -8,18 +8,19 @@
 use crate::abi::{FnAbi, Layout, LayoutShape};
 use crate::crate_def::Attribute;
 use crate::mir::alloc::{AllocId, GlobalAlloc};
-use crate::mir::mono::{Instance, InstanceDef, StaticDef};
+use crate::mir::borrowck::BorrowckFacts;
+use crate::mir::mono::{Instance, InstanceDef, MonoItem, StaticDef};
 use crate::mir::{BinOp, Body, Place, UnOp};
 use crate::target::MachineInfo;
 use crate::ty::{
-    AdtDef, AdtKind, Allocation, ClosureDef, ClosureKind, FieldDef, FnDef, ForeignDef,
+    AdtDef, AdtKind, Allocation, ClosureDef, ClosureKind, ExpnData, FieldDef, FnDef, ForeignDef,
     ForeignItemKind, ForeignModule, ForeignModuleDef, GenericArgs, GenericPredicates, Generics,
-    ImplDef, ImplTrait, IntrinsicDef, LineInfo, MirConst, PolyFnSig, RigidTy, Span, TraitDecl,
-    TraitDef, Ty, TyConst, TyConstId, TyKind, UintTy, VariantDef,
+    ImplDef, ImplTrait, IntrinsicDef, LineInfo, MirConst, ParamEnv, PolyFnSig, RigidTy, Span,
+    TraitDecl, TraitDef, TraitRef, Ty, TyConst, TyConstId, TyKind, UintTy, VariantDef,
 };
 use crate::{
     Crate, CrateItem, CrateItems, CrateNum, DefId, Error, Filename, ImplTraitDecls, ItemKind,
-    Symbol, TraitDecls, mir,
+    Symbol, TraitDecls, hir, mir,
 };
 
 /// This trait defines the interface between stable_mir and the Rust compiler.
@@ -33,6 +34,23 @@ pub trait Context {
     fn mir_body(&self, item: DefId) -> mir::Body;
     /// Check whether the body of a function is available.
     fn has_body(&self, item: DefId) -> bool;
+    /// Retrieve the HIR body of a local item.
+    fn hir_body(&self, item: DefId) -> Result<hir::Body, Error>;
+    /// Retrieve the borrow checker results for the body of a local function.
+    fn borrowck_facts(&self, item: DefId) -> Result<BorrowckFacts, Error>;
+    /// Replace the body of a local function that will be used for code generation.
+    fn replace_mir_body(&self, item: DefId, body: &Body) -> Result<(), Error>;
+    /// Check whether a trait reference holds in the given environment.
+    fn implements_trait(&self, trait_ref: &TraitRef, env: ParamEnv) -> Result<bool, Error>;
+    /// Normalize the associated type projections of a type in the given environment.
+    fn normalize(&self, ty: Ty, env: ParamEnv) -> Result<Ty, Error>;
+    /// Normalize the associated type `name` of a trait reference in the given environment.
+    fn type_of_projection(
+        &self,
+        trait_ref: &TraitRef,
+        name: &str,
+        env: ParamEnv,
+    ) -> Result<Ty, Error>;
     fn foreign_modules(&self, crate_num: CrateNum) -> Vec<ForeignModuleDef>;
     fn foreign_module(&self, mod_def: ForeignModuleDef) -> ForeignModule;
     fn foreign_items(&self, mod_def: ForeignModuleDef) -> Vec<ForeignDef>;
@@ -74,6 +92,9 @@ pub trait Context {
     /// Return lines corresponding to this `Span`
     fn get_lines(&self, span: &Span) -> LineInfo;
 
+    /// Return the macro expansions that produced this `Span`, innermost first.
+    fn span_macro_backtrace(&self, span: &Span) -> Vec<ExpnData>;
+
     /// Returns the `kind` of given `DefId`
     fn item_kind(&self, item: CrateItem) -> ItemKind;
 
@@ -188,6 +209,12 @@ pub trait Context {
     /// Item requires monomorphization.
     fn requires_monomorphization(&self, def_id: DefId) -> bool;
 
+    /// Collect the mono items of the local crate.
+    fn all_mono_items(&self) -> Vec<MonoItem>;
+
+    /// Get the mono items used by the given mono item.
+    fn mono_item_uses(&self, item: &MonoItem) -> Vec<MonoItem>;
+
     /// Resolve an instance from the given function definition and generic arguments.
     fn resolve_instance(&self, def: FnDef, args: &GenericArgs) -> Option<Instance>;
 
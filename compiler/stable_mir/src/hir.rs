@@ -0,0 +1,299 @@
+//! This module provides a read-only view of the bodies of local items before they are lowered
+//! to MIR.
+//!
+//! The representation follows the source code closely, which makes it a better fit than MIR
+//! for analyses that need to report results in terms of expressions, patterns, and their spans.
+//! Syntactic sugar, such as `for` loops and the `?` operator, is still desugared, but the
+//! desugaring is recorded in [MatchSource] and [LoopSource], and in the macro backtrace of the
+//! spans involved, see [crate::ty::Span::macro_backtrace].
+//!
+//! Constructs that are not represented in StableMIR yet are reported as [ExprKind::Other] and
+//! [PatKind::Other].
+
+use serde::{Deserialize, Serialize};
+
+use crate::mir::Mutability;
+use crate::ty::{ClosureDef, Span, Ty};
+use crate::{DefId, Symbol};
+
+/// Identifies a binding in the body of an item.
+///
+/// Closures share the identifiers of their parent body, so a binding captured by a closure has
+/// the same identifier inside and outside of the closure.
+pub type BindingId = usize;
+
+/// The body of a function, constant, or static.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Body {
+    pub params: Vec<Param>,
+    pub value: Expr,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Param {
+    pub pat: Pat,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Expr {
+    pub kind: ExprKind,
+    /// The type of this expression before any adjustment (e.g. auto-deref) is applied.
+    pub ty: Option<Ty>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ExprKind {
+    /// An array, e.g., `[a, b, c]`.
+    Array(Vec<Expr>),
+    /// A function call, where the callee can be any expression.
+    Call(Box<Expr>, Vec<Expr>),
+    /// A method call, e.g., `x.foo(a, b)`.
+    ///
+    /// The method definition is only missing if type checking failed.
+    MethodCall {
+        name: Symbol,
+        def: Option<DefId>,
+        receiver: Box<Expr>,
+        args: Vec<Expr>,
+    },
+    Tup(Vec<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Unary(UnOp, Box<Expr>),
+    /// A literal, in its source representation, e.g., `1u8` or `"hello"`.
+    Lit(String),
+    /// A cast, e.g., `x as u64`. The target type is the type of this expression.
+    Cast(Box<Expr>),
+    /// A `let` expression in the condition of an `if` or `while`.
+    Let(Box<Pat>, Box<Expr>),
+    If(Box<Expr>, Box<Expr>, Option<Box<Expr>>),
+    /// A loop, which includes desugared `while` and `for` loops.
+    Loop(Block, LoopSource),
+    Match(Box<Expr>, Vec<Arm>, MatchSource),
+    Closure(Box<Closure>),
+    Block(Block),
+    Assign(Box<Expr>, Box<Expr>),
+    AssignOp(BinOp, Box<Expr>, Box<Expr>),
+    Field(Box<Expr>, Symbol),
+    Index(Box<Expr>, Box<Expr>),
+    Path(Res),
+    /// A reference, e.g., `&x`, or a raw borrow, e.g., `&raw const x`.
+    AddrOf {
+        raw: bool,
+        mutability: Mutability,
+        expr: Box<Expr>,
+    },
+    Break(Option<Box<Expr>>),
+    Continue,
+    Ret(Option<Box<Expr>>),
+    /// A struct or enum variant literal, e.g., `Foo { x, ..base }`.
+    Struct(Res, Vec<ExprField>, Option<Box<Expr>>),
+    /// An array built by repeating an element, e.g., `[x; N]`.
+    Repeat(Box<Expr>),
+    Yield(Box<Expr>),
+    /// A construct that is not supported yet, e.g., inline assembly.
+    Other,
+}
+
+/// What a path refers to.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Res {
+    /// An item, e.g., a function, a constant, or a tuple struct constructor.
+    Def(DefId),
+    /// A local binding.
+    Local(BindingId),
+    /// A primitive type, `Self`, or a path that failed to resolve.
+    Other,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ExprField {
+    pub name: Symbol,
+    pub expr: Expr,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Block {
+    pub stmts: Vec<Stmt>,
+    /// The trailing expression of the block, if any.
+    pub expr: Option<Box<Expr>>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum StmtKind {
+    /// A local declaration, e.g., `let x = 1;`.
+    Let(LetStmt),
+    /// A nested item.
+    Item(DefId),
+    /// An expression without a trailing semicolon.
+    Expr(Expr),
+    /// An expression with a trailing semicolon.
+    Semi(Expr),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LetStmt {
+    pub pat: Pat,
+    pub init: Option<Expr>,
+    /// The `else` block of a `let ... else` statement.
+    pub els: Option<Block>,
+}
+
+/// A match arm, e.g., `Some(x) if x > 0 => x`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Arm {
+    pub pat: Pat,
+    pub guard: Option<Expr>,
+    pub body: Expr,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Closure {
+    pub def: ClosureDef,
+    /// Whether the closure was declared with the `move` keyword.
+    pub is_move: bool,
+    pub body: Body,
+    /// The span of the closure header, e.g., `move |x|`.
+    pub fn_decl_span: Span,
+    /// The places captured by this closure. This is only empty if type checking failed, or if
+    /// the closure doesn't capture anything.
+    pub captures: Vec<Capture>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Capture {
+    /// The captured place, e.g., `x.field`.
+    pub place: String,
+    /// The binding from which the captured place originates.
+    pub root: BindingId,
+    pub kind: CaptureKind,
+    /// The span of the use that required this capture.
+    pub span: Span,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum CaptureKind {
+    ByValue,
+    /// A capture by reference. Unique immutable borrows are reported as mutable borrows.
+    ByRef(Mutability),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Pat {
+    pub kind: PatKind,
+    pub ty: Option<Ty>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum PatKind {
+    Wild,
+    /// A binding, e.g., `ref mut x @ Some(_)`.
+    Binding {
+        mode: BindingMode,
+        id: BindingId,
+        name: Symbol,
+        sub: Option<Box<Pat>>,
+    },
+    /// A struct or enum variant pattern. The last field is `true` if the pattern has `..`.
+    Struct(Res, Vec<PatField>, bool),
+    /// A tuple struct or enum variant pattern. The last field is the position of `..`, if any.
+    TupleStruct(Res, Vec<Pat>, Option<usize>),
+    Or(Vec<Pat>),
+    /// The never pattern `!`.
+    Never,
+    /// A path pattern, e.g., a unit struct or a constant.
+    Path(Res),
+    /// A tuple pattern. The last field is the position of `..`, if any.
+    Tuple(Vec<Pat>, Option<usize>),
+    Box(Box<Pat>),
+    Deref(Box<Pat>),
+    Ref(Box<Pat>, Mutability),
+    Lit(Box<Expr>),
+    Range {
+        start: Option<Box<Expr>>,
+        end: Option<Box<Expr>>,
+        inclusive: bool,
+    },
+    Slice {
+        prefix: Vec<Pat>,
+        middle: Option<Box<Pat>>,
+        suffix: Vec<Pat>,
+    },
+    /// A pattern that failed to be lowered.
+    Other,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PatField {
+    pub name: Symbol,
+    pub pat: Pat,
+    pub span: Span,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct BindingMode {
+    /// The mutability of the reference for `ref` bindings.
+    pub by_ref: Option<Mutability>,
+    pub mutability: Mutability,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    /// The lazy boolean operator `&&`.
+    And,
+    /// The lazy boolean operator `||`.
+    Or,
+    BitXor,
+    BitAnd,
+    BitOr,
+    Shl,
+    Shr,
+    Eq,
+    Lt,
+    Le,
+    Ne,
+    Ge,
+    Gt,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum UnOp {
+    Deref,
+    Not,
+    Neg,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum LoopSource {
+    Loop,
+    While,
+    ForLoop,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum MatchSource {
+    Normal,
+    /// A postfix match, e.g., `x.match { ... }`.
+    Postfix,
+    ForLoopDesugar,
+    /// The desugaring of the `?` operator.
+    TryDesugar,
+    AwaitDesugar,
+    FormatArgs,
+}
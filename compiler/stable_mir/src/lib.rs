@@ -20,11 +20,12 @@
 use std::fmt::Debug;
 use std::{fmt, io};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::compiler_interface::with;
 pub use crate::crate_def::{CrateDef, CrateDefType, DefId};
 pub use crate::error::*;
+use crate::mir::mono::MonoItem;
 use crate::mir::{Body, Mutability};
 use crate::ty::{ForeignModuleDef, ImplDef, IndexedVal, Span, TraitDef, Ty};
 
@@ -34,7 +35,9 @@
 pub mod compiler_interface;
 #[macro_use]
 pub mod error;
+pub mod hir;
 pub mod mir;
+pub mod snapshot;
 pub mod target;
 pub mod ty;
 pub mod visitor;
@@ -74,7 +77,7 @@ fn to_index(&self) -> usize {
 pub type ImplTraitDecls = Vec<ImplDef>;
 
 /// Holds information about a crate.
-#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct Crate {
     pub id: CrateNum,
     pub name: Symbol,
@@ -98,7 +101,7 @@ pub fn trait_impls(&self) -> ImplTraitDecls {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, Serialize)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 pub enum ItemKind {
     Fn,
     Static,
@@ -106,7 +109,7 @@ pub enum ItemKind {
     Ctor(CtorKind),
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, Serialize)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 pub enum CtorKind {
     Const,
     Fn,
@@ -116,7 +119,7 @@ pub enum CtorKind {
 
 crate_def_with_ty! {
     /// Holds information about an item in a crate.
-    #[derive(Serialize)]
+    #[derive(Serialize, Deserialize)]
     pub CrateItem;
 }
 
@@ -128,6 +131,35 @@ pub fn body(&self) -> mir::Body {
         with(|cx| cx.mir_body(self.0))
     }
 
+    /// Return the HIR body of this item, which is closer to the source code than its MIR body.
+    ///
+    /// This will return an error if the item is not local or doesn't have a body.
+    pub fn hir_body(&self) -> Result<hir::Body, Error> {
+        with(|cx| cx.hir_body(self.0))
+    }
+
+    /// Return the borrow checker results for the body of this item.
+    ///
+    /// This will return an error if the item is not local, or if its results are no longer
+    /// available. See `rustc_internal::retain_borrowck_facts` for more details.
+    pub fn borrowck_facts(&self) -> Result<mir::borrowck::BorrowckFacts, Error> {
+        with(|cx| cx.borrowck_facts(self.0))
+    }
+
+    /// Replace the body of this item by the given one.
+    ///
+    /// The new body is used for monomorphization and code generation of the current crate,
+    /// so the item must be a function defined in the local crate, and its replacement must
+    /// happen before code generation starts. Bodies that have already been optimized, e.g.,
+    /// where this item was inlined, will not observe the new body.
+    ///
+    /// An error is returned if the body has a different signature, contains constructs that
+    /// are not fully represented in StableMIR (e.g., inline assembly), or fails MIR validation.
+    /// Replacing bodies is not supported in incremental mode.
+    pub fn replace_body(&self, body: mir::Body) -> Result<(), Error> {
+        with(|cx| cx.replace_mir_body(self.0, &body))
+    }
+
     pub fn span(&self) -> Span {
         with(|cx| cx.span_of_an_item(self.0))
     }
@@ -180,6 +212,25 @@ pub fn all_local_items() -> CrateItems {
     with(|cx| cx.all_local_items())
 }
 
+/// Retrieve the mono items that the compiler collects for code generation of the local crate.
+///
+/// The same collection strategy as code generation is used, so only items that are reachable
+/// from the crate roots are included, unless eager collection was requested, e.g., with
+/// `-Clink-dead-code`. The collector runs when this is first called, which may trigger
+/// post-monomorphization errors, such as constant evaluation failures.
+pub fn all_mono_items() -> Vec<MonoItem> {
+    with(|cx| cx.all_mono_items())
+}
+
+/// Retrieve the mono items used by the given one, e.g., the functions it calls, the statics it
+/// refers to, and the drop glue it requires.
+///
+/// This includes items that end up being inlined into the given one. The result is empty for
+/// items that are not part of [all_mono_items].
+pub fn mono_item_uses(item: &MonoItem) -> Vec<MonoItem> {
+    with(|cx| cx.mono_item_uses(item))
+}
+
 pub fn all_trait_decls() -> TraitDecls {
     with(|cx| cx.all_trait_decls())
 }
@@ -189,7 +240,7 @@ pub fn all_trait_impls() -> ImplTraitDecls {
 }
 
 /// A type that provides internal information but that can still be used for debug purpose.
-#[derive(Clone, PartialEq, Eq, Hash, Serialize)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Opaque(String);
 
 impl std::fmt::Display for Opaque {
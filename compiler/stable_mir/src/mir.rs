@@ -1,5 +1,7 @@
 pub mod alloc;
 mod body;
+pub mod borrowck;
+pub mod dataflow;
 pub mod mono;
 pub mod pretty;
 pub mod visit;
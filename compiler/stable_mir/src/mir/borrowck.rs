@@ -0,0 +1,71 @@
+//! This module provides the results of the borrow checker for a function body.
+//!
+//! The borrow checker runs on a version of the body that has not been optimized yet.
+//! Thus, all the locations in [BorrowckFacts] refer to [BorrowckFacts::body], and not to
+//! the body returned by [crate::CrateItem::body].
+
+use serde::{Deserialize, Serialize};
+
+use crate::Opaque;
+use crate::mir::{Body, BodyLocation, BorrowKind, Place};
+use crate::ty::{RegionVid, Span};
+
+/// The index of a borrow in [BorrowckFacts::borrows].
+pub type BorrowIndex = usize;
+
+/// The borrow checker results for a body.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BorrowckFacts {
+    /// The body that was borrow checked.
+    ///
+    /// Its regions have been replaced by region inference variables, see [RegionKind::ReVar].
+    ///
+    /// [RegionKind::ReVar]: crate::ty::RegionKind::ReVar
+    pub body: Body,
+    /// The borrows that occur in `body`, indexed by [BorrowIndex].
+    pub borrows: Vec<BorrowData>,
+    /// The outlives constraints that were used to infer the region variables.
+    pub outlives_constraints: Vec<OutlivesConstraint>,
+    /// The locations contained in each region variable after inference, indexed by [RegionVid].
+    pub region_values: Vec<Vec<BodyLocation>>,
+    /// The borrows that are live right before each location of `body` executes.
+    pub live_loans: Vec<(BodyLocation, Vec<BorrowIndex>)>,
+}
+
+impl BorrowckFacts {
+    /// The borrows that are live right before the given location executes.
+    pub fn live_loans_at(&self, location: BodyLocation) -> &[BorrowIndex] {
+        self.live_loans
+            .binary_search_by_key(&location, |(loc, _)| *loc)
+            .map_or(&[], |idx| &self.live_loans[idx].1)
+    }
+}
+
+/// A borrow that occurs in the body, i.e., an assignment of the form `_1 = &_2`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BorrowData {
+    /// Location where the borrow reservation starts.
+    pub reserve_location: BodyLocation,
+    /// Location where a two-phase borrow is activated, if there's one.
+    pub activation_location: Option<BodyLocation>,
+    pub kind: BorrowKind,
+    /// The region for which this borrow is live.
+    pub region: RegionVid,
+    /// The place being borrowed.
+    pub borrowed_place: Place,
+    /// The place the reference is stored into.
+    pub assigned_place: Place,
+}
+
+/// A constraint that requires region `sup` to outlive region `sub`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OutlivesConstraint {
+    pub sup: RegionVid,
+    pub sub: RegionVid,
+    /// The location where this constraint must hold.
+    /// If `None`, the constraint must hold at all locations.
+    pub location: Option<BodyLocation>,
+    pub span: Span,
+    /// What caused this constraint.
+    pub category: Opaque,
+}
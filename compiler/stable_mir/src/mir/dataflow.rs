@@ -0,0 +1,449 @@
+//! # Dataflow analysis over StableMIR
+//!
+//! This module provides a generic fixpoint engine for dataflow analyses over a [Body], similar
+//! to the one in `rustc_mir_dataflow`, as well as a few standard analyses built on top of it:
+//! [MaybeLiveLocals], [MaybeInitializedLocals], [MaybeUninitializedLocals] and
+//! [MaybeBorrowedLocals].
+//!
+//! To implement a new analysis, implement the [Analysis] trait and call
+//! [Analysis::iterate_to_fixpoint]. Transfer functions are usually written as a [MirVisitor]
+//! that inspects the [PlaceContext] of every place that is accessed by a statement or terminator.
+//!
+//! [MirVisitor]: crate::mir::MirVisitor
+//! [PlaceContext]: crate::mir::visit::PlaceContext
+
+use std::collections::VecDeque;
+use std::fmt::{self, Debug, Formatter};
+
+use crate::mir::{BasicBlockIdx, Body, BodyLocation, Place, Statement, Terminator, TerminatorKind};
+
+mod impls;
+
+pub use impls::{
+    MaybeBorrowedLocals, MaybeInitializedLocals, MaybeLiveLocals, MaybeUninitializedLocals,
+};
+
+/// A lattice with a join operation, which is used to combine the states of different paths.
+pub trait JoinSemiLattice: Eq {
+    /// Computes the least upper bound of `self` and `other`, and stores it in `self`.
+    ///
+    /// Returns `true` if `self` has changed.
+    fn join(&mut self, other: &Self) -> bool;
+}
+
+/// The direction in which the analysis propagates information.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// From the start of the body towards its exits, e.g., initialization.
+    Forward,
+    /// From the exits of the body towards its start, e.g., liveness.
+    Backward,
+}
+
+/// A dataflow analysis.
+///
+/// The effects of each statement and terminator are always applied in the direction of the
+/// analysis. For a backward analysis, the state passed to the transfer functions is the state
+/// right after the statement or terminator executes, and it should be updated to the state right
+/// before it executes.
+pub trait Analysis {
+    /// The type that holds the dataflow state at any given point of the body.
+    type Domain: Clone + JoinSemiLattice;
+
+    /// The direction of this analysis.
+    const DIRECTION: Direction;
+
+    /// The initial value of the state of every basic block, which should be the bottom value of
+    /// the lattice.
+    fn bottom_value(&self, body: &Body) -> Self::Domain;
+
+    /// Mutates the state of the start block to reflect the state on entry to the body,
+    /// e.g., that function arguments are initialized.
+    ///
+    /// This is only used by forward analyses.
+    fn initialize_start_block(&self, body: &Body, state: &mut Self::Domain);
+
+    /// Updates the state with the effect of a statement.
+    fn apply_statement_effect(
+        &mut self,
+        state: &mut Self::Domain,
+        statement: &Statement,
+        location: BodyLocation,
+    );
+
+    /// Updates the state with the effect of a terminator.
+    ///
+    /// The effect of writing the destination of a call when the call returns successfully
+    /// should be applied in [Analysis::apply_call_return_effect] instead.
+    fn apply_terminator_effect(
+        &mut self,
+        state: &mut Self::Domain,
+        terminator: &Terminator,
+        location: BodyLocation,
+    );
+
+    /// Updates the state with the effect of a call returning successfully to its target block.
+    ///
+    /// This is only applied to the edge between the call and its target, not to its unwind
+    /// edge.
+    fn apply_call_return_effect(
+        &mut self,
+        state: &mut Self::Domain,
+        destination: &Place,
+        location: BodyLocation,
+    ) {
+        let _ = (state, destination, location);
+    }
+
+    /// Computes the fixpoint of this analysis for the given body.
+    fn iterate_to_fixpoint(self, body: &Body) -> Results<Self>
+    where
+        Self: Sized,
+    {
+        Engine::new(body, self).iterate_to_fixpoint()
+    }
+}
+
+/// The result of a dataflow analysis.
+///
+/// It stores the state at the entry of every basic block, i.e., at its start for forward
+/// analyses, and at its end for backward analyses. The state at any other location is
+/// recomputed from there on demand.
+pub struct Results<A: Analysis> {
+    pub analysis: A,
+    entry_states: Vec<A::Domain>,
+}
+
+impl<A: Analysis> Results<A> {
+    /// The state at the entry of a basic block, in the direction of the analysis.
+    pub fn entry_state(&self, block: BasicBlockIdx) -> &A::Domain {
+        &self.entry_states[block]
+    }
+
+    /// The state right before the statement or terminator at `location` executes.
+    pub fn state_before(&mut self, body: &Body, location: BodyLocation) -> A::Domain {
+        match A::DIRECTION {
+            Direction::Forward => self.forward_state(body, location, false),
+            Direction::Backward => self.backward_state(body, location, true),
+        }
+    }
+
+    /// The state right after the statement or terminator at `location` executes.
+    ///
+    /// For a call terminator, this does not include [Analysis::apply_call_return_effect].
+    pub fn state_after(&mut self, body: &Body, location: BodyLocation) -> A::Domain {
+        match A::DIRECTION {
+            Direction::Forward => self.forward_state(body, location, true),
+            Direction::Backward => self.backward_state(body, location, false),
+        }
+    }
+
+    /// Apply the effects of the block start up to `location`.
+    fn forward_state(
+        &mut self,
+        body: &Body,
+        location: BodyLocation,
+        include_location: bool,
+    ) -> A::Domain {
+        let block = &body.blocks[location.block];
+        let mut state = self.entry_states[location.block].clone();
+        let end = location.statement_index + include_location as usize;
+        for (statement_index, statement) in block.statements.iter().enumerate().take(end) {
+            let location = BodyLocation { block: location.block, statement_index };
+            self.analysis.apply_statement_effect(&mut state, statement, location);
+        }
+        if end > block.statements.len() {
+            let location = BodyLocation { block: location.block, statement_index: end - 1 };
+            self.analysis.apply_terminator_effect(&mut state, &block.terminator, location);
+        }
+        state
+    }
+
+    /// Apply the effects of the block end down to `location`.
+    fn backward_state(
+        &mut self,
+        body: &Body,
+        location: BodyLocation,
+        include_location: bool,
+    ) -> A::Domain {
+        let block = &body.blocks[location.block];
+        let mut state = self.entry_states[location.block].clone();
+        let start = location.statement_index + !include_location as usize;
+        let terminator_location =
+            BodyLocation { block: location.block, statement_index: block.statements.len() };
+        if start <= block.statements.len() {
+            self.analysis.apply_terminator_effect(
+                &mut state,
+                &block.terminator,
+                terminator_location,
+            );
+        }
+        for (statement_index, statement) in block.statements.iter().enumerate().skip(start).rev() {
+            let location = BodyLocation { block: location.block, statement_index };
+            self.analysis.apply_statement_effect(&mut state, statement, location);
+        }
+        state
+    }
+}
+
+/// Runs an analysis until it reaches a fixpoint.
+struct Engine<'a, A: Analysis> {
+    body: &'a Body,
+    analysis: A,
+    entry_states: Vec<A::Domain>,
+}
+
+impl<'a, A: Analysis> Engine<'a, A> {
+    fn new(body: &'a Body, analysis: A) -> Self {
+        let bottom = analysis.bottom_value(body);
+        let mut entry_states = vec![bottom; body.blocks.len()];
+        if A::DIRECTION == Direction::Forward && !entry_states.is_empty() {
+            analysis.initialize_start_block(body, &mut entry_states[0]);
+        }
+        Engine { body, analysis, entry_states }
+    }
+
+    fn iterate_to_fixpoint(mut self) -> Results<A> {
+        let num_blocks = self.body.blocks.len();
+        let mut worklist: VecDeque<BasicBlockIdx> = match A::DIRECTION {
+            Direction::Forward => (0..num_blocks).collect(),
+            Direction::Backward => (0..num_blocks).rev().collect(),
+        };
+        let mut in_worklist = vec![true; num_blocks];
+        let predecessors = match A::DIRECTION {
+            Direction::Forward => vec![],
+            Direction::Backward => predecessors(self.body),
+        };
+
+        while let Some(block) = worklist.pop_front() {
+            in_worklist[block] = false;
+            let propagate = match A::DIRECTION {
+                Direction::Forward => self.propagate_forward(block),
+                Direction::Backward => self.propagate_backward(block, &predecessors[block]),
+            };
+            for target in propagate {
+                if !in_worklist[target] {
+                    in_worklist[target] = true;
+                    worklist.push_back(target);
+                }
+            }
+        }
+        Results { analysis: self.analysis, entry_states: self.entry_states }
+    }
+
+    /// Apply the effects of `block` and join the result into the state of its successors.
+    /// Returns the successors whose state has changed.
+    fn propagate_forward(&mut self, block: BasicBlockIdx) -> Vec<BasicBlockIdx> {
+        let data = &self.body.blocks[block];
+        let mut state = self.entry_states[block].clone();
+        for (statement_index, statement) in data.statements.iter().enumerate() {
+            let location = BodyLocation { block, statement_index };
+            self.analysis.apply_statement_effect(&mut state, statement, location);
+        }
+        let location = BodyLocation { block, statement_index: data.statements.len() };
+        self.analysis.apply_terminator_effect(&mut state, &data.terminator, location);
+
+        let return_edge = match &data.terminator.kind {
+            TerminatorKind::Call { destination, target: Some(target), .. } => {
+                Some((*target, destination))
+            }
+            _ => None,
+        };
+        let mut changed = vec![];
+        for successor in data.terminator.successors() {
+            let succ_changed = match return_edge {
+                Some((target, destination)) if target == successor => {
+                    let mut return_state = state.clone();
+                    self.analysis.apply_call_return_effect(
+                        &mut return_state,
+                        destination,
+                        location,
+                    );
+                    self.entry_states[successor].join(&return_state)
+                }
+                _ => self.entry_states[successor].join(&state),
+            };
+            if succ_changed {
+                changed.push(successor);
+            }
+        }
+        changed
+    }
+
+    /// Apply the effects of `block` and join the result into the state of its predecessors.
+    /// Returns the predecessors whose state has changed.
+    fn propagate_backward(
+        &mut self,
+        block: BasicBlockIdx,
+        predecessors: &[BasicBlockIdx],
+    ) -> Vec<BasicBlockIdx> {
+        let data = &self.body.blocks[block];
+        let mut state = self.entry_states[block].clone();
+        let location = BodyLocation { block, statement_index: data.statements.len() };
+        self.analysis.apply_terminator_effect(&mut state, &data.terminator, location);
+        for (statement_index, statement) in data.statements.iter().enumerate().rev() {
+            let location = BodyLocation { block, statement_index };
+            self.analysis.apply_statement_effect(&mut state, statement, location);
+        }
+
+        let mut changed = vec![];
+        for &pred in predecessors {
+            let pred_data = &self.body.blocks[pred];
+            let pred_changed = match &pred_data.terminator.kind {
+                TerminatorKind::Call { destination, target: Some(target), .. }
+                    if *target == block =>
+                {
+                    let location =
+                        BodyLocation { block: pred, statement_index: pred_data.statements.len() };
+                    let mut return_state = state.clone();
+                    self.analysis.apply_call_return_effect(
+                        &mut return_state,
+                        destination,
+                        location,
+                    );
+                    self.entry_states[pred].join(&return_state)
+                }
+                _ => self.entry_states[pred].join(&state),
+            };
+            if pred_changed {
+                changed.push(pred);
+            }
+        }
+        changed
+    }
+}
+
+/// The predecessors of every basic block, without duplicates.
+fn predecessors(body: &Body) -> Vec<Vec<BasicBlockIdx>> {
+    let mut predecessors = vec![vec![]; body.blocks.len()];
+    for (block, data) in body.blocks.iter().enumerate() {
+        for successor in data.terminator.successors() {
+            let preds: &mut Vec<BasicBlockIdx> = &mut predecessors[successor];
+            if preds.last() != Some(&block) {
+                preds.push(block);
+            }
+        }
+    }
+    predecessors
+}
+
+/// A fixed-size set of indices, e.g., locals, which is used as the domain of many analyses.
+///
+/// Its join operation is the set union.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    domain_size: usize,
+    words: Vec<u64>,
+}
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+impl BitSet {
+    /// Creates an empty set that can hold the indices in `0..domain_size`.
+    pub fn new_empty(domain_size: usize) -> BitSet {
+        BitSet { domain_size, words: vec![0; domain_size.div_ceil(WORD_BITS)] }
+    }
+
+    /// Creates a set that contains every index in `0..domain_size`.
+    pub fn new_filled(domain_size: usize) -> BitSet {
+        let mut set = BitSet::new_empty(domain_size);
+        set.insert_all();
+        set
+    }
+
+    pub fn domain_size(&self) -> usize {
+        self.domain_size
+    }
+
+    /// Inserts `elem`, and returns `true` if it was not in the set before.
+    pub fn insert(&mut self, elem: usize) -> bool {
+        let (word, mask) = self.word_and_mask(elem);
+        let changed = self.words[word] & mask == 0;
+        self.words[word] |= mask;
+        changed
+    }
+
+    /// Removes `elem`, and returns `true` if it was in the set before.
+    pub fn remove(&mut self, elem: usize) -> bool {
+        let (word, mask) = self.word_and_mask(elem);
+        let changed = self.words[word] & mask != 0;
+        self.words[word] &= !mask;
+        changed
+    }
+
+    pub fn contains(&self, elem: usize) -> bool {
+        let (word, mask) = self.word_and_mask(elem);
+        self.words[word] & mask != 0
+    }
+
+    pub fn insert_all(&mut self) {
+        self.words.fill(!0);
+        self.clear_excess_bits();
+    }
+
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Iterates over the elements of the set in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.domain_size).filter(|elem| self.contains(*elem))
+    }
+
+    /// Adds all the elements of `other` to `self`, and returns `true` if `self` has changed.
+    pub fn union(&mut self, other: &BitSet) -> bool {
+        assert_eq!(self.domain_size, other.domain_size);
+        let mut changed = false;
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            let new = *word | other;
+            changed |= new != *word;
+            *word = new;
+        }
+        changed
+    }
+
+    /// Removes all the elements of `other` from `self`, and returns `true` if `self` has changed.
+    pub fn subtract(&mut self, other: &BitSet) -> bool {
+        assert_eq!(self.domain_size, other.domain_size);
+        let mut changed = false;
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            let new = *word & !other;
+            changed |= new != *word;
+            *word = new;
+        }
+        changed
+    }
+
+    fn word_and_mask(&self, elem: usize) -> (usize, u64) {
+        assert!(elem < self.domain_size, "index {elem} out of bounds {}", self.domain_size);
+        (elem / WORD_BITS, 1 << (elem % WORD_BITS))
+    }
+
+    fn clear_excess_bits(&mut self) {
+        let excess = self.domain_size % WORD_BITS;
+        if excess != 0 {
+            let last = self.words.len() - 1;
+            self.words[last] &= (1 << excess) - 1;
+        }
+    }
+}
+
+impl JoinSemiLattice for BitSet {
+    fn join(&mut self, other: &Self) -> bool {
+        self.union(other)
+    }
+}
+
+impl Debug for BitSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,334 @@
+//! Standard dataflow analyses over locals.
+
+use super::{Analysis, BitSet, Direction};
+use crate::mir::visit::{Location, PlaceContext};
+use crate::mir::{
+    Body, BodyLocation, Local, MirVisitor, Place, ProjectionElem, Statement, StatementKind,
+    Terminator,
+};
+
+/// Computes the locals that may be used later, i.e., before they are overwritten.
+///
+/// This is a backward analysis, so [Results::entry_state] returns the locals that are live at
+/// the end of a block.
+///
+/// [Results::entry_state]: super::Results::entry_state
+#[derive(Default)]
+pub struct MaybeLiveLocals;
+
+impl Analysis for MaybeLiveLocals {
+    type Domain = BitSet;
+
+    const DIRECTION: Direction = Direction::Backward;
+
+    fn bottom_value(&self, body: &Body) -> BitSet {
+        BitSet::new_empty(body.locals().len())
+    }
+
+    fn initialize_start_block(&self, _body: &Body, _state: &mut BitSet) {
+        // Nothing is live on function entry.
+    }
+
+    fn apply_statement_effect(
+        &mut self,
+        state: &mut BitSet,
+        statement: &Statement,
+        _location: BodyLocation,
+    ) {
+        match &statement.kind {
+            // The value of a local cannot be used across storage markers.
+            StatementKind::StorageLive(local) | StatementKind::StorageDead(local) => {
+                state.remove(*local);
+            }
+            // Setting the discriminant doesn't read the local, unless it goes through a pointer.
+            StatementKind::SetDiscriminant { place, .. } => {
+                if is_indirect(place) {
+                    state.insert(place.local);
+                }
+            }
+            _ => LivenessTransfer(state).visit_statement(statement, Location(statement.span)),
+        }
+    }
+
+    fn apply_terminator_effect(
+        &mut self,
+        state: &mut BitSet,
+        terminator: &Terminator,
+        _location: BodyLocation,
+    ) {
+        LivenessTransfer(state).visit_terminator(terminator, Location(terminator.span));
+    }
+
+    fn apply_call_return_effect(
+        &mut self,
+        state: &mut BitSet,
+        destination: &Place,
+        _location: BodyLocation,
+    ) {
+        LivenessTransfer(state).def(destination);
+    }
+}
+
+/// Since liveness is a backward analysis, the visitor must visit definitions before uses, which
+/// is the order in which `super_statement` and `super_terminator` visit them.
+struct LivenessTransfer<'a>(&'a mut BitSet);
+
+impl LivenessTransfer<'_> {
+    /// The place is overwritten without being read.
+    fn def(&mut self, place: &Place) {
+        if is_indirect(place) {
+            self.0.insert(place.local);
+        } else if place.projection.is_empty() {
+            self.0.remove(place.local);
+        }
+        self.use_indices(place);
+    }
+
+    /// The locals used to index into a place are always read.
+    fn use_indices(&mut self, place: &Place) {
+        for elem in &place.projection {
+            if let ProjectionElem::Index(local) = elem {
+                self.0.insert(*local);
+            }
+        }
+    }
+}
+
+impl MirVisitor for LivenessTransfer<'_> {
+    fn visit_place(&mut self, place: &Place, ptx: PlaceContext, _location: Location) {
+        if ptx.is_call_destination() {
+            // Handled by `apply_call_return_effect`.
+        } else if ptx.is_store() {
+            self.def(place);
+        } else if ptx.is_use() {
+            self.0.insert(place.local);
+            self.use_indices(place);
+        }
+    }
+
+    fn visit_local(&mut self, local: &Local, ptx: PlaceContext, _location: Location) {
+        if ptx.is_use() {
+            self.0.insert(*local);
+        }
+    }
+}
+
+/// Computes the locals that may be initialized, i.e., that are initialized on at least one path
+/// that reaches a location.
+///
+/// The locals are tracked as a whole: a local that is partially initialized is considered to be
+/// maybe initialized. Writes through a pointer do not affect the local holding the pointer.
+#[derive(Default)]
+pub struct MaybeInitializedLocals;
+
+impl Analysis for MaybeInitializedLocals {
+    type Domain = BitSet;
+
+    const DIRECTION: Direction = Direction::Forward;
+
+    fn bottom_value(&self, body: &Body) -> BitSet {
+        BitSet::new_empty(body.locals().len())
+    }
+
+    fn initialize_start_block(&self, body: &Body, state: &mut BitSet) {
+        for arg in 1..=body.arg_locals().len() {
+            state.insert(arg);
+        }
+    }
+
+    fn apply_statement_effect(
+        &mut self,
+        state: &mut BitSet,
+        statement: &Statement,
+        _location: BodyLocation,
+    ) {
+        InitTransfer { state, maybe_init: true }.apply_statement(statement);
+    }
+
+    fn apply_terminator_effect(
+        &mut self,
+        state: &mut BitSet,
+        terminator: &Terminator,
+        _location: BodyLocation,
+    ) {
+        InitTransfer { state, maybe_init: true }
+            .visit_terminator(terminator, Location(terminator.span));
+    }
+
+    fn apply_call_return_effect(
+        &mut self,
+        state: &mut BitSet,
+        destination: &Place,
+        _location: BodyLocation,
+    ) {
+        InitTransfer { state, maybe_init: true }.init(destination);
+    }
+}
+
+/// Computes the locals that may be uninitialized, i.e., that are not initialized on at least one
+/// path that reaches a location.
+///
+/// The locals are tracked as a whole: a local that is partially moved out of is considered to be
+/// maybe uninitialized. Writes through a pointer do not affect the local holding the pointer.
+#[derive(Default)]
+pub struct MaybeUninitializedLocals;
+
+impl Analysis for MaybeUninitializedLocals {
+    type Domain = BitSet;
+
+    const DIRECTION: Direction = Direction::Forward;
+
+    fn bottom_value(&self, body: &Body) -> BitSet {
+        BitSet::new_empty(body.locals().len())
+    }
+
+    fn initialize_start_block(&self, body: &Body, state: &mut BitSet) {
+        state.insert_all();
+        for arg in 1..=body.arg_locals().len() {
+            state.remove(arg);
+        }
+    }
+
+    fn apply_statement_effect(
+        &mut self,
+        state: &mut BitSet,
+        statement: &Statement,
+        _location: BodyLocation,
+    ) {
+        InitTransfer { state, maybe_init: false }.apply_statement(statement);
+    }
+
+    fn apply_terminator_effect(
+        &mut self,
+        state: &mut BitSet,
+        terminator: &Terminator,
+        _location: BodyLocation,
+    ) {
+        InitTransfer { state, maybe_init: false }
+            .visit_terminator(terminator, Location(terminator.span));
+    }
+
+    fn apply_call_return_effect(
+        &mut self,
+        state: &mut BitSet,
+        destination: &Place,
+        _location: BodyLocation,
+    ) {
+        InitTransfer { state, maybe_init: false }.init(destination);
+    }
+}
+
+/// The transfer function shared by [MaybeInitializedLocals] and [MaybeUninitializedLocals].
+///
+/// The state holds the maybe initialized locals if `maybe_init` is set, or the maybe
+/// uninitialized locals otherwise.
+struct InitTransfer<'a> {
+    state: &'a mut BitSet,
+    maybe_init: bool,
+}
+
+impl InitTransfer<'_> {
+    fn apply_statement(&mut self, statement: &Statement) {
+        match &statement.kind {
+            StatementKind::StorageLive(local) | StatementKind::StorageDead(local) => {
+                self.uninit(*local, true)
+            }
+            StatementKind::Deinit(place) if !is_indirect(place) => {
+                self.uninit(place.local, place.projection.is_empty())
+            }
+            _ => self.visit_statement(statement, Location(statement.span)),
+        }
+    }
+
+    /// The place is written to.
+    fn init(&mut self, place: &Place) {
+        if is_indirect(place) {
+            return;
+        }
+        if self.maybe_init {
+            self.state.insert(place.local);
+        } else if place.projection.is_empty() {
+            self.state.remove(place.local);
+        }
+    }
+
+    /// The local is moved out of, or its storage is invalidated.
+    fn uninit(&mut self, local: Local, whole_local: bool) {
+        if !self.maybe_init {
+            self.state.insert(local);
+        } else if whole_local {
+            self.state.remove(local);
+        }
+    }
+}
+
+impl MirVisitor for InitTransfer<'_> {
+    fn visit_place(&mut self, place: &Place, ptx: PlaceContext, _location: Location) {
+        if ptx.is_call_destination() {
+            // Handled by `apply_call_return_effect`.
+        } else if ptx.is_store() {
+            self.init(place);
+        } else if (ptx.is_move() || ptx.is_drop()) && !is_indirect(place) {
+            self.uninit(place.local, place.projection.is_empty());
+        }
+    }
+}
+
+/// Computes the locals that may have been borrowed, i.e., a reference or a raw pointer to them
+/// may exist.
+///
+/// Borrows are assumed to live until the storage of the local is invalidated.
+#[derive(Default)]
+pub struct MaybeBorrowedLocals;
+
+impl Analysis for MaybeBorrowedLocals {
+    type Domain = BitSet;
+
+    const DIRECTION: Direction = Direction::Forward;
+
+    fn bottom_value(&self, body: &Body) -> BitSet {
+        BitSet::new_empty(body.locals().len())
+    }
+
+    fn initialize_start_block(&self, _body: &Body, _state: &mut BitSet) {
+        // No locals are borrowed on function entry.
+    }
+
+    fn apply_statement_effect(
+        &mut self,
+        state: &mut BitSet,
+        statement: &Statement,
+        _location: BodyLocation,
+    ) {
+        if let StatementKind::StorageDead(local) = statement.kind {
+            state.remove(local);
+        } else {
+            BorrowTransfer(state).visit_statement(statement, Location(statement.span));
+        }
+    }
+
+    fn apply_terminator_effect(
+        &mut self,
+        state: &mut BitSet,
+        terminator: &Terminator,
+        _location: BodyLocation,
+    ) {
+        BorrowTransfer(state).visit_terminator(terminator, Location(terminator.span));
+    }
+}
+
+struct BorrowTransfer<'a>(&'a mut BitSet);
+
+impl MirVisitor for BorrowTransfer<'_> {
+    fn visit_place(&mut self, place: &Place, ptx: PlaceContext, _location: Location) {
+        // Drop glue may take a mutable reference to the place being dropped.
+        if (ptx.is_borrow() || ptx.is_drop()) && !is_indirect(place) {
+            self.0.insert(place.local);
+        }
+    }
+}
+
+/// Whether the place is accessed through a pointer.
+fn is_indirect(place: &Place) -> bool {
+    place.projection.iter().any(|elem| matches!(elem, ProjectionElem::Deref))
+}
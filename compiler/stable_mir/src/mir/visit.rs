@@ -191,16 +191,20 @@ fn super_statement(&mut self, stmt: &Statement, location: Location) {
         self.visit_span(span);
         match kind {
             StatementKind::Assign(place, rvalue) => {
-                self.visit_place(place, PlaceContext::MUTATING, location);
+                self.visit_place(place, PlaceContext::STORE, location);
                 self.visit_rvalue(rvalue, location);
             }
             StatementKind::FakeRead(_, place) | StatementKind::PlaceMention(place) => {
-                self.visit_place(place, PlaceContext::NON_MUTATING, location);
+                self.visit_place(place, PlaceContext::INSPECT, location);
             }
-            StatementKind::SetDiscriminant { place, .. }
-            | StatementKind::Deinit(place)
-            | StatementKind::Retag(_, place) => {
-                self.visit_place(place, PlaceContext::MUTATING, location);
+            StatementKind::SetDiscriminant { place, .. } => {
+                self.visit_place(place, PlaceContext::SET_DISCRIMINANT, location);
+            }
+            StatementKind::Deinit(place) => {
+                self.visit_place(place, PlaceContext::DEINIT, location);
+            }
+            StatementKind::Retag(_, place) => {
+                self.visit_place(place, PlaceContext::RETAG, location);
             }
             StatementKind::StorageLive(local) | StatementKind::StorageDead(local) => {
                 self.visit_local(local, PlaceContext::NON_USE, location);
@@ -241,14 +245,14 @@ fn super_terminator(&mut self, term: &Terminator, location: Location) {
                 self.visit_assert_msg(msg, location);
             }
             TerminatorKind::Drop { place, target: _, unwind: _ } => {
-                self.visit_place(place, PlaceContext::MUTATING, location);
+                self.visit_place(place, PlaceContext::DROP, location);
             }
             TerminatorKind::Call { func, args, destination, target: _, unwind: _ } => {
                 self.visit_operand(func, location);
                 for arg in args {
                     self.visit_operand(arg, location);
                 }
-                self.visit_place(destination, PlaceContext::MUTATING, location);
+                self.visit_place(destination, PlaceContext::CALL, location);
             }
             TerminatorKind::InlineAsm { operands, .. } => {
                 for op in operands {
@@ -257,13 +261,13 @@ fn super_terminator(&mut self, term: &Terminator, location: Location) {
                         self.visit_operand(input, location);
                     }
                     if let Some(output) = out_place {
-                        self.visit_place(output, PlaceContext::MUTATING, location);
+                        self.visit_place(output, PlaceContext::ASM_OUTPUT, location);
                     }
                 }
             }
             TerminatorKind::Return => {
                 let local = RETURN_LOCAL;
-                self.visit_local(&local, PlaceContext::NON_MUTATING, location);
+                self.visit_local(&local, PlaceContext::MOVE, location);
             }
             TerminatorKind::SwitchInt { discr, targets: _ } => {
                 self.visit_operand(discr, location);
@@ -289,7 +293,7 @@ fn super_place(&mut self, place: &Place, ptx: PlaceContext, location: Location)
     fn super_projection_elem(
         &mut self,
         elem: &ProjectionElem,
-        ptx: PlaceContext,
+        _ptx: PlaceContext,
         location: Location,
     ) {
         match elem {
@@ -298,7 +302,9 @@ fn super_projection_elem(
             | ProjectionElem::Deref
             | ProjectionElem::Subslice { from: _, to: _, from_end: _ } => {}
             ProjectionElem::Field(_idx, ty) => self.visit_ty(ty, location),
-            ProjectionElem::Index(local) => self.visit_local(local, ptx, location),
+            // The index is only read, even if the indexed place is written to or borrowed, so it
+            // is visited as a copy instead of with the context of the place, like rustc does.
+            ProjectionElem::Index(local) => self.visit_local(local, PlaceContext::COPY, location),
             ProjectionElem::OpaqueCast(ty) | ProjectionElem::Subtype(ty) => {
                 self.visit_ty(ty, location)
             }
@@ -308,7 +314,7 @@ fn super_projection_elem(
     fn super_rvalue(&mut self, rvalue: &Rvalue, location: Location) {
         match rvalue {
             Rvalue::AddressOf(mutability, place) => {
-                let pcx = PlaceContext { is_mut: *mutability == Mutability::Mut };
+                let pcx = PlaceContext::address_of(*mutability == Mutability::Mut);
                 self.visit_place(place, pcx, location);
             }
             Rvalue::Aggregate(_, operands) => {
@@ -325,11 +331,11 @@ fn super_rvalue(&mut self, rvalue: &Rvalue, location: Location) {
                 self.visit_ty(ty, location);
             }
             Rvalue::CopyForDeref(place) | Rvalue::Discriminant(place) | Rvalue::Len(place) => {
-                self.visit_place(place, PlaceContext::NON_MUTATING, location);
+                self.visit_place(place, PlaceContext::INSPECT, location);
             }
             Rvalue::Ref(region, kind, place) => {
                 self.visit_region(region, location);
-                let pcx = PlaceContext { is_mut: matches!(kind, BorrowKind::Mut { .. }) };
+                let pcx = PlaceContext::borrow(matches!(kind, BorrowKind::Mut { .. }));
                 self.visit_place(place, pcx, location);
             }
             Rvalue::Repeat(op, constant) => {
@@ -352,9 +358,8 @@ fn super_rvalue(&mut self, rvalue: &Rvalue, location: Location) {
 
     fn super_operand(&mut self, operand: &Operand, location: Location) {
         match operand {
-            Operand::Copy(place) | Operand::Move(place) => {
-                self.visit_place(place, PlaceContext::NON_MUTATING, location)
-            }
+            Operand::Copy(place) => self.visit_place(place, PlaceContext::COPY, location),
+            Operand::Move(place) => self.visit_place(place, PlaceContext::MOVE, location),
             Operand::Constant(constant) => {
                 self.visit_const_operand(constant, location);
             }
@@ -447,7 +452,7 @@ fn visit_opaque(_: &Opaque) {}
 
 /// The location of a statement / terminator in the code and the CFG.
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
-pub struct Location(Span);
+pub struct Location(pub(crate) Span);
 
 impl Location {
     pub fn span(&self) -> Span {
@@ -490,14 +495,86 @@ pub struct PlaceContext {
     /// Whether the access is mutable or not. Keep this private so we can increment the type in a
     /// backward compatible manner.
     is_mut: bool,
+    /// How the place is being used.
+    kind: PlaceUse,
+}
+
+/// The different ways a place can be used. This mirrors `rustc_middle::mir::visit::PlaceContext`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+enum PlaceUse {
+    Store,
+    Call,
+    AsmOutput,
+    Deinit,
+    SetDiscriminant,
+    Retag,
+    Drop,
+    Borrow,
+    AddressOf,
+    Inspect,
+    Copy,
+    Move,
+    NonUse,
 }
 
 impl PlaceContext {
-    const MUTATING: Self = PlaceContext { is_mut: true };
-    const NON_MUTATING: Self = PlaceContext { is_mut: false };
-    const NON_USE: Self = PlaceContext { is_mut: false };
+    const STORE: Self = PlaceContext { is_mut: true, kind: PlaceUse::Store };
+    const CALL: Self = PlaceContext { is_mut: true, kind: PlaceUse::Call };
+    const ASM_OUTPUT: Self = PlaceContext { is_mut: true, kind: PlaceUse::AsmOutput };
+    const DEINIT: Self = PlaceContext { is_mut: true, kind: PlaceUse::Deinit };
+    const SET_DISCRIMINANT: Self = PlaceContext { is_mut: true, kind: PlaceUse::SetDiscriminant };
+    const RETAG: Self = PlaceContext { is_mut: true, kind: PlaceUse::Retag };
+    const DROP: Self = PlaceContext { is_mut: true, kind: PlaceUse::Drop };
+    const INSPECT: Self = PlaceContext { is_mut: false, kind: PlaceUse::Inspect };
+    const COPY: Self = PlaceContext { is_mut: false, kind: PlaceUse::Copy };
+    const MOVE: Self = PlaceContext { is_mut: false, kind: PlaceUse::Move };
+    const NON_USE: Self = PlaceContext { is_mut: false, kind: PlaceUse::NonUse };
+
+    const fn borrow(is_mut: bool) -> Self {
+        PlaceContext { is_mut, kind: PlaceUse::Borrow }
+    }
+
+    const fn address_of(is_mut: bool) -> Self {
+        PlaceContext { is_mut, kind: PlaceUse::AddressOf }
+    }
 
     pub fn is_mutating(&self) -> bool {
         self.is_mut
     }
+
+    /// Whether the place is accessed at all. This is `false` for storage markers, debug
+    /// information and type ascriptions.
+    pub fn is_use(&self) -> bool {
+        self.kind != PlaceUse::NonUse
+    }
+
+    /// Whether the place is overwritten without being read, e.g., the destination of an
+    /// assignment, of a call or of an inline assembly output, or a `Deinit` statement.
+    pub fn is_store(&self) -> bool {
+        matches!(
+            self.kind,
+            PlaceUse::Store | PlaceUse::Call | PlaceUse::AsmOutput | PlaceUse::Deinit
+        )
+    }
+
+    /// Whether the place is the destination of a call. Note that the destination is only
+    /// written to if the call returns.
+    pub fn is_call_destination(&self) -> bool {
+        self.kind == PlaceUse::Call
+    }
+
+    /// Whether the place is dropped by a `Drop` terminator.
+    pub fn is_drop(&self) -> bool {
+        self.kind == PlaceUse::Drop
+    }
+
+    /// Whether a reference or a raw pointer to the place is created.
+    pub fn is_borrow(&self) -> bool {
+        matches!(self.kind, PlaceUse::Borrow | PlaceUse::AddressOf)
+    }
+
+    /// Whether the place is moved out of.
+    pub fn is_move(&self) -> bool {
+        self.kind == PlaceUse::Move
+    }
 }
@@ -0,0 +1,547 @@
+//! Self-contained snapshots of a crate that can be serialized.
+//!
+//! Most StableMIR constructs, such as [Ty], [Span], [DefId] and [AllocId], are handles into
+//! tables owned by the compiler. Serializing them on their own is not enough to analyze a crate
+//! without linking against the compiler. A [CrateSnapshot] also includes the contents of every
+//! handle that is reachable from the local crate, so it can be written to a file using any
+//! `serde` format (e.g. JSON) and analyzed offline. [CrateSnapshot::save] and
+//! [CrateSnapshot::load] do so in JSON.
+//!
+//! The handles are only meaningful within the snapshot where they were recorded.
+
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::io::{Read, Write};
+use std::ops::ControlFlow;
+
+use serde::{Deserialize, Serialize};
+
+use crate::abi::LayoutShape;
+use crate::mir::alloc::{AllocId, GlobalAlloc};
+use crate::mir::mono::{Instance, InstanceDef};
+use crate::mir::visit::Location;
+use crate::mir::{Body, MirVisitor};
+use crate::target::MachineInfo;
+use crate::ty::{
+    AdtDef, AdtKind, Allocation, ClauseKind, CoercePredicate, ConstantKind, GenericArgs,
+    GenericPredicates, Generics, ImplDef, ImplTrait, LineInfo, MirConst, OutlivesPredicate,
+    PredicateKind, RigidTy, Span, SubtypePredicate, TraitDecl, TraitDef, TraitRef, Ty, TyConst,
+    TyConstKind, TyKind,
+};
+use crate::visitor::{Visitable, Visitor};
+use crate::{
+    Crate, CrateDef, CrateItem, DefId, Error, Filename, ItemKind, Symbol, all_local_items,
+    local_crate, with,
+};
+
+/// The version of the [CrateSnapshot] format.
+///
+/// This is bumped every time the format changes in a way that may break existing consumers.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// A snapshot of the local crate, including the data of every handle it references.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CrateSnapshot {
+    /// The version of the format used by this snapshot. See [SNAPSHOT_VERSION].
+    pub version: u32,
+    pub krate: Crate,
+    /// The target machine, which is needed to interpret the contents of allocations.
+    pub machine: MachineInfo,
+    pub items: Vec<ItemSnapshot>,
+    pub trait_decls: Vec<TraitDeclSnapshot>,
+    pub trait_impls: Vec<ImplSnapshot>,
+    /// The definitions referenced by the snapshot.
+    pub defs: Vec<DefSnapshot>,
+    /// The definitions of the ADTs used by the types of the snapshot.
+    pub adts: Vec<AdtSnapshot>,
+    /// The types referenced by the snapshot, including the types they are made of.
+    pub types: Vec<TySnapshot>,
+    /// The global allocations referenced by the snapshot.
+    pub allocs: Vec<AllocSnapshot>,
+    /// The instances referenced by the snapshot, e.g. by function pointers.
+    pub instances: Vec<InstanceSnapshot>,
+    /// The spans referenced by the snapshot.
+    pub spans: Vec<SpanSnapshot>,
+}
+
+/// An item of the local crate.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ItemSnapshot {
+    pub item: CrateItem,
+    pub kind: ItemKind,
+    pub ty: Ty,
+    pub span: Span,
+    /// The body of the item, if there is one.
+    pub body: Option<Body>,
+}
+
+/// A trait declaration of the local crate.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TraitDeclSnapshot {
+    pub decl: TraitDecl,
+    pub generics: Generics,
+    pub predicates: GenericPredicates,
+}
+
+/// A trait implementation of the local crate.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImplSnapshot {
+    pub def: ImplDef,
+    pub trait_impl: ImplTrait,
+    pub generics: Generics,
+    pub predicates: GenericPredicates,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DefSnapshot {
+    pub def_id: DefId,
+    pub name: Symbol,
+    pub krate: Crate,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AdtSnapshot {
+    pub def: AdtDef,
+    pub kind: AdtKind,
+    pub variants: Vec<VariantSnapshot>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VariantSnapshot {
+    pub name: Symbol,
+    pub fields: Vec<FieldSnapshot>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FieldSnapshot {
+    pub def: DefId,
+    pub name: Symbol,
+    /// The declared type of the field, which may refer to the generic parameters of the ADT.
+    pub ty: Ty,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TySnapshot {
+    pub ty: Ty,
+    pub kind: TyKind,
+    /// The layout of the type, if it can be computed, i.e., the type is not generic.
+    pub layout: Option<LayoutShape>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AllocSnapshot {
+    pub id: AllocId,
+    pub alloc: GlobalAlloc,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InstanceSnapshot {
+    pub def: InstanceDef,
+    pub def_id: DefId,
+    pub args: GenericArgs,
+    pub name: Symbol,
+    pub ty: Ty,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SpanSnapshot {
+    pub span: Span,
+    pub filename: Filename,
+    pub lines: LineInfo,
+}
+
+impl CrateSnapshot {
+    /// Take a snapshot of the local crate.
+    ///
+    /// Note that this retrieves the body of every local item, and the layout of every type they
+    /// reference, which may be expensive for large crates.
+    pub fn local() -> CrateSnapshot {
+        let mut collector = Collector::default();
+        let items = all_local_items()
+            .into_iter()
+            .map(|item| {
+                let body = with(|cx| cx.has_body(item.0)).then(|| item.body());
+                if let Some(body) = &body {
+                    collector.visit_body(body);
+                    collector.add_span(body.span);
+                }
+                let snapshot = ItemSnapshot {
+                    item,
+                    kind: item.kind(),
+                    ty: item.ty(),
+                    span: item.span(),
+                    body,
+                };
+                collector.add_def(item.0);
+                collector.add_ty(snapshot.ty);
+                collector.add_span(snapshot.span);
+                snapshot
+            })
+            .collect();
+
+        let krate = local_crate();
+        let trait_decls = krate
+            .trait_decls()
+            .iter()
+            .map(|def| {
+                collector.add_def(def.def_id());
+                let snapshot = TraitDeclSnapshot {
+                    decl: TraitDef::declaration(def),
+                    generics: with(|cx| cx.generics_of(def.def_id())),
+                    predicates: with(|cx| cx.predicates_of(def.def_id())),
+                };
+                collector.add_generics(&snapshot.generics);
+                collector.add_predicates(&snapshot.predicates);
+                snapshot
+            })
+            .collect();
+        let trait_impls = krate
+            .trait_impls()
+            .into_iter()
+            .map(|def| {
+                collector.add_def(def.def_id());
+                let snapshot = ImplSnapshot {
+                    def,
+                    trait_impl: def.trait_impl(),
+                    generics: with(|cx| cx.generics_of(def.def_id())),
+                    predicates: with(|cx| cx.predicates_of(def.def_id())),
+                };
+                collector.add_trait_ref(&snapshot.trait_impl.value);
+                collector.add_generics(&snapshot.generics);
+                collector.add_predicates(&snapshot.predicates);
+                snapshot
+            })
+            .collect();
+
+        // The spans of the definitions are part of the snapshot as well, so they have to be
+        // collected before the spans are recorded.
+        let defs: Vec<_> = collector
+            .defs
+            .items
+            .iter()
+            .map(|def_id| DefSnapshot {
+                def_id: *def_id,
+                name: with(|cx| cx.def_name(*def_id, false)),
+                krate: with(|cx| cx.krate(*def_id)),
+                span: with(|cx| cx.span_of_an_item(*def_id)),
+            })
+            .collect();
+        for def in &defs {
+            collector.add_span(def.span);
+        }
+
+        CrateSnapshot {
+            version: SNAPSHOT_VERSION,
+            krate,
+            machine: MachineInfo::target(),
+            items,
+            trait_decls,
+            trait_impls,
+            defs,
+            adts: collector.adts,
+            types: collector
+                .types
+                .items
+                .into_iter()
+                .map(|ty| TySnapshot {
+                    ty,
+                    kind: ty.kind(),
+                    layout: ty.layout().ok().map(|layout| layout.shape()),
+                })
+                .collect(),
+            allocs: collector
+                .allocs
+                .items
+                .into_iter()
+                .map(|id| AllocSnapshot { id, alloc: GlobalAlloc::from(id) })
+                .collect(),
+            instances: collector
+                .instances
+                .items
+                .into_iter()
+                .map(|instance| InstanceSnapshot {
+                    def: instance.def,
+                    def_id: instance.def.def_id(),
+                    args: instance.args(),
+                    name: instance.name(),
+                    ty: instance.ty(),
+                })
+                .collect(),
+            spans: collector
+                .spans
+                .items
+                .into_iter()
+                .map(|span| SpanSnapshot {
+                    span,
+                    filename: span.get_filename(),
+                    lines: span.get_lines(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Write the snapshot as JSON.
+    pub fn save(&self, writer: impl Write) -> Result<(), Error> {
+        serde_json::to_writer(writer, self)
+            .map_err(|err| Error::new(format!("Failed to write snapshot: {err}")))
+    }
+
+    /// Read a snapshot written by [CrateSnapshot::save]. This does not require the compiler.
+    pub fn load(reader: impl Read) -> Result<CrateSnapshot, Error> {
+        let snapshot: CrateSnapshot = serde_json::from_reader(reader)
+            .map_err(|err| Error::new(format!("Failed to read snapshot: {err}")))?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(Error::new(format!(
+                "Snapshot has version {}, but only version {SNAPSHOT_VERSION} is supported",
+                snapshot.version
+            )));
+        }
+        Ok(snapshot)
+    }
+
+    /// Find the recorded data of a definition.
+    pub fn def(&self, def_id: DefId) -> Option<&DefSnapshot> {
+        self.defs.iter().find(|def| def.def_id == def_id)
+    }
+
+    /// Find the recorded data of a type.
+    pub fn ty(&self, ty: Ty) -> Option<&TySnapshot> {
+        self.types.iter().find(|snapshot| snapshot.ty == ty)
+    }
+
+    /// Find the recorded definition of an ADT.
+    pub fn adt(&self, def: AdtDef) -> Option<&AdtSnapshot> {
+        self.adts.iter().find(|adt| adt.def == def)
+    }
+
+    /// Find the recorded location of a span.
+    pub fn span(&self, span: Span) -> Option<&SpanSnapshot> {
+        self.spans.iter().find(|snapshot| snapshot.span == span)
+    }
+}
+
+/// A set that keeps track of the insertion order.
+struct OrderedSet<T> {
+    items: Vec<T>,
+    seen: HashSet<T>,
+}
+
+impl<T> Default for OrderedSet<T> {
+    fn default() -> Self {
+        OrderedSet { items: Vec::new(), seen: HashSet::new() }
+    }
+}
+
+impl<T: Copy + Eq + Hash> OrderedSet<T> {
+    /// Insert a new element, and return whether it was not present before.
+    fn insert(&mut self, item: T) -> bool {
+        let is_new = self.seen.insert(item);
+        if is_new {
+            self.items.push(item);
+        }
+        is_new
+    }
+}
+
+/// Collect the handles that are reachable from the crate items.
+#[derive(Default)]
+struct Collector {
+    defs: OrderedSet<DefId>,
+    types: OrderedSet<Ty>,
+    adts: Vec<AdtSnapshot>,
+    allocs: OrderedSet<AllocId>,
+    instances: OrderedSet<Instance>,
+    spans: OrderedSet<Span>,
+}
+
+impl Collector {
+    fn add_def(&mut self, def_id: DefId) {
+        self.defs.insert(def_id);
+    }
+
+    fn add_span(&mut self, span: Span) {
+        self.spans.insert(span);
+    }
+
+    fn add_ty(&mut self, ty: Ty) {
+        let _ = ty.visit(&mut TyCollector(self));
+    }
+
+    fn add_args(&mut self, args: &GenericArgs) {
+        self.add(args);
+    }
+
+    /// Collect the handles reachable from a type-level value.
+    fn add(&mut self, value: &impl Visitable) {
+        let _ = value.visit(&mut TyCollector(self));
+    }
+
+    fn add_trait_ref(&mut self, trait_ref: &TraitRef) {
+        self.add_def(trait_ref.def_id.def_id());
+        self.add_args(trait_ref.args());
+    }
+
+    fn add_generics(&mut self, generics: &Generics) {
+        if let Some(parent) = generics.parent {
+            self.add_def(parent.def_id());
+        }
+        for param in &generics.params {
+            self.add_def(param.def_id.def_id());
+        }
+        if let Some(span) = generics.has_late_bound_regions {
+            self.add_span(span);
+        }
+    }
+
+    fn add_predicates(&mut self, predicates: &GenericPredicates) {
+        if let Some(parent) = predicates.parent {
+            self.add_def(parent.def_id());
+        }
+        for (predicate, span) in &predicates.predicates {
+            self.add_span(*span);
+            match predicate {
+                PredicateKind::Clause(clause) => match clause {
+                    ClauseKind::Trait(predicate) => self.add_trait_ref(&predicate.trait_ref),
+                    ClauseKind::RegionOutlives(_) => {}
+                    ClauseKind::TypeOutlives(OutlivesPredicate(ty, _)) => self.add_ty(*ty),
+                    ClauseKind::Projection(predicate) => {
+                        self.add_def(predicate.projection_term.def_id.def_id());
+                        self.add_args(&predicate.projection_term.args);
+                        self.add(&predicate.term);
+                    }
+                    ClauseKind::ConstArgHasType(constant, ty) => {
+                        self.add(constant);
+                        self.add_ty(*ty);
+                    }
+                    ClauseKind::WellFormed(arg) => self.add(arg),
+                    ClauseKind::ConstEvaluatable(constant) => self.add(constant),
+                },
+                PredicateKind::DynCompatible(def) => self.add_def(def.def_id()),
+                PredicateKind::SubType(SubtypePredicate { a, b })
+                | PredicateKind::Coerce(CoercePredicate { a, b }) => {
+                    self.add_ty(*a);
+                    self.add_ty(*b);
+                }
+                PredicateKind::ConstEquate(a, b) => {
+                    self.add(a);
+                    self.add(b);
+                }
+                PredicateKind::Ambiguous => {}
+                PredicateKind::AliasRelate(a, b, _) => {
+                    self.add(a);
+                    self.add(b);
+                }
+            }
+        }
+    }
+
+    /// Record the definition of an ADT, including the types of its fields.
+    fn add_adt(&mut self, def: AdtDef) {
+        if self.adts.iter().any(|adt| adt.def == def) {
+            return;
+        }
+        self.add_def(def.def_id());
+        // Record the ADT before visiting its fields, which may refer to it again.
+        self.adts.push(AdtSnapshot { def, kind: def.kind(), variants: Vec::new() });
+        let variants = def
+            .variants_iter()
+            .map(|variant| VariantSnapshot {
+                name: variant.name(),
+                fields: variant
+                    .fields()
+                    .into_iter()
+                    .map(|field| {
+                        let ty = field.ty();
+                        self.add_def(field.def);
+                        self.add_ty(ty);
+                        FieldSnapshot { def: field.def, name: field.name, ty }
+                    })
+                    .collect(),
+            })
+            .collect();
+        self.adts.iter_mut().find(|adt| adt.def == def).unwrap().variants = variants;
+    }
+
+    fn add_allocation(&mut self, allocation: &Allocation) {
+        for (_, prov) in &allocation.provenance.ptrs {
+            let id = prov.0;
+            if !self.allocs.insert(id) {
+                continue;
+            }
+            match GlobalAlloc::from(id) {
+                GlobalAlloc::Function(instance) => {
+                    if self.instances.insert(instance) {
+                        self.add_def(instance.def.def_id());
+                        self.add_args(&instance.args());
+                        self.add_ty(instance.ty());
+                    }
+                }
+                GlobalAlloc::VTable(ty, trait_ref) => {
+                    self.add_ty(ty);
+                    if let Some(trait_ref) = trait_ref {
+                        self.add_def(trait_ref.value.def_id.def_id());
+                        self.add_args(&trait_ref.value.generic_args);
+                    }
+                }
+                GlobalAlloc::Static(def) => self.add_def(def.def_id()),
+                GlobalAlloc::Memory(allocation) => self.add_allocation(&allocation),
+            }
+        }
+    }
+}
+
+impl MirVisitor for Collector {
+    fn visit_ty(&mut self, ty: &Ty, location: Location) {
+        self.add_ty(*ty);
+        self.add_span(location.span());
+    }
+
+    fn visit_span(&mut self, span: &Span) {
+        self.add_span(*span);
+    }
+
+    fn visit_mir_const(&mut self, constant: &MirConst, location: Location) {
+        if let ConstantKind::Allocated(allocation) = constant.kind() {
+            self.add_allocation(allocation);
+        }
+        let _ = constant.visit(&mut TyCollector(self));
+        self.super_mir_const(constant, location)
+    }
+
+    fn visit_ty_const(&mut self, constant: &TyConst, _location: Location) {
+        let _ = constant.visit(&mut TyCollector(self));
+    }
+}
+
+/// Collect the types, definitions and allocations that are reachable from a type.
+struct TyCollector<'a>(&'a mut Collector);
+
+impl Visitor for TyCollector<'_> {
+    type Break = ();
+
+    fn visit_ty(&mut self, ty: &Ty) -> ControlFlow<Self::Break> {
+        if !self.0.types.insert(*ty) {
+            return ControlFlow::Continue(());
+        }
+        if let TyKind::RigidTy(rigid) = ty.kind() {
+            match rigid {
+                RigidTy::Adt(def, _) => self.0.add_adt(def),
+                RigidTy::Foreign(def) => self.0.add_def(def.def_id()),
+                RigidTy::FnDef(def, _) => self.0.add_def(def.def_id()),
+                RigidTy::Closure(def, _) => self.0.add_def(def.def_id()),
+                RigidTy::Coroutine(def, _, _) => self.0.add_def(def.def_id()),
+                RigidTy::CoroutineWitness(def, _) => self.0.add_def(def.def_id()),
+                _ => {}
+            }
+        }
+        ty.super_visit(self)
+    }
+
+    fn visit_const(&mut self, c: &TyConst) -> ControlFlow<Self::Break> {
+        if let TyConstKind::Value(_, allocation) = &c.kind {
+            self.0.add_allocation(allocation);
+        }
+        c.super_visit(self)
+    }
+}
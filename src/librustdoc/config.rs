@@ -300,6 +300,9 @@ pub(crate) struct RenderOptions {
     pub(crate) include_parts_dir: Vec<PathToParts>,
     /// Where to write crate-info
     pub(crate) parts_out_dir: Option<PathToParts>,
+    /// Where the fingerprints of the inputs of the rendered pages are stored, to reuse the pages
+    /// whose inputs did not change in the next run.
+    pub(crate) render_cache_dir: Option<PathBuf>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -758,6 +761,7 @@ fn println_condition(condition: Condition) {
         let extern_html_root_takes_precedence =
             matches.opt_present("extern-html-root-takes-precedence");
         let html_no_source = matches.opt_present("html-no-source");
+        let render_cache_dir = matches.opt_str("render-cache-dir").map(PathBuf::from);
         let should_merge = match parse_merge(matches) {
             Ok(result) => result,
             Err(e) => dcx.fatal(format!("--merge option error: {e}")),
@@ -852,6 +856,7 @@ fn println_condition(condition: Condition) {
             should_merge,
             include_parts_dir,
             parts_out_dir,
+            render_cache_dir,
         };
         Some((input, options, render_options))
     }
@@ -16,7 +16,9 @@
 
 use super::print_item::{full_path, item_path, print_item};
 use super::sidebar::{ModuleLike, Sidebar, print_sidebar, sidebar_module_like};
-use super::{AllTypes, LinkFromSrc, StylePath, collect_spans_and_sources, scrape_examples_help};
+use super::{
+    AllTypes, LinkFromSrc, RenderCache, StylePath, collect_spans_and_sources, scrape_examples_help,
+};
 use crate::clean::types::ExternalLocation;
 use crate::clean::utils::has_doc_flag;
 use crate::clean::{self, ExternalCrate};
@@ -132,6 +134,10 @@ pub(crate) struct SharedContext<'tcx> {
     /// Controls whether we read / write to cci files in the doc root. Defaults read=true,
     /// write=true
     should_merge: ShouldMerge,
+    /// `None` by default, depends on the `--render-cache-dir` option flag. If this field is set to
+    /// `Some(...)`, the files whose inputs didn't change since the previous run are not rendered
+    /// again.
+    pub(crate) render_cache: Option<RenderCache>,
 }
 
 impl SharedContext<'_> {
@@ -173,6 +179,25 @@ pub(super) fn root_path(&self) -> String {
         "../".repeat(self.current.len())
     }
 
+    /// Returns whether the page of `it` at `dst`, rendered by the previous run, can be reused as
+    /// is because its inputs didn't change. Redirect pages are always rendered again, as they
+    /// record the redirections.
+    fn reuse_rendered_page(&self, it: &clean::Item, dst: &Path) -> bool {
+        let Some(render_cache) = &self.shared.render_cache else {
+            return false;
+        };
+        if self.render_redirect_pages || it.is_stripped() {
+            return false;
+        }
+        let fingerprint = render_cache.page_fingerprint(
+            self.tcx(),
+            &self.current,
+            self.is_inside_inlined_module,
+            it,
+        );
+        render_cache.reuse(dst, fingerprint)
+    }
+
     fn render_item(&mut self, it: &clean::Item, is_module: bool) -> String {
         let mut render_redirect_pages = self.render_redirect_pages;
         // If the item is stripped but inlined, links won't point to the item so no need to generate
@@ -531,6 +556,11 @@ fn init(
             generate_link_to_definition,
         );
 
+        let render_cache = md_opts
+            .render_cache_dir
+            .as_deref()
+            .map(|dir| RenderCache::load(dir, krate.name(tcx), tcx, &md_opts, &cache));
+
         let (sender, receiver) = channel();
         let scx = SharedContext {
             tcx,
@@ -554,6 +584,7 @@ fn init(
             cache,
             call_locations,
             should_merge: options.should_merge,
+            render_cache,
         };
 
         let dst = output;
@@ -762,6 +793,9 @@ fn after_krate(&mut self) -> Result<(), Error> {
         let nb_errors = self.shared.errors.iter().map(|err| self.tcx().dcx().err(err)).count();
         if nb_errors > 0 {
             Err(Error::new(io::Error::new(io::ErrorKind::Other, "I/O error"), ""))
+        } else if let Some(render_cache) = &self.shared.render_cache {
+            // All the files have been written, their fingerprints can be reused.
+            render_cache.save()
         } else {
             Ok(())
         }
@@ -784,12 +818,12 @@ fn mod_item_in(&mut self, item: &clean::Item) -> Result<(), Error> {
 
         info!("Recursing into {}", self.dst.display());
 
-        if !item.is_stripped() {
+        let joint_dst = self.dst.join("index.html");
+        if !item.is_stripped() && !self.reuse_rendered_page(item, &joint_dst) {
             let buf = self.render_item(item, true);
             // buf will be empty if the module is stripped and there is no redirect for it
             if !buf.is_empty() {
                 self.shared.ensure_dir(&self.dst)?;
-                let joint_dst = self.dst.join("index.html");
                 self.shared.fs.write(joint_dst, buf)?;
             }
         }
@@ -840,15 +874,19 @@ fn item(&mut self, item: clean::Item) -> Result<(), Error> {
             self.render_redirect_pages = item.is_stripped();
         }
 
-        let buf = self.render_item(&item, false);
+        let name = item.name.as_ref().unwrap();
+        let item_type = item.type_();
+        let file_name = &item_path(item_type, name.as_str());
+        let joint_dst = self.dst.join(file_name);
+
+        let reused = self.reuse_rendered_page(&item, &joint_dst);
+        let buf = if reused { String::new() } else { self.render_item(&item, false) };
         // buf will be empty if the item is stripped and there is no redirect for it
-        if !buf.is_empty() {
-            let name = item.name.as_ref().unwrap();
-            let item_type = item.type_();
-            let file_name = &item_path(item_type, name.as_str());
-            self.shared.ensure_dir(&self.dst)?;
-            let joint_dst = self.dst.join(file_name);
-            self.shared.fs.write(joint_dst, buf)?;
+        if reused || !buf.is_empty() {
+            if !reused {
+                self.shared.ensure_dir(&self.dst)?;
+                self.shared.fs.write(joint_dst, buf)?;
+            }
 
             if !self.render_redirect_pages {
                 self.shared.all.borrow_mut().append(full_path(self, &item), &item_type);
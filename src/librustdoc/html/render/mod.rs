@@ -31,6 +31,7 @@
 mod context;
 mod ordered_json;
 mod print_item;
+mod render_cache;
 pub(crate) mod sidebar;
 mod sorted_template;
 mod span_map;
@@ -60,6 +61,7 @@
 use tracing::{debug, info};
 
 pub(crate) use self::context::*;
+pub(crate) use self::render_cache::RenderCache;
 pub(crate) use self::span_map::{LinkFromSrc, collect_spans_and_sources};
 pub(crate) use self::write_shared::*;
 use crate::clean::{self, ItemId, RenderedLink};
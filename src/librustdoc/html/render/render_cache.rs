@@ -0,0 +1,373 @@
+//! Reuse of the files rendered by a previous run, enabled with `--render-cache-dir`.
+//!
+//! Rendering is a function of the cleaned items, the [`Cache`], the render options and the sources
+//! of the documented crate. For every page it writes, rustdoc records a fingerprint of the inputs
+//! it was rendered from, and the next run doesn't render the pages whose fingerprint is unchanged,
+//! as long as they are still in the output directory.
+//!
+//! The inputs are split in two:
+//!
+//! - the ones any page may depend on, fingerprinted once per run: the options, the crate hashes of
+//!   the dependencies, the sources of the local crate, and the documentation of the impls and
+//!   traits, which is shown on the pages of other items. The text of the doc comments is not part
+//!   of the sources, only the number of lines they span, since it shifts the lines of the source
+//!   links. The rest of the [`Cache`] is derived from these inputs.
+//! - the ones of a single page: the documentation of its item and of the items it contains, e.g.,
+//!   fields, methods, or for a module its children.
+//!
+//! The fingerprints are computed with a [`StableHasher`] over these explicit inputs, where items
+//! are identified by their `DefPathHash`, which unlike their `DefId` is stable across runs.
+//!
+//! Editing a doc comment, without changing the lines of the code after it, thus only re-renders
+//! the pages showing it. Source pages only depend on the options and their source file, unless
+//! they link to definitions.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rustc_data_structures::fingerprint::Fingerprint;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::stable_hasher::{HashStable, StableHasher};
+use rustc_hir::def_id::LOCAL_CRATE;
+use rustc_lexer::TokenKind;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::{HashStableContext, Symbol};
+
+use crate::clean::{self, ExternalLocation, ItemId, TypeAliasInnerType};
+use crate::config::RenderOptions;
+use crate::error::Error;
+use crate::formats::cache::Cache;
+use crate::try_err;
+use crate::visit::DocVisitor;
+
+/// The fingerprints of the inputs of the files rendered by the previous run and by this one.
+pub(crate) struct RenderCache {
+    /// Where the fingerprints of this crate's files are stored.
+    path: PathBuf,
+    /// The fingerprint of the options.
+    options: Fingerprint,
+    /// The fingerprint of the inputs common to all pages.
+    global: Fingerprint,
+    /// Whether source pages link to definitions, and thus depend on the inputs of all pages.
+    link_to_definition: bool,
+    /// The fingerprints recorded by the previous run, by path of the rendered file.
+    previous: FxHashMap<String, String>,
+    /// The fingerprints recorded by this run.
+    current: RefCell<BTreeMap<String, String>>,
+}
+
+impl RenderCache {
+    /// Loads the fingerprints recorded by the previous run for `crate_name`, if any, and
+    /// fingerprints the inputs common to all pages of this run.
+    pub(crate) fn load(
+        dir: &Path,
+        crate_name: Symbol,
+        tcx: TyCtxt<'_>,
+        options: &RenderOptions,
+        cache: &Cache,
+    ) -> Self {
+        let path = dir.join(format!("{crate_name}.json"));
+
+        // Missing or unreadable fingerprints only mean that nothing is reused. They are removed
+        // until this run completes, so that files overwritten by a run that doesn't complete are
+        // not reused by the next one.
+        let previous = fs::read_to_string(&path)
+            .ok()
+            .and_then(|fingerprints| serde_json::from_str(&fingerprints).ok())
+            .unwrap_or_default();
+        let _ = fs::remove_file(&path);
+
+        let options_fingerprint = tcx.with_stable_hashing_context(|hcx| {
+            let mut hasher = InputsHasher { hcx, hasher: StableHasher::new() };
+            hash_options(&mut hasher, tcx, options);
+            hasher.finish()
+        });
+        let global = tcx.with_stable_hashing_context(|hcx| {
+            let mut hasher = InputsHasher { hcx, hasher: StableHasher::new() };
+            hasher.hash(options_fingerprint);
+            hash_global_inputs(&mut hasher, tcx, cache);
+            hasher.finish()
+        });
+        RenderCache {
+            path,
+            options: options_fingerprint,
+            global,
+            link_to_definition: options.generate_link_to_definition,
+            previous,
+            current: Default::default(),
+        }
+    }
+
+    /// Returns the fingerprint of the page of `item`, in addition to the inputs common to all
+    /// pages.
+    pub(crate) fn page_fingerprint(
+        &self,
+        tcx: TyCtxt<'_>,
+        current: &[Symbol],
+        is_inside_inlined_module: bool,
+        item: &clean::Item,
+    ) -> Fingerprint {
+        tcx.with_stable_hashing_context(|hcx| {
+            let mut hasher = InputsHasher { hcx, hasher: StableHasher::new() };
+            hasher.hash((self.global, current, is_inside_inlined_module));
+            hasher.visit_item(item);
+            hasher.finish()
+        })
+    }
+
+    /// Returns the fingerprint of the shard of the search index descriptions at `index`.
+    pub(crate) fn search_desc_fingerprint(&self, index: usize, contents: &str) -> Fingerprint {
+        let mut hasher = StableHasher::new();
+        (self.global, index, contents).hash_stable(&mut (), &mut hasher);
+        hasher.finish()
+    }
+
+    /// Returns the fingerprint of the source page of the file at `path`.
+    pub(crate) fn source_fingerprint(&self, path: &Path, contents: &str) -> Fingerprint {
+        let common = if self.link_to_definition { self.global } else { self.options };
+        let mut hasher = StableHasher::new();
+        (common, path, contents).hash_stable(&mut (), &mut hasher);
+        hasher.finish()
+    }
+
+    /// Records that the file at `dst` is rendered from inputs with the given fingerprint. Returns
+    /// whether the file rendered by the previous run from the same inputs is still there, in which
+    /// case it doesn't need to be rendered again.
+    pub(crate) fn reuse(&self, dst: &Path, fingerprint: Fingerprint) -> bool {
+        let dst_key = dst.to_string_lossy().into_owned();
+        let fingerprint = fingerprint.to_hex();
+        let reusable = self.previous.get(&dst_key) == Some(&fingerprint) && dst.exists();
+        self.current.borrow_mut().insert(dst_key, fingerprint);
+        reusable
+    }
+
+    /// Stores the fingerprints recorded by this run, for the next one. This must only be called
+    /// once all the files have been successfully written.
+    pub(crate) fn save(&self) -> Result<(), Error> {
+        let dir = self.path.parent().unwrap();
+        try_err!(fs::create_dir_all(dir), dir);
+        let fingerprints = serde_json::to_string(&*self.current.borrow()).unwrap();
+        try_err!(fs::write(&self.path, fingerprints), &self.path);
+        Ok(())
+    }
+}
+
+/// Hashes the inputs of the rendered files. Visiting an item hashes its identity and its
+/// documentation, and those of the items it contains.
+struct InputsHasher<Hcx> {
+    hcx: Hcx,
+    hasher: StableHasher,
+}
+
+impl<Hcx: HashStableContext> InputsHasher<Hcx> {
+    fn hash(&mut self, value: impl HashStable<Hcx>) {
+        value.hash_stable(&mut self.hcx, &mut self.hasher);
+    }
+
+    /// Hashes the elements of an unordered collection, independently of their order.
+    fn hash_unordered<T>(
+        &mut self,
+        elements: impl Iterator<Item = T>,
+        mut hash_element: impl FnMut(&mut Self, T),
+    ) {
+        let mut fingerprints: Vec<Fingerprint> = elements
+            .map(|element| {
+                let hasher = std::mem::replace(&mut self.hasher, StableHasher::new());
+                hash_element(self, element);
+                std::mem::replace(&mut self.hasher, hasher).finish()
+            })
+            .collect();
+        fingerprints.sort_unstable();
+        self.hash(fingerprints);
+    }
+
+    fn hash_item_id(&mut self, item_id: ItemId) {
+        self.hash(std::mem::discriminant(&item_id));
+        match item_id {
+            ItemId::DefId(def_id) => self.hash(def_id),
+            ItemId::Auto { trait_, for_ } => self.hash((trait_, for_)),
+            ItemId::Blanket { impl_id, for_ } => self.hash((impl_id, for_)),
+        }
+    }
+
+    fn finish(self) -> Fingerprint {
+        self.hasher.finish()
+    }
+}
+
+impl<'a, Hcx: HashStableContext> DocVisitor<'a> for InputsHasher<Hcx> {
+    fn visit_item(&mut self, item: &'a clean::Item) {
+        self.hash_item_id(item.item_id);
+        self.hash(item.name);
+        self.hash(item.attrs.doc_strings.len());
+        for fragment in &item.attrs.doc_strings {
+            self.hash((fragment.item_id, fragment.doc, fragment.indent));
+        }
+
+        // The fields or variants of the aliased type are shown on the page of a type alias.
+        if let clean::TypeAliasItem(type_alias) = &item.kind
+            && let Some(inner_type) = &type_alias.inner_type
+        {
+            match inner_type {
+                TypeAliasInnerType::Enum { variants, .. } => {
+                    variants.iter().for_each(|variant| self.visit_item(variant))
+                }
+                TypeAliasInnerType::Union { fields }
+                | TypeAliasInnerType::Struct { fields, .. } => {
+                    fields.iter().for_each(|field| self.visit_item(field))
+                }
+            }
+        }
+
+        self.visit_item_recur(item);
+    }
+}
+
+/// Hashes the rustdoc version, the compiler options and the render options that affect the
+/// rendered files.
+fn hash_options<Hcx: HashStableContext>(
+    hasher: &mut InputsHasher<Hcx>,
+    tcx: TyCtxt<'_>,
+    options: &RenderOptions,
+) {
+    hasher.hash(rustc_interface::util::version_str!().unwrap_or("unknown version"));
+    hasher.hash(tcx.sess.opts.dep_tracking_hash(false));
+    hasher.hash_unordered(tcx.sess.psess.config.iter(), |hasher, &(name, value)| {
+        hasher.hash((name, value))
+    });
+
+    let RenderOptions {
+        output: _,
+        external_html,
+        id_map: _,
+        playground_url,
+        module_sorting,
+        themes,
+        extension_css,
+        extern_html_root_urls,
+        extern_html_root_takes_precedence,
+        default_settings,
+        resource_suffix,
+        enable_index_page,
+        index_page,
+        static_root_path,
+        markdown_no_toc: _,
+        markdown_css: _,
+        markdown_playground_url: _,
+        document_private,
+        document_hidden,
+        generate_redirect_map,
+        show_type_layout,
+        unstable_features,
+        emit,
+        generate_link_to_definition,
+        call_locations,
+        no_emit_shared,
+        html_no_source,
+        output_to_stdout: _,
+        should_merge: _,
+        include_parts_dir: _,
+        parts_out_dir: _,
+        render_cache_dir: _,
+    } = options;
+    hasher.hash((
+        &external_html.in_header,
+        &external_html.before_content,
+        &external_html.after_content,
+    ));
+    hasher.hash((playground_url, std::mem::discriminant(module_sorting)));
+    hasher.hash(themes.len());
+    for theme in themes {
+        hasher.hash(&theme.path);
+    }
+    hasher.hash((extension_css, extern_html_root_urls, extern_html_root_takes_precedence));
+    hasher.hash((default_settings, resource_suffix, enable_index_page, index_page));
+    hasher.hash((static_root_path, document_private, document_hidden, generate_redirect_map));
+    hasher.hash((show_type_layout, unstable_features.is_nightly_build()));
+    hasher.hash(emit.len());
+    for emit_type in emit {
+        hasher.hash(std::mem::discriminant(emit_type));
+    }
+    hasher.hash((generate_link_to_definition, no_emit_shared, html_no_source));
+
+    // The examples scraped from other crates.
+    hasher.hash(call_locations.len());
+    for (def_path_hash, fn_call_locations) in call_locations {
+        hasher.hash((def_path_hash, fn_call_locations.len()));
+        for (path, call_data) in fn_call_locations {
+            hasher.hash((path, &call_data.url, &call_data.display_name));
+            hasher.hash((call_data.edition, call_data.is_bin, call_data.locations.len()));
+            for location in &call_data.locations {
+                for range in [&location.call_expr, &location.call_ident, &location.enclosing_item] {
+                    hasher.hash((range.byte_span, range.line_span));
+                }
+            }
+        }
+    }
+}
+
+/// Hashes the inputs common to all pages, except for the options.
+fn hash_global_inputs<Hcx: HashStableContext>(
+    hasher: &mut InputsHasher<Hcx>,
+    tcx: TyCtxt<'_>,
+    cache: &Cache,
+) {
+    for &cnum in tcx.crates(()) {
+        hasher.hash(tcx.crate_hash(cnum).as_u128());
+    }
+    hash_local_code(hasher, tcx);
+
+    // Where the documentation of the other crates is depends on the output directory.
+    for (&cnum, location) in &cache.extern_locations {
+        hasher.hash(cnum.as_def_id());
+        match location {
+            ExternalLocation::Remote(url) => hasher.hash((0u8, url)),
+            ExternalLocation::Local => hasher.hash(1u8),
+            ExternalLocation::Unknown => hasher.hash(2u8),
+        }
+    }
+
+    // The documentation of the impls is shown on the pages of the types and traits they are
+    // about, and the documentation of the traits on the pages of their implementors.
+    hasher.hash_unordered(cache.impls.iter(), |hasher, (&def_id, impls)| {
+        hasher.hash(def_id);
+        impls.iter().for_each(|impl_| hasher.visit_item(&impl_.impl_item));
+    });
+    hasher.hash_unordered(cache.implementors.iter(), |hasher, (&def_id, impls)| {
+        hasher.hash(def_id);
+        impls.iter().for_each(|impl_| hasher.visit_item(&impl_.impl_item));
+    });
+    hasher.hash_unordered(cache.traits.iter(), |hasher, (&def_id, trait_)| {
+        hasher.hash(def_id);
+        trait_.items.iter().for_each(|item| hasher.visit_item(item));
+    });
+}
+
+/// Hashes the sources of the local crate, except for the text of the doc comments: the
+/// documentation of an item is part of the inputs of the pages showing it.
+fn hash_local_code<Hcx: HashStableContext>(hasher: &mut InputsHasher<Hcx>, tcx: TyCtxt<'_>) {
+    for source_file in tcx.sess.source_map().files().iter() {
+        if source_file.cnum != LOCAL_CRATE {
+            continue;
+        }
+        let Some(src) = &source_file.src else {
+            continue;
+        };
+
+        hasher.hash(source_file.stable_id);
+        let mut pos = 0;
+        for token in rustc_lexer::tokenize(src) {
+            let text = &src[pos..pos + token.len as usize];
+            pos += token.len as usize;
+            match token.kind {
+                TokenKind::LineComment { doc_style: Some(_) }
+                | TokenKind::BlockComment { doc_style: Some(_), .. } => {
+                    hasher.hash((true, text.lines().count()));
+                }
+                _ => hasher.hash((false, text)),
+            }
+        }
+    }
+}
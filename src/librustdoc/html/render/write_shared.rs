@@ -225,23 +225,55 @@ fn write_search_desc(
     let crate_name = krate.name(cx.tcx()).to_string();
     let encoded_crate_name = OrderedJson::serialize(&crate_name).unwrap();
     let path = PathBuf::from_iter([&cx.dst, Path::new("search.desc"), Path::new(&crate_name)]);
-    if path.exists() {
-        try_err!(fs::remove_dir_all(&path), &path);
-    }
-    for (i, (_, part)) in search_desc.iter().enumerate() {
-        let filename = static_files::suffix_path(
-            &format!("{crate_name}-desc-{i}-.js"),
-            &cx.shared.resource_suffix,
-        );
-        let path = path.join(filename);
-        let part = OrderedJson::serialize(&part).unwrap();
-        let part = format!("searchState.loadedDescShard({encoded_crate_name}, {i}, {part})");
-        create_parents(&path)?;
-        try_err!(fs::write(&path, part), &path);
+    let filenames: Vec<_> = (0..search_desc.len())
+        .map(|i| {
+            static_files::suffix_path(
+                &format!("{crate_name}-desc-{i}-.js"),
+                &cx.shared.resource_suffix,
+            )
+        })
+        .collect();
+    if let Some(render_cache) = &cx.shared.render_cache {
+        // Only remove the shards of the previous run that are not written again, the others may
+        // be reused.
+        if path.exists() {
+            for entry in try_err!(fs::read_dir(&path), &path) {
+                let entry = try_err!(entry, &path);
+                if !filenames.iter().any(|filename| *filename == entry.file_name()) {
+                    try_err!(fs::remove_file(entry.path()), &entry.path());
+                }
+            }
+        }
+        for (i, ((_, part), filename)) in search_desc.iter().zip(&filenames).enumerate() {
+            let path = path.join(filename);
+            if !render_cache.reuse(&path, render_cache.search_desc_fingerprint(i, part)) {
+                write_search_desc_shard(&path, &encoded_crate_name, i, part)?;
+            }
+        }
+    } else {
+        if path.exists() {
+            try_err!(fs::remove_dir_all(&path), &path);
+        }
+        for (i, ((_, part), filename)) in search_desc.iter().zip(&filenames).enumerate() {
+            write_search_desc_shard(&path.join(filename), &encoded_crate_name, i, part)?;
+        }
     }
     Ok(())
 }
 
+fn write_search_desc_shard(
+    path: &Path,
+    encoded_crate_name: &OrderedJson,
+    i: usize,
+    part: &str,
+) -> Result<(), Error> {
+    let part = OrderedJson::serialize(&part).unwrap();
+    let part = format!("searchState.loadedDescShard({encoded_crate_name}, {i}, {part})");
+    create_parents(path)?;
+    try_err!(fs::write(path, part), path);
+    Ok(())
+}
+
 /// Contains pre-rendered contents to insert into the CCI template
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub(crate) struct CrateInfo {
@@ -233,6 +233,13 @@ fn emit_source(
 
         cur.push(&fname);
 
+        if let Some(render_cache) = &shared.render_cache
+            && render_cache.reuse(&cur, render_cache.source_fingerprint(&p, contents))
+        {
+            self.emitted_local_sources.insert(p);
+            return Ok(());
+        }
+
         let title = format!("{} - source", src_fname.to_string_lossy());
         let desc =
             format!("Source of the Rust file `{}`.", filename.prefer_remapped_unconditionaly());
@@ -631,6 +631,14 @@ fn opts() -> Vec<RustcOptGroup> {
                 "path/to/doc.parts/<crate-name>",
             )
         }),
+        unstable("render-cache-dir", |o| {
+            o.optopt(
+                "",
+                "render-cache-dir",
+                "Reuses the pages rendered by a previous run when their inputs did not change, tracked in the provided directory",
+                "PATH",
+            )
+        }),
         // deprecated / removed options
         unstable("disable-minification", |o| o.optflagmulti("", "disable-minification", "removed")),
         stable("plugin-path", |o| {
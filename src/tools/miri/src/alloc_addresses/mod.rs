@@ -253,6 +253,26 @@ fn addr_from_alloc_id(
                 // First time we're looking for the absolute address of this allocation.
                 let base_addr =
                     self.addr_from_alloc_id_uncached(global_state, alloc_id, memory_kind)?;
+                let base_addr = if ecx.machine.native_lib.is_some() {
+                    base_addr
+                } else {
+                    // When replaying, we prefer the recorded address. To not collide with any
+                    // other allocation, it has to be in the part of the address space we have not
+                    // handed out yet.
+                    let (size, align, _) = ecx.get_alloc_info(alloc_id);
+                    ecx.machine.recorder.address(base_addr, |addr| {
+                        let Some(end) = addr.checked_add(max(size.bytes(), 1)) else {
+                            return false;
+                        };
+                        let usable = addr % align.bytes() == 0
+                            && addr >= global_state.next_base_addr
+                            && end <= ecx.target_usize_max();
+                        if usable {
+                            global_state.next_base_addr = end;
+                        }
+                        usable
+                    })
+                };
                 trace!("Assigning base address {:#x} to allocation {:?}", base_addr, alloc_id);
 
                 // Store address in cache.
@@ -25,10 +25,13 @@
 
 use std::env::{self, VarError};
 use std::num::NonZero;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-use miri::{BacktraceStyle, BorrowTrackerMethod, ProvenanceMode, RetagFields, ValidationMode};
+use miri::{
+    BacktraceStyle, BorrowTrackerMethod, ProvenanceMode, Recording, RetagFields, Schedule,
+    ValidationMode,
+};
 use rustc_data_structures::sync::Lrc;
 use rustc_driver::Compilation;
 use rustc_hir::def_id::LOCAL_CRATE;
@@ -86,7 +89,6 @@ fn after_analysis<'tcx>(
                 tcx.dcx().fatal("miri only makes sense on bin crates");
             }
 
-            let (entry_def_id, entry_type) = entry_fn(tcx);
             let mut config = self.miri_config.clone();
 
             // Add filename to `miri` arguments.
@@ -111,7 +113,14 @@ fn after_analysis<'tcx>(
                     optimizations is usually marginal at best.");
             }
 
-            if let Some(return_code) = miri::eval_entry(tcx, entry_def_id, entry_type, config) {
+            // Fuzz targets usually have no `main`, so only look for one when we need it.
+            let return_code = if config.fuzz_corpus.is_some() {
+                miri::eval_fuzz_corpus(tcx, config)
+            } else {
+                let (entry_def_id, entry_type) = entry_fn(tcx);
+                miri::eval_entry(tcx, entry_def_id, entry_type, config)
+            };
+            if let Some(return_code) = return_code {
                 std::process::exit(
                     i32::try_from(return_code).expect("Return value was too large!"),
                 );
@@ -595,6 +604,31 @@ fn main() {
         } else if let Some(param) = arg.strip_prefix("-Zmiri-preemption-rate=") {
             miri_config.preemption_rate =
                 parse_rate(param).unwrap_or_else(|err| show_error!("-Zmiri-preemption-rate {err}"));
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-pct=") {
+            let depth = param
+                .parse::<u32>()
+                .unwrap_or_else(|err| show_error!("-Zmiri-pct requires a `u32`: {}", err));
+            if depth == 0 {
+                show_error!("-Zmiri-pct requires a depth of at least 1");
+            }
+            miri_config.pct_depth = Some(depth);
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-pct-scheduling-points=") {
+            miri_config.pct_scheduling_points = param.parse::<u64>().unwrap_or_else(|err| {
+                show_error!("-Zmiri-pct-scheduling-points requires a `u64`: {}", err)
+            });
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-replay-schedule=") {
+            let schedule = param
+                .parse::<Schedule>()
+                .unwrap_or_else(|err| show_error!("-Zmiri-replay-schedule: {err}"));
+            miri_config.replay_schedule = Some(schedule);
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-record=") {
+            miri_config.record = Some(PathBuf::from(param));
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-replay=") {
+            let recording = Recording::load(Path::new(param))
+                .unwrap_or_else(|err| show_error!("-Zmiri-replay: cannot load `{param}`: {err}"));
+            miri_config.replay = Some(recording);
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-fuzz-corpus=") {
+            miri_config.fuzz_corpus = Some(PathBuf::from(param));
         } else if arg == "-Zmiri-report-progress" {
             // This makes it take a few seconds between progress reports on my laptop.
             miri_config.report_progress = Some(1_000_000);
@@ -659,6 +693,33 @@ fn main() {
             "-Zmiri-unique-is-unique only has an effect when -Zmiri-tree-borrows is also used"
         );
     }
+    // A replayed schedule already fixes all scheduling decisions.
+    if miri_config.pct_depth.is_some() && miri_config.replay_schedule.is_some() {
+        show_error!("-Zmiri-pct and -Zmiri-replay-schedule cannot be used together");
+    }
+    // The seed and the schedule of a replayed execution are taken from the recording.
+    if let Some(recording) = &miri_config.replay {
+        if miri_config.record.is_some() {
+            show_error!("-Zmiri-record and -Zmiri-replay cannot be used together");
+        }
+        if miri_config.replay_schedule.is_some() {
+            show_error!("-Zmiri-replay-schedule and -Zmiri-replay cannot be used together");
+        }
+        if miri_config.seed.is_some_and(|seed| seed != recording.seed) {
+            show_error!(
+                "-Zmiri-seed does not match the seed of the recording passed to -Zmiri-replay"
+            );
+        }
+        miri_config.seed = Some(recording.seed);
+    }
+    // A recording describes a single execution, but fuzzing performs one per input.
+    if miri_config.fuzz_corpus.is_some()
+        && (miri_config.record.is_some() || miri_config.replay.is_some())
+    {
+        show_error!(
+            "-Zmiri-fuzz-corpus cannot be used together with -Zmiri-record or -Zmiri-replay"
+        );
+    }
     // Tree Borrows + permissive provenance does not work.
     if miri_config.provenance_mode == ProvenanceMode::Permissive
         && matches!(miri_config.borrow_tracker, Some(BorrowTrackerMethod::TreeBorrows))
@@ -1,5 +1,8 @@
 use std::cell::Cell;
-use std::time::{Duration, Instant as StdInstant};
+use std::rc::Rc;
+use std::time::{Duration, Instant as StdInstant, SystemTime};
+
+use crate::Recorder;
 
 /// When using a virtual clock, this defines how many nanoseconds we pretend are passing for each
 /// basic block.
@@ -44,22 +47,23 @@ pub fn duration_since(&self, earlier: Instant) -> Duration {
             (
                 InstantKind::Virtual { nanoseconds },
                 InstantKind::Virtual { nanoseconds: earlier },
-            ) => {
-                let duration = nanoseconds.saturating_sub(earlier);
-                // `Duration` does not provide a nice constructor from a `u128` of nanoseconds,
-                // so we have to implement this ourselves.
-                // It is possible for second to overflow because u64::MAX < (u128::MAX / 1e9).
-                // It will be saturated to u64::MAX seconds if the value after division exceeds u64::MAX.
-                let seconds = u64::try_from(duration / 1_000_000_000).unwrap_or(u64::MAX);
-                // It is impossible for nanosecond to overflow because u32::MAX > 1e9.
-                let nanosecond = u32::try_from(duration.wrapping_rem(1_000_000_000)).unwrap();
-                Duration::new(seconds, nanosecond)
-            }
+            ) => duration_from_nanos(nanoseconds.saturating_sub(earlier)),
             _ => panic!("all `Instant` must be of the same kind"),
         }
     }
 }
 
+fn duration_from_nanos(nanoseconds: u128) -> Duration {
+    // `Duration` does not provide a nice constructor from a `u128` of nanoseconds,
+    // so we have to implement this ourselves.
+    // It is possible for second to overflow because u64::MAX < (u128::MAX / 1e9).
+    // It will be saturated to u64::MAX seconds if the value after division exceeds u64::MAX.
+    let seconds = u64::try_from(nanoseconds / 1_000_000_000).unwrap_or(u64::MAX);
+    // It is impossible for nanosecond to overflow because u32::MAX > 1e9.
+    let nanosecond = u32::try_from(nanoseconds.wrapping_rem(1_000_000_000)).unwrap();
+    Duration::new(seconds, nanosecond)
+}
+
 /// A monotone clock used for `Instant` simulation.
 #[derive(Debug)]
 pub struct Clock {
@@ -72,6 +76,8 @@ enum ClockKind {
         /// The "epoch" for this machine's monotone clock:
         /// the moment we consider to be time = 0.
         epoch: StdInstant,
+        /// Records the readings of the host clocks, or replays them.
+        recorder: Rc<Recorder>,
     },
     Virtual {
         /// The "current virtual time".
@@ -81,9 +87,9 @@ enum ClockKind {
 
 impl Clock {
     /// Create a new clock based on the availability of communication with the host.
-    pub fn new(communicate: bool) -> Self {
+    pub fn new(communicate: bool, recorder: Rc<Recorder>) -> Self {
         let kind = if communicate {
-            ClockKind::Host { epoch: StdInstant::now() }
+            ClockKind::Host { epoch: StdInstant::now(), recorder }
         } else {
             ClockKind::Virtual { nanoseconds: 0.into() }
         };
@@ -106,7 +112,11 @@ pub fn tick(&self) {
     /// Sleep for the desired duration.
     pub fn sleep(&self, duration: Duration) {
         match &self.kind {
-            ClockKind::Host { .. } => std::thread::sleep(duration),
+            ClockKind::Host { recorder, .. } =>
+                if !recorder.is_replaying() {
+                    // When replaying, the clock readings after the sleep come from the recording.
+                    std::thread::sleep(duration)
+                },
             ClockKind::Virtual { nanoseconds } => {
                 // Just pretend that we have slept for some time.
                 let nanos: u128 = duration.as_nanos();
@@ -121,16 +131,36 @@ pub fn sleep(&self, duration: Duration) {
     /// Return the `epoch` instant (time = 0), to convert between monotone instants and absolute durations.
     pub fn epoch(&self) -> Instant {
         match &self.kind {
-            ClockKind::Host { epoch } => Instant { kind: InstantKind::Host(*epoch) },
+            ClockKind::Host { epoch, .. } => Instant { kind: InstantKind::Host(*epoch) },
             ClockKind::Virtual { .. } => Instant { kind: InstantKind::Virtual { nanoseconds: 0 } },
         }
     }
 
     pub fn now(&self) -> Instant {
         match &self.kind {
-            ClockKind::Host { .. } => Instant { kind: InstantKind::Host(StdInstant::now()) },
+            ClockKind::Host { epoch, recorder } => {
+                let nanoseconds = recorder.instant(|| epoch.elapsed().as_nanos());
+                let instant = Instant { kind: InstantKind::Host(*epoch) };
+                instant.add_lossy(duration_from_nanos(nanoseconds))
+            }
             ClockKind::Virtual { nanoseconds } =>
                 Instant { kind: InstantKind::Virtual { nanoseconds: nanoseconds.get() } },
         }
     }
+
+    /// The current system time. This is only available when communicating with the host.
+    pub fn system_time(&self) -> SystemTime {
+        match &self.kind {
+            ClockKind::Host { recorder, .. } => {
+                let nanoseconds = recorder.system_time(|| {
+                    SystemTime::now()
+                        .duration_since(SystemTime::UNIX_EPOCH)
+                        .expect("the host system time is before the Unix epoch")
+                        .as_nanos()
+                });
+                SystemTime::UNIX_EPOCH + duration_from_nanos(nanoseconds)
+            }
+            ClockKind::Virtual { .. } => panic!("the system time is not available in isolation"),
+        }
+    }
 }
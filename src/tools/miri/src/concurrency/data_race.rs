@@ -1750,6 +1750,7 @@ fn maybe_perform_sync_operation<'tcx>(
         op: impl FnOnce(VectorIdx, RefMut<'_, ThreadClockSet>) -> InterpResult<'tcx, bool>,
     ) -> InterpResult<'tcx> {
         if self.multi_threaded.get() {
+            thread_mgr.scheduler().sync_operation();
             let (index, clocks) = self.active_thread_state_mut(thread_mgr);
             if op(index, clocks)? {
                 let (_, mut clocks) = self.active_thread_state_mut(thread_mgr);
@@ -1772,6 +1773,7 @@ fn print_thread_metadata(&self, thread_mgr: &ThreadManager<'_>, vector: VectorId
     /// As this is an acquire operation, the thread timestamp is not
     /// incremented.
     pub fn acquire_clock<'tcx>(&self, clock: &VClock, threads: &ThreadManager<'tcx>) {
+        threads.scheduler().sync_operation();
         let thread = threads.active_thread();
         let (_, mut clocks) = self.thread_state_mut(thread);
         clocks.clock.join(clock);
@@ -1785,6 +1787,7 @@ pub fn release_clock<'tcx, R>(
         threads: &ThreadManager<'tcx>,
         callback: impl FnOnce(&VClock) -> R,
     ) -> R {
+        threads.scheduler().sync_operation();
         let thread = threads.active_thread();
         let span = threads.active_thread_ref().current_span();
         let (index, mut clocks) = self.thread_state_mut(thread);
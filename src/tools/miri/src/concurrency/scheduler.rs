@@ -0,0 +1,409 @@
+//! The policies that decide when to switch threads, and which thread to switch to.
+//!
+//! By default, Miri runs the active thread until it blocks or yields, and preempts it randomly at
+//! the end of basic blocks. Alternatively, interleavings can be explored systematically with
+//! probabilistic concurrency testing (PCT), or a schedule printed by an earlier execution or
+//! stored in a recording can be replayed.
+//!
+//! PCT is described in "A Randomized Scheduler with Probabilistic Guarantees of Finding Bugs" by
+//! Burckhardt, Kothari, Musuvathi and Nagarakatte. Every thread gets a random priority, and the
+//! enabled thread with the highest priority runs. At `depth - 1` randomly chosen scheduling
+//! points, the priority of the active thread is lowered below that of all other threads. If a bug
+//! can be triggered by a particular ordering of `depth` events, then a single execution finds it
+//! with a probability of at least `1 / (n * k^(depth - 1))`, where `n` is the number of threads
+//! and `k` the number of scheduling points.
+//!
+//! To keep `k` small, only synchronizing operations, i.e. atomic accesses, fences and everything
+//! else that acquires or releases a vector clock of the data race detector, are scheduling points.
+//! Non-atomic accesses cannot be affected by the order in which threads run without the data race
+//! detector reporting a race, so it is sufficient to switch threads right after synchronizing
+//! operations. Without the data race detector, every step is a scheduling point.
+//!
+//! This is not a partial-order reduction: we don't use the vector clocks to tell whether the
+//! operations of two threads are independent, e.g., because they access different atomics. Every
+//! synchronizing operation is a scheduling point, so `k` still counts preemptions that cannot
+//! change the outcome, and schedules that only differ in the order of independent operations are
+//! explored as if they were different.
+
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use rustc_index::IndexVec;
+
+use crate::*;
+
+/// The threads that an execution ran, as a sequence of runs of steps of a single thread.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Schedule(Vec<(ThreadId, u64)>);
+
+impl Schedule {
+    /// Records that `thread` executed a step.
+    fn push_step(&mut self, thread: ThreadId) {
+        match self.0.last_mut() {
+            Some((last, steps)) if *last == thread => *steps += 1,
+            _ => self.0.push((thread, 1)),
+        }
+    }
+}
+
+/// The format is a comma-separated list of `thread:steps` pairs.
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (thread, steps)) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}:{steps}", thread.to_u32())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut schedule = Schedule::default();
+        for run in s.split(',').filter(|run| !run.is_empty()) {
+            let (thread, steps) = run
+                .split_once(':')
+                .ok_or_else(|| format!("expected `thread:steps`, but got `{run}`"))?;
+            let thread = thread
+                .parse::<u32>()
+                .map_err(|err| format!("invalid thread ID `{thread}`: {err}"))?;
+            let steps = steps
+                .parse::<u64>()
+                .map_err(|err| format!("invalid step count `{steps}`: {err}"))?;
+            if steps > 0 {
+                schedule.0.push((ThreadId::from(thread), steps));
+            }
+        }
+        Ok(schedule)
+    }
+}
+
+/// The state of probabilistic concurrency testing.
+#[derive(Debug)]
+struct Pct {
+    /// The bug depth, i.e., how many ordering constraints the bugs we are looking for have.
+    depth: u32,
+    /// The priority of each thread. Higher values mean higher priorities.
+    priorities: IndexVec<ThreadId, i64>,
+    /// The scheduling points at which the priority of the active thread gets lowered, in
+    /// decreasing order, so the next one can be popped off the end.
+    change_points: Vec<u64>,
+    /// The number of scheduling points passed so far.
+    scheduling_points: u64,
+    /// Whether only synchronizing operations are scheduling points.
+    only_sync_points: bool,
+    /// The priority given to the next thread that yields.
+    next_yield_priority: i64,
+    rng: StdRng,
+}
+
+impl Pct {
+    fn new(depth: u32, scheduling_points: u64, only_sync_points: bool, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let num_change_points = usize::try_from(depth - 1).unwrap();
+        let range = usize::try_from(scheduling_points).unwrap().max(num_change_points);
+        let mut change_points: Vec<u64> =
+            rand::seq::index::sample(&mut rng, range, num_change_points)
+                .into_iter()
+                .map(|point| u64::try_from(point).unwrap() + 1)
+                .collect();
+        change_points.sort_unstable_by(|a, b| b.cmp(a));
+        let mut pct = Pct {
+            depth,
+            priorities: IndexVec::new(),
+            change_points,
+            scheduling_points: 0,
+            only_sync_points,
+            next_yield_priority: 0,
+            rng,
+        };
+        pct.thread_created(ThreadId::MAIN_THREAD);
+        pct
+    }
+
+    fn thread_created(&mut self, thread: ThreadId) {
+        // Initial priorities are at least `depth`, so they are higher than all the priorities
+        // assigned at change points.
+        let priority = i64::from(self.depth) + i64::from(self.rng.next_u32());
+        self.priorities.ensure_contains_elem(thread, || 0);
+        self.priorities[thread] = priority;
+    }
+
+    /// Counts a scheduling point of the active thread. Returns whether the priority of the active
+    /// thread was lowered.
+    fn scheduling_point(&mut self, active: ThreadId) -> bool {
+        self.scheduling_points += 1;
+        if self.change_points.last() != Some(&self.scheduling_points) {
+            return false;
+        }
+        self.change_points.pop();
+        // The `i`-th change point lowers the priority to `depth - i`.
+        let remaining = u32::try_from(self.change_points.len()).unwrap();
+        self.priorities[active] = i64::from(remaining + 1);
+        true
+    }
+
+    /// Gives a thread that yields the lowest priority, so that spin loops can make progress.
+    fn thread_yielded(&mut self, active: ThreadId) {
+        self.priorities[active] = self.next_yield_priority;
+        self.next_yield_priority -= 1;
+    }
+
+    /// Returns the enabled thread with the highest priority.
+    fn next_thread(&self, is_enabled: impl Fn(ThreadId) -> bool) -> Option<ThreadId> {
+        self.priorities
+            .iter_enumerated()
+            .filter(|&(thread, _)| is_enabled(thread))
+            // Prefer lower IDs if the priorities are equal.
+            .max_by_key(|&(thread, &priority)| (priority, std::cmp::Reverse(thread)))
+            .map(|(thread, _)| thread)
+    }
+}
+
+#[derive(Debug)]
+enum Policy {
+    /// Preempt threads randomly, as configured by `-Zmiri-preemption-rate`.
+    Random,
+    Pct(Pct),
+    /// Run the threads of a given schedule.
+    Replay {
+        /// The runs of the schedule being replayed that have not been executed yet.
+        runs: VecDeque<(ThreadId, u64)>,
+        /// Whether the schedule was passed with `-Zmiri-replay-schedule`, so it not matching the
+        /// execution is an error. The schedule of a recording is replayed on a best-effort basis
+        /// instead, and a mismatch is reported as a divergence from the recording.
+        strict: bool,
+        /// Whether the random preemption decisions of the recorded execution still get drawn, so
+        /// that the RNG stays in sync with it.
+        draw_preemptions: bool,
+    },
+}
+
+/// Decides when the active thread gets preempted, and which thread runs next.
+#[derive(Debug)]
+pub struct Scheduler {
+    policy: Policy,
+    /// Set by the data race detector when the active thread performs a synchronizing operation.
+    sync_operation: Cell<bool>,
+    /// Whether the active thread should be preempted at the next opportunity.
+    preemption_pending: bool,
+    /// The schedule of this execution so far.
+    schedule: Schedule,
+}
+
+impl Scheduler {
+    /// Creates the scheduler of an execution. PCT is seeded from the machine RNG `rng`, so
+    /// different `-Zmiri-seed`s explore different schedules. The other policies don't draw from
+    /// `rng`, so they don't change the random choices of the execution.
+    ///
+    /// The schedule of a recording passed with `-Zmiri-replay` takes precedence over the
+    /// configured policy. We still draw the PCT seed if the recorded execution did, so the RNG
+    /// produces the same values as in the recorded execution.
+    pub(crate) fn new(config: &MiriConfig, rng: &mut impl RngCore) -> Self {
+        let policy = if let Some(recording) = &config.replay {
+            if config.pct_depth.is_some() {
+                rng.next_u64();
+            }
+            Policy::Replay {
+                runs: recording.schedule.0.iter().copied().collect(),
+                strict: false,
+                draw_preemptions: config.pct_depth.is_none(),
+            }
+        } else if let Some(depth) = config.pct_depth {
+            Policy::Pct(Pct::new(
+                depth,
+                config.pct_scheduling_points,
+                config.data_race_detector,
+                rng.next_u64(),
+            ))
+        } else if let Some(schedule) = &config.replay_schedule {
+            Policy::Replay {
+                runs: schedule.0.iter().copied().collect(),
+                strict: true,
+                draw_preemptions: false,
+            }
+        } else {
+            Policy::Random
+        };
+        Scheduler {
+            policy,
+            sync_operation: Cell::new(false),
+            preemption_pending: false,
+            schedule: Schedule::default(),
+        }
+    }
+
+    /// Whether threads get preempted randomly, as configured by `-Zmiri-preemption-rate`.
+    pub fn is_random(&self) -> bool {
+        matches!(self.policy, Policy::Random)
+    }
+
+    /// Whether random preemption decisions get drawn from the RNG, even if they are not used.
+    pub fn draws_preemptions(&self) -> bool {
+        matches!(self.policy, Policy::Random | Policy::Replay { draw_preemptions: true, .. })
+    }
+
+    /// Called by the data race detector whenever the active thread performs a synchronizing
+    /// operation.
+    pub fn sync_operation(&self) {
+        self.sync_operation.set(true);
+    }
+
+    /// The schedule of this execution so far.
+    pub fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+
+    /// Returns the flag that replays the schedule of this execution, if the schedule was chosen
+    /// systematically.
+    pub fn replay_flag(&self) -> Option<String> {
+        match self.policy {
+            Policy::Pct(_) => Some(format!("-Zmiri-replay-schedule={}", self.schedule)),
+            Policy::Random | Policy::Replay { .. } => None,
+        }
+    }
+
+    pub(super) fn thread_created(&mut self, thread: ThreadId) {
+        if let Policy::Pct(pct) = &mut self.policy {
+            pct.thread_created(thread);
+            // The new thread may have a higher priority than the active one.
+            self.preemption_pending = true;
+        }
+    }
+
+    pub(super) fn thread_yielded(&mut self, active: ThreadId) {
+        if let Policy::Pct(pct) = &mut self.policy {
+            pct.thread_yielded(active);
+        }
+    }
+
+    /// Decides whether the active thread, which could keep running, should be preempted.
+    pub(super) fn should_preempt(&mut self, active: ThreadId) -> bool {
+        match &mut self.policy {
+            Policy::Random => {}
+            Policy::Pct(pct) =>
+                if self.sync_operation.replace(false) || !pct.only_sync_points {
+                    pct.scheduling_point(active);
+                    // Running the synchronizing operation may have enabled a thread with a higher
+                    // priority, so we have to reconsider our choice whether or not the priority
+                    // changed.
+                    self.preemption_pending = true;
+                },
+            Policy::Replay { runs, .. } =>
+                if let Some(&(thread, steps)) = runs.front() {
+                    self.preemption_pending |= thread != active || steps == 0;
+                },
+        }
+        self.preemption_pending
+    }
+
+    /// Picks the thread to run next. Returns `None` if the default round-robin choice should be
+    /// used.
+    pub(super) fn next_thread<'tcx>(
+        &mut self,
+        is_enabled: impl Fn(ThreadId) -> bool,
+    ) -> InterpResult<'tcx, Option<ThreadId>> {
+        self.preemption_pending = false;
+        match &mut self.policy {
+            Policy::Random => interp_ok(None),
+            Policy::Pct(pct) => interp_ok(pct.next_thread(is_enabled)),
+            Policy::Replay { runs, strict, .. } => {
+                while runs.front().is_some_and(|&(_, steps)| steps == 0) {
+                    runs.pop_front();
+                }
+                let Some(&(thread, _)) = runs.front() else {
+                    // The replayed schedule is over, continue with the default policy.
+                    return interp_ok(None);
+                };
+                if !is_enabled(thread) {
+                    if !*strict {
+                        // The execution diverged from the recording, which is detected by
+                        // comparing the schedules at the end. Continue with the default policy.
+                        runs.clear();
+                        return interp_ok(None);
+                    }
+                    throw_unsup_format!(
+                        "the replayed schedule does not match this execution: thread {} cannot run \
+                         after step {}",
+                        thread.to_u32(),
+                        self.schedule.0.iter().map(|&(_, steps)| steps).sum::<u64>()
+                    );
+                }
+                interp_ok(Some(thread))
+            }
+        }
+    }
+
+    /// Records that `thread` is about to execute a step.
+    pub(super) fn record_step(&mut self, thread: ThreadId) {
+        self.schedule.push_step(thread);
+        if let Policy::Replay { runs, .. } = &mut self.policy
+            && let Some((run_thread, steps)) = runs.front_mut()
+            && *run_thread == thread
+        {
+            *steps -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schedule_roundtrip() {
+        let schedule: Schedule = "0:120,1:3,0:7,2:1".parse().unwrap();
+        assert_eq!(schedule.0.len(), 4);
+        assert_eq!(schedule.to_string(), "0:120,1:3,0:7,2:1");
+        assert_eq!("".parse::<Schedule>().unwrap(), Schedule::default());
+        // Empty runs are dropped.
+        assert_eq!("0:5,1:0".parse::<Schedule>().unwrap().to_string(), "0:5");
+    }
+
+    #[test]
+    fn schedule_errors() {
+        assert!("0".parse::<Schedule>().is_err());
+        assert!("a:1".parse::<Schedule>().is_err());
+        assert!("0:-1".parse::<Schedule>().is_err());
+    }
+
+    #[test]
+    fn schedule_push_step() {
+        let mut schedule = Schedule::default();
+        for thread in [0, 0, 1, 1, 1, 0] {
+            schedule.push_step(ThreadId::from(thread));
+        }
+        assert_eq!(schedule.to_string(), "0:2,1:3,0:1");
+    }
+
+    #[test]
+    fn pct_change_points() {
+        let depth = 3;
+        let mut pct = Pct::new(depth, 10, true, 42);
+        pct.thread_created(ThreadId::from(1));
+        assert_eq!(pct.change_points.len(), 2);
+        assert!(pct.change_points.iter().all(|&point| (1..=10).contains(&point)));
+
+        // Every change point lowers the priority of the active thread below all initial ones.
+        let mut lowered = vec![];
+        for _ in 0..10 {
+            if pct.scheduling_point(ThreadId::MAIN_THREAD) {
+                lowered.push(pct.priorities[ThreadId::MAIN_THREAD]);
+            }
+        }
+        assert_eq!(lowered, [2, 1]);
+        assert_eq!(pct.next_thread(|_| true), Some(ThreadId::from(1)));
+        assert_eq!(
+            pct.next_thread(|thread| thread == ThreadId::MAIN_THREAD),
+            Some(ThreadId::MAIN_THREAD)
+        );
+    }
+}
@@ -7,6 +7,7 @@
 use std::time::{Duration, SystemTime};
 
 use either::Either;
+use rand::RngCore;
 use rustc_const_eval::CTRL_C_RECEIVED;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def_id::DefId;
@@ -173,6 +174,8 @@ pub enum BlockReason {
     InitOnce(InitOnceId),
     /// Blocked on epoll.
     Epoll,
+    /// Blocked on a socket operation.
+    Socket,
 }
 
 /// The state of a thread.
@@ -415,7 +418,7 @@ fn get_wait_time(&self, clock: &Clock) -> Duration {
         match self {
             Timeout::Monotonic(instant) => instant.duration_since(clock.now()),
             Timeout::RealTime(time) =>
-                time.duration_since(SystemTime::now()).unwrap_or(Duration::ZERO),
+                time.duration_since(clock.system_time()).unwrap_or(Duration::ZERO),
         }
     }
 
@@ -461,6 +464,8 @@ pub struct ThreadManager<'tcx> {
     thread_local_allocs: FxHashMap<(DefId, ThreadId), StrictPointer>,
     /// A flag that indicates that we should change the active thread.
     yield_active_thread: bool,
+    /// Decides when to switch threads, and which thread to switch to.
+    scheduler: Scheduler,
 }
 
 impl VisitProvenance for ThreadManager<'_> {
@@ -470,6 +475,7 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
             thread_local_allocs,
             active_thread: _,
             yield_active_thread: _,
+            scheduler: _,
         } = self;
 
         for thread in threads {
@@ -481,8 +487,8 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
     }
 }
 
-impl<'tcx> Default for ThreadManager<'tcx> {
-    fn default() -> Self {
+impl<'tcx> ThreadManager<'tcx> {
+    pub(crate) fn new(config: &MiriConfig, rng: &mut impl RngCore) -> Self {
         let mut threads = IndexVec::new();
         // Create the main thread and add it to the list of threads.
         threads.push(Thread::new(Some("main"), None));
@@ -491,11 +497,10 @@ fn default() -> Self {
             threads,
             thread_local_allocs: Default::default(),
             yield_active_thread: false,
+            scheduler: Scheduler::new(config, rng),
         }
     }
-}
 
-impl<'tcx> ThreadManager<'tcx> {
     pub(crate) fn init(
         ecx: &mut MiriInterpCx<'tcx>,
         on_main_stack_empty: StackEmptyCallback<'tcx>,
@@ -544,6 +549,7 @@ pub fn all_stacks(
     fn create_thread(&mut self, on_stack_empty: StackEmptyCallback<'tcx>) -> ThreadId {
         let new_thread_id = ThreadId::new(self.threads.len());
         self.threads.push(Thread::new(None, Some(on_stack_empty)));
+        self.scheduler.thread_created(new_thread_id);
         new_thread_id
     }
 
@@ -728,6 +734,12 @@ fn yield_active_thread(&mut self) {
         // could lead to all sorts of confusion.
         // We should only switch stacks between steps.
         self.yield_active_thread = true;
+        self.scheduler.thread_yielded(self.active_thread);
+    }
+
+    /// Get the scheduler that decides which thread runs when.
+    pub fn scheduler(&self) -> &Scheduler {
+        &self.scheduler
     }
 
     /// Get the wait time for the next timeout, or `None` if no timeout is pending.
@@ -750,10 +762,19 @@ fn next_callback_wait_time(&self, clock: &Clock) -> Option<Duration> {
     /// used in stateless model checkers such as Loom: run the active thread as
     /// long as we can and switch only when we have to (the active thread was
     /// blocked, terminated, or has explicitly asked to be preempted).
+    ///
+    /// With `-Zmiri-pct` or `-Zmiri-replay-schedule`, the [`Scheduler`] can additionally preempt
+    /// the active thread and choose the thread to run next.
     fn schedule(&mut self, clock: &Clock) -> InterpResult<'tcx, SchedulingAction> {
+        // Ask the scheduler first, so that it sees every scheduling point.
+        let preempt = self.scheduler.should_preempt(self.active_thread);
         // This thread and the program can keep going.
-        if self.threads[self.active_thread].state.is_enabled() && !self.yield_active_thread {
+        if self.threads[self.active_thread].state.is_enabled()
+            && !self.yield_active_thread
+            && !preempt
+        {
             // The currently active thread is still enabled, just continue with it.
+            self.scheduler.record_step(self.active_thread);
             return interp_ok(SchedulingAction::ExecuteStep);
         }
         // The active thread yielded or got terminated. Let's see if there are any timeouts to take
@@ -767,6 +788,42 @@ fn schedule(&mut self, clock: &Clock) -> InterpResult<'tcx, SchedulingAction> {
             return interp_ok(SchedulingAction::ExecuteTimeoutCallback);
         }
         // No callbacks immediately scheduled, pick a regular thread to execute.
+        let threads = &self.threads;
+        let next_thread = self
+            .scheduler
+            .next_thread(|id| threads.get(id).is_some_and(|thread| thread.state.is_enabled()))?;
+        if let Some(id) = next_thread {
+            if id != self.active_thread {
+                info!(
+                    "---------- Now executing on thread `{}` (previous: `{}`) ----------------------------------------",
+                    self.get_thread_display_name(id),
+                    self.get_thread_display_name(self.active_thread)
+                );
+                self.active_thread = id;
+            }
+        } else {
+            self.switch_round_robin();
+        }
+        self.yield_active_thread = false;
+        if self.threads[self.active_thread].state.is_enabled() {
+            self.scheduler.record_step(self.active_thread);
+            return interp_ok(SchedulingAction::ExecuteStep);
+        }
+        // We have not found a thread to execute.
+        if self.threads.iter().all(|thread| thread.state.is_terminated()) {
+            unreachable!("all threads terminated without the main thread terminating?!");
+        } else if let Some(sleep_time) = potential_sleep_time {
+            // All threads are currently blocked, but we have unexecuted
+            // timeout_callbacks, which may unblock some of the threads. Hence,
+            // sleep until the first callback.
+            interp_ok(SchedulingAction::Sleep(sleep_time))
+        } else {
+            throw_machine_stop!(TerminationInfo::Deadlock);
+        }
+    }
+
+    /// Switch to the next enabled thread after the active one, if there is any.
+    fn switch_round_robin(&mut self) {
         // The active thread blocked or yielded. So we go search for another enabled thread.
         // Crucially, we start searching at the current active thread ID, rather than at 0, since we
         // want to avoid always scheduling threads 0 and 1 without ever making progress in thread 2.
@@ -791,21 +848,6 @@ fn schedule(&mut self, clock: &Clock) -> InterpResult<'tcx, SchedulingAction> {
                 break;
             }
         }
-        self.yield_active_thread = false;
-        if self.threads[self.active_thread].state.is_enabled() {
-            return interp_ok(SchedulingAction::ExecuteStep);
-        }
-        // We have not found a thread to execute.
-        if self.threads.iter().all(|thread| thread.state.is_terminated()) {
-            unreachable!("all threads terminated without the main thread terminating?!");
-        } else if let Some(sleep_time) = potential_sleep_time {
-            // All threads are currently blocked, but we have unexecuted
-            // timeout_callbacks, which may unblock some of the threads. Hence,
-            // sleep until the first callback.
-            interp_ok(SchedulingAction::Sleep(sleep_time))
-        } else {
-            throw_machine_stop!(TerminationInfo::Deadlock);
-        }
     }
 }
 
@@ -1044,7 +1086,7 @@ fn block_thread(
                     );
                     Timeout::RealTime(match anchor {
                         TimeoutAnchor::Absolute => SystemTime::UNIX_EPOCH,
-                        TimeoutAnchor::Relative => SystemTime::now(),
+                        TimeoutAnchor::Relative => this.machine.clock.system_time(),
                     })
                 }
                 TimeoutClock::Monotonic =>
@@ -1182,7 +1224,14 @@ fn maybe_preempt_active_thread(&mut self) {
         use rand::Rng as _;
 
         let this = self.eval_context_mut();
-        if this.machine.rng.get_mut().gen_bool(this.machine.preemption_rate) {
+        // Systematic schedulers make their own preemption decisions. When replaying a recording,
+        // we still draw the decisions of the recorded execution, but the recorded schedule wins.
+        let scheduler = &this.machine.threads.scheduler;
+        if !scheduler.draws_preemptions() {
+            return;
+        }
+        let is_random = scheduler.is_random();
+        if this.machine.rng.get_mut().gen_bool(this.machine.preemption_rate) && is_random {
             this.yield_active_thread();
         }
     }
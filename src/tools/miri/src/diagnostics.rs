@@ -219,11 +219,22 @@ pub fn report_error<'tcx>(
 
     let mut msg = vec![];
 
-    let (title, helps) = if let MachineStop(info) = e.kind() {
+    let replay_flag = ecx.machine.threads.scheduler().replay_flag();
+
+    let (title, mut helps) = if let MachineStop(info) = e.kind() {
         let info = info.downcast_ref::<TerminationInfo>().expect("invalid MachineStop payload");
         use TerminationInfo::*;
         let title = match info {
-            &Exit { code, leak_check } => return Some((code, leak_check)),
+            &Exit { code, leak_check } => {
+                if code != 0
+                    && let Some(flag) = &replay_flag
+                {
+                    ecx.tcx.dcx().note(format!(
+                        "to replay the thread schedule that led to this exit code, pass `{flag}` instead of `-Zmiri-pct`"
+                    ));
+                }
+                return Some((code, leak_check));
+            }
             Abort(_) => Some("abnormal termination"),
             UnsupportedInIsolation(_) | Int2PtrWithStrictProvenance | UnsupportedForeignItem(_) =>
                 Some("unsupported operation"),
@@ -382,6 +393,11 @@ pub fn report_error<'tcx>(
         };
         (Some(title), helps)
     };
+    if let Some(flag) = replay_flag {
+        helps.push(note!(
+            "to replay the thread schedule that led to this error, pass `{flag}` instead of `-Zmiri-pct`"
+        ));
+    }
 
     let stacktrace = ecx.generate_stacktrace();
     let (stacktrace, mut any_pruned) = prune_stacktrace(stacktrace, &ecx.machine);
@@ -4,14 +4,17 @@
 use std::panic::{self, AssertUnwindSafe};
 use std::path::PathBuf;
 use std::task::Poll;
-use std::{iter, thread};
+use std::{fs, io, iter, thread};
 
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_hir::def::Namespace;
 use rustc_hir::def_id::DefId;
+use rustc_middle::mir::Mutability;
 use rustc_middle::ty::layout::{LayoutCx, LayoutOf};
 use rustc_middle::ty::{self, Ty, TyCtxt};
 use rustc_session::config::EntryFnType;
+use rustc_span::Symbol;
+use rustc_target::abi::Align;
 use rustc_target::spec::abi::Abi;
 
 use crate::concurrency::thread::TlsAllocAction;
@@ -24,6 +27,10 @@
 /// will hang the program.
 const MAIN_THREAD_YIELDS_AT_SHUTDOWN: u32 = 256;
 
+/// The function that `-Zmiri-fuzz-corpus` calls for each input. This is the symbol libFuzzer calls,
+/// so the targets defined by `libfuzzer_sys::fuzz_target!` can be used as they are.
+const FUZZ_TARGET: &str = "LLVMFuzzerTestOneInput";
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum AlignmentCheck {
     /// Do not check alignment.
@@ -138,6 +145,21 @@ pub struct MiriConfig {
     pub mute_stdout_stderr: bool,
     /// The probability of the active thread being preempted at the end of each basic block.
     pub preemption_rate: f64,
+    /// If set, threads are scheduled by probabilistic concurrency testing with the given bug depth
+    /// instead of being preempted randomly.
+    pub pct_depth: Option<u32>,
+    /// The expected number of scheduling points in one execution, used to pick the points at
+    /// which PCT changes thread priorities.
+    pub pct_scheduling_points: u64,
+    /// A schedule printed by an earlier execution that should be replayed.
+    pub replay_schedule: Option<Schedule>,
+    /// The file to write a recording of this execution to.
+    pub record: Option<PathBuf>,
+    /// A recording to replay.
+    pub replay: Option<Recording>,
+    /// If set, the fuzz target of the program is run on every input in this directory instead of
+    /// running `main`.
+    pub fuzz_corpus: Option<PathBuf>,
     /// Report the current instruction being executed every N basic blocks.
     pub report_progress: Option<u32>,
     /// Whether Stacked Borrows and Tree Borrows retagging should recurse into fields of datatypes.
@@ -185,6 +207,12 @@ fn default() -> MiriConfig {
             provenance_mode: ProvenanceMode::Default,
             mute_stdout_stderr: false,
             preemption_rate: 0.01, // 1%
+            pct_depth: None,
+            pct_scheduling_points: 1000,
+            replay_schedule: None,
+            record: None,
+            replay: None,
+            fuzz_corpus: None,
             report_progress: None,
             retag_fields: RetagFields::Yes,
             native_lib: None,
@@ -248,7 +276,8 @@ fn on_main_stack_empty(
             Done => {
                 // Figure out exit code.
                 let ret_place = this.machine.main_fn_ret_place.clone().unwrap();
-                let exit_code = this.read_target_isize(&ret_place)?;
+                let exit_code = this.read_scalar(&ret_place)?.to_int(ret_place.layout.size)?;
+                let exit_code = i64::try_from(exit_code).unwrap();
                 // Deal with our thread-local memory. We do *not* want to actually free it, instead we consider TLS
                 // to be like a global `static`, so that all memory reached by it is considered to "not leak".
                 this.terminate_active_thread(TlsAllocAction::Leak)?;
@@ -260,12 +289,9 @@ fn on_main_stack_empty(
     }
 }
 
-/// Returns a freshly created `InterpCx`.
-/// Public because this is also used by `priroda`.
-pub fn create_ecx<'tcx>(
+/// Returns a freshly created `InterpCx` without any stack frames.
+fn create_bare_ecx<'tcx>(
     tcx: TyCtxt<'tcx>,
-    entry_id: DefId,
-    entry_type: EntryFnType,
     config: &MiriConfig,
 ) -> InterpResult<'tcx, InterpCx<'tcx, MiriMachine<'tcx>>> {
     let param_env = ty::ParamEnv::reveal_all();
@@ -290,6 +316,19 @@ pub fn create_ecx<'tcx>(
         );
     }
 
+    interp_ok(ecx)
+}
+
+/// Returns a freshly created `InterpCx`.
+/// Public because this is also used by `priroda`.
+pub fn create_ecx<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    entry_id: DefId,
+    entry_type: EntryFnType,
+    config: &MiriConfig,
+) -> InterpResult<'tcx, InterpCx<'tcx, MiriMachine<'tcx>>> {
+    let mut ecx = create_bare_ecx(tcx, config)?;
+
     // Setup first stack frame.
     let entry_instance = ty::Instance::mono(tcx, entry_id);
 
@@ -420,6 +459,49 @@ pub fn create_ecx<'tcx>(
     interp_ok(ecx)
 }
 
+/// Returns a freshly created `InterpCx` that calls the fuzz target of the program on `input`.
+fn create_fuzz_ecx<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    input: &[u8],
+    config: &MiriConfig,
+) -> InterpResult<'tcx, InterpCx<'tcx, MiriMachine<'tcx>>> {
+    let mut ecx = create_bare_ecx(tcx, config)?;
+
+    let Some((_body, instance)) = ecx.lookup_exported_symbol(Symbol::intern(FUZZ_TARGET))? else {
+        tcx.dcx().fatal(format!(
+            "-Zmiri-fuzz-corpus requires the program to define a fuzz target; \
+            `libfuzzer_sys::fuzz_target!` defines one, or you can export a `{FUZZ_TARGET}` function"
+        ));
+    };
+    let sig = tcx.fn_sig(instance.def_id()).instantiate_identity().skip_binder();
+    let data_ty = Ty::new_imm_ptr(tcx, tcx.types.u8);
+    if sig.inputs() != [data_ty, tcx.types.usize] || sig.output() != tcx.types.i32 {
+        tcx.dcx().fatal(format!(
+            "`{FUZZ_TARGET}` must have the following signature:\n\
+            fn {FUZZ_TARGET}(data: *const u8, size: usize) -> i32"
+        ));
+    }
+
+    // The input is read-only, like the buffer libFuzzer passes to the fuzz target.
+    let data =
+        ecx.allocate_bytes_ptr(input, Align::ONE, MiriMemoryKind::Machine.into(), Mutability::Not)?;
+    let data = ImmTy::from_scalar(Scalar::from_pointer(data, &ecx), ecx.layout_of(data_ty)?);
+    let size = ImmTy::from_uint(u64::try_from(input.len()).unwrap(), ecx.machine.layouts.usize);
+
+    // Return place (in static memory so that it does not count as leak).
+    let ret_place = ecx.allocate(ecx.machine.layouts.i32, MiriMemoryKind::Machine.into())?;
+    ecx.machine.main_fn_ret_place = Some(ret_place.clone());
+    ecx.call_function(
+        instance,
+        sig.abi,
+        &[data, size],
+        Some(&ret_place),
+        StackPopCleanup::Root { cleanup: true },
+    )?;
+
+    interp_ok(ecx)
+}
+
 /// Evaluates the entry function specified by `entry_id`.
 /// Returns `Some(return_code)` if program executed completed.
 /// Returns `None` if an evaluation error occurred.
@@ -430,10 +512,7 @@ pub fn eval_entry<'tcx>(
     entry_type: EntryFnType,
     config: MiriConfig,
 ) -> Option<i64> {
-    // Copy setting before we move `config`.
-    let ignore_leaks = config.ignore_leaks;
-
-    let mut ecx = match create_ecx(tcx, entry_id, entry_type, &config).report_err() {
+    let ecx = match create_ecx(tcx, entry_id, entry_type, &config).report_err() {
         Ok(v) => v,
         Err(err) => {
             let (kind, backtrace) = err.into_parts();
@@ -441,7 +520,59 @@ pub fn eval_entry<'tcx>(
             panic!("Miri initialization error: {kind:?}")
         }
     };
+    run_ecx(tcx, ecx, &config)
+}
 
+/// Evaluates the fuzz target of the program on every input in `config.fuzz_corpus`, each time in a
+/// fresh machine. Errors are reported together with the input that triggered them.
+/// Returns `Some(0)` if no input triggered an error, and `None` otherwise.
+pub fn eval_fuzz_corpus(tcx: TyCtxt<'_>, config: MiriConfig) -> Option<i64> {
+    let corpus = config.fuzz_corpus.as_deref().unwrap();
+    let mut inputs = fs::read_dir(corpus)
+        .and_then(|dir| {
+            dir.map(|entry| entry.map(|entry| entry.path())).collect::<io::Result<Vec<_>>>()
+        })
+        .unwrap_or_else(|err| {
+            tcx.dcx().fatal(format!("failed to read fuzz corpus `{}`: {err}", corpus.display()))
+        });
+    inputs.retain(|path| path.is_file());
+    // Make the order in which errors are reported deterministic.
+    inputs.sort();
+
+    let mut failed = 0;
+    for path in &inputs {
+        let input = fs::read(path).unwrap_or_else(|err| {
+            tcx.dcx().fatal(format!("failed to read fuzz input `{}`: {err}", path.display()))
+        });
+        let ecx = match create_fuzz_ecx(tcx, &input, &config).report_err() {
+            Ok(v) => v,
+            Err(err) => {
+                let (kind, backtrace) = err.into_parts();
+                backtrace.print_backtrace();
+                panic!("Miri initialization error: {kind:?}")
+            }
+        };
+        if run_ecx(tcx, ecx, &config).is_none() {
+            tcx.dcx()
+                .note(format!("the error above was triggered by fuzz input `{}`", path.display()));
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        tcx.dcx().err(format!("{failed} of {} fuzz inputs triggered an error", inputs.len()));
+        return None;
+    }
+    Some(0)
+}
+
+/// Runs `ecx` until the program terminates and reports the result.
+/// Returns `Some(return_code)` if program executed completed.
+/// Returns `None` if an evaluation error occurred.
+fn run_ecx<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    mut ecx: InterpCx<'tcx, MiriMachine<'tcx>>,
+    config: &MiriConfig,
+) -> Option<i64> {
     // Perform the main execution.
     let res: thread::Result<InterpResult<'_, !>> =
         panic::catch_unwind(AssertUnwindSafe(|| ecx.run_threads()));
@@ -452,6 +583,21 @@ pub fn eval_entry<'tcx>(
     // `Ok` can never happen.
     let Err(err) = res.report_err();
 
+    if let Some(path) = &config.record {
+        let seed = config.seed.unwrap_or(0);
+        if let Err(err) =
+            ecx.machine.recorder.save(path, seed, ecx.machine.threads.scheduler().schedule())
+        {
+            tcx.dcx().warn(format!("failed to write recording to `{}`: {err}", path.display()));
+        }
+    }
+    if ecx.machine.recorder.diverged(ecx.machine.threads.scheduler().schedule()) {
+        tcx.dcx().warn(
+            "this execution diverged from the recording passed to `-Zmiri-replay`, \
+            so it does not necessarily reproduce the recorded run",
+        );
+    }
+
     // Machine cleanup. Only do this if all threads have terminated; threads that are still running
     // might cause Stacked Borrows errors (https://github.com/rust-lang/miri/issues/2396).
     if ecx.have_all_terminated() {
@@ -464,7 +610,7 @@ pub fn eval_entry<'tcx>(
 
     // Process the result.
     let (return_code, leak_check) = report_error(&ecx, err)?;
-    if leak_check && !ignore_leaks {
+    if leak_check && !config.ignore_leaks {
         // Check for thread leaks.
         if !ecx.have_all_terminated() {
             tcx.dcx().err("the main thread terminated without waiting for all remaining threads");
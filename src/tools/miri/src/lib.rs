@@ -88,6 +88,7 @@
 mod operator;
 mod provenance_gc;
 mod range_map;
+mod recording;
 mod shims;
 
 // Establish a "crate-wide prelude": we often import `crate::*`.
@@ -122,6 +123,7 @@
     AtomicFenceOrd, AtomicReadOrd, AtomicRwOrd, AtomicWriteOrd, EvalContextExt as _,
 };
 pub use crate::concurrency::init_once::{EvalContextExt as _, InitOnceId};
+pub use crate::concurrency::scheduler::{Schedule, Scheduler};
 pub use crate::concurrency::sync::{
     CondvarId, EvalContextExt as _, MutexId, RwLockId, SynchronizationObjects,
 };
@@ -134,7 +136,7 @@
 };
 pub use crate::eval::{
     AlignmentCheck, BacktraceStyle, IsolatedOp, MiriConfig, RejectOpWith, ValidationMode,
-    create_ecx, eval_entry,
+    create_ecx, eval_entry, eval_fuzz_corpus,
 };
 pub use crate::helpers::{AccessKind, EvalContextExt as _};
 pub use crate::intrinsics::EvalContextExt as _;
@@ -146,6 +148,7 @@
 pub use crate::operator::EvalContextExt as _;
 pub use crate::provenance_gc::{EvalContextExt as _, LiveAllocs, VisitProvenance, VisitWith};
 pub use crate::range_map::RangeMap;
+pub use crate::recording::{FileKind, HostDirEntry, HostMetadata, Recorder, Recording};
 pub use crate::shims::EmulateItemResult;
 pub use crate::shims::env::{EnvVars, EvalContextExt as _};
 pub use crate::shims::foreign_items::{DynSym, EvalContextExt as _};
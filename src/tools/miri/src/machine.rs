@@ -6,6 +6,7 @@
 use std::cell::RefCell;
 use std::collections::hash_map::Entry;
 use std::path::Path;
+use std::rc::Rc;
 use std::{fmt, process};
 
 use rand::rngs::StdRng;
@@ -479,6 +480,12 @@ pub struct MiriMachine<'tcx> {
     /// The list of all EpollEventInterest.
     pub(crate) epoll_interests: shims::EpollInterestTable,
 
+    /// The virtual loopback network, and the host sockets.
+    pub(crate) network: shims::VirtualNetwork,
+
+    /// Records the nondeterministic inputs of this execution, or replays them.
+    pub(crate) recorder: Rc<Recorder>,
+
     /// This machine's monotone clock.
     pub(crate) clock: Clock,
 
@@ -610,7 +617,7 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx>) -> Self {
             let path = Path::new(out).join(filename);
             measureme::Profiler::new(path).expect("Couldn't create `measureme` profiler")
         });
-        let rng = StdRng::seed_from_u64(config.seed.unwrap_or(0));
+        let mut rng = StdRng::seed_from_u64(config.seed.unwrap_or(0));
         let borrow_tracker = config.borrow_tracker.map(|bt| bt.instantiate_global_state(config));
         let data_race = config.data_race_detector.then(|| data_race::GlobalState::new(config));
         // Determine page size, stack address, and stack size.
@@ -645,7 +652,8 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx>) -> Self {
             cpu_affinity::MAX_CPUS,
             config.num_cpus
         );
-        let threads = ThreadManager::default();
+        let threads = ThreadManager::new(config, &mut rng);
+        let recorder = Rc::new(Recorder::new(config));
         let mut thread_cpu_affinity = FxHashMap::default();
         if matches!(&*tcx.sess.target.os, "linux" | "freebsd" | "android") {
             thread_cpu_affinity
@@ -667,6 +675,7 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx>) -> Self {
             validation: config.validation,
             fds: shims::FdTable::init(config.mute_stdout_stderr),
             epoll_interests: shims::EpollInterestTable::new(),
+            network: shims::VirtualNetwork::new(),
             dirs: Default::default(),
             layouts,
             threads,
@@ -689,7 +698,8 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx>) -> Self {
             preemption_rate: config.preemption_rate,
             report_progress: config.report_progress,
             basic_block_count: 0,
-            clock: Clock::new(config.isolated_op == IsolatedOp::Allow),
+            clock: Clock::new(config.isolated_op == IsolatedOp::Allow, Rc::clone(&recorder)),
+            recorder,
             #[cfg(unix)]
             native_lib: config.native_lib.as_ref().map(|lib_file_path| {
                 let target_triple = tcx.sess.opts.target_triple.triple();
@@ -806,9 +816,11 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
             alloc_addresses,
             fds,
             epoll_interests:_,
+            network: _,
             tcx: _,
             isolated_op: _,
             validation: _,
+            recorder: _,
             clock: _,
             layouts: _,
             static_roots: _,
@@ -0,0 +1,808 @@
+//! Recording the nondeterministic inputs of an execution, and replaying them later.
+//!
+//! Given the same program and seed, Miri is deterministic, except for what it gets from the host
+//! when isolation is disabled. `-Zmiri-record=<file>` logs everything that is needed to re-execute
+//! a run even when the host or the program changed a bit since: the thread schedule, readings of
+//! the host clocks, the base addresses of allocations, random bytes and the environment of the
+//! host, and the results of opening, inspecting and reading host files, directories and sockets.
+//! `-Zmiri-replay=<file>` feeds these back into the execution instead of asking the host or the
+//! RNG.
+//!
+//! The recorded schedule drives the scheduler, so the threads run in the recorded order no matter
+//! what the configured scheduling policy would decide. Operations with side effects on the host,
+//! like opening a file or accepting a connection, are still performed if they succeeded in the
+//! recording, since the execution needs the host handle they return; only their failures and the
+//! values we get from the host are replayed.
+//!
+//! Signs of divergence are the schedule not matching the recorded one, running out of recorded
+//! inputs, a recorded address not being available anymore, or a host operation failing that
+//! succeeded in the recording. In those cases, we fall back to the live values and warn at the end.
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fmt::Write as _;
+use std::fs::FileType;
+use std::io::{self, ErrorKind};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::*;
+
+/// The type of a host file, as far as Miri distinguishes them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileKind {
+    File,
+    Dir,
+    Symlink,
+    BlockDevice,
+    CharDevice,
+    Fifo,
+    Socket,
+    Unknown,
+}
+
+impl FileKind {
+    const NAMES: [(FileKind, &'static str); 8] = [
+        (FileKind::File, "file"),
+        (FileKind::Dir, "dir"),
+        (FileKind::Symlink, "symlink"),
+        (FileKind::BlockDevice, "block-device"),
+        (FileKind::CharDevice, "char-device"),
+        (FileKind::Fifo, "fifo"),
+        (FileKind::Socket, "socket"),
+        (FileKind::Unknown, "unknown"),
+    ];
+
+    fn name(self) -> &'static str {
+        Self::NAMES.iter().find(|&&(kind, _)| kind == self).unwrap().1
+    }
+
+    fn parse(name: &str) -> Result<FileKind, String> {
+        Self::NAMES
+            .iter()
+            .find(|&&(_, kind_name)| kind_name == name)
+            .map(|&(kind, _)| kind)
+            .ok_or_else(|| format!("unknown file type `{name}`"))
+    }
+}
+
+impl From<FileType> for FileKind {
+    fn from(file_type: FileType) -> Self {
+        #[cfg(unix)]
+        use std::os::unix::fs::FileTypeExt;
+
+        match () {
+            _ if file_type.is_dir() => FileKind::Dir,
+            _ if file_type.is_file() => FileKind::File,
+            _ if file_type.is_symlink() => FileKind::Symlink,
+            // Certain file types are only supported when the host is a Unix system.
+            #[cfg(unix)]
+            _ if file_type.is_block_device() => FileKind::BlockDevice,
+            #[cfg(unix)]
+            _ if file_type.is_char_device() => FileKind::CharDevice,
+            #[cfg(unix)]
+            _ if file_type.is_fifo() => FileKind::Fifo,
+            #[cfg(unix)]
+            _ if file_type.is_socket() => FileKind::Socket,
+            _ => FileKind::Unknown,
+        }
+    }
+}
+
+/// The metadata of a host file that the `stat` family of shims reports.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HostMetadata {
+    pub kind: FileKind,
+    pub size: u64,
+    pub created: Option<SystemTime>,
+    pub accessed: Option<SystemTime>,
+    pub modified: Option<SystemTime>,
+}
+
+impl From<std::fs::Metadata> for HostMetadata {
+    fn from(metadata: std::fs::Metadata) -> Self {
+        HostMetadata {
+            kind: metadata.file_type().into(),
+            size: metadata.len(),
+            created: metadata.created().ok(),
+            accessed: metadata.accessed().ok(),
+            modified: metadata.modified().ok(),
+        }
+    }
+}
+
+/// An entry of a host directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HostDirEntry {
+    pub name: OsString,
+    /// The inode number, or 0 if the host does not have inodes.
+    pub ino: u64,
+    file_type: Result<FileKind, Option<i32>>,
+}
+
+impl HostDirEntry {
+    pub fn file_type(&self) -> io::Result<FileKind> {
+        self.file_type.map_err(error_from_code)
+    }
+}
+
+impl From<std::fs::DirEntry> for HostDirEntry {
+    fn from(entry: std::fs::DirEntry) -> Self {
+        // If the host is a Unix system, use the real inode number.
+        #[cfg(unix)]
+        let ino = std::os::unix::fs::DirEntryExt::ino(&entry);
+        #[cfg(not(unix))]
+        let ino = 0u64;
+        let file_type = entry.file_type().map(FileKind::from).map_err(|err| err.raw_os_error());
+        HostDirEntry { name: entry.file_name(), ino, file_type }
+    }
+}
+
+/// Turns a recorded OS error code back into an error.
+fn error_from_code(code: Option<i32>) -> io::Error {
+    match code {
+        Some(code) => io::Error::from_raw_os_error(code),
+        None => ErrorKind::Other.into(),
+    }
+}
+
+/// An input of an execution that does not follow from the program and the seed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Event {
+    /// A reading of the monotone host clock, in nanoseconds since the epoch of the [`Clock`].
+    Instant(u128),
+    /// A reading of the host system time, in nanoseconds since the Unix epoch.
+    SystemTime(u128),
+    /// The base address of an allocation.
+    Address(u64),
+    /// The result of a read from the host: either the bytes that were read, or the OS error code.
+    Read(Result<Vec<u8>, Option<i32>>),
+    /// Random bytes generated by the host.
+    Random(Vec<u8>),
+    /// Whether opening a host file or directory succeeded.
+    Open(Result<(), Option<i32>>),
+    /// The result of querying the metadata of a host file.
+    Metadata(Result<HostMetadata, Option<i32>>),
+    /// The result of reading the next entry of a host directory: `None` at its end.
+    DirEntry(Result<Option<HostDirEntry>, Option<i32>>),
+    /// The result of accepting a connection on a host socket: the address of the peer.
+    Accept(Result<SocketAddr, Option<i32>>),
+    /// The environment variables of the host.
+    Env(Vec<(OsString, OsString)>),
+}
+
+fn parse_hex(hex: &str) -> Result<Vec<u8>, String> {
+    if hex.len() % 2 != 0 {
+        return Err(format!("odd number of hex digits in `{hex}`"));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+        .collect::<Result<_, _>>()
+        .map_err(|err| format!("invalid hex digits in `{hex}`: {err}"))
+}
+
+fn write_hex(out: &mut String, bytes: &[u8]) {
+    for byte in bytes {
+        write!(out, "{byte:02x}").unwrap();
+    }
+}
+
+#[cfg(unix)]
+fn os_string_from_bytes(bytes: Vec<u8>) -> Result<OsString, String> {
+    Ok(std::os::unix::ffi::OsStringExt::from_vec(bytes))
+}
+#[cfg(not(unix))]
+fn os_string_from_bytes(bytes: Vec<u8>) -> Result<OsString, String> {
+    // We cannot use `from_encoded_bytes_unchecked` here since we can't trust `bytes`.
+    String::from_utf8(bytes).map(OsString::from).map_err(|err| err.to_string())
+}
+
+/// Times are stored as nanoseconds relative to the Unix epoch, or `-` if the host did not know
+/// them.
+fn parse_time(arg: &str) -> Result<Option<SystemTime>, String> {
+    if arg == "-" {
+        return Ok(None);
+    }
+    let nanos = arg.parse::<i128>().map_err(|err| format!("invalid time `{arg}`: {err}"))?;
+    let duration = u64::try_from(nanos.unsigned_abs() / 1_000_000_000)
+        .map(|secs| {
+            Duration::new(secs, u32::try_from(nanos.unsigned_abs() % 1_000_000_000).unwrap())
+        })
+        .map_err(|err| format!("invalid time `{arg}`: {err}"))?;
+    let time = if nanos < 0 {
+        SystemTime::UNIX_EPOCH.checked_sub(duration)
+    } else {
+        SystemTime::UNIX_EPOCH.checked_add(duration)
+    };
+    time.map(Some).ok_or_else(|| format!("time `{arg}` is out of range"))
+}
+
+fn write_time(out: &mut String, time: Option<SystemTime>) {
+    match time.map(|time| time.duration_since(SystemTime::UNIX_EPOCH)) {
+        None => write!(out, "-"),
+        Some(Ok(duration)) => write!(out, "{}", duration.as_nanos()),
+        Some(Err(err)) => write!(out, "-{}", err.duration().as_nanos()),
+    }
+    .unwrap();
+}
+
+impl Event {
+    fn parse(line: &str) -> Result<Event, String> {
+        let (kind, arg) = line.split_once(' ').unwrap_or((line, ""));
+        let int_error = |err| format!("invalid number in `{line}`: {err}");
+        // Failed operations are stored as `<kind>-error`, followed by the OS error code if any.
+        if let Some(kind) = kind.strip_suffix("-error") {
+            let code = if arg.is_empty() { None } else { Some(arg.parse().map_err(int_error)?) };
+            return match kind {
+                "read" => Ok(Event::Read(Err(code))),
+                "open" => Ok(Event::Open(Err(code))),
+                "metadata" => Ok(Event::Metadata(Err(code))),
+                "dir" => Ok(Event::DirEntry(Err(code))),
+                "accept" => Ok(Event::Accept(Err(code))),
+                _ => Err(format!("unknown event `{line}`")),
+            };
+        }
+        let mut args = arg.split(' ');
+        let mut next_arg = || {
+            args.next()
+                .filter(|arg| !arg.is_empty())
+                .ok_or_else(|| format!("missing argument in `{line}`"))
+        };
+        let event = match kind {
+            "instant" => Event::Instant(arg.parse().map_err(int_error)?),
+            "system-time" => Event::SystemTime(arg.parse().map_err(int_error)?),
+            "address" => {
+                let hex = arg.strip_prefix("0x").unwrap_or(arg);
+                Event::Address(u64::from_str_radix(hex, 16).map_err(int_error)?)
+            }
+            "read" => Event::Read(Ok(parse_hex(arg)?)),
+            "random" => Event::Random(parse_hex(arg)?),
+            "open" => Event::Open(Ok(())),
+            "metadata" =>
+                Event::Metadata(Ok(HostMetadata {
+                    kind: FileKind::parse(next_arg()?)?,
+                    size: next_arg()?.parse().map_err(int_error)?,
+                    created: parse_time(next_arg()?)?,
+                    accessed: parse_time(next_arg()?)?,
+                    modified: parse_time(next_arg()?)?,
+                })),
+            "dir-entry" => {
+                let ino = next_arg()?.parse().map_err(int_error)?;
+                let file_type = match next_arg()? {
+                    "error" => Err(None),
+                    kind =>
+                        match kind.strip_prefix("error:") {
+                            Some(code) => Err(Some(code.parse().map_err(int_error)?)),
+                            None => Ok(FileKind::parse(kind)?),
+                        },
+                };
+                // Empty names never occur, so the name is not optional.
+                let name = os_string_from_bytes(parse_hex(next_arg()?)?)?;
+                Event::DirEntry(Ok(Some(HostDirEntry { name, ino, file_type })))
+            }
+            "dir-end" => Event::DirEntry(Ok(None)),
+            "accept" =>
+                Event::Accept(Ok(arg
+                    .parse()
+                    .map_err(|err| format!("invalid address in `{line}`: {err}"))?)),
+            "env" =>
+                Event::Env(
+                    arg.split(' ')
+                        .filter(|var| !var.is_empty())
+                        .map(|var| {
+                            let (name, value) = var.split_once('=').ok_or_else(|| {
+                                format!("expected `name=value` in `{line}`, but got `{var}`")
+                            })?;
+                            Ok((
+                                os_string_from_bytes(parse_hex(name)?)?,
+                                os_string_from_bytes(parse_hex(value)?)?,
+                            ))
+                        })
+                        .collect::<Result<_, String>>()?,
+                ),
+            _ => return Err(format!("unknown event `{line}`")),
+        };
+        Ok(event)
+    }
+
+    fn write(&self, out: &mut String) {
+        let error = |out: &mut String, kind: &str, code: &Option<i32>| {
+            match code {
+                Some(code) => writeln!(out, "{kind}-error {code}"),
+                None => writeln!(out, "{kind}-error"),
+            }
+            .unwrap()
+        };
+        match self {
+            Event::Instant(nanos) => writeln!(out, "instant {nanos}").unwrap(),
+            Event::SystemTime(nanos) => writeln!(out, "system-time {nanos}").unwrap(),
+            Event::Address(addr) => writeln!(out, "address {addr:#x}").unwrap(),
+            Event::Read(Ok(bytes)) => {
+                out.push_str("read ");
+                write_hex(out, bytes);
+                out.push('\n');
+            }
+            Event::Read(Err(code)) => error(out, "read", code),
+            Event::Random(bytes) => {
+                out.push_str("random ");
+                write_hex(out, bytes);
+                out.push('\n');
+            }
+            Event::Open(Ok(())) => out.push_str("open\n"),
+            Event::Open(Err(code)) => error(out, "open", code),
+            Event::Metadata(Ok(metadata)) => {
+                write!(out, "metadata {} {}", metadata.kind.name(), metadata.size).unwrap();
+                for time in [metadata.created, metadata.accessed, metadata.modified] {
+                    out.push(' ');
+                    write_time(out, time);
+                }
+                out.push('\n');
+            }
+            Event::Metadata(Err(code)) => error(out, "metadata", code),
+            Event::DirEntry(Ok(Some(entry))) => {
+                write!(out, "dir-entry {} ", entry.ino).unwrap();
+                match entry.file_type {
+                    Ok(kind) => out.push_str(kind.name()),
+                    Err(None) => out.push_str("error"),
+                    Err(Some(code)) => write!(out, "error:{code}").unwrap(),
+                }
+                out.push(' ');
+                write_hex(out, entry.name.as_encoded_bytes());
+                out.push('\n');
+            }
+            Event::DirEntry(Ok(None)) => out.push_str("dir-end\n"),
+            Event::DirEntry(Err(code)) => error(out, "dir", code),
+            Event::Accept(Ok(addr)) => writeln!(out, "accept {addr}").unwrap(),
+            Event::Accept(Err(code)) => error(out, "accept", code),
+            Event::Env(vars) => {
+                out.push_str("env");
+                for (name, value) in vars {
+                    out.push(' ');
+                    write_hex(out, name.as_encoded_bytes());
+                    out.push('=');
+                    write_hex(out, value.as_encoded_bytes());
+                }
+                out.push('\n');
+            }
+        }
+    }
+}
+
+/// A recorded execution, as loaded by `-Zmiri-replay`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Recording {
+    /// The seed of the recorded execution.
+    pub seed: u64,
+    /// The threads the recorded execution ran.
+    pub schedule: Schedule,
+    instants: VecDeque<u128>,
+    system_times: VecDeque<u128>,
+    addresses: VecDeque<u64>,
+    reads: VecDeque<Result<Vec<u8>, Option<i32>>>,
+    randoms: VecDeque<Vec<u8>>,
+    opens: VecDeque<Result<(), Option<i32>>>,
+    metadata: VecDeque<Result<HostMetadata, Option<i32>>>,
+    dir_entries: VecDeque<Result<Option<HostDirEntry>, Option<i32>>>,
+    accepts: VecDeque<Result<SocketAddr, Option<i32>>>,
+    env: Option<Vec<(OsString, OsString)>>,
+}
+
+impl Recording {
+    /// Loads a recording written by `-Zmiri-record`.
+    pub fn load(path: &Path) -> io::Result<Recording> {
+        Self::parse(&std::fs::read_to_string(path)?)
+            .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+    }
+
+    fn parse(contents: &str) -> Result<Recording, String> {
+        let mut recording = Recording::default();
+        let mut lines = contents.lines().filter(|line| !line.is_empty() && !line.starts_with('#'));
+        let mut header = |name: &str| {
+            lines
+                .next()
+                .and_then(|line| line.strip_prefix(name))
+                .and_then(|line| line.strip_prefix(' '))
+                .ok_or_else(|| format!("missing `{name}` line"))
+        };
+        let seed = header("seed")?;
+        recording.seed = seed.parse().map_err(|err| format!("invalid seed `{seed}`: {err}"))?;
+        recording.schedule = header("schedule")?.parse()?;
+        for line in lines {
+            match Event::parse(line)? {
+                Event::Instant(nanos) => recording.instants.push_back(nanos),
+                Event::SystemTime(nanos) => recording.system_times.push_back(nanos),
+                Event::Address(addr) => recording.addresses.push_back(addr),
+                Event::Read(result) => recording.reads.push_back(result),
+                Event::Random(bytes) => recording.randoms.push_back(bytes),
+                Event::Open(result) => recording.opens.push_back(result),
+                Event::Metadata(result) => recording.metadata.push_back(result),
+                Event::DirEntry(result) => recording.dir_entries.push_back(result),
+                Event::Accept(result) => recording.accepts.push_back(result),
+                Event::Env(vars) => recording.env = Some(vars),
+            }
+        }
+        Ok(recording)
+    }
+}
+
+#[derive(Debug)]
+enum Mode {
+    Off,
+    /// The events of this execution so far, in the order they happened.
+    Record(RefCell<Vec<Event>>),
+    /// The events of the recorded execution that have not been replayed yet.
+    Replay {
+        recording: RefCell<Recording>,
+        diverged: Cell<bool>,
+    },
+}
+
+/// Records or replays the nondeterministic inputs of this execution.
+#[derive(Debug)]
+pub struct Recorder {
+    mode: Mode,
+}
+
+impl Recorder {
+    pub fn new(config: &MiriConfig) -> Self {
+        let mode = if config.record.is_some() {
+            Mode::Record(RefCell::new(Vec::new()))
+        } else if let Some(recording) = &config.replay {
+            Mode::Replay { recording: RefCell::new(recording.clone()), diverged: Cell::new(false) }
+        } else {
+            Mode::Off
+        };
+        Recorder { mode }
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        matches!(self.mode, Mode::Replay { .. })
+    }
+
+    /// Whether the replayed execution, which ran `schedule`, diverged from the recorded one.
+    pub fn diverged(&self, schedule: &Schedule) -> bool {
+        match &self.mode {
+            Mode::Replay { recording, diverged } =>
+                diverged.get() || recording.borrow().schedule != *schedule,
+            Mode::Off | Mode::Record(_) => false,
+        }
+    }
+
+    /// Returns the next recorded value if we are replaying, and `live()` otherwise. When
+    /// recording, `live()` gets logged.
+    fn replay_or_record<T: Clone>(
+        &self,
+        live: impl FnOnce() -> T,
+        event: impl FnOnce(T) -> Event,
+        replay: impl FnOnce(&mut Recording) -> Option<T>,
+    ) -> T {
+        match &self.mode {
+            Mode::Off => live(),
+            Mode::Record(events) => {
+                let value = live();
+                events.borrow_mut().push(event(value.clone()));
+                value
+            }
+            Mode::Replay { recording, diverged } => {
+                let recorded = replay(&mut recording.borrow_mut());
+                recorded.unwrap_or_else(|| {
+                    diverged.set(true);
+                    live()
+                })
+            }
+        }
+    }
+
+    /// Performs the host operation `op`, which has side effects and hence cannot be skipped if it
+    /// succeeds. `value` extracts what gets recorded about a success. When replaying, a recorded
+    /// failure is returned without calling `op`, and a recorded value replaces the live one.
+    fn host_op<T, V: Clone>(
+        &self,
+        op: impl FnOnce() -> io::Result<T>,
+        value: impl FnOnce(&T) -> V,
+        event: impl FnOnce(Result<V, Option<i32>>) -> Event,
+        replay: impl FnOnce(&mut Recording) -> Option<Result<V, Option<i32>>>,
+    ) -> io::Result<(T, V)> {
+        let live = || {
+            op().map(|result| {
+                let value = value(&result);
+                (result, value)
+            })
+        };
+        match &self.mode {
+            Mode::Off => live(),
+            Mode::Record(events) => {
+                let result = live();
+                let recorded = result
+                    .as_ref()
+                    .map(|(_, value)| value.clone())
+                    .map_err(io::Error::raw_os_error);
+                events.borrow_mut().push(event(recorded));
+                result
+            }
+            Mode::Replay { recording, diverged } => {
+                let recorded = replay(&mut recording.borrow_mut());
+                match recorded {
+                    Some(Err(code)) => Err(error_from_code(code)),
+                    Some(Ok(value)) =>
+                        match live() {
+                            Ok((result, _)) => Ok((result, value)),
+                            Err(err) => {
+                                diverged.set(true);
+                                Err(err)
+                            }
+                        },
+                    None => {
+                        diverged.set(true);
+                        live()
+                    }
+                }
+            }
+        }
+    }
+
+    /// A reading of the monotone host clock, in nanoseconds since the epoch of the clock.
+    pub fn instant(&self, live: impl FnOnce() -> u128) -> u128 {
+        self.replay_or_record(live, Event::Instant, |recording| recording.instants.pop_front())
+    }
+
+    /// A reading of the host system time, in nanoseconds since the Unix epoch.
+    pub fn system_time(&self, live: impl FnOnce() -> u128) -> u128 {
+        self.replay_or_record(live, Event::SystemTime, |recording| {
+            recording.system_times.pop_front()
+        })
+    }
+
+    /// The base address of a new allocation. `computed` is the address Miri picked; a replayed
+    /// address is only used if `usable` accepts it.
+    pub fn address(&self, computed: u64, usable: impl FnOnce(u64) -> bool) -> u64 {
+        self.replay_or_record(
+            || computed,
+            Event::Address,
+            |recording| {
+                recording.addresses.pop_front().filter(|&addr| addr == computed || usable(addr))
+            },
+        )
+    }
+
+    /// Reads from the host into `bytes` with `read`. When replaying, `read` is not called; the
+    /// recorded result is returned instead.
+    pub fn host_read(
+        &self,
+        bytes: &mut [u8],
+        read: impl FnOnce(&mut [u8]) -> io::Result<usize>,
+    ) -> io::Result<usize> {
+        let len = bytes.len();
+        let result = self.replay_or_record(
+            || read(bytes).map(|read| bytes[..read].to_vec()).map_err(|err| err.raw_os_error()),
+            Event::Read,
+            |recording| {
+                recording.reads.pop_front().filter(|result| {
+                    // The buffer may have gotten smaller since the recording.
+                    result.as_ref().is_ok_and(|data| data.len() <= len) || result.is_err()
+                })
+            },
+        );
+        let data = result.map_err(error_from_code)?;
+        // When recording, `bytes` already holds the data.
+        bytes[..data.len()].copy_from_slice(&data);
+        Ok(data.len())
+    }
+
+    /// Fills `bytes` with random bytes from the host with `fill`. When replaying, `fill` is not
+    /// called; the recorded bytes are used instead.
+    pub fn host_random<E>(
+        &self,
+        bytes: &mut [u8],
+        fill: impl FnOnce(&mut [u8]) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let len = bytes.len();
+        let mut result = Ok(());
+        let data = self.replay_or_record(
+            || {
+                result = fill(bytes);
+                bytes.to_vec()
+            },
+            Event::Random,
+            |recording| recording.randoms.pop_front().filter(|data| data.len() == len),
+        );
+        result?;
+        bytes.copy_from_slice(&data);
+        Ok(())
+    }
+
+    /// Opens a host file or directory with `open`. When replaying, a recorded failure is returned
+    /// without calling `open`.
+    pub fn host_open<T>(&self, open: impl FnOnce() -> io::Result<T>) -> io::Result<T> {
+        self.host_op(open, |_| (), Event::Open, |recording| recording.opens.pop_front())
+            .map(|(handle, ())| handle)
+    }
+
+    /// Queries the metadata of a host file with `live`. When replaying, `live` is not called; the
+    /// recorded result is returned instead.
+    pub fn host_metadata(
+        &self,
+        live: impl FnOnce() -> io::Result<HostMetadata>,
+    ) -> io::Result<HostMetadata> {
+        self.replay_or_record(
+            || live().map_err(|err| err.raw_os_error()),
+            Event::Metadata,
+            |recording| recording.metadata.pop_front(),
+        )
+        .map_err(error_from_code)
+    }
+
+    /// Reads the next entry of a host directory with `next`, which works like
+    /// [`Iterator::next`]. When replaying, `next` is not called; the recorded result is returned
+    /// instead.
+    pub fn host_dir_entry(
+        &self,
+        next: impl FnOnce() -> Option<io::Result<HostDirEntry>>,
+    ) -> Option<io::Result<HostDirEntry>> {
+        self.replay_or_record(
+            || next().transpose().map_err(|err| err.raw_os_error()),
+            Event::DirEntry,
+            |recording| recording.dir_entries.pop_front(),
+        )
+        .map_err(error_from_code)
+        .transpose()
+    }
+
+    /// Accepts a connection on a host socket with `accept`, which returns the new connection and
+    /// the address of its peer. When replaying, a recorded failure is returned without calling
+    /// `accept`, and the recorded peer address is returned instead of the live one.
+    pub fn host_accept<S>(
+        &self,
+        accept: impl FnOnce() -> io::Result<(S, SocketAddr)>,
+    ) -> io::Result<(S, SocketAddr)> {
+        self.host_op(
+            accept,
+            |&(_, peer_addr)| peer_addr,
+            Event::Accept,
+            |recording| recording.accepts.pop_front(),
+        )
+        .map(|((stream, _), peer_addr)| (stream, peer_addr))
+    }
+
+    /// The environment variables of the host, as returned by `live`.
+    pub fn host_env(
+        &self,
+        live: impl FnOnce() -> Vec<(OsString, OsString)>,
+    ) -> Vec<(OsString, OsString)> {
+        self.replay_or_record(live, Event::Env, |recording| recording.env.take())
+    }
+
+    /// Writes the recording of this execution to `path`.
+    pub fn save(&self, path: &Path, seed: u64, schedule: &Schedule) -> io::Result<()> {
+        let Mode::Record(events) = &self.mode else {
+            panic!("saving a recording requires `-Zmiri-record`")
+        };
+        std::fs::write(path, Self::serialize(seed, schedule, &events.borrow()))
+    }
+
+    fn serialize(seed: u64, schedule: &Schedule, events: &[Event]) -> String {
+        let mut out = String::from("# Miri recording, replay it with `-Zmiri-replay=<file>`\n");
+        writeln!(out, "seed {seed}").unwrap();
+        writeln!(out, "schedule {schedule}").unwrap();
+        for event in events {
+            event.write(&mut out);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let schedule: Schedule = "0:10,1:3".parse().unwrap();
+        let metadata = HostMetadata {
+            kind: FileKind::File,
+            size: 12,
+            created: None,
+            accessed: Some(SystemTime::UNIX_EPOCH - Duration::new(3, 5)),
+            modified: Some(SystemTime::UNIX_EPOCH + Duration::new(1_700_000_000, 42)),
+        };
+        let entry = HostDirEntry {
+            name: OsString::from("a b=c"),
+            ino: 7,
+            file_type: Ok(FileKind::Symlink),
+        };
+        let env = vec![
+            (OsString::from("HOME"), OsString::from("/home/me")),
+            (OsString::from("EMPTY"), OsString::new()),
+        ];
+        let events = [
+            Event::Instant(1500),
+            Event::Address(0x1f00),
+            Event::SystemTime(1_700_000_000_000_000_000),
+            Event::Read(Ok(b"hi\n".to_vec())),
+            Event::Read(Ok(vec![])),
+            Event::Read(Err(Some(11))),
+            Event::Read(Err(None)),
+            Event::Instant(2000),
+            Event::Random(vec![0, 255, 16]),
+            Event::Open(Ok(())),
+            Event::Open(Err(Some(2))),
+            Event::Metadata(Ok(metadata.clone())),
+            Event::Metadata(Err(None)),
+            Event::DirEntry(Ok(Some(entry.clone()))),
+            Event::DirEntry(Ok(Some(HostDirEntry { file_type: Err(Some(5)), ..entry.clone() }))),
+            Event::DirEntry(Ok(None)),
+            Event::DirEntry(Err(Some(9))),
+            Event::Accept(Ok("[::1]:4000".parse().unwrap())),
+            Event::Accept(Err(Some(11))),
+            Event::Env(env.clone()),
+        ];
+        let serialized = Recorder::serialize(42, &schedule, &events);
+        let recording = Recording::parse(&serialized).unwrap();
+        assert_eq!(recording.seed, 42);
+        assert_eq!(recording.schedule, schedule);
+        assert_eq!(recording.instants, [1500, 2000]);
+        assert_eq!(recording.system_times, [1_700_000_000_000_000_000]);
+        assert_eq!(recording.addresses, [0x1f00]);
+        assert_eq!(recording.reads, [Ok(b"hi\n".to_vec()), Ok(vec![]), Err(Some(11)), Err(None)]);
+        assert_eq!(recording.randoms, [vec![0, 255, 16]]);
+        assert_eq!(recording.opens, [Ok(()), Err(Some(2))]);
+        assert_eq!(recording.metadata, [Ok(metadata), Err(None)]);
+        assert_eq!(
+            recording.dir_entries,
+            [
+                Ok(Some(entry.clone())),
+                Ok(Some(HostDirEntry { file_type: Err(Some(5)), ..entry })),
+                Ok(None),
+                Err(Some(9)),
+            ]
+        );
+        assert_eq!(recording.accepts, [Ok("[::1]:4000".parse().unwrap()), Err(Some(11))]);
+        assert_eq!(recording.env, Some(env));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Recording::parse("").is_err());
+        assert!(Recording::parse("seed 1\n").is_err());
+        assert!(Recording::parse("seed 1\nschedule 0:1\nread abc\n").is_err());
+        assert!(Recording::parse("seed 1\nschedule 0:1\nfoo 3\n").is_err());
+        assert!(Recording::parse("seed 1\nschedule 0:1\nfoo-error 3\n").is_err());
+        assert!(Recording::parse("seed 1\nschedule 0:1\nmetadata file 3 - -\n").is_err());
+        assert!(Recording::parse("seed 1\nschedule 0:1\ndir-entry 1 pipe 61\n").is_err());
+        assert!(Recording::parse("seed 1\nschedule 0:1\nenv 61\n").is_err());
+        assert!(Recording::parse("seed 1\nschedule \ninstant 3\n").is_ok());
+    }
+
+    #[test]
+    fn replay_falls_back() {
+        let recording = Recording::parse(
+            "seed 0\nschedule 0:1\naddress 0x100\nread 6869\nopen-error 2\nopen\naccept 1.2.3.4:5\n",
+        )
+        .unwrap();
+        let recorder = Recorder {
+            mode: Mode::Replay { recording: RefCell::new(recording), diverged: Cell::new(false) },
+        };
+        // Recorded values are used if they are usable.
+        assert_eq!(recorder.address(0x200, |_| true), 0x100);
+        let mut bytes = [0; 4];
+        let read = recorder.host_read(&mut bytes, |_| unreachable!()).unwrap();
+        assert_eq!(&bytes[..read], b"hi");
+        // Recorded failures are replayed without performing the operation, but successful
+        // operations are performed and the recorded values replace the live ones.
+        let err = recorder.host_open(|| -> io::Result<()> { unreachable!() }).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(2));
+        assert_eq!(recorder.host_open(|| Ok(5)).unwrap(), 5);
+        let (_, peer_addr) =
+            recorder.host_accept(|| Ok(((), "9.9.9.9:9".parse().unwrap()))).unwrap();
+        assert_eq!(peer_addr, "1.2.3.4:5".parse().unwrap());
+        let schedule = "0:1".parse().unwrap();
+        assert!(!recorder.diverged(&schedule));
+        assert!(recorder.diverged(&"0:2".parse().unwrap()));
+        // After the recording ends, live values are used.
+        assert_eq!(recorder.instant(|| 7), 7);
+        assert!(recorder.diverged(&schedule));
+    }
+}
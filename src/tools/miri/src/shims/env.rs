@@ -33,7 +33,8 @@ pub(crate) fn init(
         // Skip the loop entirely if we don't want to forward anything.
         let mut env_vars = FxHashMap::default();
         if ecx.machine.communicate() || !config.forwarded_env_vars.is_empty() {
-            for (name, value) in &config.env {
+            let host_env = ecx.machine.recorder.host_env(|| config.env.clone());
+            for (name, value) in &host_env {
                 let forward = ecx.machine.communicate()
                     || config.forwarded_env_vars.iter().any(|v| **v == *name);
                 if forward {
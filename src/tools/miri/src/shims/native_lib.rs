@@ -3,12 +3,21 @@
 
 use libffi::high::call as ffi;
 use libffi::low::CodePtr;
+use rustc_data_structures::fx::FxHashSet;
 use rustc_middle::ty::{self as ty, IntTy, UintTy};
 use rustc_span::Symbol;
-use rustc_target::abi::{Abi, HasDataLayout};
+use rustc_target::abi::{Abi, HasDataLayout, Size};
 
 use crate::*;
 
+/// The state of an allocation that native code can access, from before the call.
+struct AllocSnapshot {
+    alloc_id: AllocId,
+    bytes: Vec<u8>,
+    /// The pointers stored in the allocation, by offset.
+    ptrs: Vec<(Size, Provenance)>,
+}
+
 impl<'tcx> EvalContextExtPriv<'tcx> for crate::MiriInterpCx<'tcx> {}
 trait EvalContextExtPriv<'tcx>: crate::MiriInterpCxExt<'tcx> {
     /// Call native host function and return the output as an immediate.
@@ -121,6 +130,120 @@ fn get_func_ptr_explicitly_from_lib(&mut self, link_name: Symbol) -> Option<Code
         // Return a pointer to the function.
         Some(CodePtr(*func.deref() as *mut _))
     }
+
+    /// Prepares the allocations that native code can reach from `roots` for the call: native code
+    /// can follow all pointers stored in them, so we expose all of them. Returns a snapshot of
+    /// all writable allocations among them.
+    fn prepare_native_memory(
+        &mut self,
+        roots: Vec<AllocId>,
+    ) -> InterpResult<'tcx, Vec<AllocSnapshot>> {
+        let this = self.eval_context_mut();
+        let ptr_size = this.pointer_size();
+        let mut snapshots = Vec::new();
+        let mut visited = FxHashSet::default();
+        let mut todo = roots;
+        while let Some(alloc_id) = todo.pop() {
+            if !visited.insert(alloc_id) {
+                continue;
+            }
+            // Functions and vtables have no bytes native code could access.
+            if !matches!(this.get_alloc_info(alloc_id).2, AllocKind::LiveData) {
+                continue;
+            }
+            let provenances: Vec<Provenance> = if this.get_alloc_mutability(alloc_id)?.is_mut() {
+                let alloc = this.get_alloc_for_native_call_mut(alloc_id)?;
+                let len = alloc.len();
+                let ptrs = (0..len.saturating_sub(ptr_size.bytes_usize() - 1))
+                    .filter_map(|offset| {
+                        let offset = Size::from_bytes(offset);
+                        alloc.provenance().get_ptr(offset).map(|prov| (offset, prov))
+                    })
+                    .collect();
+                let bytes = alloc.inspect_with_uninit_and_ptr_outside_interpreter(0..len);
+                snapshots.push(AllocSnapshot { alloc_id, bytes: bytes.to_vec(), ptrs });
+                alloc.provenance().provenances().collect()
+            } else {
+                match this.memory.alloc_map().get(alloc_id) {
+                    Some((_kind, alloc)) => alloc.provenance().provenances().collect(),
+                    // This global allocation was never accessed by the interpreter, so it
+                    // cannot contain pointers we would have to track.
+                    None => Vec::new(),
+                }
+            };
+            for prov in provenances {
+                if let Provenance::Concrete { alloc_id, tag } = prov {
+                    this.expose_ptr(alloc_id, tag)?;
+                    todo.push(alloc_id);
+                }
+            }
+        }
+        interp_ok(snapshots)
+    }
+
+    /// Makes the changes native code made to the memory in `snapshots` visible to the interpreter.
+    fn finish_native_memory(&mut self, snapshots: Vec<AllocSnapshot>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let tcx = this.tcx.tcx;
+        let ptr_size = this.pointer_size();
+        let endian = this.data_layout().endian;
+        for AllocSnapshot { alloc_id, bytes: old_bytes, ptrs } in snapshots {
+            let alloc = this.get_alloc_for_native_call_mut(alloc_id)?;
+            let len = alloc.len();
+            // The bytes themselves are shared with native code, so they are up to date already.
+            let bytes = alloc.inspect_with_uninit_and_ptr_outside_interpreter(0..len).to_vec();
+            // We cannot know which bytes native code wrote, so we approximate by marking every
+            // byte of the allocation as initialized, even the ones native code did not touch. This
+            // means that reads of bytes that were uninitialized before the call are not reported.
+            // This also removes all provenance, which we restore below where nothing changed.
+            alloc
+                .get_bytes_unchecked_for_overwrite(
+                    &tcx,
+                    alloc_range(Size::ZERO, Size::from_bytes(len)),
+                )
+                .map_err(|e| e.to_interp_error(alloc_id))?;
+            let ptr_at = |offset: usize| {
+                let addr =
+                    read_target_uint(endian, &bytes[offset..][..ptr_size.bytes_usize()]).unwrap();
+                Size::from_bytes(addr)
+            };
+            let changed = |offset: usize| {
+                let range = offset..offset + ptr_size.bytes_usize();
+                bytes[range.clone()] != old_bytes[range]
+            };
+            for (offset, prov) in ptrs {
+                if !changed(offset.bytes_usize()) {
+                    let ptr = Pointer::new(Some(prov), ptr_at(offset.bytes_usize()));
+                    alloc
+                        .write_scalar(
+                            &tcx,
+                            alloc_range(offset, ptr_size),
+                            Scalar::from_maybe_pointer(ptr, &tcx),
+                        )
+                        .map_err(|e| e.to_interp_error(alloc_id))?;
+                }
+            }
+            // Native code may have stored pointers in the bytes it changed. Since it could only
+            // have gotten them from exposed allocations, wildcard provenance lets the interpreted
+            // program use them. We approximate by only looking for pointers at pointer-aligned
+            // offsets: a pointer native code stored at an unaligned offset has no provenance, so
+            // using it is reported as UB.
+            for offset in (0..len).step_by(ptr_size.bytes_usize()) {
+                let range = alloc_range(Size::from_bytes(offset), ptr_size);
+                if range.end().bytes_usize() > len
+                    || !changed(offset)
+                    || !alloc.provenance().range_empty(range, &tcx)
+                {
+                    continue;
+                }
+                let ptr = Pointer::new(Some(Provenance::Wildcard), ptr_at(offset));
+                alloc
+                    .write_scalar(&tcx, range, Scalar::from_maybe_pointer(ptr, &tcx))
+                    .map_err(|e| e.to_interp_error(alloc_id))?;
+            }
+        }
+        interp_ok(())
+    }
 }
 
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
@@ -148,12 +271,27 @@ fn call_native_fn(
 
         // Get the function arguments, and convert them to `libffi`-compatible form.
         let mut libffi_args = Vec::<CArg>::with_capacity(args.len());
+        // The allocations that pointer arguments point to.
+        let mut roots = Vec::new();
         for arg in args.iter() {
             if !matches!(arg.layout.abi, Abi::Scalar(_)) {
                 throw_unsup_format!("only scalar argument types are support for native calls")
             }
-            libffi_args.push(imm_to_carg(this.read_immediate(arg)?, this)?);
+            let imm = this.read_immediate(arg)?;
+            if matches!(imm.layout.ty.kind(), ty::RawPtr(..)) {
+                let ptr = imm.to_scalar().to_pointer(this)?;
+                if let Some(Provenance::Concrete { alloc_id, tag }) = ptr.provenance {
+                    this.expose_ptr(alloc_id, tag)?;
+                }
+                // Wildcard pointers may point to any exposed allocation; use the one that
+                // contains the address.
+                if let Ok((alloc_id, _offset, _extra)) = this.ptr_try_get_alloc_id(ptr, 0) {
+                    roots.push(alloc_id);
+                }
+            }
+            libffi_args.push(imm_to_carg(imm, this)?);
         }
+        let snapshots = this.prepare_native_memory(roots)?;
 
         // Convert them to `libffi::high::Arg` type.
         let libffi_args = libffi_args
@@ -163,6 +301,7 @@ fn call_native_fn(
 
         // Call the function and store output, depending on return type in the function signature.
         let ret = this.call_native_with_args(link_name, dest, code_ptr, libffi_args)?;
+        this.finish_native_memory(snapshots)?;
         this.write_immediate(*ret, dest)?;
         interp_ok(true)
     }
@@ -238,18 +377,10 @@ fn imm_to_carg<'tcx>(v: ImmTy<'tcx>, cx: &impl HasDataLayout) -> InterpResult<'t
         ty::Uint(UintTy::U64) => CArg::UInt64(v.to_scalar().to_u64()?),
         ty::Uint(UintTy::Usize) =>
             CArg::USize(v.to_scalar().to_target_usize(cx)?.try_into().unwrap()),
-        ty::RawPtr(_, mutability) => {
-            // Arbitrary mutable pointer accesses are not currently supported in Miri.
-            if mutability.is_mut() {
-                throw_unsup_format!(
-                    "unsupported mutable pointer type for native call: {}",
-                    v.layout.ty
-                );
-            } else {
-                let s = v.to_scalar().to_pointer(cx)?.addr();
-                // This relies on the `expose_provenance` in `addr_from_alloc_id`.
-                CArg::RawPtr(std::ptr::with_exposed_provenance_mut(s.bytes_usize()))
-            }
+        ty::RawPtr(..) => {
+            let s = v.to_scalar().to_pointer(cx)?.addr();
+            // This relies on the `expose_provenance` in `addr_from_alloc_id`.
+            CArg::RawPtr(std::ptr::with_exposed_provenance_mut(s.bytes_usize()))
         }
         _ => throw_unsup_format!("unsupported argument type for native call: {}", v.layout.ty),
     })
@@ -112,6 +112,11 @@ fn flock<'tcx>(
         throw_unsup_format!("cannot flock {}", self.name());
     }
 
+    /// Enables or disables non-blocking mode, as requested by `ioctl(FIONBIO)`.
+    fn set_nonblocking<'tcx>(&self, _nonblocking: bool) -> InterpResult<'tcx, io::Result<()>> {
+        throw_unsup_format!("cannot set {} to non-blocking mode", self.name());
+    }
+
     fn is_tty(&self, _communicate_allowed: bool) -> bool {
         // Most FDs are not tty's and the consequence of a wrong `false` are minor,
         // so we use a default impl here.
@@ -150,7 +155,8 @@ fn read<'tcx>(
             // We want isolation mode to be deterministic, so we have to disallow all reads, even stdin.
             helpers::isolation_abort_error("`read` from stdin")?;
         }
-        let result = Read::read(&mut { self }, &mut bytes);
+        let result =
+            ecx.machine.recorder.host_read(&mut bytes, |bytes| Read::read(&mut { self }, bytes));
         match result {
             Ok(read_size) => ecx.return_read_success(ptr, &bytes, read_size, dest),
             Err(e) => ecx.set_last_error_and_return(e, dest),
@@ -541,6 +547,37 @@ fn fcntl(&mut self, args: &[OpTy<'tcx>]) -> InterpResult<'tcx, Scalar> {
         }
     }
 
+    fn ioctl(&mut self, args: &[OpTy<'tcx>]) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let [fd_num, request] = check_min_arg_count("ioctl", args)?;
+
+        let fd_num = this.read_scalar(fd_num)?.to_i32()?;
+        // The type of the request differs between targets, so we compare its bits.
+        let request = this.read_scalar(request)?.to_uint(request.layout.size)?;
+
+        let fioclex = this.eval_libc("FIOCLEX");
+        let fioclex = fioclex.to_uint(fioclex.size())?;
+        let fionbio = this.eval_libc("FIONBIO");
+        let fionbio = fionbio.to_uint(fionbio.size())?;
+
+        let Some(fd) = this.machine.fds.get(fd_num) else {
+            return interp_ok(Scalar::from_i32(this.fd_not_found()?));
+        };
+        if request == fioclex {
+            // As usual we ignore CLOEXEC.
+            interp_ok(Scalar::from_i32(0))
+        } else if request == fionbio {
+            let [_, _, arg] = check_min_arg_count("ioctl(fd, FIONBIO, ...)", args)?;
+            let arg = this.deref_pointer_as(arg, this.machine.layouts.i32)?;
+            let nonblocking = this.read_scalar(&arg)?.to_i32()? != 0;
+            let result = fd.set_nonblocking(nonblocking)?.map(|()| 0i32);
+            interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result)?))
+        } else {
+            throw_unsup_format!("ioctl: unsupported request {request:#x}");
+        }
+    }
+
     fn close(&mut self, fd_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
         let this = self.eval_context_mut();
 
@@ -1,9 +1,7 @@
 //! File and file system access
 
 use std::borrow::Cow;
-use std::fs::{
-    DirBuilder, File, FileType, OpenOptions, ReadDir, read_dir, remove_dir, remove_file, rename,
-};
+use std::fs::{DirBuilder, File, OpenOptions, ReadDir, read_dir, remove_dir, remove_file, rename};
 use std::io::{self, ErrorKind, IsTerminal, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
@@ -41,7 +39,8 @@ fn read<'tcx>(
     ) -> InterpResult<'tcx> {
         assert!(communicate_allowed, "isolation should have prevented even opening a file");
         let mut bytes = vec![0; len];
-        let result = (&mut &self.file).read(&mut bytes);
+        let result =
+            ecx.machine.recorder.host_read(&mut bytes, |bytes| (&mut &self.file).read(bytes));
         match result {
             Ok(read_size) => ecx.return_read_success(ptr, &bytes, read_size, dest),
             Err(e) => ecx.set_last_error_and_return(e, dest),
@@ -81,16 +80,16 @@ fn pread<'tcx>(
         // Correctness of this emulation relies on sequential nature of Miri execution.
         // The closure is used to emulate `try` block, since we "bubble" `io::Error` using `?`.
         let file = &mut &self.file;
-        let mut f = || {
+        let f = |bytes: &mut [u8]| {
             let cursor_pos = file.stream_position()?;
             file.seek(SeekFrom::Start(offset))?;
-            let res = file.read(&mut bytes);
+            let res = file.read(bytes);
             // Attempt to restore cursor position even if the read has failed
             file.seek(SeekFrom::Start(cursor_pos))
                 .expect("failed to restore file position, this shouldn't be possible");
             res
         };
-        let result = f();
+        let result = ecx.machine.recorder.host_read(&mut bytes, f);
         match result {
             Ok(read_size) => ecx.return_read_success(ptr, &bytes, read_size, dest),
             Err(e) => ecx.set_last_error_and_return(e, dest),
@@ -307,35 +306,22 @@ fn macos_stat_write_buf(
 
     fn file_type_to_d_type(
         &mut self,
-        file_type: std::io::Result<FileType>,
+        file_type: std::io::Result<FileKind>,
     ) -> InterpResult<'tcx, i32> {
-        #[cfg(unix)]
-        use std::os::unix::fs::FileTypeExt;
-
         let this = self.eval_context_mut();
         match file_type {
             Ok(file_type) => {
-                match () {
-                    _ if file_type.is_dir() => interp_ok(this.eval_libc("DT_DIR").to_u8()?.into()),
-                    _ if file_type.is_file() => interp_ok(this.eval_libc("DT_REG").to_u8()?.into()),
-                    _ if file_type.is_symlink() =>
-                        interp_ok(this.eval_libc("DT_LNK").to_u8()?.into()),
-                    // Certain file types are only supported when the host is a Unix system.
-                    #[cfg(unix)]
-                    _ if file_type.is_block_device() =>
-                        interp_ok(this.eval_libc("DT_BLK").to_u8()?.into()),
-                    #[cfg(unix)]
-                    _ if file_type.is_char_device() =>
-                        interp_ok(this.eval_libc("DT_CHR").to_u8()?.into()),
-                    #[cfg(unix)]
-                    _ if file_type.is_fifo() =>
-                        interp_ok(this.eval_libc("DT_FIFO").to_u8()?.into()),
-                    #[cfg(unix)]
-                    _ if file_type.is_socket() =>
-                        interp_ok(this.eval_libc("DT_SOCK").to_u8()?.into()),
-                    // Fallback
-                    _ => interp_ok(this.eval_libc("DT_UNKNOWN").to_u8()?.into()),
-                }
+                let name = match file_type {
+                    FileKind::Dir => "DT_DIR",
+                    FileKind::File => "DT_REG",
+                    FileKind::Symlink => "DT_LNK",
+                    FileKind::BlockDevice => "DT_BLK",
+                    FileKind::CharDevice => "DT_CHR",
+                    FileKind::Fifo => "DT_FIFO",
+                    FileKind::Socket => "DT_SOCK",
+                    FileKind::Unknown => "DT_UNKNOWN",
+                };
+                interp_ok(this.eval_libc(name).to_u8()?.into())
             }
             Err(e) =>
                 match e.raw_os_error() {
@@ -569,8 +555,10 @@ fn open(&mut self, args: &[OpTy<'tcx>]) -> InterpResult<'tcx, Scalar> {
             return interp_ok(Scalar::from_i32(-1));
         }
 
-        let fd = options
-            .open(path)
+        let fd = this
+            .machine
+            .recorder
+            .host_open(|| options.open(path))
             .map(|file| this.machine.fds.insert_new(FileHandle { file, writable }));
 
         interp_ok(Scalar::from_i32(this.try_unwrap_io_result(fd)?))
@@ -1024,7 +1012,7 @@ fn opendir(&mut self, name_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
             return interp_ok(Scalar::null_ptr(this));
         }
 
-        let result = read_dir(name);
+        let result = this.machine.recorder.host_open(|| read_dir(name));
 
         match result {
             Ok(dir_iter) => {
@@ -1061,7 +1049,12 @@ fn linux_readdir64(&mut self, dirp_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar
             err_unsup_format!("the DIR pointer passed to readdir64 did not come from opendir")
         })?;
 
-        let entry = match open_dir.read_dir.next() {
+        let read_dir = &mut open_dir.read_dir;
+        let entry = this
+            .machine
+            .recorder
+            .host_dir_entry(|| read_dir.next().map(|entry| entry.map(HostDirEntry::from)));
+        let entry = match entry {
             Some(Ok(dir_entry)) => {
                 // Write the directory entry into a newly allocated buffer.
                 // The name is written with write_bytes, while the rest of the
@@ -1076,7 +1069,7 @@ fn linux_readdir64(&mut self, dirp_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar
                 //     pub d_name: [c_char; 256],
                 // }
 
-                let mut name = dir_entry.file_name(); // not a Path as there are no separators!
+                let mut name = dir_entry.name.clone(); // not a Path as there are no separators!
                 name.push("\0"); // Add a NUL terminator
                 let name_bytes = name.as_encoded_bytes();
                 let name_len = u64::try_from(name_bytes.len()).unwrap();
@@ -1092,12 +1085,8 @@ fn linux_readdir64(&mut self, dirp_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar
                 )?;
                 let entry: Pointer = entry.into();
 
-                // If the host is a Unix system, fill in the inode number with its real value.
-                // If not, use 0 as a fallback value.
-                #[cfg(unix)]
-                let ino = std::os::unix::fs::DirEntryExt::ino(&dir_entry);
-                #[cfg(not(unix))]
-                let ino = 0u64;
+                // If the host is not a Unix system, the inode number is 0.
+                let ino = dir_entry.ino;
 
                 let file_type = this.file_type_to_d_type(dir_entry.file_type())?;
 
@@ -1159,7 +1148,12 @@ fn macos_fbsd_readdir_r(
         let open_dir = this.machine.dirs.streams.get_mut(&dirp).ok_or_else(|| {
             err_unsup_format!("the DIR pointer passed to readdir_r did not come from opendir")
         })?;
-        interp_ok(Scalar::from_i32(match open_dir.read_dir.next() {
+        let read_dir = &mut open_dir.read_dir;
+        let entry = this
+            .machine
+            .recorder
+            .host_dir_entry(|| read_dir.next().map(|entry| entry.map(HostDirEntry::from)));
+        interp_ok(Scalar::from_i32(match entry {
             Some(Ok(dir_entry)) => {
                 // Write into entry, write pointer to result, return 0 on success.
                 // The name is written with write_os_str_to_c_str, while the rest of the
@@ -1178,9 +1172,9 @@ fn macos_fbsd_readdir_r(
                 let entry_place = this.deref_pointer_as(entry_op, this.libc_ty_layout("dirent"))?;
                 let name_place = this.project_field_named(&entry_place, "d_name")?;
 
-                let file_name = dir_entry.file_name(); // not a Path as there are no separators!
+                // The name is not a Path as there are no separators!
                 let (name_fits, file_name_buf_len) = this.write_os_str_to_c_str(
-                    &file_name,
+                    &dir_entry.name,
                     name_place.ptr(),
                     name_place.layout.size.bytes(),
                 )?;
@@ -1191,12 +1185,8 @@ fn macos_fbsd_readdir_r(
                     );
                 }
 
-                // If the host is a Unix system, fill in the inode number with its real value.
-                // If not, use 0 as a fallback value.
-                #[cfg(unix)]
-                let ino = std::os::unix::fs::DirEntryExt::ino(&dir_entry);
-                #[cfg(not(unix))]
-                let ino = 0u64;
+                // If the host is not a Unix system, the inode number is 0.
+                let ino = dir_entry.ino;
 
                 let file_type = this.file_type_to_d_type(dir_entry.file_type())?;
 
@@ -1673,12 +1663,9 @@ fn mkstemp(&mut self, template_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
 }
 
 /// Extracts the number of seconds and nanoseconds elapsed between `time` and the unix epoch when
-/// `time` is Ok. Returns `None` if `time` is an error. Fails if `time` happens before the unix
-/// epoch.
-fn extract_sec_and_nsec<'tcx>(
-    time: std::io::Result<SystemTime>,
-) -> InterpResult<'tcx, Option<(u64, u32)>> {
-    match time.ok() {
+/// `time` is known. Returns `None` if it is not. Fails if `time` happens before the unix epoch.
+fn extract_sec_and_nsec<'tcx>(time: Option<SystemTime>) -> InterpResult<'tcx, Option<(u64, u32)>> {
+    match time {
         Some(time) => {
             let duration = system_time_to_duration(&time)?;
             interp_ok(Some((duration.as_secs(), duration.subsec_nanos())))
@@ -1703,8 +1690,14 @@ fn from_path<'tcx>(
         path: &Path,
         follow_symlink: bool,
     ) -> InterpResult<'tcx, Option<FileMetadata>> {
-        let metadata =
-            if follow_symlink { std::fs::metadata(path) } else { std::fs::symlink_metadata(path) };
+        let metadata = ecx.machine.recorder.host_metadata(|| {
+            let metadata = if follow_symlink {
+                std::fs::metadata(path)
+            } else {
+                std::fs::symlink_metadata(path)
+            };
+            metadata.map(HostMetadata::from)
+        });
 
         FileMetadata::from_meta(ecx, metadata)
     }
@@ -1726,14 +1719,15 @@ fn from_fd_num<'tcx>(
             })?
             .file;
 
-        let metadata = file.metadata();
+        let metadata =
+            ecx.machine.recorder.host_metadata(|| file.metadata().map(HostMetadata::from));
         drop(fd);
         FileMetadata::from_meta(ecx, metadata)
     }
 
     fn from_meta<'tcx>(
         ecx: &mut MiriInterpCx<'tcx>,
-        metadata: Result<std::fs::Metadata, std::io::Error>,
+        metadata: Result<HostMetadata, std::io::Error>,
     ) -> InterpResult<'tcx, Option<FileMetadata>> {
         let metadata = match metadata {
             Ok(metadata) => metadata,
@@ -1743,23 +1737,19 @@ fn from_meta<'tcx>(
             }
         };
 
-        let file_type = metadata.file_type();
-
-        let mode_name = if file_type.is_file() {
-            "S_IFREG"
-        } else if file_type.is_dir() {
-            "S_IFDIR"
-        } else {
-            "S_IFLNK"
+        let mode_name = match metadata.kind {
+            FileKind::File => "S_IFREG",
+            FileKind::Dir => "S_IFDIR",
+            _ => "S_IFLNK",
         };
 
         let mode = ecx.eval_libc(mode_name);
 
-        let size = metadata.len();
+        let size = metadata.size;
 
-        let created = extract_sec_and_nsec(metadata.created())?;
-        let accessed = extract_sec_and_nsec(metadata.accessed())?;
-        let modified = extract_sec_and_nsec(metadata.modified())?;
+        let created = extract_sec_and_nsec(metadata.created)?;
+        let accessed = extract_sec_and_nsec(metadata.accessed)?;
+        let modified = extract_sec_and_nsec(metadata.modified)?;
 
         // FIXME: Provide more fields using platform specific methods.
         interp_ok(Some(FileMetadata { mode, size, created, accessed, modified }))
@@ -468,27 +468,23 @@ fn epoll_wait(
         // is not close after the thread unblocks.
         let weak_epfd = epfd.downgrade();
 
-        // We just need to know if the ready list is empty and borrow the thread_ids out.
-        // The whole logic is wrapped inside a block so we don't need to manually drop epfd later.
-        let ready_list_empty;
-        let mut thread_ids;
-        {
-            let epoll_file_description = epfd
-                .downcast::<Epoll>()
-                .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_wait`"))?;
-            ready_list_empty = epoll_file_description.ready_list.mapping.borrow().is_empty();
-            thread_ids = epoll_file_description.thread_id.borrow_mut();
-        }
+        // Miri does not learn about host sockets becoming ready on its own.
+        this.poll_host_sockets()?;
+
+        let ready_list_empty = epfd
+            .downcast::<Epoll>()
+            .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_wait`"))?
+            .ready_list
+            .mapping
+            .borrow()
+            .is_empty();
         if timeout == 0 || !ready_list_empty {
             // If the ready list is not empty, or the timeout is 0, we can return immediately.
             return_ready_list(epfd_value, weak_epfd, dest, &event, this)?;
         } else {
             // Blocking
             let timeout = match timeout {
-                0.. => {
-                    let duration = Duration::from_millis(timeout.try_into().unwrap());
-                    Some((TimeoutClock::Monotonic, TimeoutAnchor::Relative, duration))
-                }
+                0.. => Some(Duration::from_millis(timeout.try_into().unwrap())),
                 -1 => None,
                 ..-1 => {
                     throw_unsup_format!(
@@ -496,37 +492,7 @@ fn epoll_wait(
                     );
                 }
             };
-            thread_ids.push(this.active_thread());
-            let dest = dest.clone();
-            this.block_thread(
-                BlockReason::Epoll,
-                timeout,
-                callback!(
-                    @capture<'tcx> {
-                        epfd_value: i32,
-                        weak_epfd: WeakFileDescriptionRef,
-                        dest: MPlaceTy<'tcx>,
-                        event: MPlaceTy<'tcx>,
-                    }
-                    @unblock = |this| {
-                        return_ready_list(epfd_value, weak_epfd, &dest, &event, this)?;
-                        interp_ok(())
-                    }
-                    @timeout = |this| {
-                        // No notification after blocking timeout.
-                        let Some(epfd) = weak_epfd.upgrade() else {
-                            throw_unsup_format!("epoll FD {epfd_value} got closed while blocking.")
-                        };
-                        // Remove the current active thread_id from the blocked thread_id list.
-                        epfd.downcast::<Epoll>()
-                            .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_wait`"))?
-                            .thread_id.borrow_mut()
-                            .retain(|&id| id != this.active_thread());
-                        this.write_int(0, &dest)?;
-                        interp_ok(())
-                    }
-                ),
-            );
+            block_in_epoll_wait(epfd_value, weak_epfd, dest.clone(), event, timeout, this)?;
         }
         interp_ok(())
     }
@@ -636,6 +602,75 @@ fn check_and_update_one_event_interest<'tcx>(
     }
 }
 
+/// Blocks the active thread in `epoll_wait` until an event is ready or `timeout` expires.
+/// Miri is not notified when host sockets become ready, so while there are host sockets, the
+/// thread also wakes up periodically to poll them.
+fn block_in_epoll_wait<'tcx>(
+    epfd_value: i32,
+    weak_epfd: WeakFileDescriptionRef,
+    dest: MPlaceTy<'tcx>,
+    event: MPlaceTy<'tcx>,
+    timeout: Option<Duration>,
+    ecx: &mut MiriInterpCx<'tcx>,
+) -> InterpResult<'tcx> {
+    let Some(epfd) = weak_epfd.upgrade() else {
+        throw_unsup_format!("epoll FD {epfd_value} got closed while blocking.")
+    };
+    epfd.downcast::<Epoll>()
+        .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_wait`"))?
+        .thread_id
+        .borrow_mut()
+        .push(ecx.active_thread());
+    let wait = match (timeout, ecx.machine.network.host_poll_interval()) {
+        (Some(timeout), Some(interval)) => Some(timeout.min(interval)),
+        (timeout, interval) => timeout.or(interval),
+    };
+    // How many milliseconds of the timeout are left once we wake up without a notification.
+    let remaining_ms: Option<u64> = timeout
+        .map(|timeout| u64::try_from(timeout.saturating_sub(wait.unwrap()).as_millis()).unwrap());
+    ecx.block_thread(
+        BlockReason::Epoll,
+        wait.map(|wait| (TimeoutClock::Monotonic, TimeoutAnchor::Relative, wait)),
+        callback!(
+            @capture<'tcx> {
+                epfd_value: i32,
+                weak_epfd: WeakFileDescriptionRef,
+                dest: MPlaceTy<'tcx>,
+                event: MPlaceTy<'tcx>,
+                remaining_ms: Option<u64>,
+            }
+            @unblock = |this| {
+                return_ready_list(epfd_value, weak_epfd, &dest, &event, this)?;
+                interp_ok(())
+            }
+            @timeout = |this| {
+                let Some(epfd) = weak_epfd.upgrade() else {
+                    throw_unsup_format!("epoll FD {epfd_value} got closed while blocking.")
+                };
+                let epoll = epfd
+                    .downcast::<Epoll>()
+                    .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_wait`"))?;
+                // Remove the current active thread_id from the blocked thread_id list.
+                epoll.thread_id.borrow_mut().retain(|&id| id != this.active_thread());
+                if remaining_ms == Some(0) {
+                    // No notification after blocking timeout.
+                    this.write_int(0, &dest)?;
+                    return interp_ok(());
+                }
+                // We only woke up to poll the host sockets.
+                this.poll_host_sockets()?;
+                if epoll.ready_list.mapping.borrow().is_empty() {
+                    let timeout = remaining_ms.map(Duration::from_millis);
+                    block_in_epoll_wait(epfd_value, weak_epfd, dest, event, timeout, this)
+                } else {
+                    return_ready_list(epfd_value, weak_epfd, &dest, &event, this)
+                }
+            }
+        ),
+    );
+    interp_ok(())
+}
+
 /// Stores the ready list of the `epfd` epoll instance into `events` (which must be an array),
 /// and the number of returned events into `dest`.
 fn return_ready_list<'tcx>(
@@ -0,0 +1,1851 @@
+//! This implements the sockets created by `socket`: stream (TCP) and datagram (UDP) sockets of the
+//! `AF_INET` and `AF_INET6` families.
+//!
+//! With isolation disabled, these are backed by real host sockets. The host sockets are always put
+//! into non-blocking mode; a blocking operation is emulated by blocking the interpreted thread and
+//! retrying the operation periodically. The standard library can only establish a connection by
+//! blocking, so host connections are established on a separate host thread.
+//!
+//! With isolation enabled, the sockets are attached to a virtual loopback network that only exists
+//! inside Miri: only the loopback and unspecified addresses can be used, and sockets can only talk
+//! to other sockets of the interpreted program. This keeps the execution deterministic.
+
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, VecDeque};
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{
+    IpAddr, Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, SocketAddrV4, SocketAddrV6, TcpListener,
+    TcpStream, UdpSocket,
+};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::Duration;
+use std::{iter, thread};
+
+use rustc_target::abi::Size;
+
+use crate::concurrency::VClock;
+use crate::concurrency::thread::DynUnblockCallback;
+use crate::shims::io_error::IoError;
+use crate::shims::unix::fd::{FileDescriptionRef, WeakFileDescriptionRef};
+use crate::shims::unix::linux::epoll::{EpollReadyEvents, EvalContextExt as _};
+use crate::shims::unix::*;
+use crate::*;
+
+/// The maximum number of bytes that can be buffered on the receiving end of a virtual socket.
+/// Like for `socketpair`, this number is arbitrary.
+const MAX_SOCKET_BUFFER_CAPACITY: usize = 212992;
+
+/// The largest payload of a UDP datagram.
+const MAX_DATAGRAM_SIZE: usize = 65507;
+
+/// The first port handed out when a virtual socket is bound to port 0.
+/// This is the start of the IANA dynamic port range.
+const FIRST_EPHEMERAL_PORT: u16 = 49152;
+
+/// How often threads blocked on a host socket check whether they can make progress.
+const HOST_SOCKET_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum SocketKind {
+    Stream,
+    Datagram,
+}
+
+/// A socket created by `socket`.
+#[derive(Debug)]
+struct Socket {
+    /// Whether this is an `AF_INET6` socket.
+    is_ipv6: bool,
+    kind: SocketKind,
+    /// Whether this socket is backed by a host socket, or attached to the virtual network.
+    is_host: bool,
+    is_nonblock: Cell<bool>,
+    state: RefCell<SocketState>,
+    /// The readiness of a host socket that was last reported to epoll, as an event bitmask. This
+    /// is reset whenever the socket is used, so that the next poll reports its readiness again.
+    reported_readiness: Cell<u32>,
+    /// The error of a host connection that failed after `connect` returned EINPROGRESS. It is
+    /// reported by `SO_ERROR`.
+    connect_error: RefCell<Option<io::Error>>,
+}
+
+#[derive(Debug)]
+enum SocketState {
+    /// Neither bound nor connected yet.
+    Unbound,
+    /// A stream socket that was bound, but on which `listen` was not called yet.
+    Bound(Listener),
+    /// A stream socket that accepts connections.
+    Listening(Listener),
+    /// A host stream socket whose connection is being established by a host thread. Once that
+    /// is done, the socket is either connected, or unbound again with `connect_error` set.
+    Connecting(Receiver<io::Result<TcpStream>>),
+    /// A connected stream socket.
+    Connected(Stream),
+    /// A bound datagram socket.
+    Datagram(Datagram),
+}
+
+#[derive(Debug)]
+enum Listener {
+    /// Host listeners already accept connections as soon as they are bound.
+    Host {
+        listener: TcpListener,
+        /// A connection that was accepted on the host while checking the readiness of the
+        /// listener, and that has not been returned by `accept` yet.
+        pending: Option<(TcpStream, SocketAddr)>,
+    },
+    Virtual {
+        addr: SocketAddr,
+        /// The connections that were not accepted yet, together with the clock of the thread
+        /// that connected.
+        backlog: VecDeque<(FileDescriptionRef, VClock)>,
+    },
+}
+
+#[derive(Debug)]
+enum Stream {
+    Host(TcpStream),
+    Virtual(VirtualStream),
+}
+
+/// One end of a connection on the virtual network.
+#[derive(Debug)]
+struct VirtualStream {
+    local_addr: SocketAddr,
+    peer_addr: SocketAddr,
+    /// The socket at the other end of the connection. This is a weak reference because the peer
+    /// may be closed before us; reads will then return EOF and writes will trigger EPIPE.
+    peer: WeakFileDescriptionRef,
+    /// The data sent by the peer that was not read yet.
+    readbuf: VecDeque<u8>,
+    /// The clocks of the writes to `readbuf`, so that reads can synchronize with them.
+    clock: VClock,
+    /// Whether the peer shut down writing, i.e. no more data arrives once `readbuf` is empty.
+    peer_write_shutdown: bool,
+    read_shutdown: bool,
+    write_shutdown: bool,
+}
+
+impl VirtualStream {
+    fn new(local_addr: SocketAddr, peer_addr: SocketAddr, peer: WeakFileDescriptionRef) -> Self {
+        VirtualStream {
+            local_addr,
+            peer_addr,
+            peer,
+            readbuf: VecDeque::new(),
+            clock: VClock::default(),
+            peer_write_shutdown: false,
+            read_shutdown: false,
+            write_shutdown: false,
+        }
+    }
+
+    /// Whether reading returns EOF once `readbuf` is empty.
+    fn is_eof(&self) -> bool {
+        self.read_shutdown || self.peer_write_shutdown || self.peer.upgrade().is_none()
+    }
+}
+
+#[derive(Debug)]
+enum Datagram {
+    Host(UdpSocket),
+    Virtual(VirtualDatagram),
+}
+
+/// A datagram socket on the virtual network.
+#[derive(Debug)]
+struct VirtualDatagram {
+    local_addr: SocketAddr,
+    /// The default destination, set by `connect`.
+    peer_addr: Option<SocketAddr>,
+    /// The received datagrams that were not read yet, together with their source address and the
+    /// clock of the thread that sent them.
+    queue: VecDeque<(SocketAddr, Vec<u8>, VClock)>,
+}
+
+impl Socket {
+    fn new(is_ipv6: bool, kind: SocketKind, is_host: bool, state: SocketState) -> Self {
+        Socket {
+            is_ipv6,
+            kind,
+            is_host,
+            is_nonblock: Cell::new(false),
+            state: RefCell::new(state),
+            reported_readiness: Cell::new(0),
+            connect_error: RefCell::new(None),
+        }
+    }
+
+    /// Checks whether the host thread establishing the connection of this socket is done, and
+    /// updates the state of the socket accordingly.
+    fn finish_connecting(&self) {
+        let mut state = self.state.borrow_mut();
+        let SocketState::Connecting(receiver) = &*state else { return };
+        let stream = match receiver.try_recv() {
+            Ok(stream) => stream,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) =>
+                panic!("the host thread establishing a connection panicked"),
+        };
+        match stream.and_then(|stream| {
+            stream.set_nonblocking(true)?;
+            Ok(stream)
+        }) {
+            Ok(stream) => *state = SocketState::Connected(Stream::Host(stream)),
+            Err(e) => {
+                *state = SocketState::Unbound;
+                *self.connect_error.borrow_mut() = Some(e);
+            }
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.finish_connecting();
+        match &*self.state.borrow() {
+            SocketState::Unbound | SocketState::Connecting(_) =>
+                Ok(SocketAddr::new(unspecified_ip(self.is_ipv6), 0)),
+            SocketState::Bound(listener) | SocketState::Listening(listener) =>
+                match listener {
+                    Listener::Host { listener, .. } => listener.local_addr(),
+                    Listener::Virtual { addr, .. } => Ok(*addr),
+                },
+            SocketState::Connected(Stream::Host(stream)) => stream.local_addr(),
+            SocketState::Connected(Stream::Virtual(stream)) => Ok(stream.local_addr),
+            SocketState::Datagram(Datagram::Host(socket)) => socket.local_addr(),
+            SocketState::Datagram(Datagram::Virtual(socket)) => Ok(socket.local_addr),
+        }
+    }
+
+    fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.finish_connecting();
+        match &*self.state.borrow() {
+            SocketState::Connected(Stream::Host(stream)) => stream.peer_addr(),
+            SocketState::Connected(Stream::Virtual(stream)) => Ok(stream.peer_addr),
+            SocketState::Datagram(Datagram::Host(socket)) => socket.peer_addr(),
+            SocketState::Datagram(Datagram::Virtual(VirtualDatagram {
+                peer_addr: Some(addr),
+                ..
+            })) => Ok(*addr),
+            _ => Err(ErrorKind::NotConnected.into()),
+        }
+    }
+
+    /// Runs `f` on the virtual stream of this socket, which must be the peer of another virtual
+    /// stream.
+    fn with_virtual_stream<R>(&self, f: impl FnOnce(&mut VirtualStream) -> R) -> R {
+        match &mut *self.state.borrow_mut() {
+            SocketState::Connected(Stream::Virtual(stream)) => f(stream),
+            _ => panic!("the peer of a virtual stream must be a virtual stream"),
+        }
+    }
+}
+
+fn unspecified_ip(is_ipv6: bool) -> IpAddr {
+    if is_ipv6 { Ipv6Addr::UNSPECIFIED.into() } else { Ipv4Addr::UNSPECIFIED.into() }
+}
+
+fn loopback_ip(is_ipv6: bool) -> IpAddr {
+    if is_ipv6 { Ipv6Addr::LOCALHOST.into() } else { Ipv4Addr::LOCALHOST.into() }
+}
+
+/// Returns the address that a packet sent to `addr` on the virtual network arrives at. Like on
+/// Linux, the unspecified address refers to the local host.
+fn virtual_destination(addr: SocketAddr) -> Result<SocketAddr, IoError> {
+    if addr.ip().is_unspecified() {
+        Ok(SocketAddr::new(loopback_ip(addr.is_ipv6()), addr.port()))
+    } else if addr.ip().is_loopback() {
+        Ok(addr)
+    } else {
+        Err(ErrorKind::NetworkUnreachable.into())
+    }
+}
+
+fn is_would_block(err: &IoError) -> bool {
+    matches!(err, IoError::HostError(err) if err.kind() == ErrorKind::WouldBlock)
+}
+
+impl FileDescription for Socket {
+    fn name(&self) -> &'static str {
+        "socket"
+    }
+
+    fn read<'tcx>(
+        &self,
+        self_ref: &FileDescriptionRef,
+        _communicate_allowed: bool,
+        ptr: Pointer,
+        len: usize,
+        dest: &MPlaceTy<'tcx>,
+        ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx> {
+        ecx.socket_recv(
+            self_ref.downgrade(),
+            ptr,
+            len,
+            /* peek */ false,
+            /* dontwait */ false,
+            None,
+            dest.clone(),
+        )
+    }
+
+    fn write<'tcx>(
+        &self,
+        self_ref: &FileDescriptionRef,
+        _communicate_allowed: bool,
+        ptr: Pointer,
+        len: usize,
+        dest: &MPlaceTy<'tcx>,
+        ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx> {
+        ecx.socket_send(
+            self_ref.downgrade(),
+            ptr,
+            len,
+            /* dontwait */ false,
+            None,
+            dest.clone(),
+        )
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+        ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<()>> {
+        // Host sockets are closed by dropping them. For virtual sockets, we have to tell the peers
+        // that the connection is gone.
+        let mut peers = Vec::new();
+        match self.state.into_inner() {
+            SocketState::Connected(Stream::Virtual(stream)) => peers.extend(stream.peer.upgrade()),
+            SocketState::Listening(Listener::Virtual { backlog, .. }) =>
+                for (socket, _clock) in backlog {
+                    // Connections that were never accepted get closed along with the listener.
+                    peers.extend(
+                        socket
+                            .downcast::<Socket>()
+                            .unwrap()
+                            .with_virtual_stream(|stream| stream.peer.upgrade()),
+                    );
+                },
+            _ => {}
+        }
+        ecx.machine.network.forget_closed_sockets();
+        for peer in peers {
+            ecx.check_and_update_readiness(&peer)?;
+        }
+        ecx.wake_socket_waiters()?;
+        interp_ok(Ok(()))
+    }
+
+    fn set_nonblocking<'tcx>(&self, nonblocking: bool) -> InterpResult<'tcx, io::Result<()>> {
+        // Host sockets are always non-blocking on the host, so this only affects how we emulate
+        // the socket operations.
+        self.is_nonblock.set(nonblocking);
+        interp_ok(Ok(()))
+    }
+
+    fn get_epoll_ready_events<'tcx>(&self) -> InterpResult<'tcx, EpollReadyEvents> {
+        let mut events = EpollReadyEvents::new();
+        self.finish_connecting();
+        match &mut *self.state.borrow_mut() {
+            SocketState::Unbound | SocketState::Bound(_) => {
+                // Sending on a datagram socket never blocks, but a stream socket that is not
+                // connected is reported as hung up, like on Linux. A failed connection is also
+                // reported as an error.
+                events.epollout = true;
+                events.epollhup = self.kind == SocketKind::Stream;
+                events.epollerr = self.connect_error.borrow().is_some();
+            }
+            // Nothing can be done on the socket until the connection is established.
+            SocketState::Connecting(_) => {}
+            SocketState::Listening(Listener::Host { listener, pending }) => {
+                // The only way to find out whether a connection is waiting is to accept it.
+                if pending.is_none() {
+                    match listener.accept() {
+                        Ok(connection) => *pending = Some(connection),
+                        Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                        Err(_) => events.epollerr = true,
+                    }
+                }
+                events.epollin = pending.is_some();
+            }
+            SocketState::Listening(Listener::Virtual { backlog, .. }) => {
+                events.epollin = !backlog.is_empty();
+            }
+            SocketState::Connected(Stream::Host(stream)) => {
+                match stream.peek(&mut [0]) {
+                    Ok(0) => {
+                        events.epollin = true;
+                        events.epollrdhup = true;
+                    }
+                    Ok(_) => events.epollin = true,
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                    Err(_) => {
+                        events.epollin = true;
+                        events.epollerr = true;
+                    }
+                }
+                // We cannot tell whether the host send buffer is full, so we assume it is not.
+                events.epollout = true;
+            }
+            SocketState::Connected(Stream::Virtual(stream)) => {
+                events.epollin = !stream.readbuf.is_empty() || stream.is_eof();
+                events.epollrdhup = stream.is_eof();
+                if let Some(peer) = stream.peer.upgrade() {
+                    let peer_buffered = peer
+                        .downcast::<Socket>()
+                        .unwrap()
+                        .with_virtual_stream(|peer_stream| peer_stream.readbuf.len());
+                    events.epollout =
+                        stream.write_shutdown || peer_buffered < MAX_SOCKET_BUFFER_CAPACITY;
+                } else {
+                    // Writes return EPIPE, so they do not block. Like for TCP, the peer closing
+                    // the connection only shows up as EPOLLRDHUP, not as EPOLLHUP.
+                    events.epollout = true;
+                }
+            }
+            SocketState::Datagram(Datagram::Host(socket)) => {
+                match socket.peek_from(&mut [0]) {
+                    Ok(_) => events.epollin = true,
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                    Err(_) => {
+                        events.epollin = true;
+                        events.epollerr = true;
+                    }
+                }
+                events.epollout = true;
+            }
+            SocketState::Datagram(Datagram::Virtual(socket)) => {
+                events.epollin = !socket.queue.is_empty();
+                events.epollout = true;
+            }
+        }
+        interp_ok(events)
+    }
+}
+
+/// The virtual loopback network used by sockets while isolation is enabled, as well as the
+/// bookkeeping for host sockets.
+#[derive(Debug)]
+pub struct VirtualNetwork {
+    /// The virtual sockets, by the address they are bound to.
+    bound: BTreeMap<(SocketKind, SocketAddr), WeakFileDescriptionRef>,
+    /// The next candidate when handing out an ephemeral port.
+    next_ephemeral_port: u16,
+    /// The threads blocked on a virtual socket. They are woken up whenever the virtual network
+    /// changes, and retry their operation.
+    blocked_threads: Vec<ThreadId>,
+    /// All host sockets, whose readiness has to be polled for epoll.
+    host_sockets: Vec<WeakFileDescriptionRef>,
+}
+
+impl VirtualNetwork {
+    pub(crate) fn new() -> Self {
+        VirtualNetwork {
+            bound: BTreeMap::new(),
+            next_ephemeral_port: FIRST_EPHEMERAL_PORT,
+            blocked_threads: Vec::new(),
+            host_sockets: Vec::new(),
+        }
+    }
+
+    /// Returns how often the readiness of host sockets should be polled, or `None` if there are
+    /// no host sockets.
+    pub fn host_poll_interval(&self) -> Option<Duration> {
+        (!self.host_sockets.is_empty()).then_some(HOST_SOCKET_POLL_INTERVAL)
+    }
+
+    fn forget_closed_sockets(&mut self) {
+        self.bound.retain(|_, socket| socket.upgrade().is_some());
+        self.host_sockets.retain(|socket| socket.upgrade().is_some());
+    }
+
+    /// Returns the socket receiving what is sent to `addr`. This is either the socket bound to
+    /// exactly that address, or the one bound to the unspecified address and the same port.
+    fn lookup(&self, kind: SocketKind, addr: SocketAddr) -> Option<FileDescriptionRef> {
+        let unspecified = SocketAddr::new(unspecified_ip(addr.is_ipv6()), addr.port());
+        [addr, unspecified]
+            .into_iter()
+            .find_map(|addr| self.bound.get(&(kind, addr)).and_then(|socket| socket.upgrade()))
+    }
+
+    fn is_in_use(&self, kind: SocketKind, addr: SocketAddr) -> bool {
+        self.bound.iter().any(|(&(bound_kind, bound_addr), socket)| {
+            bound_kind == kind
+                && bound_addr.port() == addr.port()
+                && bound_addr.is_ipv6() == addr.is_ipv6()
+                && (bound_addr.ip() == addr.ip()
+                    || bound_addr.ip().is_unspecified()
+                    || addr.ip().is_unspecified())
+                && socket.upgrade().is_some()
+        })
+    }
+
+    /// Binds `socket` to `addr`, and returns the address it got bound to. Port 0 is replaced by
+    /// an ephemeral port.
+    fn bind(
+        &mut self,
+        kind: SocketKind,
+        mut addr: SocketAddr,
+        socket: &FileDescriptionRef,
+    ) -> Result<SocketAddr, IoError> {
+        if !addr.ip().is_loopback() && !addr.ip().is_unspecified() {
+            return Err(ErrorKind::AddrNotAvailable.into());
+        }
+        if addr.port() != 0 {
+            if self.is_in_use(kind, addr) {
+                return Err(ErrorKind::AddrInUse.into());
+            }
+            self.bound.insert((kind, addr), socket.downgrade());
+            return Ok(addr);
+        }
+        // Hand out the ephemeral ports in order, so that the execution stays deterministic.
+        for _ in FIRST_EPHEMERAL_PORT..=u16::MAX {
+            let port = self.next_ephemeral_port;
+            self.next_ephemeral_port = port.checked_add(1).unwrap_or(FIRST_EPHEMERAL_PORT);
+            addr.set_port(port);
+            if !self.is_in_use(kind, addr) {
+                self.bound.insert((kind, addr), socket.downgrade());
+                return Ok(addr);
+            }
+        }
+        Err(ErrorKind::AddrInUse.into())
+    }
+}
+
+impl<'tcx> EvalContextPrivExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+trait EvalContextPrivExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// Returns the socket behind the file descriptor `fd_num`, or sets the last error and returns
+    /// `None` if there is no such socket.
+    fn get_socket(&mut self, fd_num: i32) -> InterpResult<'tcx, Option<FileDescriptionRef>> {
+        let this = self.eval_context_mut();
+        let Some(fd) = this.machine.fds.get(fd_num) else {
+            this.set_last_error(LibcError("EBADF"))?;
+            return interp_ok(None);
+        };
+        if fd.downcast::<Socket>().is_none() {
+            this.set_last_error(LibcError("ENOTSOCK"))?;
+            return interp_ok(None);
+        }
+        interp_ok(Some(fd))
+    }
+
+    /// Reads the `sockaddr` of length `len` at `ptr`.
+    fn read_socket_addr(
+        &self,
+        ptr: Pointer,
+        len: u32,
+    ) -> InterpResult<'tcx, Result<SocketAddr, IoError>> {
+        let this = self.eval_context_ref();
+        if u64::from(len) < this.libc_ty_layout("sa_family_t").size.bytes() {
+            return interp_ok(Err(LibcError("EINVAL")));
+        }
+        let sockaddr = this.ptr_to_mplace(ptr, this.libc_ty_layout("sockaddr"));
+        let family = this.project_field_named(&sockaddr, "sa_family")?;
+        let family = this.read_scalar(&family)?.to_uint(family.layout.size)?;
+        let family = i32::try_from(family).unwrap();
+
+        if family == this.eval_libc_i32("AF_INET") {
+            let sockaddr = this.ptr_to_mplace(ptr, this.libc_ty_layout("sockaddr_in"));
+            if u64::from(len) < sockaddr.layout.size.bytes() {
+                return interp_ok(Err(LibcError("EINVAL")));
+            }
+            let port = this.project_field_named(&sockaddr, "sin_port")?;
+            let port = this.read_bytes_ptr_strip_provenance(port.ptr(), Size::from_bytes(2))?;
+            let port = u16::from_be_bytes(port.try_into().unwrap());
+            let ip = this.project_field_named(&sockaddr, "sin_addr")?;
+            let ip = this.read_bytes_ptr_strip_provenance(ip.ptr(), Size::from_bytes(4))?;
+            let ip = Ipv4Addr::from(<[u8; 4]>::try_from(ip).unwrap());
+            interp_ok(Ok(SocketAddrV4::new(ip, port).into()))
+        } else if family == this.eval_libc_i32("AF_INET6") {
+            let sockaddr = this.ptr_to_mplace(ptr, this.libc_ty_layout("sockaddr_in6"));
+            if u64::from(len) < sockaddr.layout.size.bytes() {
+                return interp_ok(Err(LibcError("EINVAL")));
+            }
+            let port = this.project_field_named(&sockaddr, "sin6_port")?;
+            let port = this.read_bytes_ptr_strip_provenance(port.ptr(), Size::from_bytes(2))?;
+            let port = u16::from_be_bytes(port.try_into().unwrap());
+            let ip = this.project_field_named(&sockaddr, "sin6_addr")?;
+            let ip = this.read_bytes_ptr_strip_provenance(ip.ptr(), Size::from_bytes(16))?;
+            let ip = Ipv6Addr::from(<[u8; 16]>::try_from(ip).unwrap());
+            let flowinfo = this.project_field_named(&sockaddr, "sin6_flowinfo")?;
+            let flowinfo = this.read_scalar(&flowinfo)?.to_u32()?;
+            let scope_id = this.project_field_named(&sockaddr, "sin6_scope_id")?;
+            let scope_id = this.read_scalar(&scope_id)?.to_u32()?;
+            interp_ok(Ok(SocketAddrV6::new(ip, port, flowinfo, scope_id).into()))
+        } else {
+            interp_ok(Err(LibcError("EAFNOSUPPORT")))
+        }
+    }
+
+    /// Reads the address argument of `bind`, `connect` or `sendto`, which has to match the family
+    /// of `socket`.
+    fn read_socket_addr_for(
+        &self,
+        socket: &Socket,
+        ptr: &OpTy<'tcx>,
+        len: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Result<SocketAddr, IoError>> {
+        let this = self.eval_context_ref();
+        let ptr = this.read_pointer(ptr)?;
+        let len = this.read_scalar(len)?.to_u32()?;
+        interp_ok(this.read_socket_addr(ptr, len)?.and_then(|addr| {
+            if addr.is_ipv6() == socket.is_ipv6 { Ok(addr) } else { Err(LibcError("EAFNOSUPPORT")) }
+        }))
+    }
+
+    /// Writes `addr` to the `sockaddr` buffer at `ptr`, whose length is stored at `len_ptr`. The
+    /// address gets truncated if the buffer is too short, and its actual length is stored at
+    /// `len_ptr`. Nothing happens if `ptr` is null.
+    fn write_socket_addr(
+        &mut self,
+        addr: SocketAddr,
+        ptr: Pointer,
+        len_ptr: Pointer,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        if this.ptr_is_null(ptr)? {
+            return interp_ok(());
+        }
+        let len_place = this.ptr_to_mplace(len_ptr, this.libc_ty_layout("socklen_t"));
+        let buf_len = this.read_scalar(&len_place)?.to_u32()?;
+
+        // Build the full address in a temporary allocation, and then copy as much as fits.
+        let (layout, prefix) = match addr {
+            SocketAddr::V4(_) => (this.libc_ty_layout("sockaddr_in"), "sin"),
+            SocketAddr::V6(_) => (this.libc_ty_layout("sockaddr_in6"), "sin6"),
+        };
+        let sockaddr = this.allocate(layout, MiriMemoryKind::Machine.into())?;
+        this.write_bytes_ptr(sockaddr.ptr(), iter::repeat(0u8).take(layout.size.bytes_usize()))?;
+        // BSDs store the length of the address in its first byte.
+        let len_field = format!("{prefix}_len");
+        if this.projectable_has_field(&sockaddr, &len_field) {
+            let len_field = this.project_field_named(&sockaddr, &len_field)?;
+            this.write_int(layout.size.bytes(), &len_field)?;
+        }
+        let port = this.project_field_named(&sockaddr, &format!("{prefix}_port"))?;
+        this.write_bytes_ptr(port.ptr(), addr.port().to_be_bytes())?;
+        match addr {
+            SocketAddr::V4(addr) => {
+                let family = this.project_field_named(&sockaddr, "sin_family")?;
+                this.write_int(this.eval_libc_i32("AF_INET"), &family)?;
+                let ip = this.project_field_named(&sockaddr, "sin_addr")?;
+                this.write_bytes_ptr(ip.ptr(), addr.ip().octets())?;
+            }
+            SocketAddr::V6(addr) => {
+                let family = this.project_field_named(&sockaddr, "sin6_family")?;
+                this.write_int(this.eval_libc_i32("AF_INET6"), &family)?;
+                let ip = this.project_field_named(&sockaddr, "sin6_addr")?;
+                this.write_bytes_ptr(ip.ptr(), addr.ip().octets())?;
+                this.write_int_fields_named(
+                    &[
+                        ("sin6_flowinfo", addr.flowinfo().into()),
+                        ("sin6_scope_id", addr.scope_id().into()),
+                    ],
+                    &sockaddr,
+                )?;
+            }
+        }
+        let copy_len = u64::from(buf_len).min(layout.size.bytes());
+        this.mem_copy(
+            sockaddr.ptr(),
+            ptr,
+            Size::from_bytes(copy_len),
+            /* nonoverlapping */ true,
+        )?;
+        this.deallocate_ptr(sockaddr.ptr(), None, MiriMemoryKind::Machine.into())?;
+        this.write_int(layout.size.bytes(), &len_place)?;
+        interp_ok(())
+    }
+
+    /// Shared implementation of `getsockname` and `getpeername`.
+    fn write_socket_addr_of(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        address: &OpTy<'tcx>,
+        address_len: &OpTy<'tcx>,
+        get_addr: fn(&Socket) -> io::Result<SocketAddr>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        let address = this.read_pointer(address)?;
+        let address_len = this.read_pointer(address_len)?;
+        let Some(fd) = this.get_socket(fd_num)? else {
+            return interp_ok(Scalar::from_i32(-1));
+        };
+        match get_addr(fd.downcast::<Socket>().unwrap()) {
+            Ok(addr) => {
+                this.write_socket_addr(addr, address, address_len)?;
+                interp_ok(Scalar::from_i32(0))
+            }
+            Err(e) => this.set_last_error_and_return_i32(e),
+        }
+    }
+
+    /// Binds `fd` to `addr`.
+    fn bind_socket(
+        &mut self,
+        fd: &FileDescriptionRef,
+        addr: SocketAddr,
+    ) -> InterpResult<'tcx, Result<(), IoError>> {
+        let this = self.eval_context_mut();
+        let socket = fd.downcast::<Socket>().unwrap();
+        if !matches!(*socket.state.borrow(), SocketState::Unbound) {
+            return interp_ok(Err(LibcError("EINVAL")));
+        }
+        let state = if socket.is_host {
+            let state = match socket.kind {
+                SocketKind::Stream =>
+                    TcpListener::bind(addr).and_then(|listener| {
+                        listener.set_nonblocking(true)?;
+                        Ok(SocketState::Bound(Listener::Host { listener, pending: None }))
+                    }),
+                SocketKind::Datagram =>
+                    UdpSocket::bind(addr).and_then(|socket| {
+                        socket.set_nonblocking(true)?;
+                        Ok(SocketState::Datagram(Datagram::Host(socket)))
+                    }),
+            };
+            state.map_err(IoError::from)
+        } else {
+            this.machine.network.bind(socket.kind, addr, fd).map(|addr| {
+                match socket.kind {
+                    SocketKind::Stream =>
+                        SocketState::Bound(Listener::Virtual { addr, backlog: VecDeque::new() }),
+                    SocketKind::Datagram =>
+                        SocketState::Datagram(Datagram::Virtual(VirtualDatagram {
+                            local_addr: addr,
+                            peer_addr: None,
+                            queue: VecDeque::new(),
+                        })),
+                }
+            })
+        };
+        interp_ok(state.map(|state| *socket.state.borrow_mut() = state))
+    }
+
+    /// Connects the virtual stream socket `fd` to the listener at `addr`. The connection is
+    /// established right away, and queued on the listener until it gets accepted.
+    fn connect_virtual_stream(
+        &mut self,
+        fd: &FileDescriptionRef,
+        local_addr: Option<SocketAddr>,
+        addr: SocketAddr,
+    ) -> InterpResult<'tcx, Result<(), IoError>> {
+        let this = self.eval_context_mut();
+        let socket = fd.downcast::<Socket>().unwrap();
+        let addr = match virtual_destination(addr) {
+            Ok(addr) => addr,
+            Err(e) => return interp_ok(Err(e)),
+        };
+        let listener = this.machine.network.lookup(SocketKind::Stream, addr).filter(|listener| {
+            matches!(
+                *listener.downcast::<Socket>().unwrap().state.borrow(),
+                SocketState::Listening(_)
+            )
+        });
+        let Some(listener) = listener else {
+            return interp_ok(Err(ErrorKind::ConnectionRefused.into()));
+        };
+        let local_addr = match local_addr {
+            Some(local_addr) if local_addr.ip().is_unspecified() =>
+                SocketAddr::new(loopback_ip(socket.is_ipv6), local_addr.port()),
+            Some(local_addr) => local_addr,
+            None => {
+                let local_addr = SocketAddr::new(loopback_ip(socket.is_ipv6), 0);
+                match this.machine.network.bind(SocketKind::Stream, local_addr, fd) {
+                    Ok(local_addr) => local_addr,
+                    Err(e) => return interp_ok(Err(e)),
+                }
+            }
+        };
+
+        // Create the socket that `accept` will return.
+        let listener_socket = listener.downcast::<Socket>().unwrap();
+        let server = this.machine.fds.new_ref(Socket::new(
+            listener_socket.is_ipv6,
+            SocketKind::Stream,
+            /* is_host */ false,
+            SocketState::Connected(Stream::Virtual(VirtualStream::new(
+                addr,
+                local_addr,
+                fd.downgrade(),
+            ))),
+        ));
+        *socket.state.borrow_mut() = SocketState::Connected(Stream::Virtual(VirtualStream::new(
+            local_addr,
+            addr,
+            server.downgrade(),
+        )));
+        // Remember our clock so `accept` can synchronize with us.
+        let mut clock = VClock::default();
+        this.release_clock(|release_clock| clock.clone_from(release_clock));
+        match &mut *listener_socket.state.borrow_mut() {
+            SocketState::Listening(Listener::Virtual { backlog, .. }) =>
+                backlog.push_back((server, clock)),
+            _ => unreachable!(),
+        }
+
+        this.check_and_update_readiness(&listener)?;
+        this.wake_socket_waiters()?;
+        interp_ok(Ok(()))
+    }
+
+    /// Accepts a connection on `fd`, returning the new socket and the address of its peer.
+    fn try_accept(
+        &mut self,
+        fd: &FileDescriptionRef,
+    ) -> InterpResult<'tcx, Result<(FileDescriptionRef, SocketAddr), IoError>> {
+        let this = self.eval_context_mut();
+        let socket = fd.downcast::<Socket>().unwrap();
+        let mut state = socket.state.borrow_mut();
+        match &mut *state {
+            SocketState::Listening(Listener::Host { listener, pending }) => {
+                let connection = this.machine.recorder.host_accept(|| {
+                    let (stream, peer_addr) = match pending.take() {
+                        Some(connection) => connection,
+                        None => listener.accept()?,
+                    };
+                    stream.set_nonblocking(true)?;
+                    Ok((stream, peer_addr))
+                });
+                match connection {
+                    Ok((stream, peer_addr)) => {
+                        let connection = this.machine.fds.new_ref(Socket::new(
+                            socket.is_ipv6,
+                            SocketKind::Stream,
+                            /* is_host */ true,
+                            SocketState::Connected(Stream::Host(stream)),
+                        ));
+                        this.machine.network.host_sockets.push(connection.downgrade());
+                        interp_ok(Ok((connection, peer_addr)))
+                    }
+                    Err(e) => interp_ok(Err(e.into())),
+                }
+            }
+            SocketState::Listening(Listener::Virtual { backlog, .. }) => {
+                let Some((connection, clock)) = backlog.pop_front() else {
+                    return interp_ok(Err(ErrorKind::WouldBlock.into()));
+                };
+                // Synchronize with the thread that connected.
+                this.acquire_clock(&clock);
+                let peer_addr = connection.downcast::<Socket>().unwrap().peer_addr().unwrap();
+                interp_ok(Ok((connection, peer_addr)))
+            }
+            _ => interp_ok(Err(LibcError("EINVAL"))),
+        }
+    }
+
+    /// Receives at most `len` bytes from `fd`, without removing them if `peek` is set. Returns the
+    /// data, and the address it was sent from for datagram sockets.
+    fn try_recv(
+        &mut self,
+        fd: &FileDescriptionRef,
+        len: usize,
+        peek: bool,
+    ) -> InterpResult<'tcx, Result<(Vec<u8>, Option<SocketAddr>), IoError>> {
+        let this = self.eval_context_mut();
+        let socket = fd.downcast::<Socket>().unwrap();
+        socket.finish_connecting();
+        let mut bytes = vec![0; len];
+        // The virtual socket that has to be notified that we made room in its send buffer.
+        let mut peer = None;
+        let result = match &mut *socket.state.borrow_mut() {
+            SocketState::Connected(Stream::Host(stream)) => {
+                let result = this.machine.recorder.host_read(&mut bytes, |bytes| {
+                    if peek { stream.peek(bytes) } else { stream.read(bytes) }
+                });
+                result.map(|read| (read, None)).map_err(IoError::from)
+            }
+            SocketState::Connected(Stream::Virtual(stream)) =>
+                if stream.readbuf.is_empty() {
+                    if len == 0 || stream.is_eof() {
+                        Ok((0, None))
+                    } else {
+                        Err(ErrorKind::WouldBlock.into())
+                    }
+                } else {
+                    // Synchronize with all previous writes to this buffer.
+                    // FIXME: like for `socketpair`, this over-synchronizes.
+                    this.acquire_clock(&stream.clock);
+                    let read = if peek {
+                        let read = len.min(stream.readbuf.len());
+                        for (byte, buffered) in bytes.iter_mut().zip(&stream.readbuf) {
+                            *byte = *buffered;
+                        }
+                        read
+                    } else {
+                        peer = stream.peer.upgrade();
+                        stream.readbuf.read(&mut bytes).unwrap()
+                    };
+                    Ok((read, None))
+                },
+            SocketState::Datagram(Datagram::Host(socket)) => {
+                let result =
+                    if peek { socket.peek_from(&mut bytes) } else { socket.recv_from(&mut bytes) };
+                result.map(|(read, addr)| (read, Some(addr))).map_err(IoError::from)
+            }
+            SocketState::Datagram(Datagram::Virtual(socket)) => {
+                let datagram =
+                    if peek { socket.queue.front().cloned() } else { socket.queue.pop_front() };
+                match datagram {
+                    Some((addr, data, clock)) => {
+                        this.acquire_clock(&clock);
+                        // Like on a real system, the rest of a datagram that does not fit into
+                        // the buffer is discarded.
+                        let read = len.min(data.len());
+                        bytes[..read].copy_from_slice(&data[..read]);
+                        Ok((read, Some(addr)))
+                    }
+                    None => Err(ErrorKind::WouldBlock.into()),
+                }
+            }
+            // An unbound datagram socket cannot receive anything, and a stream socket that is
+            // still connecting waits for the connection.
+            SocketState::Unbound if socket.kind == SocketKind::Datagram =>
+                Err(ErrorKind::WouldBlock.into()),
+            SocketState::Connecting(_) => Err(ErrorKind::WouldBlock.into()),
+            _ => Err(ErrorKind::NotConnected.into()),
+        };
+
+        if let Some(peer) = peer {
+            this.check_and_update_readiness(&peer)?;
+            this.wake_socket_waiters()?;
+        }
+        interp_ok(result.map(|(read, addr)| {
+            bytes.truncate(read);
+            (bytes, addr)
+        }))
+    }
+
+    /// Sends `bytes` on the stream socket `fd`. Returns how many bytes were sent.
+    fn try_send_stream(
+        &mut self,
+        fd: &FileDescriptionRef,
+        bytes: &[u8],
+    ) -> InterpResult<'tcx, Result<usize, IoError>> {
+        let this = self.eval_context_mut();
+        let socket = fd.downcast::<Socket>().unwrap();
+        socket.finish_connecting();
+        let peer = match &mut *socket.state.borrow_mut() {
+            SocketState::Connected(Stream::Host(stream)) =>
+                return interp_ok(stream.write(bytes).map_err(IoError::from)),
+            SocketState::Connecting(_) => return interp_ok(Err(ErrorKind::WouldBlock.into())),
+            SocketState::Connected(Stream::Virtual(stream)) => {
+                if stream.write_shutdown {
+                    return interp_ok(Err(ErrorKind::BrokenPipe.into()));
+                }
+                // If the peer is gone, nobody can read what we write anymore.
+                let Some(peer) = stream.peer.upgrade() else {
+                    return interp_ok(Err(ErrorKind::BrokenPipe.into()));
+                };
+                peer
+            }
+            _ => return interp_ok(Err(ErrorKind::NotConnected.into())),
+        };
+        if bytes.is_empty() {
+            return interp_ok(Ok(0));
+        }
+
+        let written: Result<usize, IoError> =
+            peer.downcast::<Socket>().unwrap().with_virtual_stream(|peer_stream| {
+                let available_space =
+                    MAX_SOCKET_BUFFER_CAPACITY.saturating_sub(peer_stream.readbuf.len());
+                if available_space == 0 {
+                    return Err(ErrorKind::WouldBlock.into());
+                }
+                // Remember this clock so `recv` can synchronize with us.
+                this.release_clock(|clock| peer_stream.clock.join(clock));
+                let written = bytes.len().min(available_space);
+                peer_stream.readbuf.extend(&bytes[..written]);
+                Ok(written)
+            });
+        if written.is_ok() {
+            this.check_and_update_readiness(&peer)?;
+            this.wake_socket_waiters()?;
+        }
+        interp_ok(written)
+    }
+
+    /// Sends `bytes` as one datagram on the datagram socket `fd`, either to `addr` or to the
+    /// address the socket is connected to.
+    fn try_send_datagram(
+        &mut self,
+        fd: &FileDescriptionRef,
+        bytes: &[u8],
+        addr: Option<SocketAddr>,
+    ) -> InterpResult<'tcx, Result<usize, IoError>> {
+        let this = self.eval_context_mut();
+        let socket = fd.downcast::<Socket>().unwrap();
+        if bytes.len() > MAX_DATAGRAM_SIZE {
+            return interp_ok(Err(LibcError("EMSGSIZE")));
+        }
+        // Sending on an unbound socket binds it to an ephemeral port.
+        if matches!(*socket.state.borrow(), SocketState::Unbound) {
+            let local_addr = SocketAddr::new(unspecified_ip(socket.is_ipv6), 0);
+            if let Err(e) = this.bind_socket(fd, local_addr)? {
+                return interp_ok(Err(e));
+            }
+        }
+
+        let (local_addr, addr) = match &*socket.state.borrow() {
+            SocketState::Datagram(Datagram::Host(socket)) => {
+                let sent = match addr {
+                    Some(addr) => socket.send_to(bytes, addr),
+                    None => socket.send(bytes),
+                };
+                return interp_ok(sent.map_err(IoError::from));
+            }
+            SocketState::Datagram(Datagram::Virtual(socket)) => {
+                let Some(addr) = addr.or(socket.peer_addr) else {
+                    return interp_ok(Err(LibcError("EDESTADDRREQ")));
+                };
+                (socket.local_addr, addr)
+            }
+            _ => unreachable!(),
+        };
+        let addr = match virtual_destination(addr) {
+            Ok(addr) => addr,
+            Err(e) => return interp_ok(Err(e)),
+        };
+        let local_addr = match virtual_destination(local_addr) {
+            Ok(local_addr) => local_addr,
+            Err(e) => return interp_ok(Err(e)),
+        };
+
+        // Like on a real network, datagrams sent to a port nobody listens on, or to a socket that
+        // has no room for them, are silently dropped.
+        if let Some(receiver) = this.machine.network.lookup(SocketKind::Datagram, addr) {
+            let mut clock = VClock::default();
+            this.release_clock(|release_clock| clock.clone_from(release_clock));
+            let received = match &mut *receiver.downcast::<Socket>().unwrap().state.borrow_mut() {
+                SocketState::Datagram(Datagram::Virtual(receiver)) => {
+                    let buffered: usize =
+                        receiver.queue.iter().map(|(_, data, _)| data.len()).sum();
+                    let has_room = buffered + bytes.len() <= MAX_SOCKET_BUFFER_CAPACITY;
+                    if has_room {
+                        receiver.queue.push_back((local_addr, bytes.to_vec(), clock));
+                    }
+                    has_room
+                }
+                _ => unreachable!(),
+            };
+            if received {
+                this.check_and_update_readiness(&receiver)?;
+                this.wake_socket_waiters()?;
+            }
+        }
+        interp_ok(Ok(bytes.len()))
+    }
+
+    /// Accepts a connection on the socket, blocking until there is one unless the socket is
+    /// non-blocking. The new socket is non-blocking if `is_nonblock` is set.
+    fn socket_accept(
+        &mut self,
+        socket: WeakFileDescriptionRef,
+        address: Pointer,
+        address_len: Pointer,
+        is_nonblock: bool,
+        dest: MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let Some(fd) = socket.upgrade() else {
+            // The socket got closed while we were blocked.
+            return this.set_last_error_and_return(LibcError("EBADF"), &dest);
+        };
+        // Operating on a host socket may change its readiness, so the next poll has to report
+        // it again, like edge-triggered epoll reports new data even if some was there already.
+        fd.downcast::<Socket>().unwrap().reported_readiness.set(0);
+        match this.try_accept(&fd)? {
+            Ok((connection, peer_addr)) => {
+                connection.downcast::<Socket>().unwrap().is_nonblock.set(is_nonblock);
+                let connection = this.machine.fds.insert(connection);
+                this.write_socket_addr(peer_addr, address, address_len)?;
+                this.write_int(connection, &dest)?;
+                interp_ok(())
+            }
+            Err(e) if is_would_block(&e) => {
+                let socket_desc = fd.downcast::<Socket>().unwrap();
+                if socket_desc.is_nonblock.get() {
+                    return this.set_last_error_and_return(e, &dest);
+                }
+                let is_host = socket_desc.is_host;
+                this.block_on_socket(
+                    is_host,
+                    callback!(
+                        @capture<'tcx> {
+                            socket: WeakFileDescriptionRef,
+                            address: Pointer,
+                            address_len: Pointer,
+                            is_nonblock: bool,
+                            dest: MPlaceTy<'tcx>,
+                        }
+                        @unblock = |this| {
+                            this.socket_accept(socket, address, address_len, is_nonblock, dest)
+                        }
+                        @timeout = |this| {
+                            this.socket_accept(socket, address, address_len, is_nonblock, dest)
+                        }
+                    ),
+                );
+                interp_ok(())
+            }
+            Err(e) => this.set_last_error_and_return(e, &dest),
+        }
+    }
+
+    /// Receives data from the socket into `buf`, blocking until there is some unless the socket
+    /// is non-blocking or `dontwait` is set. For datagram sockets, the source address is stored
+    /// in `address`.
+    fn socket_recv(
+        &mut self,
+        socket: WeakFileDescriptionRef,
+        buf: Pointer,
+        len: usize,
+        peek: bool,
+        dontwait: bool,
+        address: Option<(Pointer, Pointer)>,
+        dest: MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let Some(fd) = socket.upgrade() else {
+            // The socket got closed while we were blocked.
+            return this.set_last_error_and_return(LibcError("EBADF"), &dest);
+        };
+        fd.downcast::<Socket>().unwrap().reported_readiness.set(0);
+        match this.try_recv(&fd, len, peek)? {
+            Ok((bytes, addr)) => {
+                if let (Some((address, address_len)), Some(addr)) = (address, addr) {
+                    this.write_socket_addr(addr, address, address_len)?;
+                }
+                this.return_read_success(buf, &bytes, bytes.len(), &dest)
+            }
+            Err(e) if is_would_block(&e) => {
+                let socket_desc = fd.downcast::<Socket>().unwrap();
+                if dontwait || socket_desc.is_nonblock.get() {
+                    return this.set_last_error_and_return(e, &dest);
+                }
+                let is_host = socket_desc.is_host;
+                this.block_on_socket(
+                    is_host,
+                    callback!(
+                        @capture<'tcx> {
+                            socket: WeakFileDescriptionRef,
+                            buf: Pointer,
+                            len: usize,
+                            peek: bool,
+                            address: Option<(Pointer, Pointer)>,
+                            dest: MPlaceTy<'tcx>,
+                        }
+                        @unblock = |this| {
+                            this.socket_recv(socket, buf, len, peek, false, address, dest)
+                        }
+                        @timeout = |this| {
+                            this.socket_recv(socket, buf, len, peek, false, address, dest)
+                        }
+                    ),
+                );
+                interp_ok(())
+            }
+            Err(e) => this.set_last_error_and_return(e, &dest),
+        }
+    }
+
+    /// Sends the data in `buf` on the socket, blocking until there is room for it unless the
+    /// socket is non-blocking or `dontwait` is set. Datagram sockets send it to `addr` if it is
+    /// given, stream sockets ignore `addr`.
+    fn socket_send(
+        &mut self,
+        socket: WeakFileDescriptionRef,
+        buf: Pointer,
+        len: usize,
+        dontwait: bool,
+        addr: Option<SocketAddr>,
+        dest: MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let Some(fd) = socket.upgrade() else {
+            // The socket got closed while we were blocked.
+            return this.set_last_error_and_return(LibcError("EBADF"), &dest);
+        };
+        let socket_desc = fd.downcast::<Socket>().unwrap();
+        let bytes = this.read_bytes_ptr_strip_provenance(buf, Size::from_bytes(len))?.to_vec();
+        socket_desc.reported_readiness.set(0);
+        let result = match socket_desc.kind {
+            SocketKind::Stream => this.try_send_stream(&fd, &bytes)?,
+            SocketKind::Datagram => this.try_send_datagram(&fd, &bytes, addr)?,
+        };
+        match result {
+            Ok(written) => this.return_write_success(written, &dest),
+            Err(e) if is_would_block(&e) => {
+                if dontwait || socket_desc.is_nonblock.get() {
+                    return this.set_last_error_and_return(e, &dest);
+                }
+                let is_host = socket_desc.is_host;
+                this.block_on_socket(
+                    is_host,
+                    callback!(
+                        @capture<'tcx> {
+                            socket: WeakFileDescriptionRef,
+                            buf: Pointer,
+                            len: usize,
+                            addr: Option<SocketAddr>,
+                            dest: MPlaceTy<'tcx>,
+                        }
+                        @unblock = |this| {
+                            this.socket_send(socket, buf, len, false, addr, dest)
+                        }
+                        @timeout = |this| {
+                            this.socket_send(socket, buf, len, false, addr, dest)
+                        }
+                    ),
+                );
+                interp_ok(())
+            }
+            Err(e) => this.set_last_error_and_return(e, &dest),
+        }
+    }
+
+    /// Waits until the host thread establishing the connection of the socket is done, and returns
+    /// whether the connection succeeded. Only the interpreted thread is blocked meanwhile.
+    fn socket_wait_for_connection(
+        &mut self,
+        socket: WeakFileDescriptionRef,
+        dest: MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let Some(fd) = socket.upgrade() else {
+            // The socket got closed while we were blocked.
+            return this.set_last_error_and_return(LibcError("EBADF"), &dest);
+        };
+        let socket_desc = fd.downcast::<Socket>().unwrap();
+        socket_desc.reported_readiness.set(0);
+        socket_desc.finish_connecting();
+        let result = match &*socket_desc.state.borrow() {
+            SocketState::Connecting(_) => None,
+            SocketState::Connected(_) => Some(Ok(())),
+            _ => Some(Err(socket_desc.connect_error.take().unwrap())),
+        };
+        match result {
+            Some(Ok(())) => this.write_int(0, &dest),
+            Some(Err(e)) => this.set_last_error_and_return(e, &dest),
+            None => {
+                this.block_on_socket(
+                    /* is_host */ true,
+                    callback!(
+                        @capture<'tcx> {
+                            socket: WeakFileDescriptionRef,
+                            dest: MPlaceTy<'tcx>,
+                        }
+                        @unblock = |this| {
+                            this.socket_wait_for_connection(socket, dest)
+                        }
+                        @timeout = |this| {
+                            this.socket_wait_for_connection(socket, dest)
+                        }
+                    ),
+                );
+                interp_ok(())
+            }
+        }
+    }
+
+    /// Blocks the active thread on an operation on a socket that cannot make progress yet. Once
+    /// the operation might succeed, it is retried by `callback`: threads blocked on virtual
+    /// sockets are woken up whenever the virtual network changes, and threads blocked on host
+    /// sockets poll them every `HOST_SOCKET_POLL_INTERVAL`. Both kinds of callback therefore have
+    /// to do the same thing on unblock and on timeout.
+    fn block_on_socket(&mut self, is_host: bool, callback: DynUnblockCallback<'tcx>) {
+        let this = self.eval_context_mut();
+        if is_host {
+            this.block_thread(
+                BlockReason::Socket,
+                Some((TimeoutClock::Monotonic, TimeoutAnchor::Relative, HOST_SOCKET_POLL_INTERVAL)),
+                callback,
+            );
+        } else {
+            this.machine.network.blocked_threads.push(this.active_thread());
+            this.block_thread(BlockReason::Socket, None, callback);
+        }
+    }
+
+    /// Wakes up all threads blocked on a virtual socket, so they can retry their operation.
+    fn wake_socket_waiters(&mut self) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        for thread in std::mem::take(&mut this.machine.network.blocked_threads) {
+            this.unblock_thread(thread, BlockReason::Socket)?;
+        }
+        interp_ok(())
+    }
+}
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// For more information on the arguments see the socket manpage:
+    /// <https://man7.org/linux/man-pages/man2/socket.2.html>
+    fn socket(
+        &mut self,
+        domain: &OpTy<'tcx>,
+        type_: &OpTy<'tcx>,
+        protocol: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let domain = this.read_scalar(domain)?.to_i32()?;
+        let mut type_ = this.read_scalar(type_)?.to_i32()?;
+        let protocol = this.read_scalar(protocol)?.to_i32()?;
+
+        let mut is_nonblock = false;
+
+        // Interpret the flags. Every flag we recognize is "subtracted" from `type_`, so
+        // if there is anything left at the end, that's an unsupported flag.
+        if matches!(&*this.tcx.sess.target.os, "linux" | "freebsd" | "solaris" | "illumos") {
+            let sock_nonblock = this.eval_libc_i32("SOCK_NONBLOCK");
+            let sock_cloexec = this.eval_libc_i32("SOCK_CLOEXEC");
+            if type_ & sock_nonblock == sock_nonblock {
+                is_nonblock = true;
+                type_ &= !sock_nonblock;
+            }
+            // As usual we ignore CLOEXEC.
+            if type_ & sock_cloexec == sock_cloexec {
+                type_ &= !sock_cloexec;
+            }
+        }
+
+        let is_ipv6 = if domain == this.eval_libc_i32("AF_INET") {
+            false
+        } else if domain == this.eval_libc_i32("AF_INET6") {
+            true
+        } else {
+            throw_unsup_format!(
+                "socket: domain {:#x} is unsupported, only AF_INET and AF_INET6 are allowed",
+                domain
+            );
+        };
+        let kind = if type_ == this.eval_libc_i32("SOCK_STREAM") {
+            SocketKind::Stream
+        } else if type_ == this.eval_libc_i32("SOCK_DGRAM") {
+            SocketKind::Datagram
+        } else {
+            throw_unsup_format!(
+                "socket: type {:#x} is unsupported, only SOCK_STREAM, SOCK_DGRAM, \
+                 SOCK_CLOEXEC and SOCK_NONBLOCK are allowed",
+                type_
+            );
+        };
+        let default_protocol = match kind {
+            SocketKind::Stream => this.eval_libc_i32("IPPROTO_TCP"),
+            SocketKind::Datagram => this.eval_libc_i32("IPPROTO_UDP"),
+        };
+        if protocol != 0 && protocol != default_protocol {
+            throw_unsup_format!("socket: socket protocol {protocol} is unsupported for this type");
+        }
+
+        let is_host = this.machine.communicate();
+        let socket = Socket::new(is_ipv6, kind, is_host, SocketState::Unbound);
+        socket.is_nonblock.set(is_nonblock);
+        let fd = this.machine.fds.new_ref(socket);
+        if is_host {
+            this.machine.network.host_sockets.push(fd.downgrade());
+        }
+        interp_ok(Scalar::from_i32(this.machine.fds.insert(fd)))
+    }
+
+    fn bind(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        address: &OpTy<'tcx>,
+        address_len: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        let Some(fd) = this.get_socket(fd_num)? else {
+            return interp_ok(Scalar::from_i32(-1));
+        };
+        let socket = fd.downcast::<Socket>().unwrap();
+        let result = match this.read_socket_addr_for(socket, address, address_len)? {
+            Ok(addr) => this.bind_socket(&fd, addr)?,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => interp_ok(Scalar::from_i32(0)),
+            Err(e) => this.set_last_error_and_return_i32(e),
+        }
+    }
+
+    fn listen(&mut self, socket: &OpTy<'tcx>, backlog: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        // The backlog is ignored: host listeners use the default backlog of the host, and the
+        // virtual network never refuses connections.
+        let _backlog = this.read_scalar(backlog)?.to_i32()?;
+        let Some(fd) = this.get_socket(fd_num)? else {
+            return interp_ok(Scalar::from_i32(-1));
+        };
+        let socket = fd.downcast::<Socket>().unwrap();
+        if socket.kind != SocketKind::Stream {
+            return this.set_last_error_and_return_i32(LibcError("EOPNOTSUPP"));
+        }
+
+        // Listening on an unbound socket binds it to an ephemeral port.
+        if matches!(*socket.state.borrow(), SocketState::Unbound) {
+            let addr = SocketAddr::new(unspecified_ip(socket.is_ipv6), 0);
+            if let Err(e) = this.bind_socket(&fd, addr)? {
+                return this.set_last_error_and_return_i32(e);
+            }
+        }
+        let mut state = socket.state.borrow_mut();
+        match std::mem::replace(&mut *state, SocketState::Unbound) {
+            SocketState::Bound(listener) | SocketState::Listening(listener) => {
+                *state = SocketState::Listening(listener);
+                interp_ok(Scalar::from_i32(0))
+            }
+            other => {
+                *state = other;
+                drop(state);
+                this.set_last_error_and_return_i32(LibcError("EINVAL"))
+            }
+        }
+    }
+
+    /// On the virtual network, connections are established right away. Host connections are
+    /// established by a host thread: for non-blocking sockets, this returns EINPROGRESS and epoll
+    /// reports when the connection is done, otherwise the interpreted thread blocks until then.
+    fn connect(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        address: &OpTy<'tcx>,
+        address_len: &OpTy<'tcx>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        let Some(fd) = this.get_socket(fd_num)? else {
+            return this.write_int(-1, dest);
+        };
+        let socket = fd.downcast::<Socket>().unwrap();
+        let addr = match this.read_socket_addr_for(socket, address, address_len)? {
+            Ok(addr) => addr,
+            Err(e) => return this.set_last_error_and_return(e, dest),
+        };
+        socket.reported_readiness.set(0);
+        socket.finish_connecting();
+
+        let result = match socket.kind {
+            SocketKind::Datagram => {
+                // Connecting an unbound socket binds it to an ephemeral port.
+                if matches!(*socket.state.borrow(), SocketState::Unbound) {
+                    let local_addr = SocketAddr::new(unspecified_ip(socket.is_ipv6), 0);
+                    if let Err(e) = this.bind_socket(&fd, local_addr)? {
+                        return this.set_last_error_and_return(e, dest);
+                    }
+                }
+                match &mut *socket.state.borrow_mut() {
+                    SocketState::Datagram(Datagram::Host(socket)) =>
+                        socket.connect(addr).map_err(IoError::from),
+                    SocketState::Datagram(Datagram::Virtual(socket)) =>
+                        virtual_destination(addr).map(|addr| socket.peer_addr = Some(addr)),
+                    _ => unreachable!(),
+                }
+            }
+            SocketKind::Stream => {
+                let local_addr = match &*socket.state.borrow() {
+                    SocketState::Unbound => None,
+                    SocketState::Bound(Listener::Virtual { addr, .. }) => Some(*addr),
+                    SocketState::Bound(Listener::Host { .. }) =>
+                        throw_unsup_format!(
+                            "connect: connecting a bound stream socket is not supported \
+                             when isolation is disabled"
+                        ),
+                    SocketState::Connected(_) => {
+                        return this.set_last_error_and_return(LibcError("EISCONN"), dest);
+                    }
+                    SocketState::Connecting(_) => {
+                        return this.set_last_error_and_return(LibcError("EALREADY"), dest);
+                    }
+                    _ => return this.set_last_error_and_return(LibcError("EINVAL"), dest),
+                };
+                if socket.is_host {
+                    // Forget the error of a previous attempt.
+                    socket.connect_error.take();
+                    let (sender, receiver) = mpsc::channel();
+                    thread::spawn(move || {
+                        // The socket may have been closed meanwhile, then nobody is waiting.
+                        let _ = sender.send(TcpStream::connect(addr));
+                    });
+                    *socket.state.borrow_mut() = SocketState::Connecting(receiver);
+                    if socket.is_nonblock.get() {
+                        return this.set_last_error_and_return(LibcError("EINPROGRESS"), dest);
+                    }
+                    return this.socket_wait_for_connection(fd.downgrade(), dest.clone());
+                } else {
+                    this.connect_virtual_stream(&fd, local_addr, addr)?
+                }
+            }
+        };
+        match result {
+            Ok(()) => this.write_int(0, dest),
+            Err(e) => this.set_last_error_and_return(e, dest),
+        }
+    }
+
+    /// This implements both `accept`, where `flags` is `None`, and `accept4`.
+    fn accept4(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        address: &OpTy<'tcx>,
+        address_len: &OpTy<'tcx>,
+        flags: Option<&OpTy<'tcx>>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        let address = this.read_pointer(address)?;
+        let address_len = this.read_pointer(address_len)?;
+        let mut flags = match flags {
+            Some(flags) => this.read_scalar(flags)?.to_i32()?,
+            None => 0,
+        };
+
+        let mut is_nonblock = false;
+        if flags != 0 {
+            let sock_nonblock = this.eval_libc_i32("SOCK_NONBLOCK");
+            let sock_cloexec = this.eval_libc_i32("SOCK_CLOEXEC");
+            if flags & sock_nonblock == sock_nonblock {
+                is_nonblock = true;
+                flags &= !sock_nonblock;
+            }
+            // As usual we ignore CLOEXEC.
+            if flags & sock_cloexec == sock_cloexec {
+                flags &= !sock_cloexec;
+            }
+            if flags != 0 {
+                throw_unsup_format!("unsupported flags in `accept4`");
+            }
+        }
+
+        let Some(fd) = this.get_socket(fd_num)? else {
+            this.write_int(-1, dest)?;
+            return interp_ok(());
+        };
+        this.socket_accept(fd.downgrade(), address, address_len, is_nonblock, dest.clone())
+    }
+
+    /// This implements both `recv`, where `address` is `None`, and `recvfrom`.
+    fn recv(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        buf: &OpTy<'tcx>,
+        len: &OpTy<'tcx>,
+        flags: &OpTy<'tcx>,
+        address: Option<(&OpTy<'tcx>, &OpTy<'tcx>)>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        let buf = this.read_pointer(buf)?;
+        let len = this.read_target_usize(len)?;
+        let mut flags = this.read_scalar(flags)?.to_i32()?;
+        let address = match address {
+            Some((address, address_len)) =>
+                Some((this.read_pointer(address)?, this.read_pointer(address_len)?)),
+            None => None,
+        };
+
+        let msg_peek = this.eval_libc_i32("MSG_PEEK");
+        let msg_dontwait = this.eval_libc_i32("MSG_DONTWAIT");
+        let peek = flags & msg_peek == msg_peek;
+        flags &= !msg_peek;
+        let dontwait = flags & msg_dontwait == msg_dontwait;
+        flags &= !msg_dontwait;
+        if flags != 0 {
+            throw_unsup_format!("recv: flag {:#x} is unsupported", flags);
+        }
+
+        // Check that the *entire* buffer is actually valid memory, and cap the length like `read`.
+        this.check_ptr_access(buf, Size::from_bytes(len), CheckInAllocMsg::MemoryAccessTest)?;
+        let len = len
+            .min(u64::try_from(this.target_isize_max()).unwrap())
+            .min(u64::try_from(isize::MAX).unwrap());
+        let len = usize::try_from(len).unwrap();
+
+        let Some(fd) = this.get_socket(fd_num)? else {
+            this.write_int(-1, dest)?;
+            return interp_ok(());
+        };
+        this.socket_recv(fd.downgrade(), buf, len, peek, dontwait, address, dest.clone())
+    }
+
+    /// This implements both `send`, where `address` is `None`, and `sendto`.
+    fn send(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        buf: &OpTy<'tcx>,
+        len: &OpTy<'tcx>,
+        flags: &OpTy<'tcx>,
+        address: Option<(&OpTy<'tcx>, &OpTy<'tcx>)>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        let buf = this.read_pointer(buf)?;
+        let len = this.read_target_usize(len)?;
+        let mut flags = this.read_scalar(flags)?.to_i32()?;
+
+        let msg_dontwait = this.eval_libc_i32("MSG_DONTWAIT");
+        let dontwait = flags & msg_dontwait == msg_dontwait;
+        flags &= !msg_dontwait;
+        // We never raise SIGPIPE, so MSG_NOSIGNAL has no effect. It does not exist on Apple
+        // targets, which use the SO_NOSIGPIPE socket option instead.
+        if !this.tcx.sess.target.is_like_osx {
+            let msg_nosignal = this.eval_libc_i32("MSG_NOSIGNAL");
+            flags &= !msg_nosignal;
+        }
+        if flags != 0 {
+            throw_unsup_format!("send: flag {:#x} is unsupported", flags);
+        }
+
+        // Check that the *entire* buffer is actually valid memory, and cap the length like `write`.
+        this.check_ptr_access(buf, Size::from_bytes(len), CheckInAllocMsg::MemoryAccessTest)?;
+        let len = len
+            .min(u64::try_from(this.target_isize_max()).unwrap())
+            .min(u64::try_from(isize::MAX).unwrap());
+        let len = usize::try_from(len).unwrap();
+
+        let Some(fd) = this.get_socket(fd_num)? else {
+            this.write_int(-1, dest)?;
+            return interp_ok(());
+        };
+        let addr = match address {
+            Some((address, address_len)) => {
+                let socket = fd.downcast::<Socket>().unwrap();
+                match this.read_socket_addr_for(socket, address, address_len)? {
+                    Ok(addr) => Some(addr),
+                    Err(e) => return this.set_last_error_and_return(e, dest),
+                }
+            }
+            None => None,
+        };
+        this.socket_send(fd.downgrade(), buf, len, dontwait, addr, dest.clone())
+    }
+
+    fn shutdown(&mut self, socket: &OpTy<'tcx>, how: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        let how = this.read_scalar(how)?.to_i32()?;
+        let how = if how == this.eval_libc_i32("SHUT_RD") {
+            Shutdown::Read
+        } else if how == this.eval_libc_i32("SHUT_WR") {
+            Shutdown::Write
+        } else if how == this.eval_libc_i32("SHUT_RDWR") {
+            Shutdown::Both
+        } else {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        };
+        let Some(fd) = this.get_socket(fd_num)? else {
+            return interp_ok(Scalar::from_i32(-1));
+        };
+        let socket = fd.downcast::<Socket>().unwrap();
+        socket.finish_connecting();
+
+        let peer = match &mut *socket.state.borrow_mut() {
+            SocketState::Connected(Stream::Host(stream)) => {
+                let result = stream.shutdown(how).map(|()| 0);
+                return interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result)?));
+            }
+            SocketState::Connected(Stream::Virtual(stream)) => {
+                if how != Shutdown::Write {
+                    stream.read_shutdown = true;
+                }
+                if how != Shutdown::Read {
+                    stream.write_shutdown = true;
+                }
+                stream.peer.upgrade()
+            }
+            _ => return this.set_last_error_and_return_i32(ErrorKind::NotConnected),
+        };
+        if let Some(peer) = &peer
+            && how != Shutdown::Read
+        {
+            peer.downcast::<Socket>()
+                .unwrap()
+                .with_virtual_stream(|peer_stream| peer_stream.peer_write_shutdown = true);
+        }
+        // Both ends may have become ready for reading or writing.
+        this.check_and_update_readiness(&fd)?;
+        if let Some(peer) = &peer {
+            this.check_and_update_readiness(peer)?;
+        }
+        this.wake_socket_waiters()?;
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn getsockname(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        address: &OpTy<'tcx>,
+        address_len: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+        this.write_socket_addr_of(socket, address, address_len, Socket::local_addr)
+    }
+
+    fn getpeername(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        address: &OpTy<'tcx>,
+        address_len: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+        this.write_socket_addr_of(socket, address, address_len, Socket::peer_addr)
+    }
+
+    fn setsockopt(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        level: &OpTy<'tcx>,
+        option_name: &OpTy<'tcx>,
+        option_value: &OpTy<'tcx>,
+        option_len: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        let level = this.read_scalar(level)?.to_i32()?;
+        let option_name = this.read_scalar(option_name)?.to_i32()?;
+        let option_len = this.read_scalar(option_len)?.to_u32()?;
+        let Some(fd) = this.get_socket(fd_num)? else {
+            return interp_ok(Scalar::from_i32(-1));
+        };
+        let socket = fd.downcast::<Socket>().unwrap();
+
+        // All the options we support are integers.
+        if u64::from(option_len) < this.machine.layouts.i32.size.bytes() {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+        let value = this.deref_pointer_as(option_value, this.machine.layouts.i32)?;
+        let value = this.read_scalar(&value)?.to_i32()?;
+
+        if level == this.eval_libc_i32("SOL_SOCKET") {
+            let mut ignored =
+                vec![this.eval_libc_i32("SO_REUSEADDR"), this.eval_libc_i32("SO_KEEPALIVE")];
+            // We never raise SIGPIPE.
+            if this.tcx.sess.target.is_like_osx {
+                ignored.push(this.eval_libc_i32("SO_NOSIGPIPE"));
+            }
+            // Host listeners are created with SO_REUSEADDR by the standard library, and virtual
+            // sockets do not linger after they are closed, so address reuse is always allowed.
+            // Keep-alive messages would not change anything observable on a loopback network.
+            if !ignored.contains(&option_name) {
+                throw_unsup_format!(
+                    "setsockopt: option {option_name:#x} on SOL_SOCKET is unsupported"
+                );
+            }
+        } else if level == this.eval_libc_i32("IPPROTO_TCP")
+            && option_name == this.eval_libc_i32("TCP_NODELAY")
+        {
+            if let SocketState::Connected(Stream::Host(stream)) = &*socket.state.borrow() {
+                let result = stream.set_nodelay(value != 0).map(|()| 0);
+                return interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result)?));
+            }
+            // The virtual network delivers all data right away anyway.
+        } else if level == this.eval_libc_i32("IPPROTO_IPV6")
+            && option_name == this.eval_libc_i32("IPV6_V6ONLY")
+        {
+            // IPv4 and IPv6 sockets never talk to each other on the virtual network. Host sockets
+            // use the default of the host.
+        } else {
+            throw_unsup_format!(
+                "setsockopt: level {level:#x} option {option_name:#x} is unsupported"
+            );
+        }
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn getsockopt(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        level: &OpTy<'tcx>,
+        option_name: &OpTy<'tcx>,
+        option_value: &OpTy<'tcx>,
+        option_len: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        let level = this.read_scalar(level)?.to_i32()?;
+        let option_name = this.read_scalar(option_name)?.to_i32()?;
+        let option_len = this.deref_pointer_as(option_len, this.libc_ty_layout("socklen_t"))?;
+        let Some(fd) = this.get_socket(fd_num)? else {
+            return interp_ok(Scalar::from_i32(-1));
+        };
+        let socket = fd.downcast::<Socket>().unwrap();
+
+        if level != this.eval_libc_i32("SOL_SOCKET") {
+            throw_unsup_format!("getsockopt: level {level:#x} is unsupported");
+        }
+        let value = if option_name == this.eval_libc_i32("SO_ERROR") {
+            // There are no pending errors on virtual sockets. Host sockets may report the error
+            // of a connection that failed after `connect` returned EINPROGRESS.
+            socket.finish_connecting();
+            let error = match &*socket.state.borrow() {
+                SocketState::Connected(Stream::Host(stream)) => stream.take_error(),
+                SocketState::Datagram(Datagram::Host(socket)) => socket.take_error(),
+                _ => Ok(socket.connect_error.take()),
+            };
+            match error {
+                Ok(None) => 0,
+                Ok(Some(error)) => this.io_error_to_errnum(error)?.to_i32()?,
+                Err(e) => return this.set_last_error_and_return_i32(e),
+            }
+        } else if option_name == this.eval_libc_i32("SO_TYPE") {
+            match socket.kind {
+                SocketKind::Stream => this.eval_libc_i32("SOCK_STREAM"),
+                SocketKind::Datagram => this.eval_libc_i32("SOCK_DGRAM"),
+            }
+        } else {
+            throw_unsup_format!("getsockopt: option {option_name:#x} on SOL_SOCKET is unsupported");
+        };
+
+        let i32_size = this.machine.layouts.i32.size.bytes();
+        if u64::from(this.read_scalar(&option_len)?.to_u32()?) < i32_size {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+        let option_value = this.deref_pointer_as(option_value, this.machine.layouts.i32)?;
+        this.write_int(value, &option_value)?;
+        this.write_int(i32_size, &option_len)?;
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    /// Checks the readiness of all host sockets, and notifies epoll about those that became ready
+    /// since the last check. Miri does not learn about changes to host sockets when they happen,
+    /// so this has to be called before looking at the ready lists of epoll instances.
+    fn poll_host_sockets(&mut self) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let sockets: Vec<_> =
+            this.machine.network.host_sockets.iter().filter_map(|fd| fd.upgrade()).collect();
+        for fd in sockets {
+            let socket = fd.downcast::<Socket>().unwrap();
+            let readiness = fd.get_epoll_ready_events()?.get_event_bitmask(this);
+            let newly_ready = readiness & !socket.reported_readiness.replace(readiness);
+            if newly_ready != 0 {
+                this.check_and_update_readiness(&fd)?;
+            }
+        }
+        interp_ok(())
+    }
+}
@@ -0,0 +1,35 @@
+//! The reader assumes that the writer already published its value. The default scheduler runs the
+//! threads in the order they are spawned, but PCT gives every thread a random priority, so the
+//! reader runs first in about a third of the rounds. `tests/ui.rs` checks that the printed schedule
+//! reproduces the error.
+//@compile-flags: -Zmiri-pct=1
+//@normalize-stderr-test: "-Zmiri-replay-schedule=[0-9:,]+" -> "-Zmiri-replay-schedule=SCHEDULE"
+
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::{ptr, thread};
+
+static VALUE: AtomicPtr<u32> = AtomicPtr::new(ptr::null_mut());
+
+fn round() {
+    let writer = thread::spawn(|| {
+        VALUE.store(Box::into_raw(Box::new(42)), Ordering::Release);
+    });
+    let reader = thread::Builder::new()
+        .name("reader".into())
+        .spawn(|| {
+            let value = VALUE.load(Ordering::Acquire);
+            assert_eq!(unsafe { *value }, 42); //~ ERROR: null pointer
+        })
+        .unwrap();
+    writer.join().unwrap();
+    reader.join().unwrap();
+    drop(unsafe { Box::from_raw(VALUE.swap(ptr::null_mut(), Ordering::Relaxed)) });
+}
+
+fn main() {
+    // Every round runs in a new thread, so the threads of a round get fresh priorities relative
+    // to the thread that spawns them.
+    for _ in 0..32 {
+        thread::spawn(round).join().unwrap();
+    }
+}
@@ -0,0 +1,14 @@
+//@compile-flags: -Zmiri-fuzz-corpus=tests/fail/fuzz_corpus
+//@error-in-other-file: 1 of 3 fuzz inputs triggered an error
+#![no_main]
+
+use std::slice;
+
+#[no_mangle]
+pub extern "C" fn LLVMFuzzerTestOneInput(data: *const u8, size: usize) -> i32 {
+    let data = unsafe { slice::from_raw_parts(data, size) };
+    // Only the `invalid` input is not a valid `bool`.
+    let _b = unsafe { std::mem::transmute::<u8, bool>(data[0] - b'0') };
+    //~^ ERROR: expected a boolean
+    0
+}
@@ -0,0 +1,103 @@
+// Only works on Unix targets
+//@ignore-target: windows wasm
+//@only-on-host
+
+use std::mem::MaybeUninit;
+use std::ptr;
+
+fn main() {
+    test_increment();
+
+    test_fill();
+
+    test_struct();
+
+    test_nested();
+
+    test_pointer_write();
+}
+
+// Test function that increments an integer through a pointer.
+fn test_increment() {
+    extern "C" {
+        fn increment(ptr: *mut i32);
+    }
+
+    let mut x = 41;
+
+    unsafe { increment(&mut x) };
+    assert_eq!(x, 42);
+}
+
+// Test function that initializes a buffer.
+fn test_fill() {
+    extern "C" {
+        fn fill(buf: *mut u8, len: usize, value: u8);
+    }
+
+    let mut buf = [MaybeUninit::<u8>::uninit(); 16];
+
+    unsafe { fill(buf.as_mut_ptr().cast(), buf.len(), 7) };
+    // Native code initialized the buffer, so reading it is fine.
+    let buf = unsafe { buf.map(|b| b.assume_init()) };
+    assert_eq!(buf, [7; 16]);
+}
+
+// Test function that writes to the fields of a struct.
+fn test_struct() {
+    #[repr(C)]
+    struct Pair {
+        first: i32,
+        second: i32,
+    }
+
+    extern "C" {
+        fn swap_pair(pair: *mut Pair);
+    }
+
+    let mut pair = Pair { first: 1, second: 2 };
+
+    unsafe { swap_pair(&mut pair) };
+    assert_eq!((pair.first, pair.second), (2, 1));
+}
+
+// Test function that writes to memory only reachable through pointers stored in the argument.
+fn test_nested() {
+    #[repr(C)]
+    struct Node {
+        value: i32,
+        next: Option<Box<Node>>,
+    }
+
+    extern "C" {
+        fn set_all(node: *mut Node, value: i32);
+    }
+
+    let mut list = Node {
+        value: 1,
+        next: Some(Box::new(Node {
+            value: 2,
+            next: Some(Box::new(Node { value: 3, next: None })),
+        })),
+    };
+
+    unsafe { set_all(&mut list, 9) };
+    let mut node = Some(&list);
+    while let Some(n) = node {
+        assert_eq!(n.value, 9);
+        node = n.next.as_deref();
+    }
+}
+
+// Test function that writes a pointer, which can then be used from Rust.
+fn test_pointer_write() {
+    extern "C" {
+        fn store_pointer(out: *mut *const i32, ptr: *const i32);
+    }
+
+    let x = 42;
+    let mut out = ptr::null();
+
+    unsafe { store_pointer(&mut out, &x) };
+    assert_eq!(unsafe { *out }, 42);
+}
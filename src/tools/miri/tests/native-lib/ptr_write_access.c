@@ -0,0 +1,52 @@
+#include <stddef.h>
+
+// See comments in build_native_lib()
+#define EXPORT __attribute__((visibility("default")))
+
+/* Test: test_increment */
+
+EXPORT void increment(int *ptr) {
+  *ptr += 1;
+}
+
+/* Test: test_fill */
+
+EXPORT void fill(unsigned char *buf, size_t len, unsigned char value) {
+  for (size_t i = 0; i < len; i++) {
+    buf[i] = value;
+  }
+}
+
+/* Test: test_struct */
+
+typedef struct Pair {
+  int first;
+  int second;
+} Pair;
+
+EXPORT void swap_pair(Pair *pair) {
+  int tmp = pair->first;
+  pair->first = pair->second;
+  pair->second = tmp;
+}
+
+/* Test: test_nested */
+
+typedef struct Node {
+  int value;
+  struct Node *next;
+} Node;
+
+// Sets the value of every node in the list.
+EXPORT void set_all(Node *node, int value) {
+  while (node) {
+    node->value = value;
+    node = node->next;
+  }
+}
+
+/* Test: test_pointer_write */
+
+EXPORT void store_pointer(const int **out, const int *ptr) {
+  *out = ptr;
+}
@@ -0,0 +1,339 @@
+//@only-target: linux # accept4 and epoll are Linux-specific
+//@revisions: isolation no_isolation
+//@[no_isolation]compile-flags: -Zmiri-disable-isolation
+
+use std::mem::{size_of, zeroed};
+use std::thread;
+
+fn main() {
+    test_tcp();
+    test_bind_errors();
+    test_getsockname_truncated();
+    test_accept4_nonblock();
+    test_connect_nonblock();
+    test_epoll();
+    test_udp();
+}
+
+fn errno() -> i32 {
+    std::io::Error::last_os_error().raw_os_error().unwrap()
+}
+
+fn sockaddr_loopback(port: u16) -> libc::sockaddr_in {
+    let mut addr: libc::sockaddr_in = unsafe { zeroed() };
+    addr.sin_family = libc::AF_INET as libc::sa_family_t;
+    addr.sin_port = port.to_be();
+    addr.sin_addr.s_addr = u32::from(std::net::Ipv4Addr::LOCALHOST).to_be();
+    addr
+}
+
+const SOCKADDR_IN_LEN: libc::socklen_t = size_of::<libc::sockaddr_in>() as libc::socklen_t;
+
+/// Creates a listening TCP socket on an ephemeral loopback port, and returns it with its address.
+fn listener() -> (i32, libc::sockaddr_in) {
+    unsafe {
+        let fd = libc::socket(libc::AF_INET, libc::SOCK_STREAM | libc::SOCK_CLOEXEC, 0);
+        assert!(fd >= 0);
+        let addr = sockaddr_loopback(0);
+        let res = libc::bind(fd, (&raw const addr).cast(), SOCKADDR_IN_LEN);
+        assert_eq!(res, 0);
+        assert_eq!(libc::listen(fd, 128), 0);
+
+        let mut addr: libc::sockaddr_in = zeroed();
+        let mut len = SOCKADDR_IN_LEN;
+        let res = libc::getsockname(fd, (&raw mut addr).cast(), &mut len);
+        assert_eq!(res, 0);
+        assert_eq!(len, SOCKADDR_IN_LEN);
+        assert_eq!(addr.sin_family, libc::AF_INET as libc::sa_family_t);
+        assert_ne!(addr.sin_port, 0);
+        (fd, addr)
+    }
+}
+
+fn connect(addr: &libc::sockaddr_in) -> i32 {
+    unsafe {
+        let fd = libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0);
+        assert!(fd >= 0);
+        let res = libc::connect(fd, (addr as *const libc::sockaddr_in).cast(), SOCKADDR_IN_LEN);
+        assert_eq!(res, 0);
+        fd
+    }
+}
+
+fn test_tcp() {
+    let (listener, addr) = listener();
+    let server = thread::spawn(move || unsafe {
+        let mut peer: libc::sockaddr_in = zeroed();
+        let mut len = SOCKADDR_IN_LEN;
+        let fd = libc::accept(listener, (&raw mut peer).cast(), &mut len);
+        assert!(fd >= 0);
+        assert_eq!(len, SOCKADDR_IN_LEN);
+        assert_eq!(peer.sin_addr.s_addr, u32::from(std::net::Ipv4Addr::LOCALHOST).to_be());
+
+        let mut buf = [0u8; 5];
+        let res = libc::recv(fd, buf.as_mut_ptr().cast(), buf.len(), 0);
+        assert_eq!(res, 5);
+        assert_eq!(&buf, b"hello");
+        let res = libc::send(fd, b"world".as_ptr().cast(), 5, libc::MSG_NOSIGNAL);
+        assert_eq!(res, 5);
+        assert_eq!(libc::close(fd), 0);
+    });
+
+    unsafe {
+        let fd = connect(&addr);
+        let res = libc::send(fd, b"hello".as_ptr().cast(), 5, libc::MSG_NOSIGNAL);
+        assert_eq!(res, 5);
+        let mut buf = [0u8; 5];
+        let res = libc::recv(fd, buf.as_mut_ptr().cast(), buf.len(), libc::MSG_PEEK);
+        assert_eq!(res, 5);
+        let res = libc::read(fd, buf.as_mut_ptr().cast(), buf.len());
+        assert_eq!(res, 5);
+        assert_eq!(&buf, b"world");
+        server.join().unwrap();
+
+        // The server closed its end, so we read EOF.
+        let res = libc::recv(fd, buf.as_mut_ptr().cast(), buf.len(), 0);
+        assert_eq!(res, 0);
+        assert_eq!(libc::close(fd), 0);
+        assert_eq!(libc::close(listener), 0);
+    }
+}
+
+fn test_bind_errors() {
+    let (listener, addr) = listener();
+    unsafe {
+        // The address is already in use.
+        let fd = libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0);
+        let res = libc::bind(fd, (&raw const addr).cast(), SOCKADDR_IN_LEN);
+        assert_eq!(res, -1);
+        assert_eq!(errno(), libc::EADDRINUSE);
+
+        // The address is too short.
+        let res = libc::bind(fd, (&raw const addr).cast(), SOCKADDR_IN_LEN - 1);
+        assert_eq!(res, -1);
+        assert_eq!(errno(), libc::EINVAL);
+        // Even the address family does not fit.
+        let res = libc::connect(fd, (&raw const addr).cast(), 1);
+        assert_eq!(res, -1);
+        assert_eq!(errno(), libc::EINVAL);
+
+        // An IPv6 socket cannot be bound to an IPv4 address.
+        let fd6 = libc::socket(libc::AF_INET6, libc::SOCK_STREAM, 0);
+        let res = libc::bind(fd6, (&raw const addr).cast(), SOCKADDR_IN_LEN);
+        assert_eq!(res, -1);
+
+        // Pipes are not sockets.
+        let mut pipe = [-1, -1];
+        assert_eq!(libc::pipe(pipe.as_mut_ptr()), 0);
+        let res = libc::listen(pipe[0], 1);
+        assert_eq!(res, -1);
+        assert_eq!(errno(), libc::ENOTSOCK);
+
+        // Unconnected sockets cannot be shut down.
+        let res = libc::shutdown(fd, libc::SHUT_RDWR);
+        assert_eq!(res, -1);
+        assert_eq!(errno(), libc::ENOTCONN);
+
+        for fd in [fd, fd6, pipe[0], pipe[1], listener] {
+            assert_eq!(libc::close(fd), 0);
+        }
+    }
+}
+
+fn test_getsockname_truncated() {
+    let (listener, addr) = listener();
+    unsafe {
+        // Only the first 4 bytes (family and port) fit, but the full length is reported.
+        let mut buf = [0xffu8; 8];
+        let mut len: libc::socklen_t = 4;
+        let res = libc::getsockname(listener, buf.as_mut_ptr().cast(), &mut len);
+        assert_eq!(res, 0);
+        assert_eq!(len, SOCKADDR_IN_LEN);
+        assert_eq!(&buf[2..4], &addr.sin_port.to_ne_bytes());
+        assert_eq!(&buf[4..], &[0xff; 4]);
+        assert_eq!(libc::close(listener), 0);
+    }
+}
+
+fn test_accept4_nonblock() {
+    let (listener, addr) = listener();
+    unsafe {
+        let client = connect(&addr);
+        let server = libc::accept4(
+            listener,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+        );
+        assert!(server >= 0);
+        let mut buf = [0u8; 1];
+        let res = libc::recv(server, buf.as_mut_ptr().cast(), 1, 0);
+        assert_eq!(res, -1);
+        assert_eq!(errno(), libc::EAGAIN);
+
+        // MSG_DONTWAIT works on a blocking socket.
+        let res = libc::recv(client, buf.as_mut_ptr().cast(), 1, libc::MSG_DONTWAIT);
+        assert_eq!(res, -1);
+        assert_eq!(errno(), libc::EAGAIN);
+
+        // FIONBIO makes the socket blocking again.
+        let mut nonblocking: libc::c_int = 0;
+        assert_eq!(libc::ioctl(server, libc::FIONBIO, &mut nonblocking), 0);
+        let writer = thread::spawn(move || {
+            let res = libc::send(client, b"x".as_ptr().cast(), 1, 0);
+            assert_eq!(res, 1);
+            client
+        });
+        let res = libc::recv(server, buf.as_mut_ptr().cast(), 1, 0);
+        assert_eq!(res, 1);
+        let client = writer.join().unwrap();
+
+        for fd in [client, server, listener] {
+            assert_eq!(libc::close(fd), 0);
+        }
+    }
+}
+
+fn test_connect_nonblock() {
+    let (listener, addr) = listener();
+    unsafe {
+        let fd = libc::socket(libc::AF_INET, libc::SOCK_STREAM | libc::SOCK_NONBLOCK, 0);
+        assert!(fd >= 0);
+        // The connection may be established right away, or only later.
+        let res = libc::connect(fd, (&raw const addr).cast(), SOCKADDR_IN_LEN);
+        assert!(res == 0 || errno() == libc::EINPROGRESS);
+
+        // Once it is, the socket becomes writable and there is no pending error.
+        let epfd = libc::epoll_create1(0);
+        assert_ne!(epfd, -1);
+        let mut ev = libc::epoll_event {
+            events: (libc::EPOLLOUT | libc::EPOLLET) as _,
+            u64: u64::try_from(fd).unwrap(),
+        };
+        assert_eq!(libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, fd, &mut ev), 0);
+        let mut events = [libc::epoll_event { events: 0, u64: 0 }; 4];
+        let res = libc::epoll_wait(epfd, events.as_mut_ptr(), 4, -1);
+        assert_eq!(res, 1);
+        assert_eq!(events[0].events & libc::EPOLLOUT as u32, libc::EPOLLOUT as u32);
+        let mut error: libc::c_int = -1;
+        let mut len = size_of::<libc::c_int>() as libc::socklen_t;
+        let res = libc::getsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_ERROR,
+            (&raw mut error).cast(),
+            &mut len,
+        );
+        assert_eq!(res, 0);
+        assert_eq!(error, 0);
+
+        let res = libc::connect(fd, (&raw const addr).cast(), SOCKADDR_IN_LEN);
+        assert_eq!(res, -1);
+        assert_eq!(errno(), libc::EISCONN);
+
+        for fd in [fd, epfd, listener] {
+            assert_eq!(libc::close(fd), 0);
+        }
+    }
+}
+
+fn test_epoll() {
+    let (listener, addr) = listener();
+    unsafe {
+        let epfd = libc::epoll_create1(0);
+        assert_ne!(epfd, -1);
+        let mut ev = libc::epoll_event {
+            events: (libc::EPOLLIN | libc::EPOLLET) as _,
+            u64: u64::try_from(listener).unwrap(),
+        };
+        assert_eq!(libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, listener, &mut ev), 0);
+
+        // A connection makes the listener readable.
+        let connector = thread::spawn(move || connect(&addr));
+        let mut events = [libc::epoll_event { events: 0, u64: 0 }; 4];
+        let res = libc::epoll_wait(epfd, events.as_mut_ptr(), 4, -1);
+        assert_eq!(res, 1);
+        assert_eq!(events[0].events, libc::EPOLLIN as u32);
+        assert_eq!(events[0].u64, u64::try_from(listener).unwrap());
+        let client = connector.join().unwrap();
+        let server = libc::accept(listener, std::ptr::null_mut(), std::ptr::null_mut());
+        assert!(server >= 0);
+
+        // Data, and then the peer hanging up, make the connection readable.
+        let mut ev = libc::epoll_event {
+            events: (libc::EPOLLIN | libc::EPOLLRDHUP | libc::EPOLLET) as _,
+            u64: u64::try_from(server).unwrap(),
+        };
+        assert_eq!(libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, server, &mut ev), 0);
+        let writer = thread::spawn(move || {
+            let res = libc::write(client, b"abc".as_ptr().cast(), 3);
+            assert_eq!(res, 3);
+            client
+        });
+        let res = libc::epoll_wait(epfd, events.as_mut_ptr(), 4, -1);
+        assert_eq!(res, 1);
+        assert_eq!(events[0].events & libc::EPOLLIN as u32, libc::EPOLLIN as u32);
+        assert_eq!(events[0].u64, u64::try_from(server).unwrap());
+        let client = writer.join().unwrap();
+        let mut buf = [0u8; 3];
+        assert_eq!(libc::read(server, buf.as_mut_ptr().cast(), 3), 3);
+
+        assert_eq!(libc::close(client), 0);
+        let res = libc::epoll_wait(epfd, events.as_mut_ptr(), 4, -1);
+        assert_eq!(res, 1);
+        assert_eq!(events[0].events, (libc::EPOLLIN | libc::EPOLLRDHUP) as u32);
+
+        for fd in [server, listener, epfd] {
+            assert_eq!(libc::close(fd), 0);
+        }
+    }
+}
+
+fn test_udp() {
+    unsafe {
+        let a = libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0);
+        let b = libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0);
+        assert!(a >= 0 && b >= 0);
+        let addr = sockaddr_loopback(0);
+        assert_eq!(libc::bind(a, (&raw const addr).cast(), SOCKADDR_IN_LEN), 0);
+        let mut a_addr: libc::sockaddr_in = zeroed();
+        let mut len = SOCKADDR_IN_LEN;
+        assert_eq!(libc::getsockname(a, (&raw mut a_addr).cast(), &mut len), 0);
+
+        // Sending without a destination fails.
+        let res = libc::send(b, b"x".as_ptr().cast(), 1, 0);
+        assert_eq!(res, -1);
+        assert_eq!(errno(), libc::EDESTADDRREQ);
+
+        // `b` gets bound to an ephemeral port when it first sends.
+        let res = libc::sendto(b, b"hi".as_ptr().cast(), 2, 0, (&raw const a_addr).cast(), len);
+        assert_eq!(res, 2);
+        let mut buf = [0u8; 8];
+        let mut from: libc::sockaddr_in = zeroed();
+        let mut from_len = SOCKADDR_IN_LEN;
+        let res = libc::recvfrom(
+            a,
+            buf.as_mut_ptr().cast(),
+            buf.len(),
+            0,
+            (&raw mut from).cast(),
+            &mut from_len,
+        );
+        assert_eq!(res, 2);
+        assert_eq!(&buf[..2], b"hi");
+        let mut b_addr: libc::sockaddr_in = zeroed();
+        let mut len = SOCKADDR_IN_LEN;
+        assert_eq!(libc::getsockname(b, (&raw mut b_addr).cast(), &mut len), 0);
+        assert_eq!(from.sin_port, b_addr.sin_port);
+
+        let mut ty: libc::c_int = 0;
+        let mut ty_len = size_of::<libc::c_int>() as libc::socklen_t;
+        let res =
+            libc::getsockopt(b, libc::SOL_SOCKET, libc::SO_TYPE, (&raw mut ty).cast(), &mut ty_len);
+        assert_eq!(res, 0);
+        assert_eq!(ty, libc::SOCK_DGRAM);
+
+        assert_eq!(libc::close(a), 0);
+        assert_eq!(libc::close(b), 0);
+    }
+}
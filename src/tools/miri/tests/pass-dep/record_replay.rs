@@ -0,0 +1,36 @@
+//@ignore-target: windows # File handling is not implemented yet
+//@ignore-host: windows # The recording is stored in `/tmp`
+//@revisions: record replay
+//@compile-flags: -Zmiri-disable-isolation
+//@[record]compile-flags: -Zmiri-record=/tmp/miri-test-record-replay
+//@[replay]compile-flags: -Zmiri-replay=/tmp/miri-test-record-replay
+
+// The `replay` revision re-executes the run recorded by the `record` revision. If it diverged from
+// the recording, Miri would print a warning, making the revision fail.
+
+use std::fs;
+use std::time::{Duration, Instant, SystemTime};
+
+#[path = "../utils/mod.rs"]
+mod utils;
+
+fn main() {
+    let start = Instant::now();
+
+    // `MIRI_TEMP` comes from the host environment.
+    let dir = utils::prepare_dir("miri_test_record_replay");
+    fs::create_dir(&dir).unwrap();
+    let path = dir.join("file.txt");
+    fs::write(&path, b"recorded").unwrap();
+    assert_eq!(fs::read(&path).unwrap(), b"recorded");
+    assert_eq!(fs::metadata(&path).unwrap().len(), 8);
+    let entries = fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().file_name());
+    assert_eq!(entries.collect::<Vec<_>>(), ["file.txt"]);
+    assert!(fs::File::open(dir.join("missing.txt")).is_err());
+
+    let mut data = [0; 16];
+    getrandom_02::getrandom(&mut data).unwrap();
+
+    assert!(SystemTime::now() > SystemTime::UNIX_EPOCH);
+    assert!(start.elapsed() > Duration::ZERO);
+}
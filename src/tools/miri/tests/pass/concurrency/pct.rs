@@ -0,0 +1,86 @@
+//@revisions: sync_points every_step
+//@compile-flags: -Zmiri-pct=3 -Zmiri-pct-scheduling-points=100
+//@[every_step]compile-flags: -Zmiri-disable-data-race-detector
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, mpsc};
+use std::thread;
+
+/// Threads with the lowest priority still run once everybody else is blocked.
+fn mutex_counter() {
+    let counter = Arc::new(Mutex::new(0));
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let counter = Arc::clone(&counter);
+            thread::spawn(move || {
+                for _ in 0..3 {
+                    *counter.lock().unwrap() += 1;
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(*counter.lock().unwrap(), 12);
+}
+
+/// Yielding lowers the priority of a spinning thread, so the thread it waits for gets to run.
+fn spin_loop() {
+    static FLAG: AtomicBool = AtomicBool::new(false);
+    static DATA: AtomicUsize = AtomicUsize::new(0);
+
+    let waiter = thread::spawn(|| {
+        while !FLAG.load(Ordering::Acquire) {
+            thread::yield_now();
+        }
+        DATA.load(Ordering::Relaxed)
+    });
+    let setter = thread::spawn(|| {
+        DATA.store(42, Ordering::Relaxed);
+        FLAG.store(true, Ordering::Release);
+    });
+    setter.join().unwrap();
+    assert_eq!(waiter.join().unwrap(), 42);
+}
+
+fn condvar() {
+    let pair = Arc::new((Mutex::new(false), Condvar::new()));
+    let pair2 = Arc::clone(&pair);
+    let notifier = thread::spawn(move || {
+        let (lock, cvar) = &*pair2;
+        *lock.lock().unwrap() = true;
+        cvar.notify_one();
+    });
+    let (lock, cvar) = &*pair;
+    let mut ready = lock.lock().unwrap();
+    while !*ready {
+        ready = cvar.wait(ready).unwrap();
+    }
+    drop(ready);
+    notifier.join().unwrap();
+}
+
+fn channel() {
+    let (tx, rx) = mpsc::channel();
+    let senders: Vec<_> = (0..3)
+        .map(|i| {
+            let tx = tx.clone();
+            thread::spawn(move || tx.send(i).unwrap())
+        })
+        .collect();
+    drop(tx);
+    let mut received: Vec<i32> = rx.iter().collect();
+    received.sort();
+    assert_eq!(received, [0, 1, 2]);
+    for sender in senders {
+        sender.join().unwrap();
+    }
+}
+
+fn main() {
+    mutex_counter();
+    spin_loop();
+    condvar();
+    channel();
+}
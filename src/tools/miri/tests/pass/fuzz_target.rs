@@ -0,0 +1,18 @@
+//@compile-flags: -Zmiri-fuzz-corpus=tests/pass/fuzz_corpus
+#![no_main]
+
+use std::slice;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+#[no_mangle]
+pub extern "C" fn LLVMFuzzerTestOneInput(data: *const u8, size: usize) -> i32 {
+    // Every input is run in a fresh machine, so nothing is left over from earlier inputs.
+    assert_eq!(RUNS.fetch_add(1, Ordering::Relaxed), 0);
+
+    let data = unsafe { slice::from_raw_parts(data, size) };
+    println!("{:?}", String::from_utf8_lossy(data));
+    // Rejecting an input is not an error.
+    if data.is_empty() { -1 } else { 0 }
+}
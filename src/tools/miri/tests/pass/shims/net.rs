@@ -0,0 +1,154 @@
+//@ignore-target: windows # No socket support on Windows
+//@revisions: isolation no_isolation
+//@[no_isolation]compile-flags: -Zmiri-disable-isolation
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::thread;
+
+fn main() {
+    test_tcp(Ipv4Addr::LOCALHOST.into());
+    test_tcp(Ipv6Addr::LOCALHOST.into());
+    test_tcp_threaded();
+    test_tcp_nonblocking();
+    test_tcp_shutdown();
+    test_udp();
+    test_connection_refused();
+}
+
+fn loopback(ip: std::net::IpAddr) -> SocketAddr {
+    SocketAddr::new(ip, 0)
+}
+
+fn test_tcp(ip: std::net::IpAddr) {
+    let listener = TcpListener::bind(loopback(ip)).unwrap();
+    let addr = listener.local_addr().unwrap();
+    assert_eq!(addr.ip(), ip);
+    assert_ne!(addr.port(), 0);
+
+    let mut client = TcpStream::connect(addr).unwrap();
+    let (mut server, peer_addr) = listener.accept().unwrap();
+    assert_eq!(peer_addr, client.local_addr().unwrap());
+    assert_eq!(server.peer_addr().unwrap(), client.local_addr().unwrap());
+    assert_eq!(client.peer_addr().unwrap(), addr);
+
+    client.write_all(b"hello").unwrap();
+    let mut buf = [0; 5];
+    server.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"hello");
+
+    server.write_all(b"world").unwrap();
+    let mut buf = [0; 3];
+    assert_eq!(client.peek(&mut buf).unwrap(), 3);
+    assert_eq!(&buf, b"wor");
+    let mut buf = [0; 5];
+    client.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"world");
+
+    // Closing one end makes the other one read EOF.
+    drop(client);
+    assert_eq!(server.read(&mut buf).unwrap(), 0);
+}
+
+fn test_tcp_threaded() {
+    let listener = TcpListener::bind(loopback(Ipv4Addr::LOCALHOST.into())).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    // The server blocks in `accept` and `read` until the client shows up.
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut data = Vec::new();
+        stream.read_to_end(&mut data).unwrap();
+        stream.write_all(&data).unwrap();
+        data
+    });
+
+    let mut client = TcpStream::connect(addr).unwrap();
+    let data: Vec<u8> = (0..100_000u32).map(|i| i as u8).collect();
+    client.write_all(&data).unwrap();
+    client.shutdown(Shutdown::Write).unwrap();
+    let mut echoed = Vec::new();
+    client.read_to_end(&mut echoed).unwrap();
+    assert_eq!(echoed, data);
+    assert_eq!(server.join().unwrap(), data);
+}
+
+fn test_tcp_nonblocking() {
+    let listener = TcpListener::bind(loopback(Ipv4Addr::LOCALHOST.into())).unwrap();
+    listener.set_nonblocking(true).unwrap();
+    assert_eq!(listener.accept().unwrap_err().kind(), ErrorKind::WouldBlock);
+
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    // With host sockets, the connection may take a moment to show up.
+    let mut server = loop {
+        match listener.accept() {
+            Ok((server, _)) => break server,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => thread::yield_now(),
+            Err(e) => panic!("{e}"),
+        }
+    };
+    server.set_nonblocking(true).unwrap();
+    let mut buf = [0; 4];
+    assert_eq!(server.read(&mut buf).unwrap_err().kind(), ErrorKind::WouldBlock);
+
+    client.write_all(b"ping").unwrap();
+    let read = loop {
+        match server.read(&mut buf) {
+            Ok(read) => break read,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => thread::yield_now(),
+            Err(e) => panic!("{e}"),
+        }
+    };
+    assert_eq!(&buf[..read], &b"ping"[..read]);
+}
+
+fn test_tcp_shutdown() {
+    let listener = TcpListener::bind(loopback(Ipv4Addr::LOCALHOST.into())).unwrap();
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+
+    client.shutdown(Shutdown::Write).unwrap();
+    let mut buf = [0; 1];
+    assert_eq!(server.read(&mut buf).unwrap(), 0);
+    // The other direction still works.
+    server.write_all(b"x").unwrap();
+    client.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"x");
+}
+
+fn test_udp() {
+    let a = UdpSocket::bind(loopback(Ipv4Addr::LOCALHOST.into())).unwrap();
+    let b = UdpSocket::bind(loopback(Ipv4Addr::LOCALHOST.into())).unwrap();
+    let a_addr = a.local_addr().unwrap();
+    let b_addr = b.local_addr().unwrap();
+
+    assert_eq!(a.send_to(b"hello", b_addr).unwrap(), 5);
+    let mut buf = [0; 16];
+    let (read, from) = b.recv_from(&mut buf).unwrap();
+    assert_eq!(&buf[..read], b"hello");
+    assert_eq!(from, a_addr);
+
+    // Datagrams that do not fit into the buffer are truncated.
+    b.connect(a_addr).unwrap();
+    b.send(b"a longer datagram").unwrap();
+    let mut buf = [0; 8];
+    let read = a.recv(&mut buf).unwrap();
+    assert_eq!(read, 8);
+    assert_eq!(&buf, b"a longer");
+
+    // Receiving blocks until a datagram arrives.
+    let receiver = thread::spawn(move || {
+        let mut buf = [0; 16];
+        let (read, _) = a.recv_from(&mut buf).unwrap();
+        buf[..read].to_vec()
+    });
+    b.send(b"threaded").unwrap();
+    assert_eq!(receiver.join().unwrap(), b"threaded");
+}
+
+fn test_connection_refused() {
+    // Find a port that nobody listens on.
+    let addr =
+        TcpListener::bind(loopback(Ipv4Addr::LOCALHOST.into())).unwrap().local_addr().unwrap();
+    assert_eq!(TcpStream::connect(addr).unwrap_err().kind(), ErrorKind::ConnectionRefused);
+}
@@ -8,7 +8,7 @@
 use colored::*;
 use regex::bytes::Regex;
 use ui_test::build_manager::BuildManager;
-use ui_test::color_eyre::eyre::{Context, Result};
+use ui_test::color_eyre::eyre::{Context, Result, bail, ensure};
 use ui_test::custom_flags::edition::Edition;
 use ui_test::dependencies::DependencyBuilder;
 use ui_test::per_test_config::TestConfig;
@@ -64,6 +64,7 @@ fn build_native_lib() -> PathBuf {
             // FIXME: Automate gathering of all relevant C source files in the directory.
             "tests/native-lib/scalar_arguments.c",
             "tests/native-lib/ptr_read_access.c",
+            "tests/native-lib/ptr_write_access.c",
             // Ensure we notice serious problems in the C code.
             "-Wall",
             "-Wextra",
@@ -293,6 +294,36 @@ fn ui(
         .with_context(|| format!("ui tests in {path} for {target} failed"))
 }
 
+/// Checks that passing the schedule printed by a failing run with `-Zmiri-pct` to
+/// `-Zmiri-replay-schedule` reproduces the error.
+fn replay_schedule(target: &str) -> Result<()> {
+    let path = "tests/fail/concurrency/pct_ordering_bug.rs";
+    let msg = format!("## Replaying the schedule of {path} for {target}");
+    eprintln!("{}", msg.green().bold());
+
+    let sysroot = env::var("MIRI_SYSROOT")?;
+    let run = |flag: &str| -> Result<String> {
+        let output = Command::new(miri_path())
+            .args([path, flag, "--edition=2021", "--target", target])
+            .arg(format!("--sysroot={sysroot}"))
+            .output()?;
+        ensure!(output.status.code() == Some(1), "unexpected exit status: {}", output.status);
+        Ok(String::from_utf8(output.stderr)?)
+    };
+
+    let found = run("-Zmiri-pct=1")?;
+    let Some(flag) = found.split('`').find(|part| part.starts_with("-Zmiri-replay-schedule="))
+    else {
+        bail!("the run with `-Zmiri-pct` printed no schedule:\n{found}");
+    };
+    let replayed = run(flag)?;
+    // Only the run with PCT explains how to replay it.
+    let expected: String =
+        found.lines().filter(|line| !line.contains(flag)).map(|line| format!("{line}\n")).collect();
+    ensure!(replayed == expected, "replaying `{flag}` did not reproduce the error:\n{replayed}");
+    Ok(())
+}
+
 fn get_target() -> String {
     env::var("MIRI_TEST_TARGET").ok().unwrap_or_else(get_host)
 }
@@ -316,6 +347,7 @@ fn main() -> Result<()> {
     ui(Mode::Pass, "tests/pass-dep", &target, WithDependencies, tmpdir.path())?;
     ui(Mode::Panic, "tests/panic", &target, WithDependencies, tmpdir.path())?;
     ui(Mode::Fail, "tests/fail", &target, WithoutDependencies, tmpdir.path())?;
+    replay_schedule(&target)?;
     ui(Mode::Fail, "tests/fail-dep", &target, WithDependencies, tmpdir.path())?;
     if cfg!(unix) {
         ui(Mode::Pass, "tests/native-lib/pass", &target, WithoutDependencies, tmpdir.path())?;
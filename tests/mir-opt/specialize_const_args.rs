@@ -0,0 +1,23 @@
+//@ test-mir-pass: SpecializeConstArgs
+// EMIT_MIR_FOR_EACH_PANIC_STRATEGY
+
+// Checks that calls with constant arguments call clones of the callee specialized for these
+// arguments, and that calls passing the same constants share a clone.
+
+// EMIT_MIR specialize_const_args.select.const_args(true,_).BindConstArgs.diff
+fn select(flag: bool, value: u32) -> u32 {
+    // CHECK-LABEL: fn select(
+    // CHECK: bb0: {
+    // CHECK-NEXT: _0 = copy _2;
+    // CHECK-NEXT: return;
+    if flag { value } else { value * 3 }
+}
+
+// EMIT_MIR specialize_const_args.caller.SpecializeConstArgs.diff
+pub fn caller(value: u32) -> (u32, u32, u32) {
+    // CHECK-LABEL: fn caller(
+    // CHECK: = const {select - shim(const_args: true, _) as fn(bool, u32) -> u32}(const true,
+    // CHECK: = const {select - shim(const_args: true, _) as fn(bool, u32) -> u32}(const true,
+    // CHECK: = const {select - shim(const_args: false, _) as fn(bool, u32) -> u32}(const false,
+    (select(true, value), select(true, value), select(false, value))
+}
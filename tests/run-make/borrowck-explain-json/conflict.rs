@@ -0,0 +1,7 @@
+fn main() {
+    let mut x = 0;
+    let a = &mut x;
+    let b = &mut x;
+    *a += 1;
+    *b += 1;
+}
@@ -0,0 +1,37 @@
+// Check that `-Zborrowck-explain-json` emits a structured explanation of a conflicting borrow
+// right after the JSON diagnostic of the error.
+
+use run_make_support::rustc;
+use run_make_support::serde_json::{self, Value};
+
+fn main() {
+    let output = rustc()
+        .input("conflict.rs")
+        .arg("-Zborrowck-explain-json")
+        .arg("--error-format=json")
+        .run_fail();
+    let messages: Vec<Value> = output
+        .stderr_utf8()
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("invalid JSON line"))
+        .collect();
+
+    let index = messages
+        .iter()
+        .position(|message| message["$message_type"] == "borrowck_explanation")
+        .expect("missing borrowck explanation");
+    let error = &messages[index - 1];
+    assert_eq!(error["$message_type"], "diagnostic");
+    assert_eq!(error["code"]["code"], "E0499");
+
+    let explanation = &messages[index];
+    assert_eq!(explanation["code"], "E0499");
+    assert_eq!(explanation["span"]["line_start"], 4);
+    assert_eq!(explanation["loan"]["kind"], "mutable");
+    assert_eq!(explanation["loan"]["place"], "`x`");
+    assert_eq!(explanation["loan"]["span"]["line_start"], 3);
+    assert_eq!(explanation["access"]["kind"], "mutable borrow");
+    assert_eq!(explanation["access"]["place"], "`x`");
+    assert_eq!(explanation["access"]["span"]["line_start"], 4);
+    assert!(explanation["constraint_path"].is_array());
+}
@@ -0,0 +1,11 @@
+pub fn answer() -> u32 {
+    helper() as u32
+}
+
+pub fn unrelated() -> u32 {
+    0
+}
+
+fn helper() -> u64 {
+    42
+}
@@ -0,0 +1,49 @@
+// `-Zincremental-explain` prints why the queries matching its filter are recomputed: the chain of
+// red dep-nodes leading to the input whose fingerprint changed. Codegen units are explained too.
+
+//@ ignore-cross-compile
+
+use run_make_support::{Rustc, rfs, rustc};
+
+fn compile(filter: &str) -> Rustc {
+    let mut rustc = rustc();
+    rustc
+        .input("lib.rs")
+        .crate_type("rlib")
+        .incremental("incr")
+        .arg(format!("-Zincremental-explain={filter}"));
+    rustc
+}
+
+fn main() {
+    rfs::copy("before.rs", "lib.rs");
+    compile("typeck & answer")
+        .run()
+        .assert_stderr_contains("answer)` is computed because it didn't exist in the previous")
+        .assert_stderr_not_contains("is recomputed because");
+
+    // The type of `helper` changes, so `answer` has to be type-checked again.
+    rfs::copy("after.rs", "lib.rs");
+    compile("typeck & answer")
+        .run()
+        .assert_stderr_contains("answer)` is recomputed because:")
+        .assert_stderr_contains("`, which changed")
+        .assert_stderr_contains("changed: the fingerprint of its result went from");
+
+    // Nothing changed, so nothing is recomputed.
+    compile("typeck & answer").run().assert_stderr_not_contains("is recomputed because");
+
+    // Codegen units without a cached object file are compiled, and the ones that changed are
+    // recompiled.
+    rfs::remove_dir_all("incr");
+    rfs::copy("before.rs", "lib.rs");
+    compile("CompileCodegenUnit").run().assert_stderr_contains(
+        "is computed because there is no cached object file for the codegen",
+    );
+    rfs::copy("after.rs", "lib.rs");
+    compile("CompileCodegenUnit")
+        .run()
+        .assert_stderr_contains("CompileCodegenUnit(")
+        .assert_stderr_contains("is recomputed because:");
+    compile("CompileCodegenUnit").run().assert_stderr_not_contains("[incremental]");
+}
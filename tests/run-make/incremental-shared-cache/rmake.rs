@@ -0,0 +1,52 @@
+// A session published to the shared incremental cache with `-Zincremental-shared-cache` is reused
+// by another checkout of the same sources, starting from an empty incremental directory, as long
+// as both checkouts are remapped to the same path. Without the remapping, the checkouts don't
+// share their results. Sessions that don't generate code, like the ones of `cargo check`, publish
+// their results too.
+
+//@ ignore-cross-compile
+
+use run_make_support::{Rustc, rfs, rustc};
+
+fn compile(checkout: &str, remap: bool, incr_state: &str) -> Rustc {
+    let mut rustc = rustc();
+    rustc
+        .input(format!("{checkout}/lib.rs"))
+        .crate_type("rlib")
+        .out_dir(checkout)
+        .incremental(format!("{checkout}/incr"))
+        .arg("-Zincremental-shared-cache=shared")
+        .arg("-Zincremental-info")
+        .arg(format!("-Zassert-incr-state={incr_state}"));
+    if remap {
+        rustc.remap_path_prefix(checkout, "/src");
+    }
+    rustc
+}
+
+fn main() {
+    for checkout in ["a", "b", "c", "d", "e"] {
+        rfs::create_dir(checkout);
+        rfs::copy("lib.rs", format!("{checkout}/lib.rs"));
+    }
+
+    compile("a", true, "not-loaded")
+        .run()
+        .assert_stderr_contains("shared cache: no session published for this compilation")
+        .assert_stderr_contains("files published");
+
+    compile("b", true, "loaded").run().assert_stderr_contains("files imported");
+
+    // The file names seen by the compilation differ, and so does the index.
+    compile("c", false, "not-loaded")
+        .run()
+        .assert_stderr_contains("shared cache: no session published for this compilation");
+
+    compile("d", true, "not-loaded")
+        .emit("metadata")
+        .run()
+        .assert_stderr_contains("shared cache: no session published for this compilation")
+        .assert_stderr_contains("files published");
+
+    compile("e", true, "loaded").emit("metadata").run().assert_stderr_contains("files imported");
+}
@@ -0,0 +1,7 @@
+#![crate_name = "foo"]
+
+/// Before the edit.
+pub struct Edited;
+
+/// Never edited.
+pub struct Unchanged;
@@ -0,0 +1,48 @@
+// Check that `--render-cache-dir` reuses the pages whose inputs didn't change since the previous
+// run, and renders again the ones showing an edited doc comment.
+
+use std::path::Path;
+
+use run_make_support::{path, rfs, rustdoc};
+
+const MARKER: &str = "<!-- rendered by the first run -->";
+
+fn document() {
+    rustdoc()
+        .input("lib.rs")
+        .out_dir("doc")
+        .arg("-Zunstable-options")
+        .arg("--render-cache-dir")
+        .arg("cache")
+        .run();
+}
+
+fn mark(page: &Path) {
+    let mut contents = rfs::read_to_string(page);
+    contents.push_str(MARKER);
+    rfs::write(page, contents);
+}
+
+fn main() {
+    rfs::copy("original.rs", "lib.rs");
+    document();
+
+    let edited = path("doc/foo/struct.Edited.html");
+    let unchanged = path("doc/foo/struct.Unchanged.html");
+    let source = path("doc/src/foo/lib.rs.html");
+    mark(&edited);
+    mark(&unchanged);
+    mark(&source);
+
+    // Only edit the text of a doc comment.
+    let original = rfs::read_to_string("original.rs");
+    rfs::write("lib.rs", original.replace("Before the edit.", "After the edit."));
+    document();
+
+    let edited = rfs::read_to_string(edited);
+    assert!(edited.contains("After the edit."));
+    assert!(!edited.contains(MARKER));
+    assert!(rfs::read_to_string(unchanged).contains(MARKER));
+    // The source page shows the edited doc comment.
+    assert!(!rfs::read_to_string(source).contains(MARKER));
+}
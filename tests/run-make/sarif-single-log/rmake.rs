@@ -0,0 +1,24 @@
+// A compilation creates several diagnostic contexts, e.g., the early ones for errors in command
+// line arguments and the one of the session. With `--error-format=sarif`, all of them have to
+// write to the same log, so that stderr is a single SARIF document.
+
+use run_make_support::{rustc, serde_json};
+
+fn main() {
+    let output = rustc()
+        .input("main.rs")
+        .arg("-Zunstable-options")
+        .error_format("sarif")
+        // Emits an early warning once the error format is known.
+        .arg("-Zremark-dir=remarks")
+        .run_fail();
+    let log: serde_json::Value =
+        serde_json::from_str(&output.stderr_utf8()).expect("stderr is not a single SARIF log");
+    assert_eq!(log["version"], "2.1.0");
+
+    let results = log["runs"][0]["results"].as_array().unwrap();
+    let messages: Vec<&str> =
+        results.iter().map(|result| result["message"]["text"].as_str().unwrap()).collect();
+    assert!(messages.iter().any(|message| message.contains("-Z remark-dir")), "{messages:?}");
+    assert!(messages.iter().any(|message| message.contains("mismatched types")), "{messages:?}");
+}
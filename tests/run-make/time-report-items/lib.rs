@@ -0,0 +1,11 @@
+pub fn generic<T: Clone>(x: T) -> (T, T) {
+    (x.clone(), x)
+}
+
+pub fn instantiations() -> ((u8, u8), (String, String)) {
+    (generic(1u8), generic(String::new()))
+}
+
+pub fn not_generic(x: u32) -> u32 {
+    x.wrapping_mul(3)
+}
@@ -0,0 +1,50 @@
+// `-Ztime-report=items` prints the items with the most compilation time, with the time of each
+// activity and their number of instantiations, and the LLVM time of each instantiation of generic
+// items.
+
+//@ ignore-cross-compile
+
+use run_make_support::rustc;
+
+fn main() {
+    let output = rustc().input("lib.rs").crate_type("rlib").arg("-Ztime-report=items=100").run();
+    let stderr = output.stderr_utf8();
+    // Besides the items of the crate, instantiations of upstream items like `drop_in_place` are
+    // reported.
+    output.assert_stderr_contains("items with the most compilation time, in seconds");
+    let header = stderr.lines().nth(1).unwrap();
+    for column in ["total", "typeck", "borrowck", "mir_opt", "llvm", "instances", "item"] {
+        assert!(header.contains(column), "missing column `{column}`: {header}");
+    }
+
+    // Both instantiations of `generic` are attributed to it.
+    let generic = stderr.lines().find(|line| line.contains(" generic (lib.rs:1:1")).unwrap();
+    assert_eq!(generic.split_whitespace().nth(5), Some("2"), "{generic}");
+    // Each of them gets a row below the one of `generic`.
+    let instances: Vec<_> =
+        stderr.lines().skip_while(|line| *line != generic).skip(1).take(2).collect();
+    assert!(instances.iter().any(|line| line.ends_with(" generic::<u8>")), "{instances:?}");
+    assert!(
+        instances.iter().any(|line| line.ends_with(" generic::<std::string::String>")),
+        "{instances:?}"
+    );
+    output.assert_stderr_contains(" instantiations (lib.rs:5:1");
+    output.assert_stderr_contains(" not_generic (lib.rs:9:1");
+
+    // The number of items printed is limited.
+    rustc()
+        .input("lib.rs")
+        .crate_type("rlib")
+        .arg("-Ztime-report=items=1")
+        .run()
+        .assert_stderr_contains("time-report: the 1 items with the most compilation time");
+
+    // Nothing is partitioned when only checking the crate, but the analyses are still reported.
+    rustc()
+        .input("lib.rs")
+        .crate_type("rlib")
+        .emit("metadata")
+        .arg("-Ztime-report=items")
+        .run()
+        .assert_stderr_contains(" not_generic (lib.rs:9:1");
+}
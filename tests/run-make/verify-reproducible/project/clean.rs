@@ -0,0 +1,2 @@
+pub const SET: &str = env!("VERIFY_REPRODUCIBLE_SET");
+pub static INSIDE: &[u8] = include_bytes!("inside.txt");
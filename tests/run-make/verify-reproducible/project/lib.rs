@@ -0,0 +1,4 @@
+pub const OPTIONAL: Option<&str> = option_env!("VERIFY_REPRODUCIBLE_UNSET");
+pub const SET: &str = env!("VERIFY_REPRODUCIBLE_SET");
+pub static INSIDE: &[u8] = include_bytes!("inside.txt");
+pub static OUTSIDE: &[u8] = include_bytes!("../outside.txt");
@@ -0,0 +1,55 @@
+// `-Zverify-reproducible` reports the environment variables not set with `--env-set`, the files
+// read from outside the working directory, and the absolute paths that are not remapped.
+
+//@ ignore-cross-compile
+
+use run_make_support::{cwd, rustc};
+
+fn main() {
+    // Relative paths, inside the working directory, and variables set with `--env-set` don't make
+    // the compilation depend on its environment.
+    rustc()
+        .current_dir("project")
+        .input("clean.rs")
+        .crate_type("rlib")
+        .arg("--env-set=VERIFY_REPRODUCIBLE_SET=value")
+        .arg("-Zverify-reproducible=deny")
+        .run()
+        .assert_stderr_not_contains("warning")
+        .assert_stderr_not_contains("error");
+
+    rustc()
+        .current_dir("project")
+        .input("lib.rs")
+        .crate_type("rlib")
+        .arg("--env-set=VERIFY_REPRODUCIBLE_SET=value")
+        .arg("-Zverify-reproducible=warn")
+        .run()
+        .assert_stderr_contains(
+            "warning: the environment variable `VERIFY_REPRODUCIBLE_UNSET` is read during the \
+             compilation",
+        )
+        .assert_stderr_not_contains("`VERIFY_REPRODUCIBLE_SET` is read")
+        .assert_stderr_contains("`../outside.txt` is read from outside the working directory")
+        .assert_stderr_not_contains("inside.txt");
+
+    // The absolute path of the crate root is only fine once remapped.
+    let clean = cwd().join("project/clean.rs");
+    rustc()
+        .current_dir("project")
+        .input(&clean)
+        .crate_type("rlib")
+        .arg("--env-set=VERIFY_REPRODUCIBLE_SET=value")
+        .arg("-Zverify-reproducible=deny")
+        .run_fail()
+        .assert_stderr_contains("error: the absolute path `")
+        .assert_stderr_contains("clean.rs` of a source file is not remapped");
+    rustc()
+        .current_dir("project")
+        .input(&clean)
+        .crate_type("rlib")
+        .arg("--env-set=VERIFY_REPRODUCIBLE_SET=value")
+        .arg("-Zverify-reproducible=deny")
+        .remap_path_prefix(cwd(), "/src")
+        .run();
+}
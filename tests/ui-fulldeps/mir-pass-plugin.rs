@@ -0,0 +1,105 @@
+//@ edition: 2021
+//@ run-pass
+//@ run-flags: --sysroot {{sysroot-base}} --edition=2021
+//@ ignore-stage1 (requires matching sysroot built with in-tree compiler)
+
+// Test that drivers are able to register custom MIR passes at the different phases of the MIR
+// pipeline, and that these passes can be disabled with `-Zmir-enable-passes`.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+
+use std::sync::Mutex;
+
+use rustc_driver::Compilation;
+use rustc_hir::def::DefKind;
+use rustc_interface::interface::Compiler;
+use rustc_interface::{Config, Queries};
+use rustc_middle::mir::{
+    AnalysisPhase, Body, CustomMirPass, CustomPassPhase, MirPhase, RuntimePhase,
+};
+use rustc_middle::ty::TyCtxt;
+
+/// The passes that ran: the name of the pass, the item and the phase of its body.
+static RECORDED: Mutex<Vec<(&'static str, String, MirPhase)>> = Mutex::new(Vec::new());
+
+struct RecordPass {
+    name: &'static str,
+}
+
+impl CustomMirPass for RecordPass {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn run_pass<'tcx>(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        let item = tcx.def_path_str(body.source.def_id());
+        RECORDED.lock().unwrap().push((self.name, item, body.phase));
+    }
+}
+
+struct CompilerCalls;
+
+impl rustc_driver::Callbacks for CompilerCalls {
+    fn config(&mut self, config: &mut Config) {
+        config.mir_passes.extend([
+            (CustomPassPhase::AfterOptimization, pass("AfterOptimization")),
+            (CustomPassPhase::AfterBorrowck, pass("AfterBorrowck")),
+            (CustomPassPhase::BeforeOptimization, pass("BeforeOptimization")),
+            (CustomPassPhase::BeforeOptimization, pass("Disabled")),
+        ]);
+    }
+
+    fn after_analysis<'tcx>(
+        &mut self,
+        compiler: &Compiler,
+        queries: &'tcx Queries<'tcx>,
+    ) -> Compilation {
+        compiler.sess.dcx().abort_if_errors();
+        queries.global_ctxt().unwrap().enter(|tcx| {
+            for id in tcx.hir_crate_items(()).free_items() {
+                if matches!(tcx.def_kind(id.owner_id), DefKind::Fn) {
+                    let _ = tcx.optimized_mir(id.owner_id);
+                }
+            }
+        });
+        Compilation::Stop
+    }
+}
+
+fn pass(name: &'static str) -> Box<dyn CustomMirPass> {
+    Box::new(RecordPass { name })
+}
+
+fn main() {
+    let path = "mir_pass_plugin_input.rs";
+    std::fs::write(path, "pub fn add(a: u32, b: u32) -> u32 { a + b }").unwrap();
+
+    let mut args: Vec<_> = std::env::args().collect();
+    args.extend([
+        "--crate-type=lib".to_string(),
+        "-Zmir-enable-passes=-Disabled".to_string(),
+        path.to_string(),
+    ]);
+    rustc_driver::catch_fatal_errors(|| {
+        rustc_driver::RunCompiler::new(&args, &mut CompilerCalls).run()
+    })
+    .unwrap()
+    .unwrap();
+
+    let recorded = RECORDED.lock().unwrap();
+    let add: Vec<_> = recorded
+        .iter()
+        .filter(|(_, item, _)| item == "add")
+        .map(|(name, _, phase)| (*name, *phase))
+        .collect();
+    assert_eq!(add, [
+        ("AfterBorrowck", MirPhase::Analysis(AnalysisPhase::Initial)),
+        ("BeforeOptimization", MirPhase::Runtime(RuntimePhase::PostCleanup)),
+        ("AfterOptimization", MirPhase::Runtime(RuntimePhase::Optimized)),
+    ]);
+}
@@ -0,0 +1,78 @@
+//@ run-pass
+//! Test that users are able to analyze several crates in the same process without a driver.
+
+//@ ignore-stage1
+//@ ignore-cross-compile
+//@ ignore-remote
+//@ ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+//@ edition: 2021
+
+#![feature(rustc_private)]
+#![feature(assert_matches)]
+
+extern crate rustc_driver;
+extern crate rustc_smir;
+extern crate stable_mir;
+
+use rustc_smir::rustc_internal::{CrateConfig, analyze_crate};
+use stable_mir::{CompilerError, CrateDef};
+use std::assert_matches::assert_matches;
+use std::io::Write;
+use std::ops::ControlFlow;
+
+fn local_items(config: &CrateConfig) -> Result<Vec<String>, CompilerError<()>> {
+    analyze_crate(config, rustc_driver::DEFAULT_LOCALE_RESOURCES, || {
+        assert_eq!(stable_mir::local_crate().name, config.name.as_deref().unwrap());
+        let mut names: Vec<_> =
+            stable_mir::all_local_items().iter().map(|item| item.name()).collect();
+        names.sort();
+        ControlFlow::Continue(names)
+    })
+}
+
+fn main() {
+    generate_input("first.rs", "pub fn first() {}").unwrap();
+    generate_input("second.rs", "pub fn second() {}").unwrap();
+    generate_input("broken.rs", "pub fn broken() -> u8 { \"not a number\" }").unwrap();
+
+    let mut first = CrateConfig::new("first.rs");
+    first.name = Some("first".to_string());
+    first.crate_type = Some("lib".to_string());
+    first.edition = Some("2021".to_string());
+    assert_eq!(local_items(&first).unwrap(), ["first"]);
+
+    // The `cfg` options must be taken into account.
+    let mut second = CrateConfig::new("second.rs");
+    second.name = Some("second".to_string());
+    second.crate_type = Some("lib".to_string());
+    second.cfgs.push("extra".to_string());
+    assert_eq!(local_items(&second).unwrap(), ["extra", "second"]);
+
+    // Compilation errors are reported instead of running the callback.
+    let mut broken = CrateConfig::new("broken.rs");
+    broken.name = Some("broken".to_string());
+    broken.crate_type = Some("lib".to_string());
+    assert_matches!(local_items(&broken), Err(CompilerError::Failed));
+
+    // Analyzing a crate again in the same process is fine.
+    assert_eq!(local_items(&first).unwrap(), ["first"]);
+
+    let result = analyze_crate(&first, rustc_driver::DEFAULT_LOCALE_RESOURCES, || {
+        ControlFlow::<_, ()>::Break("stop")
+    });
+    assert_matches!(result, Err(CompilerError::Interrupted("stop")));
+}
+
+fn generate_input(path: &str, source: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+        {source}
+
+        #[cfg(extra)]
+        pub fn extra() {{}}
+        "#
+    )?;
+    Ok(())
+}
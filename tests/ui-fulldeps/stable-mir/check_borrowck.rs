@@ -0,0 +1,104 @@
+//@ run-pass
+//! Test that users are able to retrieve the borrow checker results of a body.
+
+//@ ignore-stage1
+//@ ignore-cross-compile
+//@ ignore-remote
+//@ ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+
+#![feature(rustc_private)]
+#![feature(assert_matches)]
+
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_smir::rustc_internal;
+use stable_mir::mir::borrowck::BorrowckFacts;
+use stable_mir::mir::{BodyLocation, BorrowKind, Rvalue, StatementKind};
+use stable_mir::ty::RegionKind;
+use stable_mir::CrateDef;
+use std::assert_matches::assert_matches;
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+
+/// This function uses the Stable MIR APIs to get the borrow checker results.
+fn test_borrowck() -> ControlFlow<()> {
+    let items = stable_mir::all_local_items();
+    let item = |name: &str| *items.iter().find(|item| item.name() == name).unwrap();
+
+    let facts = item("two_borrows").borrowck_facts().unwrap();
+    assert_eq!(facts.borrows.len(), 2);
+    assert_matches!(facts.borrows[0].kind, BorrowKind::Shared);
+    assert_matches!(facts.borrows[1].kind, BorrowKind::Mut { .. });
+    check_borrow_stmt(&facts);
+    check_regions(&facts);
+
+    // Both loans are live when the mutable reference is used.
+    let first = facts.borrows[0].reserve_location;
+    assert!(facts.live_loans_at(first).is_empty());
+    let (last_use, _) = facts.live_loans.iter().rev().find(|(_, loans)| loans.len() == 2).unwrap();
+    assert!(*last_use > first);
+    ControlFlow::Continue(())
+}
+
+/// Check that each borrow is reserved at the statement that creates the reference.
+fn check_borrow_stmt(facts: &BorrowckFacts) {
+    for borrow in &facts.borrows {
+        let BodyLocation { block, statement_index } = borrow.reserve_location;
+        let stmt = &facts.body.blocks[block].statements[statement_index];
+        let StatementKind::Assign(place, Rvalue::Ref(region, kind, borrowed)) = &stmt.kind else {
+            panic!("Unexpected borrow statement: {stmt:?}")
+        };
+        assert_eq!(place, &borrow.assigned_place);
+        assert_eq!(borrowed, &borrow.borrowed_place);
+        assert_eq!(kind, &borrow.kind);
+        assert_eq!(region.kind, RegionKind::ReVar(borrow.region));
+    }
+}
+
+/// Check that the region of a borrow contains its reserve location.
+fn check_regions(facts: &BorrowckFacts) {
+    assert!(!facts.outlives_constraints.is_empty());
+    for borrow in &facts.borrows {
+        assert!(facts.region_values[borrow.region].contains(&borrow.reserve_location));
+    }
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// Then it will create a `StableMir` using custom arguments and then
+/// it will run the compiler.
+fn main() {
+    let path = "borrowck_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    rustc_internal::retain_borrowck_facts();
+    run!(args, test_borrowck).unwrap();
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+        pub fn two_borrows(mut v: u8, w: u8) -> u8 {{
+            let first = &w;
+            let second = &mut v;
+            *second += *first;
+            *second
+        }}
+        "#
+    )?;
+    Ok(())
+}
@@ -0,0 +1,213 @@
+//@ run-pass
+//! Test that users are able to run dataflow analyses on Stable MIR bodies.
+
+//@ ignore-stage1
+//@ ignore-cross-compile
+//@ ignore-remote
+//@ ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+
+#![feature(rustc_private)]
+
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_smir::rustc_internal;
+use stable_mir::mir::dataflow::{
+    Analysis, MaybeBorrowedLocals, MaybeInitializedLocals, MaybeLiveLocals,
+    MaybeUninitializedLocals,
+};
+use stable_mir::mir::visit::{Location, PlaceContext};
+use stable_mir::mir::{
+    Body, BodyLocation, Local, MirVisitor, ProjectionElem, StatementKind, TerminatorKind,
+    RETURN_LOCAL,
+};
+use stable_mir::CrateDef;
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+
+/// This function uses the Stable MIR APIs to run the standard dataflow analyses.
+fn test_dataflow() -> ControlFlow<()> {
+    let items = stable_mir::all_local_items();
+    let body = |name: &str| items.iter().find(|item| item.name() == name).unwrap().body();
+
+    check_init(&body("xor"));
+    check_liveness(&body("xor"));
+    check_loop(&body("count"));
+    check_borrowed(&body("borrow"));
+    check_index(&body("store"));
+    ControlFlow::Continue(())
+}
+
+/// The arguments are initialized on entry, and the return value is initialized on return.
+fn check_init(body: &Body) {
+    let start = BodyLocation { block: 0, statement_index: 0 };
+    let mut init = MaybeInitializedLocals.iterate_to_fixpoint(body);
+    let mut uninit = MaybeUninitializedLocals.iterate_to_fixpoint(body);
+    let state = init.state_before(body, start);
+    assert!(state.contains(1) && state.contains(2), "{state:?}");
+    assert!(!state.contains(RETURN_LOCAL), "{state:?}");
+    let state = uninit.state_before(body, start);
+    assert!(!state.contains(1) && !state.contains(2), "{state:?}");
+    assert!(state.contains(RETURN_LOCAL), "{state:?}");
+
+    for location in return_locations(body) {
+        assert!(init.state_before(body, location).contains(RETURN_LOCAL));
+        assert!(!uninit.state_before(body, location).contains(RETURN_LOCAL));
+    }
+}
+
+/// Both arguments are used, and only the return value is used on return.
+fn check_liveness(body: &Body) {
+    let mut live = MaybeLiveLocals.iterate_to_fixpoint(body);
+    let state = live.state_before(body, BodyLocation { block: 0, statement_index: 0 });
+    assert_eq!(state.iter().collect::<Vec<_>>(), vec![1, 2]);
+    for location in return_locations(body) {
+        let state = live.state_before(body, location);
+        assert_eq!(state.iter().collect::<Vec<_>>(), vec![RETURN_LOCAL]);
+    }
+}
+
+/// The bound of the loop must stay live across the back edge.
+fn check_loop(body: &Body) {
+    let mut live = MaybeLiveLocals.iterate_to_fixpoint(body);
+    // The overflow check of `i += 1` is the last terminator before jumping back to the loop
+    // condition, which is the only place where `n` is used.
+    let (block, data) = body
+        .blocks
+        .iter()
+        .enumerate()
+        .find(|(_, data)| matches!(data.terminator.kind, TerminatorKind::Assert { .. }))
+        .unwrap();
+    let location = BodyLocation { block, statement_index: data.statements.len() };
+    assert!(live.state_before(body, location).contains(1));
+    for location in return_locations(body) {
+        assert!(!live.state_before(body, location).contains(1));
+    }
+}
+
+/// The argument is borrowed to call `read`, and it remains borrowed until the end.
+fn check_borrowed(body: &Body) {
+    let mut borrowed = MaybeBorrowedLocals.iterate_to_fixpoint(body);
+    let state = borrowed.state_before(body, BodyLocation { block: 0, statement_index: 0 });
+    assert!(state.is_empty(), "{state:?}");
+    for location in return_locations(body) {
+        let state = borrowed.state_before(body, location);
+        assert!(state.contains(1), "{state:?}");
+        assert!(!state.contains(RETURN_LOCAL), "{state:?}");
+    }
+}
+
+/// Writing to an indexed place reads the index, so the index is live before the write.
+fn check_index(body: &Body) {
+    let (location, index) = body
+        .blocks
+        .iter()
+        .enumerate()
+        .flat_map(|(block, data)| {
+            data.statements.iter().enumerate().map(move |(statement_index, stmt)| {
+                (BodyLocation { block, statement_index }, stmt)
+            })
+        })
+        .find_map(|(location, stmt)| match &stmt.kind {
+            StatementKind::Assign(place, _) => place.projection.iter().find_map(|elem| match elem {
+                ProjectionElem::Index(local) => Some((location, *local)),
+                _ => None,
+            }),
+            _ => None,
+        })
+        .unwrap();
+    let mut live = MaybeLiveLocals.iterate_to_fixpoint(body);
+    assert!(live.state_before(body, location).contains(index));
+
+    // The index is never visited as being written to.
+    let mut visitor = LocalContexts { local: index, contexts: Vec::new() };
+    visitor.visit_body(body);
+    assert!(!visitor.contexts.is_empty());
+    for ptx in visitor.contexts {
+        assert!(ptx.is_use() && !ptx.is_mutating() && !ptx.is_store(), "{ptx:?}");
+    }
+}
+
+/// Record the contexts in which a local is visited.
+struct LocalContexts {
+    local: Local,
+    contexts: Vec<PlaceContext>,
+}
+
+impl MirVisitor for LocalContexts {
+    fn visit_local(&mut self, local: &Local, ptx: PlaceContext, _location: Location) {
+        if *local == self.local {
+            self.contexts.push(ptx);
+        }
+    }
+}
+
+fn return_locations(body: &Body) -> Vec<BodyLocation> {
+    let locations: Vec<_> = body
+        .blocks
+        .iter()
+        .enumerate()
+        .filter(|(_, data)| matches!(data.terminator.kind, TerminatorKind::Return))
+        .map(|(block, data)| BodyLocation { block, statement_index: data.statements.len() })
+        .collect();
+    assert!(!locations.is_empty());
+    locations
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// Then it will create a `StableMir` using custom arguments and then
+/// it will run the compiler.
+fn main() {
+    let path = "dataflow_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        "-Coverflow-checks=on".to_string(),
+        path.to_string(),
+    ];
+    run!(args, test_dataflow).unwrap();
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+        pub fn xor(a: u8, b: u8) -> u8 {{
+            a ^ b
+        }}
+
+        pub fn count(n: u32) -> u32 {{
+            let mut i = 0;
+            while i < n {{
+                i += 1;
+            }}
+            i
+        }}
+
+        #[inline(never)]
+        fn read(r: &u8) -> u8 {{
+            *r
+        }}
+
+        pub fn borrow(x: u8) -> u8 {{
+            read(&x)
+        }}
+
+        pub fn store(mut a: [u8; 4], i: usize) -> [u8; 4] {{
+            a[i] = 0;
+            a
+        }}
+        "#
+    )?;
+    Ok(())
+}
@@ -0,0 +1,190 @@
+//@ run-pass
+//! Test that users are able to inspect the HIR body of items and the macro backtrace of spans.
+
+//@ ignore-stage1
+//@ ignore-cross-compile
+//@ ignore-remote
+//@ ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+//@ edition: 2021
+
+#![feature(rustc_private)]
+#![feature(assert_matches)]
+
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_smir::rustc_internal;
+use stable_mir::hir::{
+    BinOp, Body, CaptureKind, Expr, ExprKind, LoopSource, MatchSource, PatKind, Res, StmtKind,
+};
+use stable_mir::mir::Mutability;
+use stable_mir::ty::{ExpnKind, MacroKind, RigidTy, TyKind, UintTy};
+use stable_mir::{CrateDef, CrateItem};
+use std::assert_matches::assert_matches;
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+
+fn test_hir() -> ControlFlow<()> {
+    let items = stable_mir::all_local_items();
+    let item = |name: &str| *items.iter().find(|item| item.name() == name).unwrap();
+    check_compute(item("compute"));
+    check_parse(item("parse"));
+    ControlFlow::Continue(())
+}
+
+fn check_compute(item: CrateItem) {
+    let body = item.hir_body().unwrap();
+    let names: Vec<_> = body.params.iter().map(|param| binding(&param.pat.kind).1).collect();
+    assert_eq!(names, ["values", "bias"]);
+    let exprs = exprs(&body);
+
+    // The multiplication comes from the expansion of `double!`.
+    let mul = exprs.iter().find(|expr| matches!(expr.kind, ExprKind::Binary(BinOp::Mul, ..)));
+    let mul = mul.unwrap();
+    assert_matches!(mul.ty.unwrap().kind(), TyKind::RigidTy(RigidTy::Uint(UintTy::U32)));
+    let backtrace = mul.span.macro_backtrace();
+    assert_eq!(backtrace.len(), 1, "{backtrace:?}");
+    assert_eq!(backtrace[0].kind, ExpnKind::Macro(MacroKind::Bang, "double".to_string()));
+    assert!(backtrace[0].macro_def.is_some());
+    assert!(!backtrace[0].call_site.from_expansion());
+
+    // The `for` loop is desugared.
+    let for_loop = exprs.iter().find(|expr| matches!(expr.kind, ExprKind::Loop(_, _)));
+    let for_loop = for_loop.unwrap();
+    assert_matches!(for_loop.kind, ExprKind::Loop(_, LoopSource::ForLoop));
+    assert!(
+        for_loop.span.macro_backtrace().iter().any(|expn| matches!(expn.kind, ExpnKind::Desugaring(_))),
+        "{for_loop:?}"
+    );
+    assert!(exprs.iter().any(|expr| matches!(expr.kind, ExprKind::Match(_, _, MatchSource::ForLoopDesugar))));
+
+    // The closure borrows `bias`.
+    let bias = binding(&body.params[1].pat.kind).0;
+    let closure = exprs.iter().find_map(|expr| match &expr.kind {
+        ExprKind::Closure(closure) => Some(closure),
+        _ => None,
+    });
+    let closure = closure.unwrap();
+    assert!(!closure.is_move);
+    assert_eq!(closure.body.params.len(), 1);
+    assert_eq!(closure.captures.len(), 1, "{:?}", closure.captures);
+    assert_eq!(closure.captures[0].place, "bias");
+    assert_eq!(closure.captures[0].root, bias);
+    assert_eq!(closure.captures[0].kind, CaptureKind::ByRef(Mutability::Not));
+    assert!(exprs.iter().any(|expr| expr.kind == ExprKind::Path(Res::Local(bias))));
+}
+
+fn check_parse(item: CrateItem) {
+    let body = item.hir_body().unwrap();
+    let exprs = exprs(&body);
+    let try_match =
+        exprs.iter().find(|expr| matches!(expr.kind, ExprKind::Match(_, _, MatchSource::TryDesugar)));
+    let try_match = try_match.unwrap();
+    let backtrace = try_match.span.macro_backtrace();
+    assert_matches!(backtrace[..], [ref expn] if matches!(expn.kind, ExpnKind::Desugaring(_)));
+
+    let method = exprs.iter().find_map(|expr| match &expr.kind {
+        ExprKind::MethodCall { name, def, .. } => Some((name, def)),
+        _ => None,
+    });
+    let (name, def) = method.unwrap();
+    assert_eq!(name, "parse");
+    assert!(def.is_some());
+}
+
+fn binding(kind: &PatKind) -> (usize, &str) {
+    match kind {
+        PatKind::Binding { id, name, .. } => (*id, name),
+        _ => panic!("Expected binding, found {kind:?}"),
+    }
+}
+
+/// Collect the expressions of a body that are relevant for this test.
+fn exprs(body: &Body) -> Vec<&Expr> {
+    let mut exprs = vec![];
+    let mut stack = vec![&body.value];
+    while let Some(expr) = stack.pop() {
+        exprs.push(expr);
+        match &expr.kind {
+            ExprKind::Call(callee, args) => {
+                stack.push(callee);
+                stack.extend(args);
+            }
+            ExprKind::MethodCall { receiver, args, .. } => {
+                stack.push(receiver);
+                stack.extend(args);
+            }
+            ExprKind::Binary(_, lhs, rhs) | ExprKind::AssignOp(_, lhs, rhs) => {
+                stack.push(lhs);
+                stack.push(rhs);
+            }
+            ExprKind::Unary(_, operand) => stack.push(operand),
+            ExprKind::Match(scrutinee, arms, _) => {
+                stack.push(scrutinee);
+                stack.extend(arms.iter().map(|arm| &arm.body));
+            }
+            ExprKind::Block(block) | ExprKind::Loop(block, _) => {
+                for stmt in &block.stmts {
+                    match &stmt.kind {
+                        StmtKind::Let(local) => stack.extend(&local.init),
+                        StmtKind::Expr(expr) | StmtKind::Semi(expr) => stack.push(expr),
+                        StmtKind::Item(_) => {}
+                    }
+                }
+                stack.extend(block.expr.as_deref());
+            }
+            ExprKind::Closure(closure) => stack.push(&closure.body.value),
+            _ => {}
+        }
+    }
+    exprs
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// Then it will create a `StableMir` using custom arguments and then
+/// it will run the compiler.
+fn main() {
+    let path = "hir_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    run!(args, test_hir).unwrap();
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+        macro_rules! double {{
+            ($e:expr) => {{ $e * 2 }};
+        }}
+
+        pub fn compute(values: &[u32], bias: u32) -> Option<u32> {{
+            let mut total = 0;
+            for value in values {{
+                total += double!(*value);
+            }}
+            let add = |x: u32| x + bias;
+            Some(add(total))
+        }}
+
+        pub fn parse(input: &str) -> Result<u32, std::num::ParseIntError> {{
+            let value: u32 = input.parse()?;
+            Ok(value)
+        }}
+        "#
+    )?;
+    Ok(())
+}
@@ -0,0 +1,103 @@
+//@ run-pass
+//! Test that users are able to retrieve the mono items collected by the compiler.
+
+//@ ignore-stage1
+//@ ignore-cross-compile
+//@ ignore-remote
+//@ ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+//@ edition: 2021
+
+#![feature(rustc_private)]
+
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_smir::rustc_internal;
+use stable_mir::CrateDef;
+use stable_mir::mir::mono::MonoItem;
+use std::collections::HashSet;
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+
+fn test_mono_items() -> ControlFlow<()> {
+    let items = stable_mir::all_mono_items();
+    let names: HashSet<_> = items.iter().map(name).collect();
+    for expected in ["main", "double::<u32>", "double::<u64>", "COUNTER"] {
+        assert!(names.contains(expected), "Missing `{expected}`: {names:?}");
+    }
+    // Unreachable items are not collected.
+    assert!(!names.contains("dead"), "{names:?}");
+    assert!(!names.contains("double::<u8>"), "{names:?}");
+
+    let main = items.iter().find(|item| name(item) == "main").unwrap();
+    let uses: HashSet<_> = stable_mir::mono_item_uses(main).iter().map(name).collect();
+    for expected in ["double::<u32>", "double::<u64>", "COUNTER"] {
+        assert!(uses.contains(expected), "Missing use of `{expected}`: {uses:?}");
+    }
+
+    // The collection is closed under uses.
+    let all: HashSet<_> = items.iter().collect();
+    for item in &items {
+        for used in stable_mir::mono_item_uses(item) {
+            assert!(all.contains(&used), "{used:?} is used by {item:?}, but wasn't collected");
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+fn name(item: &MonoItem) -> String {
+    match item {
+        MonoItem::Fn(instance) => instance.name(),
+        MonoItem::Static(def) => def.name(),
+        MonoItem::GlobalAsm(asm) => asm.to_string(),
+    }
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// Then it will create a `StableMir` using custom arguments and then
+/// it will run the compiler.
+fn main() {
+    let path = "mono_items_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=bin".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    run!(args, test_mono_items).unwrap();
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+        static COUNTER: u8 = 3;
+
+        #[inline(never)]
+        fn double<T: Copy + std::ops::Add<Output = T>>(x: T) -> T {{
+            x + x
+        }}
+
+        #[allow(dead_code)]
+        fn dead() -> u8 {{
+            double(1u8)
+        }}
+
+        fn main() {{
+            let a = double(COUNTER as u32);
+            let b = double(5u64);
+            std::hint::black_box((a, b));
+        }}
+        "#
+    )?;
+    Ok(())
+}
@@ -0,0 +1,121 @@
+//@ run-pass
+//! Test that users are able to replace the body of a function that is used for codegen.
+
+//@ ignore-stage1
+//@ ignore-cross-compile
+//@ ignore-remote
+//@ ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+
+#![feature(rustc_private)]
+
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_smir::rustc_internal;
+use stable_mir::mir::{
+    BinOp, Body, ConstOperand, FakeReadCause, Operand, Place, RETURN_LOCAL, Rvalue, Statement,
+    StatementKind, TerminatorKind,
+};
+use stable_mir::ty::{MirConst, UintTy};
+use stable_mir::CrateDef;
+use std::io::Write;
+use std::ops::ControlFlow;
+use std::process::Command;
+
+const CRATE_NAME: &str = "input";
+const OUTPUT: &str = "./replace_body_output";
+
+/// This function uses the Stable MIR APIs to replace the body of `answer`.
+fn test_replace_body() -> ControlFlow<()> {
+    let items = stable_mir::all_local_items();
+    let answer = *items.iter().find(|item| item.name() == "answer").unwrap();
+    let body = answer.body();
+
+    // A jump to a block that doesn't exist is rejected.
+    let mut invalid = body.clone();
+    invalid.blocks[0].terminator.kind = TerminatorKind::Goto { target: body.blocks.len() };
+    assert!(answer.replace_body(invalid).is_err());
+
+    // Constructs that cannot be represented in internal MIR are rejected.
+    let mut unsupported = body.clone();
+    let span = unsupported.blocks[0].terminator.span;
+    unsupported.blocks[0].statements.push(Statement {
+        kind: StatementKind::FakeRead(FakeReadCause::ForIndex, Place::from(RETURN_LOCAL)),
+        span,
+    });
+    assert!(answer.replace_body(unsupported).is_err());
+    let mut unsupported = body.clone();
+    let operand = Operand::Copy(Place::from(RETURN_LOCAL));
+    unsupported.blocks[0].statements.push(Statement {
+        kind: StatementKind::Assign(
+            Place::from(RETURN_LOCAL),
+            Rvalue::CheckedBinaryOp(BinOp::Div, operand.clone(), operand),
+        ),
+        span,
+    });
+    assert!(answer.replace_body(unsupported).is_err());
+
+    // A body that doesn't pass MIR validation is rejected.
+    let wrong_ty = MirConst::try_from_uint(42, UintTy::U16).unwrap();
+    assert!(answer.replace_body(change_return_value(body.clone(), wrong_ty)).is_err());
+
+    let new_value = MirConst::try_from_uint(42, UintTy::U8).unwrap();
+    answer.replace_body(change_return_value(body, new_value)).unwrap();
+    ControlFlow::Continue(())
+}
+
+/// Change the constant that is assigned to the return place.
+fn change_return_value(mut body: Body, new_value: MirConst) -> Body {
+    let stmt = body
+        .blocks
+        .iter_mut()
+        .flat_map(|bb| bb.statements.iter_mut())
+        .find(|stmt| matches!(&stmt.kind, StatementKind::Assign(place, _) if place.local == 0))
+        .unwrap();
+    let StatementKind::Assign(_, Rvalue::Use(Operand::Constant(constant))) = &mut stmt.kind else {
+        panic!("Unexpected statement: {stmt:?}")
+    };
+    *constant = ConstOperand { const_: new_value, span: constant.span, user_ty: None };
+    body
+}
+
+/// This test will generate a dummy binary crate, replace the body of one of its functions
+/// using the stable mir, and then run the generated binary to check that the new body was
+/// used for code generation.
+fn main() {
+    let path = "replace_body_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=bin".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        "-o".to_string(),
+        OUTPUT.to_string(),
+        path.to_string(),
+    ];
+    run!(args, test_replace_body).unwrap();
+    let status = Command::new(OUTPUT).status().unwrap();
+    assert_eq!(status.code(), Some(42));
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+        #[inline(never)]
+        fn answer() -> u8 {{
+            1
+        }}
+
+        fn main() {{
+            std::process::exit(answer() as i32);
+        }}
+        "#
+    )?;
+    Ok(())
+}
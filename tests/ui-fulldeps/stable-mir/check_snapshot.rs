@@ -0,0 +1,159 @@
+//@ run-pass
+//! Test that users are able to take a self-contained snapshot of a crate, and to analyze it
+//! after the compiler is gone.
+
+//@ ignore-stage1
+//@ ignore-cross-compile
+//@ ignore-remote
+//@ ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+//@ edition: 2021
+
+#![feature(rustc_private)]
+
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate serde_json;
+extern crate stable_mir;
+
+use rustc_smir::rustc_internal;
+use stable_mir::mir::MirVisitor;
+use stable_mir::mir::visit::Location;
+use stable_mir::snapshot::{CrateSnapshot, SNAPSHOT_VERSION};
+use stable_mir::ty::{RigidTy, Span, Ty, TyKind, UintTy};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+const SNAPSHOT_PATH: &str = "snapshot_input.json";
+
+fn save_snapshot() -> ControlFlow<()> {
+    let snapshot = CrateSnapshot::local();
+    snapshot.save(File::create(SNAPSHOT_PATH).unwrap()).unwrap();
+
+    // Nothing is lost on the way to the file.
+    let loaded = CrateSnapshot::load(File::open(SNAPSHOT_PATH).unwrap()).unwrap();
+    assert_eq!(serde_json::to_value(&snapshot).unwrap(), serde_json::to_value(&loaded).unwrap());
+    ControlFlow::Continue(())
+}
+
+/// Check the snapshot without the compiler, so any handle that was not recorded would make the
+/// lookups below fail.
+fn check_snapshot(snapshot: &CrateSnapshot) {
+    assert_eq!(snapshot.version, SNAPSHOT_VERSION);
+    assert_eq!(snapshot.krate.name, CRATE_NAME);
+
+    let names: HashSet<_> = snapshot
+        .items
+        .iter()
+        .map(|item| snapshot.def(item.item.0).expect("missing item definition").name.as_str())
+        .collect();
+    assert!(names.contains("main"), "{names:?}");
+    assert!(names.contains("GREETING"), "{names:?}");
+
+    // Every definition has a known location.
+    for def in &snapshot.defs {
+        assert!(snapshot.span(def.span).is_some(), "missing span of `{}`", def.name);
+    }
+
+    // Every type and span used by the bodies must be part of the snapshot.
+    let types: HashSet<_> = snapshot.types.iter().map(|ty| ty.ty).collect();
+    let spans: HashSet<_> = snapshot.spans.iter().map(|span| span.span).collect();
+    let mut checker = Checker { types: &types, spans: &spans };
+    for body in snapshot.items.iter().filter_map(|item| item.body.as_ref()) {
+        checker.visit_body(body);
+    }
+    // The string literal is stored in a separate allocation.
+    assert!(!snapshot.allocs.is_empty());
+    // The layout of non-generic types is known.
+    assert!(snapshot.types.iter().any(|ty| ty.layout.is_some()));
+
+    let [greet] = &snapshot.trait_decls[..] else { panic!("expected a single trait") };
+    assert_eq!(snapshot.def(greet.decl.def_id.0).unwrap().name, "Greet");
+    assert!(greet.generics.has_self);
+    let [self_param] = &greet.generics.params[..] else { panic!("expected only `Self`") };
+    assert!(snapshot.def(self_param.def_id.0).is_some());
+
+    let [world_impl] = &snapshot.trait_impls[..] else { panic!("expected a single impl") };
+    assert_eq!(world_impl.trait_impl.value.def_id, greet.decl.def_id);
+
+    // The ADTs used by the crate are part of the snapshot, including the types of their fields.
+    let world = snapshot
+        .adts
+        .iter()
+        .find(|adt| snapshot.def(adt.def.0).unwrap().name == "World")
+        .expect("missing `World`");
+    let [variant] = &world.variants[..] else { panic!("expected a struct") };
+    let [field] = &variant.fields[..] else { panic!("expected a single field") };
+    let field_ty = snapshot.ty(field.ty).expect("missing field type");
+    assert!(matches!(field_ty.kind, TyKind::RigidTy(RigidTy::Uint(UintTy::U8))));
+}
+
+struct Checker<'a> {
+    types: &'a HashSet<Ty>,
+    spans: &'a HashSet<Span>,
+}
+
+impl MirVisitor for Checker<'_> {
+    // The handles can't be printed without the compiler.
+    fn visit_ty(&mut self, ty: &Ty, location: Location) {
+        assert!(self.types.contains(ty), "Missing type");
+        assert!(self.spans.contains(&location.span()), "Missing span");
+    }
+
+    fn visit_span(&mut self, span: &Span) {
+        assert!(self.spans.contains(span), "Missing span");
+    }
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// Then it will create a `StableMir` using custom arguments and then
+/// it will run the compiler.
+fn main() {
+    let path = "snapshot_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    run!(args, save_snapshot).unwrap();
+
+    let snapshot = CrateSnapshot::load(File::open(SNAPSHOT_PATH).unwrap()).unwrap();
+    check_snapshot(&snapshot);
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+        pub static GREETING: &str = "hello";
+
+        pub trait Greet {{
+            fn greet(&self) -> &'static str;
+        }}
+
+        pub struct World(u8);
+
+        impl Greet for World {{
+            fn greet(&self) -> &'static str {{
+                GREETING
+            }}
+        }}
+
+        pub fn main() -> Option<u8> {{
+            let world = World(1);
+            let _ = world.greet();
+            Some(world.0)
+        }}
+        "#
+    )?;
+    Ok(())
+}
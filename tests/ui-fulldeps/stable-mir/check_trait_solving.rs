@@ -0,0 +1,144 @@
+//@ run-pass
+//! Test that users are able to query whether a trait is implemented and normalize types.
+
+//@ ignore-stage1
+//@ ignore-cross-compile
+//@ ignore-remote
+//@ ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+
+#![feature(rustc_private)]
+#![feature(assert_matches)]
+
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_smir::rustc_internal;
+use stable_mir::ty::{
+    GenericArgKind, GenericArgs, ParamEnv, RigidTy, TraitDef, TraitRef, Ty, TyKind, UintTy,
+};
+use stable_mir::{CrateDef, CrateItem};
+use std::assert_matches::assert_matches;
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+
+/// This function uses the Stable MIR APIs to query the trait system.
+fn test_trait_queries() -> ControlFlow<()> {
+    let items = stable_mir::all_local_items();
+    let item = |name: &str| *items.iter().find(|item| item.name() == name).unwrap();
+    let shape = stable_mir::local_crate()
+        .trait_decls()
+        .into_iter()
+        .find(|def| def.name() == "Shape")
+        .unwrap();
+
+    check_monomorphic(shape, item("square"), item("circle"));
+    check_generic(shape, item("generic"), item("unbounded"));
+    ControlFlow::Continue(())
+}
+
+fn check_monomorphic(shape: TraitDef, square: CrateItem, circle: CrateItem) {
+    let no_args = GenericArgs(vec![]);
+    let square_ty = output(square);
+    let circle_ty = output(circle);
+    assert_eq!(square_ty.implements_trait(shape, &no_args, ParamEnv::Monomorphic), Ok(true));
+    assert_eq!(circle_ty.implements_trait(shape, &no_args, ParamEnv::Monomorphic), Ok(false));
+
+    // `Shape` has no generic parameters besides `Self`.
+    let extra_args = GenericArgs(vec![GenericArgKind::Type(square_ty)]);
+    assert!(square_ty.implements_trait(shape, &extra_args, ParamEnv::Monomorphic).is_err());
+
+    let square_ref = TraitRef::new(shape, square_ty, &no_args);
+    let area = square_ref.type_of_projection("Area", ParamEnv::Monomorphic).unwrap();
+    assert_eq!(area.kind(), TyKind::RigidTy(RigidTy::Uint(UintTy::U64)));
+    assert!(square_ref.type_of_projection("Volume", ParamEnv::Monomorphic).is_err());
+
+    let circle_ref = TraitRef::new(shape, circle_ty, &no_args);
+    assert!(circle_ref.type_of_projection("Area", ParamEnv::Monomorphic).is_err());
+}
+
+fn check_generic(shape: TraitDef, generic: CrateItem, unbounded: CrateItem) {
+    let no_args = GenericArgs(vec![]);
+    let generic_env = ParamEnv::Item(generic.def_id());
+    let unbounded_env = ParamEnv::Item(unbounded.def_id());
+
+    let param = input(generic);
+    assert_matches!(param.kind(), TyKind::Param(_));
+    assert_eq!(param.implements_trait(shape, &no_args, generic_env), Ok(true));
+    let param = input(unbounded);
+    assert_eq!(param.implements_trait(shape, &no_args, unbounded_env), Ok(false));
+
+    // The return type is `<T as Shape>::Area`, which is `u8` according to the bounds.
+    let area = output(generic);
+    assert_matches!(area.kind(), TyKind::Alias(..));
+    let normalized = area.normalize(generic_env).unwrap();
+    assert_eq!(normalized.kind(), TyKind::RigidTy(RigidTy::Uint(UintTy::U8)));
+}
+
+fn output(item: CrateItem) -> Ty {
+    item.ty().kind().fn_sig().unwrap().skip_binder().output()
+}
+
+fn input(item: CrateItem) -> Ty {
+    item.ty().kind().fn_sig().unwrap().skip_binder().inputs()[0]
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// Then it will create a `StableMir` using custom arguments and then
+/// it will run the compiler.
+fn main() {
+    let path = "trait_solving_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    run!(args, test_trait_queries).unwrap();
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+        pub trait Shape {{
+            type Area;
+            fn area(&self) -> Self::Area;
+        }}
+
+        pub struct Square(pub u32);
+
+        impl Shape for Square {{
+            type Area = u64;
+            fn area(&self) -> u64 {{
+                self.0 as u64 * self.0 as u64
+            }}
+        }}
+
+        pub struct Circle;
+
+        pub fn square() -> Square {{
+            Square(2)
+        }}
+
+        pub fn circle() -> Circle {{
+            Circle
+        }}
+
+        pub fn generic<T: Shape<Area = u8>>(shape: T) -> T::Area {{
+            shape.area()
+        }}
+
+        pub fn unbounded<T>(_value: T) {{}}
+        "#
+    )?;
+    Ok(())
+}
@@ -0,0 +1,3 @@
+//@ ignore-test Not a test. Used by other tests
+
+pub struct Circle;
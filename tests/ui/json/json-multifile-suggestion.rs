@@ -0,0 +1,19 @@
+//@ compile-flags: --json=diagnostic-short --error-format=json
+
+// Tools are suggested to implement a trait next to a type defined in another file, and to import
+// the trait where its method is called.
+
+#![allow(dead_code)]
+
+#[path = "json-multifile-suggestion-aux.rs"]
+mod shapes;
+
+mod area {
+    pub trait Area {
+        fn area(&self) -> f64;
+    }
+}
+
+fn main() {
+    shapes::Circle.area();
+}
@@ -0,0 +1,7 @@
+//@ compile-flags: --json=diagnostic-short --error-format=json
+
+// Tools are suggested to create the file of a missing module.
+
+mod missing;
+
+fn main() {}
@@ -0,0 +1,9 @@
+//@ compile-flags: --json=diagnostic-short --error-format=json
+
+// Diagnostics with a string message are identified by their error code.
+
+fn foo(_x: u32) {
+    _x = 4;
+}
+
+fn main() {}
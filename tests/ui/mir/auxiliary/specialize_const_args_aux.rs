@@ -0,0 +1,34 @@
+//@ compile-flags: -O -Zinline-mir=no -Zmir-opt-level=3 -Zmir-specialize-const-args=yes
+//@ compile-flags: -Zvalidate-mir
+
+static TABLE: [u32; 4] = [1, 2, 3, 4];
+
+fn lookup(index: usize, scaled: bool) -> u32 {
+    if scaled { TABLE[index] * 10 } else { TABLE[index] }
+}
+
+// Not exported, so it may call the clones of `lookup`, which refer to the private `TABLE`.
+pub fn scaled_lookup(index: usize) -> u32 {
+    lookup(index, true)
+}
+
+// Exported through `#[inline]`: the clones it calls would need `TABLE` in the other crate.
+#[inline]
+pub fn inline_lookup(index: usize) -> u32 {
+    lookup(index, false) + lookup(index, true)
+}
+
+// Exported through monomorphization.
+pub fn generic_lookup<T: Into<usize>>(index: T) -> u32 {
+    lookup(index.into(), true)
+}
+
+// Small enough to be inferred cross-crate inlinable, so its clones may be used by other crates.
+fn add(value: u32, extra: bool) -> u32 {
+    if extra { value + 1 } else { value }
+}
+
+#[inline]
+pub fn inline_add(value: u32) -> u32 {
+    add(value, true)
+}
@@ -0,0 +1,16 @@
+//@ run-pass
+//@ aux-build:specialize_const_args_aux.rs
+//@ compile-flags: -O -Zinline-mir=no -Zmir-opt-level=3 -Zmir-specialize-const-args=yes
+//@ compile-flags: -Zvalidate-mir
+// Check that the MIR exported to other crates only calls clones of functions that are
+// `cross_crate_inlinable`, so that the program links when these functions use private items.
+// The MIR inliner is disabled, as it would inline these small functions instead.
+
+extern crate specialize_const_args_aux as aux;
+
+fn main() {
+    assert_eq!(aux::scaled_lookup(1), 20);
+    assert_eq!(aux::inline_lookup(2), 33);
+    assert_eq!(aux::generic_lookup(3u8), 40);
+    assert_eq!(aux::inline_add(5), 6);
+}
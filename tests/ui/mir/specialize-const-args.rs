@@ -0,0 +1,47 @@
+//@ run-pass
+//@ compile-flags: -Zmir-opt-level=3 -Zmir-specialize-const-args=yes -Zvalidate-mir
+// Check that specializing callees for their constant arguments preserves the behavior of the
+// program, including when the arguments are modified or borrowed by the callee.
+
+fn scale(value: u32, double: bool, offset: u32) -> u32 {
+    let mut result = if double { value * 2 } else { value };
+    if offset > 10 {
+        result += offset;
+        result = result.wrapping_mul(3);
+    } else {
+        result -= offset;
+    }
+    result
+}
+
+fn layered(value: u32, double: bool) -> u32 {
+    scale(value, double, 0) + scale(value, !double, 20)
+}
+
+fn mutated(mut flag: bool, value: u32) -> u32 {
+    if value > 3 {
+        flag = !flag;
+    }
+    if flag { value } else { value + 1 }
+}
+
+fn borrowed(flag: bool) -> bool {
+    let flag_ref = &flag;
+    *flag_ref
+}
+
+fn recursive(depth: u32, value: u32) -> u32 {
+    if depth == 0 { value } else { recursive(depth - 1, value + depth) }
+}
+
+fn main() {
+    let value = std::hint::black_box(7);
+    assert_eq!(scale(value, true, 0), 14);
+    assert_eq!(scale(value, false, 12), 57);
+    assert_eq!(layered(value, true), 14 + 81);
+    assert_eq!(layered(value, false), 7 + 102);
+    assert_eq!(mutated(true, value), 8);
+    assert_eq!(mutated(true, 2), 2);
+    assert!(borrowed(true));
+    assert_eq!(recursive(3, value), 13);
+}
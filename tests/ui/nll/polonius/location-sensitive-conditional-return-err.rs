@@ -0,0 +1,33 @@
+// A variant of NLL problem case #3 that is rejected by both NLLs and the location-sensitive
+// analysis of `-Zpolonius=next`: the borrowed place is mutated on the path where the loan is
+// returned. NLLs also reject the mutation on the path where the loan is not returned.
+
+//@ revisions: nll polonius
+//@ [polonius] compile-flags: -Zpolonius=next
+
+struct Map {}
+
+impl Map {
+    fn get(&self) -> Option<&String> {
+        None
+    }
+    fn set(&mut self, _: String) {}
+}
+
+fn err(map: &mut Map) -> &String {
+    loop {
+        match map.get() {
+            Some(v) => {
+                map.set(String::new());
+                //~^ ERROR also borrowed as immutable
+                return v;
+            }
+            None => {
+                map.set(String::new());
+                //[nll]~^ ERROR also borrowed as immutable
+            }
+        }
+    }
+}
+
+fn main() {}
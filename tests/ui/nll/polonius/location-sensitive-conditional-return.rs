@@ -0,0 +1,38 @@
+// Conditionally returning a loan out of a function, and mutating the borrowed place on the other
+// path, is rejected by NLLs: the loan flows into a free region, which is live at every point.
+// This is NLL problem case #3, accepted by the location-sensitive analysis of `-Zpolonius=next`,
+// where the loan only flows into the free region on the path where it's returned.
+
+//@ check-pass
+//@ compile-flags: -Zpolonius=next
+
+use std::collections::HashMap;
+
+fn get_default<'r>(map: &'r mut HashMap<u32, String>, key: u32) -> &'r mut String {
+    match map.get_mut(&key) {
+        Some(value) => value,
+        None => {
+            map.insert(key, String::new());
+            map.get_mut(&key).unwrap()
+        }
+    }
+}
+
+fn first_even<'r>(v: &'r mut Vec<u32>) -> &'r mut u32 {
+    loop {
+        if let Some(x) = v.iter_mut().find(|x| **x % 2 == 0) {
+            return x;
+        }
+        v.push(0);
+    }
+}
+
+fn main() {
+    let mut map = HashMap::new();
+    get_default(&mut map, 0).push('a');
+    assert_eq!(map[&0], "a");
+
+    let mut v = vec![1, 3];
+    *first_even(&mut v) += 2;
+    assert_eq!(v, [1, 3, 2]);
+}
@@ -1,5 +1,7 @@
 // Check that Polonius borrow check works for simple cases.
-//@ compile-flags: -Z polonius
+//@ revisions: legacy polonius_next
+//@ [legacy] compile-flags: -Z polonius
+//@ [polonius_next] compile-flags: -Z polonius=next
 
 pub fn return_ref_to_local() -> &'static i32 {
     let x = 0;